@@ -0,0 +1,268 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{
+            MockAuthenticator, MockColumn, MockParameter, MockResponse, MockResultSet, MockServer,
+            MockServerConfig,
+        },
+        Connection, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+
+    #[test] // cargo test --features mock_server --test test_100_mock_server -- --nocapture
+    fn test_100_mock_server() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        authentication()?;
+        queries()?;
+        prepared_statements()?;
+        redirect()?;
+        lobs()
+    }
+
+    fn authentication() -> HdbResult<()> {
+        for authenticator in [
+            MockAuthenticator::ScramSha256,
+            MockAuthenticator::ScramPbkdf2Sha256,
+        ] {
+            info!("connect with {authenticator:?}");
+            let mock = MockServer::start_with(
+                MockServerConfig::default().with_authenticator(authenticator),
+            )?;
+            let connection = Connection::new(mock.url())?;
+            assert!(connection.id()? > 0);
+            assert_eq!(mock.connection_count(), 1);
+        }
+
+        info!("wrong password is rejected");
+        let mock = MockServer::start()?;
+        let mut cp_builder = mock.connect_params_builder();
+        cp_builder.password("WrongPassword1");
+        match Connection::new(cp_builder) {
+            Err(HdbError::Authentication { .. }) => {}
+            Err(e) => panic!("unexpected error: {e:?}"),
+            Ok(_) => panic!("connect with wrong password succeeded"),
+        }
+        Ok(())
+    }
+
+    fn queries() -> HdbResult<()> {
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            "select ID, NAME from PERSONS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![
+                    MockColumn::new("ID", TypeId::INT).with_table("PERSONS"),
+                    MockColumn::new("NAME", TypeId::NVARCHAR)
+                        .with_table("PERSONS")
+                        .nullable(),
+                ])
+                .with_rows((0..100).map(|i| {
+                    vec![
+                        HdbValue::INT(i),
+                        if i % 10 == 0 {
+                            HdbValue::NULL
+                        } else {
+                            HdbValue::STRING(format!("name {i}"))
+                        },
+                    ]
+                })),
+            ),
+        );
+        mock.on_statement("delete from PERSONS", MockResponse::AffectedRows(100));
+        mock.on_statement(
+            "drop table PERSONS",
+            MockResponse::error(259, "invalid table name: PERSONS"),
+        );
+
+        let connection = Connection::new(mock.url())?;
+
+        info!("result set that needs several fetch roundtrips");
+        connection.set_fetch_size(7)?;
+        let persons: Vec<(i32, Option<String>)> = connection
+            .query("select ID, NAME from PERSONS")?
+            .try_into()?;
+        assert_eq!(persons.len(), 100);
+        assert_eq!(persons[0], (0, None));
+        assert_eq!(persons[99], (99, Some("name 99".to_string())));
+
+        info!("affected rows");
+        assert_eq!(connection.dml("delete from PERSONS")?, 100);
+
+        info!("scripted server error");
+        let err = connection.exec("drop table PERSONS").unwrap_err();
+        assert_eq!(
+            err.server_error().map(hdbconnect::ServerError::code),
+            Some(259)
+        );
+
+        info!("statement that was not scripted");
+        let err = connection.exec("drop table OTHERS").unwrap_err();
+        assert_eq!(
+            err.server_error().map(hdbconnect::ServerError::code),
+            Some(257)
+        );
+
+        info!("transaction control");
+        connection.set_auto_commit(false)?;
+        connection.commit()?;
+        connection.rollback()?;
+
+        let statements: Vec<String> = mock
+            .executions()
+            .iter()
+            .map(|execution| execution.sql().to_string())
+            .collect();
+        assert_eq!(
+            statements,
+            vec![
+                "select ID, NAME from PERSONS",
+                "delete from PERSONS",
+                "drop table PERSONS",
+                "commit",
+                "rollback",
+            ]
+        );
+        Ok(())
+    }
+
+    fn prepared_statements() -> HdbResult<()> {
+        let mock = MockServer::start()?;
+        mock.on_statement_with_parameters(
+            "insert into PERSONS (ID, NAME) values (?, ?)",
+            vec![
+                MockParameter::new(TypeId::INT).with_name("ID"),
+                MockParameter::new(TypeId::NVARCHAR)
+                    .with_name("NAME")
+                    .nullable(),
+            ],
+            MockResponse::AffectedRows(1),
+        );
+        mock.on_statement_with_parameters(
+            "select NAME from PERSONS where ID = ?",
+            vec![MockParameter::new(TypeId::INT)],
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("NAME", TypeId::NVARCHAR)])
+                    .with_row(vec![HdbValue::STRING("Alice".to_string())]),
+            ),
+        );
+
+        let connection = Connection::new(mock.url())?;
+
+        let mut insert_stmt = connection.prepare("insert into PERSONS (ID, NAME) values (?, ?)")?;
+        let descriptors = insert_stmt.parameter_descriptors();
+        assert_eq!(descriptors[0].name(), Some("ID"));
+        assert_eq!(descriptors[1].type_id(), TypeId::NVARCHAR);
+
+        insert_stmt.add_batch(&(1_i32, "Alice"))?;
+        insert_stmt.add_batch(&(2_i32, None::<String>))?;
+        let affected_rows = insert_stmt.execute_batch()?.into_affected_rows()?;
+        assert_eq!(affected_rows, vec![1, 1]);
+
+        let mut select_stmt = connection.prepare("select NAME from PERSONS where ID = ?")?;
+        let name: String = select_stmt.execute(&1_i32)?.into_result_set()?.try_into()?;
+        assert_eq!(name, "Alice");
+
+        let executions = mock.executions();
+        assert_eq!(executions.len(), 2);
+        assert_same_values(
+            executions[0].parameter_rows(),
+            &[
+                vec![HdbValue::INT(1), HdbValue::STRING("Alice".to_string())],
+                vec![HdbValue::INT(2), HdbValue::NULL],
+            ],
+        );
+        assert_same_values(executions[1].parameter_rows(), &[vec![HdbValue::INT(1)]]);
+        Ok(())
+    }
+
+    fn redirect() -> HdbResult<()> {
+        let target = MockServer::start()?;
+        target.on_statement("select 1 from dummy", int_result(1));
+
+        let redirecting = MockServer::start()?;
+        redirecting.redirect_authentication("127.0.0.1", target.port());
+
+        let connection = Connection::new(redirecting.url())?;
+        let one: i32 = connection.query("select 1 from dummy")?.try_into()?;
+        assert_eq!(one, 1);
+        assert_eq!(redirecting.connection_count(), 1);
+        assert_eq!(target.connection_count(), 1);
+
+        info!("redirect to a tenant database");
+        let system_db = MockServer::start()?;
+        system_db.redirect_database("TENANT", "127.0.0.1", target.port());
+        let mut cp_builder = system_db.connect_params_builder();
+        cp_builder.dbname("TENANT");
+        let connection = Connection::new(cp_builder)?;
+        let one: i32 = connection.query("select 1 from dummy")?.try_into()?;
+        assert_eq!(one, 1);
+        assert_eq!(target.connection_count(), 2);
+        Ok(())
+    }
+
+    fn lobs() -> HdbResult<()> {
+        let mock = MockServer::start_with(MockServerConfig::default().with_lob_chunk_size(100))?;
+
+        let bytes: Vec<u8> = (0..5_000_u32).map(|i| (i % 256) as u8).collect();
+        let text: String = "Grüße aus 東京 🚀 ".repeat(200);
+        mock.on_statement(
+            "select DATA, TEXT from DOCUMENTS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![
+                    MockColumn::new("DATA", TypeId::BLOB),
+                    MockColumn::new("TEXT", TypeId::NCLOB),
+                ])
+                .with_row(vec![
+                    HdbValue::BINARY(bytes.clone()),
+                    HdbValue::STRING(text.clone()),
+                ]),
+            ),
+        );
+        mock.on_statement_with_parameters(
+            "insert into DOCUMENTS (DATA) values (?)",
+            vec![MockParameter::new(TypeId::BLOB)],
+            MockResponse::AffectedRows(1),
+        );
+
+        let connection = Connection::new(mock.url())?;
+        connection.set_lob_read_length(1_000)?;
+
+        info!("read LOBs that need several roundtrips");
+        let (data, read_text): (serde_bytes::ByteBuf, String) = connection
+            .query("select DATA, TEXT from DOCUMENTS")?
+            .try_into()?;
+        assert_eq!(data.into_vec(), bytes);
+        assert_eq!(read_text, text);
+
+        info!("write a LOB in streaming fashion");
+        let mut insert_stmt = connection.prepare("insert into DOCUMENTS (DATA) values (?)")?;
+        let reader =
+            std::sync::Arc::new(std::sync::Mutex::new(std::io::Cursor::new(bytes.clone())));
+        insert_stmt.execute_row(vec![HdbValue::SYNC_LOBSTREAM(Some(reader))])?;
+
+        let executions = mock.executions();
+        assert_same_values(
+            executions[1].parameter_rows(),
+            &[vec![HdbValue::BINARY(bytes)]],
+        );
+        Ok(())
+    }
+
+    // HdbValue does not implement PartialEq
+    fn assert_same_values(actual: &[Vec<HdbValue<'static>>], expected: &[Vec<HdbValue<'static>>]) {
+        assert_eq!(format!("{actual:?}"), format!("{expected:?}"));
+    }
+
+    fn int_result(i: i32) -> MockResponse {
+        MockResponse::ResultSet(
+            MockResultSet::new(vec![MockColumn::new("1", TypeId::INT)])
+                .with_row(vec![HdbValue::INT(i)]),
+        )
+    }
+}
@@ -0,0 +1,128 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{MockColumn, MockParameter, MockResponse, MockResultSet, MockServer},
+        Connection, HdbError, HdbResult, HdbValue, ResultSet, Row, TypeId,
+    };
+    use log::info;
+
+    #[test] // cargo test --features mock_server --test test_101_scrollable_cursors -- --nocapture
+    fn test_101_scrollable_cursors() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        let mock = MockServer::start()?;
+        mock.on_statement("select ID from NUMBERS", numbers(20));
+        mock.on_statement_with_parameters(
+            "select ID from NUMBERS where ID < ?",
+            vec![MockParameter::new(TypeId::INT)],
+            numbers(20),
+        );
+        mock.on_statement("select ID from NOTHING", numbers(0));
+
+        let connection = Connection::new(mock.url())?;
+        connection.set_fetch_size(5)?;
+
+        not_scrollable(&connection)?;
+        scroll(&connection)?;
+        scroll_prepared(&connection)?;
+        scroll_empty(&connection)
+    }
+
+    fn not_scrollable(connection: &Connection) -> HdbResult<()> {
+        info!("result sets are not scrollable by default");
+        assert!(!connection.is_scrollable_cursor()?);
+        let mut rs = connection.query("select ID from NUMBERS")?;
+        assert_eq!(id(rs.next_row()?), Some(0));
+        assert!(matches!(rs.fetch_first(), Err(HdbError::Usage(_))));
+        Ok(())
+    }
+
+    fn scroll(connection: &Connection) -> HdbResult<()> {
+        connection.set_scrollable_cursor(true)?;
+        assert!(connection.is_scrollable_cursor()?);
+        let mut rs = connection.query("select ID from NUMBERS")?;
+
+        info!("first and last");
+        assert_eq!(id(rs.fetch_last()?), Some(19));
+        assert_eq!(id(rs.next_row()?), None);
+        assert_eq!(id(rs.fetch_first()?), Some(0));
+        assert_eq!(id(rs.next_row()?), Some(1));
+
+        info!("absolute positions");
+        assert_eq!(id(rs.fetch_absolute(12)?), Some(11));
+        assert_eq!(id(rs.next_row()?), Some(12));
+        assert_eq!(id(rs.fetch_absolute(-1)?), Some(19));
+        assert_eq!(id(rs.fetch_absolute(-20)?), Some(0));
+        assert_eq!(id(rs.fetch_absolute(21)?), None);
+        assert_eq!(id(rs.fetch_absolute(-21)?), None);
+        assert_eq!(id(rs.next_row()?), Some(0));
+        assert!(matches!(rs.fetch_absolute(0), Err(HdbError::Usage(_))));
+
+        info!("relative positions");
+        assert_eq!(id(rs.fetch_absolute(3)?), Some(2));
+        assert_eq!(id(rs.fetch_relative(2)?), Some(4)); // within the buffer
+        assert_eq!(id(rs.fetch_relative(4)?), Some(8)); // beyond the buffer
+        assert_eq!(id(rs.fetch_relative(-5)?), Some(3));
+        assert_eq!(id(rs.fetch_relative(0)?), Some(3));
+        assert_eq!(id(rs.fetch_relative(1)?), Some(4));
+        assert_eq!(id(rs.fetch_relative(-10)?), None);
+        assert_eq!(id(rs.fetch_relative(1)?), Some(0));
+
+        info!("scroll back after reading to the end");
+        let rest = collect(&mut rs)?;
+        assert_eq!(rest, (1..20).collect::<Vec<_>>());
+        assert_eq!(id(rs.fetch_relative(-3)?), Some(16));
+        assert_eq!(collect(&mut rs)?, vec![17, 18, 19]);
+        assert_eq!(id(rs.fetch_first()?), Some(0));
+        assert_eq!(rs.total_number_of_rows()?, 19);
+
+        connection.set_scrollable_cursor(false)?;
+        Ok(())
+    }
+
+    fn scroll_prepared(connection: &Connection) -> HdbResult<()> {
+        info!("prepared statement with a scrollable cursor");
+        let mut stmt = connection.prepare("select ID from NUMBERS where ID < ?")?;
+        assert!(!stmt.is_scrollable_cursor());
+        stmt.set_scrollable_cursor(true);
+        let mut rs = stmt.execute(&100_i32)?.into_result_set()?;
+        assert_eq!(id(rs.fetch_last()?), Some(19));
+        assert_eq!(id(rs.fetch_relative(-10)?), Some(9));
+        assert_eq!(id(rs.next_row()?), Some(10));
+        Ok(())
+    }
+
+    fn scroll_empty(connection: &Connection) -> HdbResult<()> {
+        info!("empty result set");
+        connection.set_scrollable_cursor(true)?;
+        let mut rs = connection.query("select ID from NOTHING")?;
+        assert_eq!(id(rs.next_row()?), None);
+        assert_eq!(id(rs.fetch_first()?), None);
+        assert_eq!(id(rs.fetch_last()?), None);
+        Ok(())
+    }
+
+    fn numbers(count: i32) -> MockResponse {
+        MockResponse::ResultSet(
+            MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                .with_rows((0..count).map(|i| vec![HdbValue::INT(i)])),
+        )
+    }
+
+    fn id(o_row: Option<Row>) -> Option<i32> {
+        o_row.map(|mut row| row.next_try_into().unwrap())
+    }
+
+    fn collect(rs: &mut ResultSet) -> HdbResult<Vec<i32>> {
+        let mut ids = Vec::new();
+        while let Some(row) = rs.next_row()? {
+            ids.push(row.try_into()?);
+        }
+        Ok(ids)
+    }
+}
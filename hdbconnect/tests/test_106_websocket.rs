@@ -0,0 +1,98 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer, MockServerConfig},
+        Connection, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+
+    const PATH: &str = "/service/hana";
+
+    #[test] // cargo test --features mock_server --test test_106_websocket -- --nocapture
+    fn test_106_websocket() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        websocket()?;
+        large_replies()?;
+        wrong_path()
+    }
+
+    fn websocket() -> HdbResult<()> {
+        info!("connect with WebSocket, using the URL option");
+        let mock = start_mock()?;
+        let connection = Connection::new(format!(
+            "hdbsql://MOCKUSER:MockPassword1@{}?websocket_path={PATH}",
+            mock.addr()
+        ))?;
+        assert_eq!(ids(&connection)?, vec![1, 2, 3]);
+        assert_eq!(ids(&connection)?, vec![1, 2, 3]);
+
+        info!("connect with WebSocket, using the builder; the leading slash is optional");
+        let mut builder = mock.connect_params_builder();
+        builder.websocket_path(PATH.trim_start_matches('/'));
+        let params = builder.build()?;
+        assert_eq!(params.websocket_path(), Some(PATH));
+        let connection = Connection::new(params)?;
+        assert_eq!(ids(&connection)?, vec![1, 2, 3]);
+        assert_eq!(mock.connection_count(), 2);
+        Ok(())
+    }
+
+    fn large_replies() -> HdbResult<()> {
+        info!("replies of more than 64 kB are transported in one message");
+        let mock = start_mock()?;
+        let connection = Connection::new(mock.url())?;
+        connection.set_fetch_size(5000)?;
+        let names: Vec<String> = connection.query("select NAME from NAMES")?.try_into()?;
+        assert_eq!(names.len(), 3000);
+        assert_eq!(names[2999], name(2999));
+        Ok(())
+    }
+
+    fn wrong_path() -> HdbResult<()> {
+        info!("the upgrade is refused for a wrong path");
+        let mock = start_mock()?;
+        assert!(Connection::new(format!(
+            "hdbsql://MOCKUSER:MockPassword1@{}?websocket_path=/service/other",
+            mock.addr()
+        ))
+        .is_err());
+        let mut builder = mock.connect_params_builder();
+        builder.websocket_path("/service/other");
+        assert!(Connection::new(builder.build()?).is_err());
+        assert_eq!(mock.connection_count(), 2);
+        Ok(())
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start_with(MockServerConfig::default().with_websocket_path(PATH))?;
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        mock.on_statement(
+            "select NAME from NAMES",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("NAME", TypeId::NVARCHAR)])
+                    .with_rows((0..3000).map(|i| vec![HdbValue::STRING(name(i))])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    fn name(i: usize) -> String {
+        format!("name number {i:05} with some padding to make the rows longer")
+    }
+
+    fn ids(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection.query("select ID from NUMBERS")?.try_into()
+    }
+}
@@ -0,0 +1,138 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        ConnectParams, Connection, ConnectionConfiguration, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    // A stand-in for a secret store, in which the password is rotated
+    #[derive(Default)]
+    struct Vault {
+        password: Mutex<String>,
+        calls: AtomicUsize,
+    }
+    impl Vault {
+        fn rotate(&self, mock: &MockServer, password: &str) {
+            mock.change_password(password);
+            *self.password.lock().unwrap() = password.to_string();
+        }
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test] // cargo test --features mock_server --test test_110_credential_provider -- --nocapture
+    fn test_110_credential_provider() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        rotated_password()?;
+        #[cfg(feature = "r2d2_pool")]
+        pooled_connections()?;
+        failing_provider()
+    }
+
+    fn rotated_password() -> HdbResult<()> {
+        info!("the provider is asked for the password with each logon");
+        let mock = start_mock()?;
+        let vault = Arc::new(Vault::default());
+        vault.rotate(&mock, "Secret1");
+        let params = connect_params(&mock, &vault)?;
+        let config = ConnectionConfiguration::default().with_session_recovery(true);
+        let connection = Connection::with_configuration(&params, &config)?;
+        assert_eq!(ids(&connection)?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 1);
+
+        info!("reconnects use the rotated password");
+        vault.rotate(&mock, "Secret2");
+        mock.break_connections();
+        assert_eq!(ids(&connection)?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 2);
+
+        info!("spawned connections use the rotated password");
+        vault.rotate(&mock, "Secret3");
+        let spawned = connection.spawn()?;
+        assert_eq!(ids(&spawned)?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 3);
+
+        info!("a stale password is rejected");
+        mock.change_password("Secret4");
+        assert!(Connection::new(&params).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "r2d2_pool")]
+    fn pooled_connections() -> HdbResult<()> {
+        info!("a pool picks up the rotated password for its new connections");
+        let mock = start_mock()?;
+        let vault = Arc::new(Vault::default());
+        vault.rotate(&mock, "Secret1");
+        let pool = r2d2::Pool::builder()
+            .max_size(2)
+            .min_idle(Some(0))
+            .test_on_check_out(false)
+            .build(hdbconnect::ConnectionManager::new(connect_params(
+                &mock, &vault,
+            )?)?)
+            .unwrap();
+        let connection = pool.get().unwrap();
+        assert_eq!(ids(&connection)?, vec![1, 2, 3]);
+
+        vault.rotate(&mock, "Secret2");
+        let other = pool.get().unwrap();
+        assert_eq!(ids(&other)?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 2);
+        Ok(())
+    }
+
+    fn failing_provider() -> HdbResult<()> {
+        info!("a failing provider lets the logon fail");
+        let mock = start_mock()?;
+        let params = ConnectParams::builder()
+            .hostname(mock.addr().ip().to_string())
+            .port(mock.port())
+            .dbuser("MOCKUSER")
+            .credential_provider(|| Err("vault not reachable".into()))
+            .build()?;
+        assert!(matches!(
+            Connection::new(params),
+            Err(HdbError::ConnParams { .. })
+        ));
+        Ok(())
+    }
+
+    fn connect_params(mock: &MockServer, vault: &Arc<Vault>) -> HdbResult<ConnectParams> {
+        let vault = Arc::clone(vault);
+        let mut builder = mock.connect_params_builder();
+        builder.unset_password().credential_provider(move || {
+            vault.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vault.password.lock().unwrap().clone())
+        });
+        builder.build()
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    fn ids(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection.query("select ID from NUMBERS")?.try_into()
+    }
+}
@@ -0,0 +1,171 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{
+            MockColumn, MockExecution, MockParameter, MockResponse, MockResultSet, MockServer,
+        },
+        Connection, ConnectionConfiguration, HdbError, HdbResult, HdbValue, TraceContext, TypeId,
+    };
+    use log::info;
+
+    const QUERY: &str = "select ID from NUMBERS";
+    const PREPARED_QUERY: &str = "select ID from NUMBERS where ID < ?";
+    const TRACEPARENT_1: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+    const TRACEPARENT_2: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    const TRACEPARENT_3: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-53995c3f42cd8ad8-00";
+
+    #[test] // cargo test --features mock_server --test test_119_trace_context -- --nocapture
+    fn test_119_trace_context() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        let mock = MockServer::start()?;
+        mock.on_statement(QUERY, numbers());
+        mock.on_statement_with_parameters(
+            PREPARED_QUERY,
+            vec![MockParameter::new(TypeId::INT)],
+            numbers(),
+        );
+
+        invalid_trace_context();
+
+        let config = ConnectionConfiguration::default().with_session_recovery(true);
+        let connection = Connection::with_configuration(mock.url(), &config)?;
+        connection_trace_context(&mock, &connection)?;
+        statement_trace_context(&mock, &connection)?;
+        replayed_after_recovery(&mock, &connection)?;
+        removed_trace_context(&mock, &connection)
+    }
+
+    fn invalid_trace_context() {
+        info!("an invalid traceparent is rejected");
+        for invalid in ["", "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331"] {
+            assert!(matches!(
+                TraceContext::new(invalid),
+                Err(HdbError::Usage(_))
+            ));
+        }
+    }
+
+    fn connection_trace_context(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("the trace context of the connection is sent with the next execution");
+        let calls_per_query = calls_of_query(connection)?;
+        assert_eq!(traceparent_of_last_execution(mock), None);
+
+        let trace_context =
+            TraceContext::new(TRACEPARENT_1)?.with_tracestate("congo=t61rcWkgMzE")?;
+        connection.set_trace_context(Some(trace_context.clone()))?;
+        assert_eq!(connection.trace_context()?, Some(trace_context));
+        assert_eq!(calls_of_query(connection)?, calls_per_query);
+        let execution = last_execution(mock);
+        assert_eq!(
+            execution.session_variable("TRACEPARENT"),
+            Some(TRACEPARENT_1)
+        );
+        assert_eq!(
+            execution.session_variable("TRACESTATE"),
+            Some("congo=t61rcWkgMzE")
+        );
+        assert!(execution.session_variable("APPLICATION").is_some());
+
+        info!("changing the trace context between statements costs no extra roundtrip");
+        connection.set_trace_context(Some(TraceContext::new(TRACEPARENT_2)?))?;
+        assert_eq!(calls_of_query(connection)?, calls_per_query);
+        let execution = last_execution(mock);
+        assert_eq!(
+            execution.session_variable("TRACEPARENT"),
+            Some(TRACEPARENT_2)
+        );
+        assert_eq!(execution.session_variable("TRACESTATE"), None);
+        Ok(())
+    }
+
+    fn statement_trace_context(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("the trace context of a prepared statement applies to its executions");
+        let mut statement = connection.prepare(PREPARED_QUERY)?;
+        assert_eq!(statement.trace_context(), None);
+        let ids: Vec<i32> = statement.execute(&5_i32)?.into_result_set()?.try_into()?;
+        assert_eq!(ids.len(), 3);
+        assert_eq!(
+            traceparent_of_last_execution(mock),
+            Some(TRACEPARENT_2.to_string())
+        );
+
+        statement.set_trace_context(Some(TraceContext::new(TRACEPARENT_3)?));
+        statement.execute(&5_i32)?;
+        assert_eq!(
+            traceparent_of_last_execution(mock),
+            Some(TRACEPARENT_3.to_string())
+        );
+
+        info!("other statements use the trace context of the connection again");
+        connection.query(QUERY)?;
+        assert_eq!(
+            traceparent_of_last_execution(mock),
+            Some(TRACEPARENT_2.to_string())
+        );
+
+        info!("a single statement can have its own trace context");
+        connection.statement_with_trace_context(QUERY, TraceContext::new(TRACEPARENT_3)?)?;
+        assert_eq!(
+            traceparent_of_last_execution(mock),
+            Some(TRACEPARENT_3.to_string())
+        );
+        connection.query(QUERY)?;
+        assert_eq!(
+            traceparent_of_last_execution(mock),
+            Some(TRACEPARENT_2.to_string())
+        );
+        Ok(())
+    }
+
+    fn replayed_after_recovery(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("the trace context is sent again to a recovered session");
+        let connection_count = mock.connection_count();
+        mock.break_connections();
+        connection.query(QUERY)?;
+        assert_eq!(mock.connection_count(), connection_count + 1);
+        assert_eq!(
+            traceparent_of_last_execution(mock),
+            Some(TRACEPARENT_2.to_string())
+        );
+        Ok(())
+    }
+
+    fn removed_trace_context(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("a removed trace context is removed on the server, too");
+        connection.set_trace_context(None)?;
+        connection.query(QUERY)?;
+        assert_eq!(traceparent_of_last_execution(mock), None);
+        Ok(())
+    }
+
+    // The number of roundtrips of a query.
+    fn calls_of_query(connection: &Connection) -> HdbResult<u32> {
+        let call_count = connection.statistics()?.call_count();
+        let ids: Vec<i32> = connection.query(QUERY)?.try_into()?;
+        assert_eq!(ids.len(), 3);
+        Ok(connection.statistics()?.call_count() - call_count)
+    }
+
+    fn last_execution(mock: &MockServer) -> MockExecution {
+        mock.executions().pop().unwrap()
+    }
+
+    fn traceparent_of_last_execution(mock: &MockServer) -> Option<String> {
+        last_execution(mock)
+            .session_variable("TRACEPARENT")
+            .map(ToString::to_string)
+    }
+
+    fn numbers() -> MockResponse {
+        MockResponse::ResultSet(
+            MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                .with_rows((0..3).map(|i| vec![HdbValue::INT(i)])),
+        )
+    }
+}
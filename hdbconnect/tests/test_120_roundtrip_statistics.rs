@@ -0,0 +1,120 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, HdbResult, HdbValue, SharedStatistics, TypeId,
+    };
+    use log::info;
+
+    const QUERY: &str = "select ID from NUMBERS";
+    const UNKNOWN: &str = "select * from UNKNOWN";
+
+    #[test] // cargo test --features mock_server --test test_120_roundtrip_statistics -- --nocapture
+    fn test_120_roundtrip_statistics() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            QUERY,
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=10).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+
+        let shared_statistics = SharedStatistics::new();
+        let config = ConnectionConfiguration::default()
+            .with_fetch_size(3)
+            .with_shared_statistics(Some(shared_statistics.clone()));
+        let connection1 = Connection::with_configuration(mock.url(), &config)?;
+        let connection2 = Connection::with_configuration(mock.url(), &config)?;
+
+        connection_statistics(&connection1)?;
+        shared(&connection2, &shared_statistics)?;
+        prometheus_text(&shared_statistics);
+        reset(&connection1, &shared_statistics)
+    }
+
+    fn connection_statistics(connection: &Connection) -> HdbResult<()> {
+        info!("the roundtrips of a connection are counted by message type");
+        connection.reset_statistics()?;
+        let ids: Vec<i32> = connection.query(QUERY)?.try_into()?;
+        assert_eq!(ids.len(), 10);
+        connection.query(UNKNOWN).unwrap_err();
+
+        let statistics = connection.statistics()?;
+        let roundtrips = statistics.roundtrips();
+        assert_eq!(
+            roundtrips.roundtrip_count(),
+            u64::from(statistics.call_count())
+        );
+        let execute_direct = roundtrips.message_type("ExecuteDirect").unwrap();
+        assert_eq!(execute_direct.count(), 2);
+        assert_eq!(execute_direct.error_count(), 1);
+        assert_eq!(execute_direct.latency().count(), 2);
+        assert!(execute_direct.server_processing_time() <= execute_direct.latency().sum());
+        assert_eq!(roundtrips.message_type("FetchNext").unwrap().count(), 3);
+
+        info!("fetch roundtrips per result set, bytes, and server errors");
+        assert_eq!(roundtrips.result_set_count(), 1);
+        assert_eq!(roundtrips.fetch_roundtrip_count(), 3);
+        assert!((roundtrips.fetch_roundtrips_per_result_set() - 3.0).abs() < f64::EPSILON);
+        assert_eq!(roundtrips.lob_roundtrip_count(), 0);
+        assert!(roundtrips.bytes_sent() > 4 * 56);
+        assert!(roundtrips.bytes_received() > 4 * 56);
+        assert_eq!(roundtrips.server_errors().collect::<Vec<_>>(), [(257, 1)]);
+        Ok(())
+    }
+
+    fn shared(connection: &Connection, shared_statistics: &SharedStatistics) -> HdbResult<()> {
+        info!("the shared statistics aggregate the roundtrips of both connections");
+        let ids: Vec<i32> = connection.query(QUERY)?.try_into()?;
+        assert_eq!(ids.len(), 10);
+
+        let snapshot = shared_statistics.snapshot();
+        assert_eq!(snapshot.message_type("Authenticate").unwrap().count(), 2);
+        assert_eq!(snapshot.message_type("ExecuteDirect").unwrap().count(), 3);
+        assert_eq!(snapshot.message_type("FetchNext").unwrap().count(), 6);
+        assert_eq!(snapshot.result_set_count(), 2);
+        assert!((snapshot.fetch_roundtrips_per_result_set() - 3.0).abs() < f64::EPSILON);
+        assert_eq!(snapshot.server_errors().collect::<Vec<_>>(), [(257, 1)]);
+        Ok(())
+    }
+
+    fn prometheus_text(shared_statistics: &SharedStatistics) {
+        info!("the shared statistics can be exported in the Prometheus text format");
+        let text = shared_statistics.prometheus_text(&[("pool", "test")]);
+        for line in [
+            "# TYPE hdbconnect_roundtrip_duration_seconds histogram",
+            "hdbconnect_roundtrips_total{pool=\"test\",message_type=\"FetchNext\"} 6",
+            "hdbconnect_roundtrip_errors_total{pool=\"test\",message_type=\"ExecuteDirect\"} 1",
+            "hdbconnect_roundtrip_duration_seconds_bucket{pool=\"test\",message_type=\"ExecuteDirect\",le=\"+Inf\"} 3",
+            "hdbconnect_roundtrip_duration_seconds_count{pool=\"test\",message_type=\"FetchNext\"} 6",
+            "hdbconnect_result_sets_total{pool=\"test\"} 2",
+            "hdbconnect_fetch_roundtrips_total{pool=\"test\"} 6",
+            "hdbconnect_server_errors_total{pool=\"test\",code=\"257\"} 1",
+        ] {
+            assert!(text.lines().any(|l| l == line), "{line}\n{text}");
+        }
+    }
+
+    fn reset(connection: &Connection, shared_statistics: &SharedStatistics) -> HdbResult<()> {
+        info!("resetting the statistics of a connection does not affect the shared statistics");
+        connection.reset_statistics()?;
+        assert_eq!(connection.statistics()?.roundtrips().roundtrip_count(), 0);
+        assert_eq!(shared_statistics.snapshot().result_set_count(), 2);
+
+        shared_statistics.reset();
+        assert_eq!(shared_statistics.snapshot().roundtrip_count(), 0);
+        connection.query(QUERY)?;
+        let snapshot = shared_statistics.snapshot();
+        assert_eq!(snapshot.message_type("ExecuteDirect").unwrap().count(), 1);
+        assert_eq!(snapshot.fetch_roundtrip_count(), 0);
+        Ok(())
+    }
+}
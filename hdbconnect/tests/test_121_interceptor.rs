@@ -0,0 +1,289 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{MockColumn, MockParameter, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, ExecutionOutcome, HdbError, HdbResult, HdbValue,
+        Interceptor, ServerUsage, TypeId,
+    };
+    use log::info;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    const QUERY: &str = "select ID from NUMBERS";
+    const QUERY_WITH_HINT: &str = "select ID from NUMBERS with hint(NO_CS_JOIN)";
+    const INSERT: &str = "insert into PERSONS (ID, NAME) values (?, ?)";
+    const UNKNOWN_WITH_HINT: &str = "select * from UNKNOWN with hint(NO_CS_JOIN)";
+    const SET_SCHEMA_A: &str = "SET SCHEMA TENANT_A";
+    const SET_SCHEMA_B: &str = "SET SCHEMA TENANT_B";
+
+    // Records all hook invocations as text, adds a hint to queries, vetoes DROP statements,
+    // and switches to the schema of the current tenant.
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+        accum_proc_time: Mutex<Duration>,
+        tenant: Mutex<Option<String>>,
+    }
+    impl Recorder {
+        fn set_tenant(&self, tenant: Option<&str>) {
+            *self.tenant.lock().unwrap() = tenant.map(ToString::to_string);
+        }
+        fn take_events(&self) -> Vec<String> {
+            std::mem::take(&mut *self.events.lock().unwrap())
+        }
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+    impl Interceptor for Recorder {
+        fn rewrite_sql(&self, sql: &str) -> Option<String> {
+            sql.starts_with("select")
+                .then(|| format!("{sql} with hint(NO_CS_JOIN)"))
+        }
+
+        fn session_setup(&self, _sql: &str) -> Option<Vec<String>> {
+            self.tenant
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|tenant| vec![format!("SET SCHEMA {tenant}")])
+        }
+
+        fn before_execute(&self, sql: &str, params: &[&[HdbValue<'_>]]) -> HdbResult<()> {
+            if sql.starts_with("drop") {
+                return Err(HdbError::Usage(format!("not allowed: {sql}").into()));
+            }
+            let params: Vec<Vec<String>> = params
+                .iter()
+                .map(|row| row.iter().map(ToString::to_string).collect())
+                .collect();
+            self.record(format!("before {sql} {params:?}"));
+            Ok(())
+        }
+
+        fn after_execute(
+            &self,
+            sql: &str,
+            outcome: &ExecutionOutcome<'_>,
+            server_usage: &ServerUsage,
+            elapsed: Duration,
+        ) {
+            assert!(elapsed > Duration::ZERO);
+            *self.accum_proc_time.lock().unwrap() = server_usage.accum_proc_time;
+            let columns: Vec<Vec<&str>> = outcome
+                .result_set_metadata()
+                .iter()
+                .map(|metadata| metadata.iter().map(|field| field.columnname()).collect())
+                .collect();
+            assert_eq!(columns.len(), outcome.result_set_count());
+            self.record(format!(
+                "after {sql} result_sets={columns:?} affected_rows={:?}",
+                outcome.affected_rows()
+            ));
+        }
+
+        fn on_error(&self, sql: &str, error: &HdbError, elapsed: Duration) {
+            assert!(elapsed > Duration::ZERO);
+            self.record(format!(
+                "error {sql} {:?}",
+                error.server_error().map(hdbconnect::ServerError::code)
+            ));
+        }
+    }
+
+    #[test] // cargo test --features mock_server --test test_121_interceptor -- --nocapture
+    fn test_121_interceptor() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            QUERY_WITH_HINT,
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        mock.on_statement_with_parameters(
+            INSERT,
+            vec![
+                MockParameter::new(TypeId::INT).with_name("ID"),
+                MockParameter::new(TypeId::NVARCHAR).with_name("NAME"),
+            ],
+            MockResponse::AffectedRows(1),
+        );
+        mock.on_statement(SET_SCHEMA_A, MockResponse::Success);
+        mock.on_statement(SET_SCHEMA_B, MockResponse::Success);
+
+        let recorder = Arc::new(Recorder::default());
+        let config = ConnectionConfiguration::default()
+            .with_interceptor(Some(Arc::clone(&recorder) as Arc<dyn Interceptor>))
+            .with_session_recovery(true);
+        let connection = Connection::with_configuration(mock.url(), &config)?;
+        recorder.take_events();
+
+        rewritten_query(&mock, &connection, &recorder)?;
+        prepared_statement(&connection, &recorder)?;
+        vetoed_statement(&mock, &connection, &recorder);
+        failed_statement(&connection, &recorder);
+        session_setup(&mock, &connection, &recorder)?;
+        session_setup_after_recovery(&mock, &connection, &recorder)
+    }
+
+    fn rewritten_query(
+        mock: &MockServer,
+        connection: &Connection,
+        recorder: &Recorder,
+    ) -> HdbResult<()> {
+        info!("the interceptor rewrites the SQL text and observes the execution");
+        let ids: Vec<i32> = connection.query(QUERY)?.try_into()?;
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(mock.executions().pop().unwrap().sql(), QUERY_WITH_HINT);
+        assert_eq!(
+            recorder.take_events(),
+            [
+                format!("before {QUERY_WITH_HINT} []"),
+                format!("after {QUERY_WITH_HINT} result_sets=[[\"ID\"]] affected_rows=[]"),
+            ]
+        );
+        Ok(())
+    }
+
+    fn prepared_statement(connection: &Connection, recorder: &Recorder) -> HdbResult<()> {
+        info!("the interceptor sees the parameters of prepared statements");
+        let mut stmt = connection.prepare(INSERT)?;
+        stmt.execute(&(1_i32, "Alice"))?;
+        assert_eq!(
+            recorder.take_events(),
+            [
+                format!("before {INSERT} [[\"1\", \"Alice\"]]"),
+                format!("after {INSERT} result_sets=[] affected_rows=[1]"),
+            ]
+        );
+
+        info!("a batch is passed with one slice per parameter row");
+        stmt.add_batch(&(2_i32, "Bob"))?;
+        stmt.add_batch(&(3_i32, "Carol"))?;
+        stmt.execute_batch()?;
+        assert_eq!(
+            recorder.take_events(),
+            [
+                format!("before {INSERT} [[\"2\", \"Bob\"], [\"3\", \"Carol\"]]"),
+                format!("after {INSERT} result_sets=[] affected_rows=[1, 1]"),
+            ]
+        );
+
+        info!("after_execute gets the server usage of the prepared statement");
+        assert!(stmt.server_usage().accum_proc_time > Duration::ZERO);
+        assert_eq!(
+            *recorder.accum_proc_time.lock().unwrap(),
+            stmt.server_usage().accum_proc_time
+        );
+        Ok(())
+    }
+
+    fn vetoed_statement(mock: &MockServer, connection: &Connection, recorder: &Recorder) {
+        info!("an error from before_execute prevents the execution");
+        let execution_count = mock.executions().len();
+        let error = connection.exec("drop table PERSONS").unwrap_err();
+        assert!(matches!(error, HdbError::Usage(_)), "{error:?}");
+        assert_eq!(mock.executions().len(), execution_count);
+        assert!(recorder.take_events().is_empty());
+    }
+
+    fn failed_statement(connection: &Connection, recorder: &Recorder) {
+        info!("on_error is called for failed executions");
+        connection.query("select * from UNKNOWN").unwrap_err();
+        assert_eq!(
+            recorder.take_events(),
+            [
+                format!("before {UNKNOWN_WITH_HINT} []"),
+                format!("error {UNKNOWN_WITH_HINT} Some(257)"),
+            ]
+        );
+    }
+
+    fn session_setup(
+        mock: &MockServer,
+        connection: &Connection,
+        recorder: &Recorder,
+    ) -> HdbResult<()> {
+        info!("the session setup is executed before the statement, and is not intercepted");
+        recorder.set_tenant(Some("TENANT_A"));
+        let count = mock.executions().len();
+        connection.query(QUERY)?;
+        assert_eq!(executed_since(mock, count), [SET_SCHEMA_A, QUERY_WITH_HINT]);
+        assert_eq!(
+            recorder.take_events(),
+            [
+                format!("before {QUERY_WITH_HINT} []"),
+                format!("after {QUERY_WITH_HINT} result_sets=[[\"ID\"]] affected_rows=[]"),
+            ]
+        );
+
+        info!("an unchanged session setup is not executed again");
+        let count = mock.executions().len();
+        connection.query(QUERY)?;
+        assert_eq!(executed_since(mock, count), [QUERY_WITH_HINT]);
+        recorder.take_events();
+
+        info!("a prepared statement is prepared with the session setup");
+        recorder.set_tenant(Some("TENANT_B"));
+        let count = mock.executions().len();
+        let mut stmt = connection.prepare(INSERT)?;
+        assert_eq!(executed_since(mock, count), [SET_SCHEMA_B]);
+        recorder.set_tenant(Some("TENANT_A"));
+        stmt.execute(&(4_i32, "Dave"))?;
+        assert_eq!(executed_since(mock, count), [SET_SCHEMA_B, INSERT]);
+        recorder.take_events();
+
+        info!("a failing session setup prevents the execution");
+        recorder.set_tenant(Some("UNKNOWN"));
+        let count = mock.executions().len();
+        connection.query(QUERY).unwrap_err();
+        assert!(executed_since(mock, count).is_empty());
+        assert_eq!(
+            recorder.take_events(),
+            [
+                format!("before {QUERY_WITH_HINT} []"),
+                format!("error {QUERY_WITH_HINT} Some(257)"),
+            ]
+        );
+        Ok(())
+    }
+
+    fn session_setup_after_recovery(
+        mock: &MockServer,
+        connection: &Connection,
+        recorder: &Recorder,
+    ) -> HdbResult<()> {
+        info!("after a session recovery, statements are prepared again with their session setup");
+        recorder.set_tenant(Some("TENANT_B"));
+        let mut stmt = connection.prepare(INSERT)?;
+        recorder.set_tenant(Some("TENANT_A"));
+        connection.query(QUERY)?;
+        mock.break_connections();
+        let count = mock.executions().len();
+        connection.query(QUERY)?;
+        assert_eq!(
+            executed_since(mock, count),
+            [SET_SCHEMA_A, SET_SCHEMA_B, SET_SCHEMA_A, QUERY_WITH_HINT]
+        );
+        stmt.execute(&(5_i32, "Eve"))?;
+        recorder.set_tenant(None);
+        Ok(())
+    }
+
+    fn executed_since(mock: &MockServer, count: usize) -> Vec<String> {
+        mock.executions()[count..]
+            .iter()
+            .map(|execution| execution.sql().to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,188 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect::{
+        mock_server::{MockColumn, MockParameter, MockResponse, MockResultSet, MockServer},
+        Connection, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+    use serde::Serialize;
+    use std::collections::HashMap;
+
+    const INSERT: &str = "insert into PERSONS (ID, NAME, CITY) values (?, ?, ?)";
+    const QUERY: &str = "select NAME from PERSONS where ID = ? or (PARENT = ? and NAME <> ':no')";
+    const CALL: &str = "call GET_NAME(?, ?)";
+
+    // The fields are deliberately not in the order of the placeholders.
+    #[derive(Serialize)]
+    struct Person {
+        city: Option<String>,
+        name: String,
+        comment: String,
+        id: i32,
+    }
+    impl Person {
+        fn new(id: i32, name: &str, city: Option<&str>) -> Self {
+            Self {
+                city: city.map(ToString::to_string),
+                name: name.to_string(),
+                comment: "not bound".to_string(),
+                id,
+            }
+        }
+    }
+
+    #[test] // cargo test --features mock_server --test test_122_named_parameters -- --nocapture
+    fn test_122_named_parameters() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+        super::test_utils::initialize_crypto();
+
+        let mock = MockServer::start()?;
+        mock.on_statement_with_parameters(
+            INSERT,
+            vec![
+                MockParameter::new(TypeId::INT),
+                MockParameter::new(TypeId::NVARCHAR),
+                MockParameter::new(TypeId::NVARCHAR).nullable(),
+            ],
+            MockResponse::AffectedRows(1),
+        );
+        mock.on_statement_with_parameters(
+            QUERY,
+            vec![
+                MockParameter::new(TypeId::INT),
+                MockParameter::new(TypeId::INT),
+            ],
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("NAME", TypeId::NVARCHAR)])
+                    .with_row(vec![HdbValue::STRING("Alice".to_string())]),
+            ),
+        );
+        mock.on_statement_with_parameters(
+            CALL,
+            vec![
+                MockParameter::new(TypeId::INT).with_name("P_ID"),
+                MockParameter::new(TypeId::NVARCHAR)
+                    .with_name("P_NAME")
+                    .output(),
+            ],
+            MockResponse::Success,
+        );
+
+        let connection = Connection::new(mock.url())?;
+        bind_struct(&mock, &connection)?;
+        bind_map(&mock, &connection)?;
+        bind_call(&mock, &connection)?;
+        bind_errors(&mock, &connection)
+    }
+
+    fn bind_struct(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("named placeholders are replaced and bound to the fields of a struct");
+        let mut stmt = connection
+            .prepare_named("insert into PERSONS (ID, NAME, CITY) values (:id, :name, :city)")?;
+        let descriptors = stmt.parameter_descriptors();
+        assert!(descriptors.has_named_placeholders());
+        let names: Vec<_> = descriptors.iter_in().map(|d| d.name()).collect();
+        assert_eq!(names, [Some("id"), Some("name"), Some("city")]);
+
+        stmt.execute(&Person::new(1, "Alice", Some("Berlin")))?;
+        let execution = mock.executions().pop().unwrap();
+        assert_eq!(execution.sql(), INSERT);
+        assert_same_values(
+            execution.parameter_rows(),
+            &[vec![
+                HdbValue::INT(1),
+                HdbValue::STRING("Alice".to_string()),
+                HdbValue::STRING("Berlin".to_string()),
+            ]],
+        );
+
+        info!("batches are bound by name, too");
+        stmt.add_batch(&Person::new(2, "Bob", None))?;
+        stmt.add_batch(&Person::new(3, "Carol", Some("Paris")))?;
+        let affected_rows = stmt.execute_batch()?.into_affected_rows()?;
+        assert_eq!(affected_rows, [1, 1]);
+        assert_same_values(
+            mock.executions().pop().unwrap().parameter_rows(),
+            &[
+                vec![
+                    HdbValue::INT(2),
+                    HdbValue::STRING("Bob".to_string()),
+                    HdbValue::NULL,
+                ],
+                vec![
+                    HdbValue::INT(3),
+                    HdbValue::STRING("Carol".to_string()),
+                    HdbValue::STRING("Paris".to_string()),
+                ],
+            ],
+        );
+        Ok(())
+    }
+
+    fn bind_map(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("a name can be used repeatedly, and be bound to the entry of a map");
+        let mut stmt = connection.prepare_named(
+            "select NAME from PERSONS where ID = @id or (PARENT = @id and NAME <> ':no')",
+        )?;
+        let input: HashMap<&str, i32> = [("id", 7)].into_iter().collect();
+        let names: Vec<String> = stmt.execute(&input)?.into_result_set()?.try_into()?;
+        assert_eq!(names, ["Alice"]);
+        let execution = mock.executions().pop().unwrap();
+        assert_eq!(execution.sql(), QUERY);
+        assert_same_values(
+            execution.parameter_rows(),
+            &[vec![HdbValue::INT(7), HdbValue::INT(7)]],
+        );
+        Ok(())
+    }
+
+    fn bind_call(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("only IN parameters of a procedure call are named after the placeholders");
+        let mut stmt = connection.prepare_named("call GET_NAME(:id, :name)")?;
+        let descriptors = stmt.parameter_descriptors();
+        let in_names: Vec<_> = descriptors.iter_in().map(|d| d.name()).collect();
+        assert_eq!(in_names, [Some("id")]);
+        let out_names: Vec<_> = descriptors.iter_out().map(|d| d.name()).collect();
+        assert_eq!(out_names, [Some("P_NAME")]);
+
+        info!("the OUT parameter is not bound, although the input has a field with its name");
+        stmt.execute(&Person::new(7, "Grace", None))?;
+        let execution = mock.executions().pop().unwrap();
+        assert_eq!(execution.sql(), CALL);
+        assert_same_values(execution.parameter_rows(), &[vec![HdbValue::INT(7)]]);
+        Ok(())
+    }
+
+    fn bind_errors(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("mixing named and positional placeholders is rejected before the roundtrip");
+        let call_count = connection.statistics()?.call_count();
+        let error = connection
+            .prepare_named("insert into PERSONS (ID, NAME, CITY) values (:id, ?, ?)")
+            .unwrap_err();
+        assert!(matches!(error, HdbError::Usage(_)), "{error:?}");
+        assert_eq!(connection.statistics()?.call_count(), call_count);
+
+        info!("a missing value is a usage error");
+        let execution_count = mock.executions().len();
+        let mut stmt = connection
+            .prepare_named("insert into PERSONS (ID, NAME, CITY) values (:id, :name, :town)")?;
+        let error = stmt
+            .execute(&Person::new(4, "Dave", Some("Rome")))
+            .unwrap_err();
+        assert!(matches!(error, HdbError::Usage(_)), "{error:?}");
+
+        info!("input that has no field names cannot be bound by name");
+        let error = stmt.execute(&(4, "Dave", "Rome")).unwrap_err();
+        assert!(matches!(error, HdbError::Serialization { .. }), "{error:?}");
+        assert_eq!(mock.executions().len(), execution_count);
+        Ok(())
+    }
+
+    fn assert_same_values(actual: &[Vec<HdbValue<'static>>], expected: &[Vec<HdbValue<'static>>]) {
+        assert_eq!(format!("{actual:?}"), format!("{expected:?}"));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    ConnectParams, Connection, ConnectionConfiguration, HdbError, HdbResult, IntoConnectParams,
+    AsyncCredentialProvider, ConnectParams, Connection, ConnectionConfiguration,
+    CredentialProvider, HdbError, HdbResult, IntoConnectParams,
 };
 use rocket_db_pools::{figment::Figment, Pool};
 
@@ -30,6 +31,41 @@ use rocket_db_pools::{figment::Figment, Pool};
 /// # Ok(())}
 /// ```
 ///
+/// ## Rotating passwords
+///
+/// A pool that is created with `ConnectParams` that have an
+/// [`AsyncCredentialProvider`](crate::AsyncCredentialProvider)
+/// or a [`CredentialProvider`](crate::CredentialProvider) asks the provider
+/// for the password whenever it opens a new connection.
+///
+/// Pools that are initialized from rocket's configuration get their provider with
+/// [`with_credential_provider`](HanaPoolForRocket::with_credential_provider)
+/// or [`with_async_credential_provider`](HanaPoolForRocket::with_async_credential_provider):
+///
+/// ```rust,no_run
+/// use hdbconnect_async::{AsyncCredentialProvider, HanaPoolForRocket};
+/// use rocket_db_pools::{figment::Figment, Pool};
+/// # use hdbconnect_async::HdbResult;
+///
+/// struct Vault;
+/// #[async_trait::async_trait]
+/// impl AsyncCredentialProvider for Vault {
+///     async fn password(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///         // fetch the current password from the secret store
+/// #       Ok(String::new())
+///     }
+/// }
+///
+/// # async fn foo(figment: &Figment) -> HdbResult<()> {
+/// let pool = HanaPoolForRocket::from_figment(figment)?
+///     .with_async_credential_provider(Vault);
+/// let conn = pool.get().await?;
+/// # Ok(())}
+/// ```
+///
+/// Note that rocket's `Pool::init` does not know about the provider, so a pool that
+/// rocket initializes itself must get its password from the configuration.
+///
 #[derive(Debug, Clone)]
 pub struct HanaPoolForRocket {
     connect_params: ConnectParams,
@@ -62,14 +98,14 @@ impl HanaPoolForRocket {
             connect_config: c,
         })
     }
-}
 
-#[rocket::async_trait]
-impl Pool for HanaPoolForRocket {
-    type Connection = Connection;
-    type Error = HdbError;
-
-    async fn init(figment: &Figment) -> HdbResult<Self> {
+    /// Creates a new `HanaPoolForRocket` from rocket's configuration,
+    /// without trying to connect.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::ConnParams` or `HdbError::Usage` if the configuration is not usable
+    pub fn from_figment(figment: &Figment) -> HdbResult<Self> {
         let connect_params =
             figment
                 .extract::<ConnectParams>()
@@ -79,10 +115,46 @@ impl Pool for HanaPoolForRocket {
         let connect_config = figment.extract::<ConnectionConfiguration>().map_err(|_| {
             HdbError::Usage(std::borrow::Cow::from("Incorrect ConnectionConfiguration"))
         })?;
-        let pool = Self {
+        Ok(Self {
             connect_params,
             connect_config,
-        };
+        })
+    }
+
+    /// Sets a credential provider, which is asked for the password
+    /// whenever the pool opens a new connection.
+    #[must_use]
+    pub fn with_credential_provider<P: CredentialProvider + 'static>(
+        mut self,
+        credential_provider: P,
+    ) -> Self {
+        self.connect_params = self
+            .connect_params
+            .with_credential_provider(credential_provider);
+        self
+    }
+
+    /// Sets an async credential provider, which is asked for the password
+    /// whenever the pool opens a new connection.
+    #[must_use]
+    pub fn with_async_credential_provider<P: AsyncCredentialProvider + 'static>(
+        mut self,
+        credential_provider: P,
+    ) -> Self {
+        self.connect_params = self
+            .connect_params
+            .with_async_credential_provider(credential_provider);
+        self
+    }
+}
+
+#[rocket::async_trait]
+impl Pool for HanaPoolForRocket {
+    type Connection = Connection;
+    type Error = HdbError;
+
+    async fn init(figment: &Figment) -> HdbResult<Self> {
+        let pool = Self::from_figment(figment)?;
         // try getting a connection to ensure it works
         pool.get().await.map(|_| pool)
     }
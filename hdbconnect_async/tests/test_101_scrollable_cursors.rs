@@ -0,0 +1,132 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockParameter, MockResponse, MockResultSet, MockServer},
+        Connection, HdbError, HdbResult, HdbValue, ResultSet, Row, TypeId,
+    };
+    use log::info;
+
+    #[tokio::test] // cargo test --features mock_server --test test_101_scrollable_cursors -- --nocapture
+    async fn test_101_scrollable_cursors() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        let mock = MockServer::start()?;
+        mock.on_statement("select ID from NUMBERS", numbers(20));
+        mock.on_statement_with_parameters(
+            "select ID from NUMBERS where ID < ?",
+            vec![MockParameter::new(TypeId::INT)],
+            numbers(20),
+        );
+        mock.on_statement("select ID from NOTHING", numbers(0));
+
+        let connection = Connection::new(mock.url()).await?;
+        connection.set_fetch_size(5).await;
+
+        not_scrollable(&connection).await?;
+        scroll(&connection).await?;
+        scroll_prepared(&connection).await?;
+        scroll_empty(&connection).await
+    }
+
+    async fn not_scrollable(connection: &Connection) -> HdbResult<()> {
+        info!("result sets are not scrollable by default");
+        assert!(!connection.is_scrollable_cursor().await);
+        let mut rs = connection.query("select ID from NUMBERS").await?;
+        assert_eq!(id(rs.next_row().await?), Some(0));
+        assert!(matches!(rs.fetch_first().await, Err(HdbError::Usage(_))));
+        Ok(())
+    }
+
+    async fn scroll(connection: &Connection) -> HdbResult<()> {
+        connection.set_scrollable_cursor(true).await;
+        assert!(connection.is_scrollable_cursor().await);
+        let mut rs = connection.query("select ID from NUMBERS").await?;
+
+        info!("first and last");
+        assert_eq!(id(rs.fetch_last().await?), Some(19));
+        assert_eq!(id(rs.next_row().await?), None);
+        assert_eq!(id(rs.fetch_first().await?), Some(0));
+        assert_eq!(id(rs.next_row().await?), Some(1));
+
+        info!("absolute positions");
+        assert_eq!(id(rs.fetch_absolute(12).await?), Some(11));
+        assert_eq!(id(rs.next_row().await?), Some(12));
+        assert_eq!(id(rs.fetch_absolute(-1).await?), Some(19));
+        assert_eq!(id(rs.fetch_absolute(-20).await?), Some(0));
+        assert_eq!(id(rs.fetch_absolute(21).await?), None);
+        assert_eq!(id(rs.fetch_absolute(-21).await?), None);
+        assert_eq!(id(rs.next_row().await?), Some(0));
+        assert!(matches!(
+            rs.fetch_absolute(0).await,
+            Err(HdbError::Usage(_))
+        ));
+
+        info!("relative positions");
+        assert_eq!(id(rs.fetch_absolute(3).await?), Some(2));
+        assert_eq!(id(rs.fetch_relative(2).await?), Some(4)); // within the buffer
+        assert_eq!(id(rs.fetch_relative(4).await?), Some(8)); // beyond the buffer
+        assert_eq!(id(rs.fetch_relative(-5).await?), Some(3));
+        assert_eq!(id(rs.fetch_relative(0).await?), Some(3));
+        assert_eq!(id(rs.fetch_relative(1).await?), Some(4));
+        assert_eq!(id(rs.fetch_relative(-10).await?), None);
+        assert_eq!(id(rs.fetch_relative(1).await?), Some(0));
+
+        info!("scroll back after reading to the end");
+        let rest = collect(&mut rs).await?;
+        assert_eq!(rest, (1..20).collect::<Vec<_>>());
+        assert_eq!(id(rs.fetch_relative(-3).await?), Some(16));
+        assert_eq!(collect(&mut rs).await?, vec![17, 18, 19]);
+        assert_eq!(id(rs.fetch_first().await?), Some(0));
+        assert_eq!(rs.total_number_of_rows().await?, 19);
+
+        connection.set_scrollable_cursor(false).await;
+        Ok(())
+    }
+
+    async fn scroll_prepared(connection: &Connection) -> HdbResult<()> {
+        info!("prepared statement with a scrollable cursor");
+        let mut stmt = connection
+            .prepare("select ID from NUMBERS where ID < ?")
+            .await?;
+        assert!(!stmt.is_scrollable_cursor());
+        stmt.set_scrollable_cursor(true);
+        let mut rs = stmt.execute(&100_i32).await?.into_result_set()?;
+        assert_eq!(id(rs.fetch_last().await?), Some(19));
+        assert_eq!(id(rs.fetch_relative(-10).await?), Some(9));
+        assert_eq!(id(rs.next_row().await?), Some(10));
+        Ok(())
+    }
+
+    async fn scroll_empty(connection: &Connection) -> HdbResult<()> {
+        info!("empty result set");
+        connection.set_scrollable_cursor(true).await;
+        let mut rs = connection.query("select ID from NOTHING").await?;
+        assert_eq!(id(rs.next_row().await?), None);
+        assert_eq!(id(rs.fetch_first().await?), None);
+        assert_eq!(id(rs.fetch_last().await?), None);
+        Ok(())
+    }
+
+    fn numbers(count: i32) -> MockResponse {
+        MockResponse::ResultSet(
+            MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                .with_rows((0..count).map(|i| vec![HdbValue::INT(i)])),
+        )
+    }
+
+    fn id(o_row: Option<Row>) -> Option<i32> {
+        o_row.map(|mut row| row.next_try_into().unwrap())
+    }
+
+    async fn collect(rs: &mut ResultSet) -> HdbResult<Vec<i32>> {
+        let mut ids = Vec::new();
+        while let Some(row) = rs.next_row().await? {
+            ids.push(row.try_into()?);
+        }
+        Ok(ids)
+    }
+}
@@ -0,0 +1,185 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockParameter, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+
+    #[tokio::test] // cargo test --features mock_server --test test_102_session_recovery -- --nocapture
+    async fn test_102_session_recovery() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        let mock = MockServer::start()?;
+        mock.on_statement("select ID from NUMBERS", numbers(20));
+        mock.on_statement_with_parameters(
+            "select ID from NUMBERS where ID < ?",
+            vec![MockParameter::new(TypeId::INT)],
+            numbers(3),
+        );
+        mock.on_statement_with_parameters(
+            "insert into NUMBERS values(?)",
+            vec![MockParameter::new(TypeId::INT)],
+            MockResponse::AffectedRows(1),
+        );
+        mock.on_statement("delete from NUMBERS", MockResponse::AffectedRows(20));
+
+        without_recovery(&mock).await?;
+
+        let config = ConnectionConfiguration::default().with_session_recovery(true);
+        let connection = Connection::with_configuration(mock.url(), &config).await?;
+        connection.set_fetch_size(5).await;
+        recover_query(&mock, &connection).await?;
+        recover_prepared_statements(&mock, &connection).await?;
+        dml_is_not_repeated(&mock, &connection).await?;
+        open_transaction_is_lost(&mock, &connection).await
+    }
+
+    async fn without_recovery(mock: &MockServer) -> HdbResult<()> {
+        info!("without session recovery, a broken connection is reported");
+        let connection = Connection::new(mock.url()).await?;
+        connection.query("select ID from NUMBERS").await?;
+        mock.break_connections();
+        assert!(connection.query("select ID from NUMBERS").await.is_err());
+        assert!(connection.is_broken().await);
+        Ok(())
+    }
+
+    async fn recover_query(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("a query is repeated transparently on a new session");
+        let connection_count = mock.connection_count();
+        mock.break_connections();
+        let ids: Vec<i32> = connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await?;
+        assert_eq!(ids, (0..20).collect::<Vec<_>>());
+        assert_eq!(mock.connection_count(), connection_count + 1);
+        assert!(!connection.is_broken().await);
+        assert_eq!(connection.fetch_size().await, 5);
+        assert!(connection.is_auto_commit().await);
+        Ok(())
+    }
+
+    async fn recover_prepared_statements(
+        mock: &MockServer,
+        connection: &Connection,
+    ) -> HdbResult<()> {
+        info!("prepared statements are prepared again on the new session");
+        let mut query = connection
+            .prepare("select ID from NUMBERS where ID < ?")
+            .await?;
+        let mut insert = connection.prepare("insert into NUMBERS values(?)").await?;
+        mock.break_connections();
+
+        let ids: Vec<i32> = query
+            .execute(&3_i32)
+            .await?
+            .into_result_set()?
+            .try_into()
+            .await?;
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        info!("new statement ids must not be confused with those of the previous session");
+        let mut query2 = connection
+            .prepare("select ID from NUMBERS where ID < ?")
+            .await?;
+        assert_eq!(
+            insert.execute(&17_i32).await?.into_affected_rows()?,
+            vec![1]
+        );
+        let ids: Vec<i32> = query2
+            .execute(&2_i32)
+            .await?
+            .into_result_set()?
+            .try_into()
+            .await?;
+        assert_eq!(ids, vec![0, 1, 2]);
+        let ids: Vec<i32> = query
+            .execute(&1_i32)
+            .await?
+            .into_result_set()?
+            .try_into()
+            .await?;
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        let executions = mock.executions();
+        let last_three: Vec<(&str, String)> = executions
+            .iter()
+            .rev()
+            .take(3)
+            .rev()
+            .map(|e| (e.sql(), format!("{:?}", e.parameter_rows())))
+            .collect();
+        assert_eq!(
+            last_three,
+            vec![
+                (
+                    "insert into NUMBERS values(?)",
+                    format!("{:?}", [[HdbValue::INT(17)]])
+                ),
+                (
+                    "select ID from NUMBERS where ID < ?",
+                    format!("{:?}", [[HdbValue::INT(2)]])
+                ),
+                (
+                    "select ID from NUMBERS where ID < ?",
+                    format!("{:?}", [[HdbValue::INT(1)]])
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    async fn dml_is_not_repeated(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("a DML statement is not repeated, because it might have been executed already");
+        let no_of_executions = mock.executions().len();
+        mock.break_connections();
+        match connection.dml("delete from NUMBERS").await {
+            Err(HdbError::RecoveryUnsafe {
+                session_recovered, ..
+            }) => assert!(session_recovered),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(mock.executions().len(), no_of_executions);
+        assert_eq!(connection.dml("delete from NUMBERS").await?, 20);
+        Ok(())
+    }
+
+    async fn open_transaction_is_lost(mock: &MockServer, connection: &Connection) -> HdbResult<()> {
+        info!("uncommitted changes are lost, and the connection is re-established later");
+        connection.set_auto_commit(false).await;
+        connection.dml("delete from NUMBERS").await?;
+        mock.break_connections();
+        let connection_count = mock.connection_count();
+        match connection.query("select ID from NUMBERS").await {
+            Err(HdbError::RecoveryUnsafe {
+                session_recovered, ..
+            }) => assert!(!session_recovered),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(mock.connection_count(), connection_count);
+
+        let ids: Vec<i32> = connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await?;
+        assert_eq!(ids.len(), 20);
+        assert_eq!(mock.connection_count(), connection_count + 1);
+        assert!(!connection.is_auto_commit().await);
+        connection.commit().await
+    }
+
+    fn numbers(count: i32) -> MockResponse {
+        MockResponse::ResultSet(
+            MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                .with_rows((0..count).map(|i| vec![HdbValue::INT(i)])),
+        )
+    }
+}
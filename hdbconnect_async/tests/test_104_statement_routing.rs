@@ -0,0 +1,147 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(all(feature = "mock_server", feature = "alpha_routing"))]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockHost, MockParameter, MockPartitioning, MockResponse, MockServer},
+        Connection, HdbResult, TypeId,
+    };
+    use log::info;
+    use std::net::TcpListener;
+
+    const LOCATED: &str = "insert into LOCATED values(?)";
+    const ROUND_ROBIN: &str = "insert into ROUND_ROBIN values(?)";
+    const HASHED: &str = "insert into HASHED values(?, ?)";
+    const ELSEWHERE: &str = "insert into ELSEWHERE values(?)";
+
+    #[tokio::test] // cargo test --features mock_server,alpha_routing --test test_104_statement_routing -- --nocapture
+    async fn test_104_statement_routing() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        // three index servers with volumes 1, 2, 3; volume 4 is not reachable
+        let mocks = [
+            MockServer::start()?,
+            MockServer::start()?,
+            MockServer::start()?,
+        ];
+        let mut topology: Vec<MockHost> = (1..)
+            .zip(&mocks)
+            .map(|(volume_id, mock)| MockHost::new("127.0.0.1", mock.port(), volume_id))
+            .collect();
+        topology.push(MockHost::new("127.0.0.1", unused_port()?, 4));
+        for mock in &mocks {
+            script(mock, &topology);
+        }
+
+        let connection = Connection::new(mocks[0].url()).await?;
+        located(&connection, &mocks).await?;
+        round_robin(&connection, &mocks).await?;
+        hashed(&connection, &mocks).await?;
+        without_auto_commit(&connection, &mocks).await?;
+        unreachable_volume(&connection, &mocks).await
+    }
+
+    async fn located(connection: &Connection, mocks: &[MockServer]) -> HdbResult<()> {
+        info!("executions go to the volume that hosts the table");
+        let mut insert = connection.prepare(LOCATED).await?;
+        for i in 0..3 {
+            assert_eq!(insert.execute(&i).await?.into_affected_rows()?, vec![1]);
+        }
+        assert_eq!(executions(mocks, LOCATED), vec![0, 3, 0]);
+        info!("the secondary session is reused");
+        assert_eq!(mocks[1].connection_count(), 1);
+        Ok(())
+    }
+
+    async fn round_robin(connection: &Connection, mocks: &[MockServer]) -> HdbResult<()> {
+        info!("executions are distributed over round-robin partitions");
+        let mut insert = connection.prepare(ROUND_ROBIN).await?;
+        for i in 0..4 {
+            insert.execute(&i).await?;
+        }
+        assert_eq!(executions(mocks, ROUND_ROBIN), vec![0, 2, 2]);
+        assert_eq!(mocks[2].connection_count(), 1);
+        Ok(())
+    }
+
+    async fn hashed(connection: &Connection, mocks: &[MockServer]) -> HdbResult<()> {
+        info!("executions on hash partitioned tables go to the coordinator");
+        let mut insert = connection.prepare(HASHED).await?;
+        for i in 0..30 {
+            insert.execute(&(i, "a")).await?;
+        }
+        assert_eq!(executions(mocks, HASHED), vec![30, 0, 0]);
+
+        info!("batches, too");
+        for i in 0..30 {
+            insert.add_batch(&(i, "b"))?;
+        }
+        insert.execute_batch().await?;
+        assert_eq!(executions(mocks, HASHED), vec![31, 0, 0]);
+        Ok(())
+    }
+
+    async fn without_auto_commit(connection: &Connection, mocks: &[MockServer]) -> HdbResult<()> {
+        info!("transactions stay on the primary session");
+        connection.set_auto_commit(false).await;
+        let mut insert = connection.prepare(LOCATED).await?;
+        insert.execute(&1).await?;
+        connection.commit().await?;
+        connection.set_auto_commit(true).await;
+        assert_eq!(executions(mocks, LOCATED), vec![1, 3, 0]);
+        Ok(())
+    }
+
+    async fn unreachable_volume(connection: &Connection, mocks: &[MockServer]) -> HdbResult<()> {
+        info!("if the volume cannot be reached, the coordinator is used");
+        let mut insert = connection.prepare(ELSEWHERE).await?;
+        insert.execute(&1).await?;
+        insert.execute(&2).await?;
+        assert_eq!(executions(mocks, ELSEWHERE), vec![2, 0, 0]);
+        Ok(())
+    }
+
+    fn script(mock: &MockServer, topology: &[MockHost]) {
+        mock.set_topology(topology.to_vec());
+        for sql in [LOCATED, ROUND_ROBIN, ELSEWHERE] {
+            mock.on_statement_with_parameters(
+                sql,
+                vec![MockParameter::new(TypeId::INT)],
+                MockResponse::AffectedRows(1),
+            );
+        }
+        mock.on_statement_with_parameters(
+            HASHED,
+            vec![
+                MockParameter::new(TypeId::INT),
+                MockParameter::new(TypeId::NVARCHAR),
+            ],
+            MockResponse::AffectedRows(1),
+        );
+        mock.set_partitioning(LOCATED, MockPartitioning::Located(vec![2]));
+        mock.set_partitioning(ROUND_ROBIN, MockPartitioning::RoundRobin(vec![2, 3]));
+        mock.set_partitioning(
+            HASHED,
+            MockPartitioning::Hash {
+                parameters: vec![0],
+                volumes: vec![1, 2, 3],
+            },
+        );
+        mock.set_partitioning(ELSEWHERE, MockPartitioning::Located(vec![4]));
+    }
+
+    // the number of executions of the statement, per mock
+    fn executions(mocks: &[MockServer], sql: &str) -> Vec<usize> {
+        mocks
+            .iter()
+            .map(|mock| mock.executions().iter().filter(|e| e.sql() == sql).count())
+            .collect()
+    }
+
+    // a local port on which nobody listens
+    fn unused_port() -> HdbResult<u16> {
+        Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+    }
+}
@@ -0,0 +1,107 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer, MockServerConfig},
+        Connection, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+
+    const PATH: &str = "/service/hana";
+
+    #[tokio::test] // cargo test --features mock_server --test test_106_websocket -- --nocapture
+    async fn test_106_websocket() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        websocket().await?;
+        large_replies().await?;
+        wrong_path().await
+    }
+
+    async fn websocket() -> HdbResult<()> {
+        info!("connect with WebSocket, using the URL option");
+        let mock = start_mock()?;
+        let connection = Connection::new(format!(
+            "hdbsql://MOCKUSER:MockPassword1@{}?websocket_path={PATH}",
+            mock.addr()
+        ))
+        .await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+
+        info!("connect with WebSocket, using the builder; the leading slash is optional");
+        let mut builder = mock.connect_params_builder();
+        builder.websocket_path(PATH.trim_start_matches('/'));
+        let params = builder.build()?;
+        assert_eq!(params.websocket_path(), Some(PATH));
+        let connection = Connection::new(params).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+        assert_eq!(mock.connection_count(), 2);
+        Ok(())
+    }
+
+    async fn large_replies() -> HdbResult<()> {
+        info!("replies of more than 64 kB are transported in one message");
+        let mock = start_mock()?;
+        let connection = Connection::new(mock.url()).await?;
+        connection.set_fetch_size(5000).await;
+        let names: Vec<String> = connection
+            .query("select NAME from NAMES")
+            .await?
+            .try_into()
+            .await?;
+        assert_eq!(names.len(), 3000);
+        assert_eq!(names[2999], name(2999));
+        Ok(())
+    }
+
+    async fn wrong_path() -> HdbResult<()> {
+        info!("the upgrade is refused for a wrong path");
+        let mock = start_mock()?;
+        assert!(Connection::new(format!(
+            "hdbsql://MOCKUSER:MockPassword1@{}?websocket_path=/service/other",
+            mock.addr()
+        ))
+        .await
+        .is_err());
+        let mut builder = mock.connect_params_builder();
+        builder.websocket_path("/service/other");
+        assert!(Connection::new(builder.build()?).await.is_err());
+        assert_eq!(mock.connection_count(), 2);
+        Ok(())
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start_with(MockServerConfig::default().with_websocket_path(PATH))?;
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        mock.on_statement(
+            "select NAME from NAMES",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("NAME", TypeId::NVARCHAR)])
+                    .with_rows((0..3000).map(|i| vec![HdbValue::STRING(name(i))])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    fn name(i: usize) -> String {
+        format!("name number {i:05} with some padding to make the rows longer")
+    }
+
+    async fn ids(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await
+    }
+}
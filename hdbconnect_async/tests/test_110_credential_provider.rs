@@ -0,0 +1,187 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        AsyncCredentialProvider, ConnectParams, Connection, ConnectionConfiguration, HdbError,
+        HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    // A stand-in for a secret store, in which the password is rotated
+    #[derive(Default)]
+    struct Vault {
+        password: Mutex<String>,
+        calls: AtomicUsize,
+    }
+    impl Vault {
+        fn rotate(&self, mock: &MockServer, password: &str) {
+            mock.change_password(password);
+            *self.password.lock().unwrap() = password.to_string();
+        }
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    struct VaultClient(Arc<Vault>);
+    #[async_trait::async_trait]
+    impl AsyncCredentialProvider for VaultClient {
+        async fn password(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            self.0.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.0.password.lock().unwrap().clone())
+        }
+    }
+
+    #[tokio::test] // cargo test --features mock_server --test test_110_credential_provider -- --nocapture
+    async fn test_110_credential_provider() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        rotated_password().await?;
+        #[cfg(feature = "bb8_pool")]
+        pooled_connections().await?;
+        #[cfg(feature = "rocket_pool")]
+        rocket_pool().await?;
+        failing_provider().await
+    }
+
+    async fn rotated_password() -> HdbResult<()> {
+        info!("the provider is asked for the password with each logon");
+        let mock = start_mock()?;
+        let vault = Arc::new(Vault::default());
+        vault.rotate(&mock, "Secret1");
+        let params = connect_params(&mock, &vault)?;
+        let config = ConnectionConfiguration::default().with_session_recovery(true);
+        let connection = Connection::with_configuration(&params, &config).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 1);
+
+        info!("reconnects use the rotated password");
+        vault.rotate(&mock, "Secret2");
+        mock.break_connections();
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 2);
+
+        info!("spawned connections use the rotated password");
+        vault.rotate(&mock, "Secret3");
+        let spawned = connection.spawn().await?;
+        assert_eq!(ids(&spawned).await?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 3);
+
+        info!("a stale password is rejected");
+        mock.change_password("Secret4");
+        assert!(Connection::new(&params).await.is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "bb8_pool")]
+    async fn pooled_connections() -> HdbResult<()> {
+        info!("a pool picks up the rotated password for its new connections");
+        let mock = start_mock()?;
+        let vault = Arc::new(Vault::default());
+        vault.rotate(&mock, "Secret1");
+        let pool = bb8::Pool::builder()
+            .max_size(2)
+            .min_idle(Some(0))
+            .test_on_check_out(false)
+            .build(hdbconnect_async::ConnectionManager::new(connect_params(
+                &mock, &vault,
+            )?)?)
+            .await?;
+        let connection = pool.get().await.unwrap();
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+
+        vault.rotate(&mock, "Secret2");
+        let other = pool.get().await.unwrap();
+        assert_eq!(ids(&other).await?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 2);
+        Ok(())
+    }
+
+    #[cfg(feature = "rocket_pool")]
+    async fn rocket_pool() -> HdbResult<()> {
+        use rocket_db_pools::{
+            figment::{providers::Serialized, Figment},
+            Pool,
+        };
+        info!("a pool from rocket's configuration gets its password from the provider");
+        let mock = start_mock()?;
+        let vault = Arc::new(Vault::default());
+        vault.rotate(&mock, "Secret1");
+        let figment = Figment::from(Serialized::defaults(serde_json::json!({
+            "host": mock.addr().ip().to_string(),
+            "port": mock.port(),
+            "dbuser": "MOCKUSER",
+            "compression": "Off",
+            "tls": "Off",
+            "auto_commit": true,
+            "command_options": "HOLD_CURSORS_OVER_COMMIT",
+            "fetch_size": ConnectionConfiguration::DEFAULT_FETCH_SIZE,
+            "lob_read_length": ConnectionConfiguration::DEFAULT_LOB_READ_LENGTH,
+            "lob_write_length": ConnectionConfiguration::DEFAULT_LOB_WRITE_LENGTH,
+            "max_buffer_size": ConnectionConfiguration::DEFAULT_MAX_BUFFER_SIZE,
+            "min_compression_size": ConnectionConfiguration::DEFAULT_MIN_COMPRESSION_SIZE,
+        })));
+        let pool = hdbconnect_async::HanaPoolForRocket::from_figment(&figment)?
+            .with_async_credential_provider(VaultClient(Arc::clone(&vault)));
+        let connection = pool.get().await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+
+        vault.rotate(&mock, "Secret2");
+        let other = pool.get().await?;
+        assert_eq!(ids(&other).await?, vec![1, 2, 3]);
+        assert_eq!(vault.calls(), 2);
+        Ok(())
+    }
+
+    async fn failing_provider() -> HdbResult<()> {
+        info!("a failing provider lets the logon fail");
+        let mock = start_mock()?;
+        let params = ConnectParams::builder()
+            .hostname(mock.addr().ip().to_string())
+            .port(mock.port())
+            .dbuser("MOCKUSER")
+            .credential_provider(|| Err("vault not reachable".into()))
+            .build()?;
+        assert!(matches!(
+            Connection::new(params).await,
+            Err(HdbError::ConnParams { .. })
+        ));
+        Ok(())
+    }
+
+    fn connect_params(mock: &MockServer, vault: &Arc<Vault>) -> HdbResult<ConnectParams> {
+        let mut builder = mock.connect_params_builder();
+        builder
+            .unset_password()
+            .async_credential_provider(VaultClient(Arc::clone(vault)));
+        builder.build()
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    async fn ids(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await
+    }
+}
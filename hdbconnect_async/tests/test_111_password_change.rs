@@ -0,0 +1,96 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer, MockServerConfig},
+        Connection, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+
+    const NEW_PASSWORD: &str = "Final\"Password1";
+
+    #[tokio::test] // cargo test --features mock_server --test test_111_password_change -- --nocapture
+    async fn test_111_password_change() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        password_change_required().await?;
+        password_change().await?;
+        no_password_change_required().await
+    }
+
+    async fn password_change_required() -> HdbResult<()> {
+        info!("a logon that requires a password change fails with a typed error");
+        let mock = start_mock(MockServerConfig::default().with_password_change_required())?;
+        let error = Connection::new(mock.connect_params_builder())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, HdbError::PasswordChangeRequired { .. }));
+        assert_eq!(error.server_error().unwrap().code(), 414);
+        Ok(())
+    }
+
+    async fn password_change() -> HdbResult<()> {
+        info!("the password is changed with the logon");
+        let mock = start_mock(MockServerConfig::default().with_password_change_required())?;
+        let old_params = mock.connect_params_builder().build()?;
+        let connection = Connection::new_with_password_change(&old_params, NEW_PASSWORD).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+
+        info!("spawned connections use the new password");
+        let spawned = connection.spawn().await?;
+        assert_eq!(ids(&spawned).await?, vec![1, 2, 3]);
+
+        info!("only the new password is accepted from now on");
+        assert!(Connection::new(&old_params).await.is_err());
+        let connection = Connection::new(mock.connect_params_builder()).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+        assert_eq!(
+            mock.connect_params_builder()
+                .get_password()
+                .unwrap()
+                .unsecure(),
+            NEW_PASSWORD
+        );
+        Ok(())
+    }
+
+    async fn no_password_change_required() -> HdbResult<()> {
+        info!("without a required password change, the password is not changed");
+        let mock = start_mock(MockServerConfig::default())?;
+        let params = mock.connect_params_builder().build()?;
+        let connection = Connection::new_with_password_change(&params, NEW_PASSWORD).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+        let connection = Connection::new(&params).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+
+        info!("an empty new password is rejected");
+        assert!(matches!(
+            Connection::new_with_password_change(&params, "").await,
+            Err(HdbError::Usage(_))
+        ));
+        Ok(())
+    }
+
+    fn start_mock(config: MockServerConfig) -> HdbResult<MockServer> {
+        let mock = MockServer::start_with(config)?;
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    async fn ids(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await
+    }
+}
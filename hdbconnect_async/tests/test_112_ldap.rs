@@ -0,0 +1,66 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{
+            MockAuthenticator, MockColumn, MockResponse, MockResultSet, MockServer,
+            MockServerConfig,
+        },
+        Connection, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+
+    #[tokio::test] // cargo test --features mock_server --test test_112_ldap -- --nocapture
+    async fn test_112_ldap() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        ldap_logon().await?;
+        wrong_password().await
+    }
+
+    async fn ldap_logon() -> HdbResult<()> {
+        info!("the server selects LDAP, and the client sends the encrypted password");
+        let mock = start_mock()?;
+        let connection = Connection::new(mock.connect_params_builder()).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+
+        info!("spawned connections also log on with LDAP");
+        let spawned = connection.spawn().await?;
+        assert_eq!(ids(&spawned).await?, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    async fn wrong_password() -> HdbResult<()> {
+        info!("a wrong password is rejected");
+        let mock = start_mock()?;
+        let mut builder = mock.connect_params_builder();
+        builder.password("WrongPassword");
+        assert!(Connection::new(builder).await.is_err());
+        Ok(())
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start_with(
+            MockServerConfig::default().with_authenticator(MockAuthenticator::Ldap),
+        )?;
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    async fn ids(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await
+    }
+}
@@ -0,0 +1,118 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+    use std::{
+        io::ErrorKind,
+        net::{SocketAddr, TcpListener, TcpStream},
+        time::{Duration, Instant},
+    };
+
+    #[tokio::test] // cargo test --features mock_server --test test_114_socket_options -- --nocapture
+    async fn test_114_socket_options() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        let mock = start_mock()?;
+        socket_options(&mock).await?;
+        connect_timeout().await
+    }
+
+    async fn socket_options(mock: &MockServer) -> HdbResult<()> {
+        info!("defaults");
+        let config = ConnectionConfiguration::default();
+        assert_eq!(
+            config.connect_timeout(),
+            ConnectionConfiguration::DEFAULT_CONNECT_TIMEOUT
+        );
+        assert_eq!(config.write_timeout(), None);
+        assert!(config.is_tcp_nodelay());
+        assert_eq!(config.tcp_keepalive(), None);
+        assert_eq!(config.tcp_keepalive_retries(), None);
+
+        info!("socket options from the configuration");
+        let config = ConnectionConfiguration::default()
+            .with_write_timeout(Some(Duration::from_secs(5)))
+            .with_tcp_nodelay(false)
+            .with_tcp_keepalive(Some(Duration::from_secs(10)))
+            .with_tcp_keepalive_retries(Some(3));
+        let connection = Connection::with_configuration(mock.url(), &config).await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+
+        info!("socket options from the URL");
+        let connection = Connection::new(format!(
+            "{}?write_timeout=5000&tcp_nodelay=false&tcp_keepalive=10000&tcp_keepalive_retries=3",
+            mock.url()
+        ))
+        .await?;
+        assert_eq!(ids(&connection).await?, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    async fn connect_timeout() -> HdbResult<()> {
+        info!("the connect timeout of the configuration applies");
+        let (_listener, _stalled, addr) = stalled_listener()?;
+        let url = format!("hdbsql://MOCKUSER:MockPassword1@{addr}");
+        let config = ConnectionConfiguration::default()
+            .with_connect_timeout(Some(Duration::from_millis(300)));
+        let start = Instant::now();
+        assert!(is_timeout(
+            Connection::with_configuration(url.as_str(), &config).await
+        ));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        info!("the connect timeout of the URL takes precedence");
+        let config = ConnectionConfiguration::default().with_connect_timeout(None);
+        let start = Instant::now();
+        assert!(is_timeout(
+            Connection::with_configuration(format!("{url}?connect_timeout=300"), &config).await
+        ));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        Ok(())
+    }
+
+    fn is_timeout(result: HdbResult<Connection>) -> bool {
+        matches!(result, Err(HdbError::Io { source }) if source.kind() == ErrorKind::TimedOut)
+    }
+
+    // A listener that does not accept connections, and whose backlog is exhausted,
+    // so that further connection attempts stall
+    fn stalled_listener() -> HdbResult<(TcpListener, Vec<TcpStream>, SocketAddr)> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let mut stalled = Vec::new();
+        for _ in 0..10_000 {
+            match TcpStream::connect_timeout(&addr, Duration::from_millis(100)) {
+                Ok(tcp_stream) => stalled.push(tcp_stream),
+                Err(_) => break,
+            }
+        }
+        Ok((listener, stalled, addr))
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    async fn ids(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await
+    }
+}
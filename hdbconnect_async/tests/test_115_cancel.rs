@@ -0,0 +1,186 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, ExecutionOutcome, HdbError, HdbResult, HdbValue,
+        Interceptor, ServerUsage, SharedStatistics, TypeId,
+    };
+    use log::info;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    const SLOW_QUERY: &str = "select ID from SLOW_NUMBERS";
+
+    #[tokio::test] // cargo test --features mock_server --test test_115_cancel -- --nocapture
+    async fn test_115_cancel() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        let mock = start_mock()?;
+        query_timeout(&mock).await?;
+        cancel_handle(&mock).await?;
+        cancel_without_side_effects(&mock).await
+    }
+
+    async fn query_timeout(mock: &MockServer) -> HdbResult<()> {
+        info!("a statement that finishes in time is not affected");
+        let connection = Connection::new(mock.url()).await?;
+        assert_eq!(connection.query_timeout().await, None);
+        let ids: Vec<i32> = connection
+            .statement_with_timeout("select ID from NUMBERS", Duration::from_secs(1))
+            .await?
+            .into_result_set()?
+            .try_into()
+            .await?;
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        info!("query timeout with a single statement");
+        let start = Instant::now();
+        assert!(is_cancelled(
+            connection
+                .statement_with_timeout(SLOW_QUERY, Duration::from_millis(500))
+                .await
+        ));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(ids_of(&connection).await?, vec![1, 2, 3]);
+
+        info!("query timeout of the connection");
+        let config =
+            ConnectionConfiguration::default().with_query_timeout(Some(Duration::from_secs(1)));
+        let connection = Connection::with_configuration(mock.url(), &config).await?;
+        assert_eq!(
+            connection.query_timeout().await,
+            Some(Duration::from_secs(1))
+        );
+        assert!(is_cancelled(connection.statement(SLOW_QUERY).await));
+        assert_eq!(ids_of(&connection).await?, vec![1, 2, 3]);
+
+        info!("query timeout of a prepared statement");
+        let connection = Connection::new(mock.url()).await?;
+        let mut stmt = connection.prepare(SLOW_QUERY).await?;
+        assert_eq!(stmt.query_timeout(), None);
+        stmt.set_query_timeout(Some(Duration::from_secs(1)));
+        assert!(is_cancelled(stmt.execute(&()).await));
+        assert_eq!(ids_of(&connection).await?, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    async fn cancel_handle(mock: &MockServer) -> HdbResult<()> {
+        info!("cancel a running statement from another task");
+        let connection = Connection::new(mock.url()).await?;
+        let cancel_handle = connection.cancel_handle().await;
+        assert_eq!(cancel_handle.connection_id(), connection.id().await);
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            cancel_handle.cancel().await
+        });
+        let start = Instant::now();
+        assert!(is_cancelled(connection.statement(SLOW_QUERY).await));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        canceller.await.unwrap()?;
+
+        info!("the connection remains usable");
+        assert!(!connection.is_broken().await);
+        assert_eq!(ids_of(&connection).await?, vec![1, 2, 3]);
+        let cancellation = format!("ALTER SYSTEM CANCEL SESSION '{}'", connection.id().await);
+        assert!(mock.executions().iter().any(|e| e.sql() == cancellation));
+
+        info!("cancelling an idle connection does not harm");
+        connection.cancel_handle().await.cancel().await?;
+        assert_eq!(ids_of(&connection).await?, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    async fn cancel_without_side_effects(mock: &MockServer) -> HdbResult<()> {
+        info!("the cancellation is neither intercepted nor counted in the shared statistics");
+        let interceptor = Arc::new(CountingInterceptor::default());
+        let statistics = SharedStatistics::new();
+        let config = ConnectionConfiguration::default()
+            .with_interceptor(Some(Arc::clone(&interceptor) as Arc<dyn Interceptor>))
+            .with_shared_statistics(Some(statistics.clone()))
+            .with_query_timeout(Some(Duration::from_secs(1)));
+        let connection = Connection::with_configuration(mock.url(), &config).await?;
+        assert_eq!(ids_of(&connection).await?, vec![1, 2, 3]);
+        let calls = interceptor.calls();
+        let roundtrips = statistics.snapshot().roundtrip_count();
+        assert!(calls > 0 && roundtrips > 0);
+
+        connection.cancel_handle().await.cancel().await?;
+        assert_eq!(interceptor.calls(), calls);
+        assert_eq!(statistics.snapshot().roundtrip_count(), roundtrips);
+        Ok(())
+    }
+
+    // Counts the invocations of all hooks
+    #[derive(Default)]
+    struct CountingInterceptor(AtomicUsize);
+    impl CountingInterceptor {
+        fn calls(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+        fn count(&self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    impl Interceptor for CountingInterceptor {
+        fn rewrite_sql(&self, _sql: &str) -> Option<String> {
+            self.count();
+            None
+        }
+        fn session_setup(&self, _sql: &str) -> Option<Vec<String>> {
+            self.count();
+            None
+        }
+        fn before_execute(&self, _sql: &str, _params: &[&[HdbValue<'_>]]) -> HdbResult<()> {
+            self.count();
+            Ok(())
+        }
+        fn after_execute(
+            &self,
+            _sql: &str,
+            _outcome: &ExecutionOutcome<'_>,
+            _server_usage: &ServerUsage,
+            _elapsed: Duration,
+        ) {
+            self.count();
+        }
+        fn on_error(&self, _sql: &str, _error: &HdbError, _elapsed: Duration) {
+            self.count();
+        }
+    }
+
+    fn is_cancelled<T>(result: HdbResult<T>) -> bool {
+        matches!(result, Err(HdbError::Cancelled { source }) if source.code() == 139)
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start()?;
+        let result_set = MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+            .with_rows((1..=3).map(|i| vec![HdbValue::INT(i)]));
+        mock.on_statement(
+            "select ID from NUMBERS",
+            MockResponse::ResultSet(result_set.clone()),
+        );
+        mock.on_statement(
+            SLOW_QUERY,
+            MockResponse::delayed(Duration::from_secs(30), MockResponse::ResultSet(result_set)),
+        );
+        Ok(mock)
+    }
+
+    async fn ids_of(connection: &Connection) -> HdbResult<Vec<i32>> {
+        connection
+            .query("select ID from NUMBERS")
+            .await?
+            .try_into()
+            .await
+    }
+}
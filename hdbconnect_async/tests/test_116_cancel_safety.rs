@@ -0,0 +1,185 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    const QUERY: &str = "select ID from NUMBERS";
+    const SLOW_QUERY: &str = "select ID from SLOW_NUMBERS";
+
+    #[tokio::test] // cargo test --features mock_server --test test_116_cancel_safety -- --nocapture
+    async fn test_116_cancel_safety() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        let mock = start_mock()?;
+        cancel_with_timeout(&mock).await?;
+        cancel_at_every_await_point(&mock, false).await?;
+        cancel_at_every_await_point(&mock, true).await?;
+        drop_after_cancellation().await
+    }
+
+    async fn cancel_with_timeout(mock: &MockServer) -> HdbResult<()> {
+        info!("a query that is cancelled by a timeout leaves the connection broken");
+        let connection = Connection::new(mock.url()).await?;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), ids(&connection, SLOW_QUERY))
+                .await
+                .is_err()
+        );
+        assert!(connection.is_broken().await);
+        assert!(is_cancellation_error(&ids(&connection, QUERY).await));
+        assert!(matches!(
+            ids(&connection, QUERY).await,
+            Err(HdbError::ConnectionBroken { source: None })
+        ));
+
+        info!("with session recovery, the connection is re-established");
+        let connection = Connection::with_configuration(
+            mock.url(),
+            &ConnectionConfiguration::default().with_session_recovery(true),
+        )
+        .await?;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), ids(&connection, SLOW_QUERY))
+                .await
+                .is_err()
+        );
+        assert_eq!(ids(&connection, QUERY).await?, expected_ids());
+        assert!(!connection.is_broken().await);
+        Ok(())
+    }
+
+    // Drops the future of a query that fetches its result in several roundtrips
+    // after it was pending for 0, 1, 2, ... times, until it completes.
+    async fn cancel_at_every_await_point(
+        mock: &MockServer,
+        session_recovery: bool,
+    ) -> HdbResult<()> {
+        info!("cancel at every await point, session recovery = {session_recovery}");
+        let config = ConnectionConfiguration::default()
+            .with_fetch_size(3)
+            .with_session_recovery(session_recovery);
+        let mut connection = Connection::with_configuration(mock.url(), &config).await?;
+        for pendings in 0..1000 {
+            if let Some(result) = CancelAfter::new(ids(&connection, QUERY), pendings).await {
+                assert_eq!(result?, expected_ids());
+                assert!(pendings > 0);
+                info!("query completed after being pending {pendings} times");
+                return Ok(());
+            }
+
+            // the next use of the connection either fails cleanly or, with session
+            // recovery, succeeds; it never reads the remainder of the cancelled roundtrip
+            let result = ids(&connection, QUERY).await;
+            if session_recovery {
+                assert_eq!(result?, expected_ids());
+            } else if connection.is_broken().await {
+                assert!(
+                    is_cancellation_error(&result)
+                        || matches!(result, Err(HdbError::ConnectionBroken { source: None }))
+                );
+                connection = Connection::with_configuration(mock.url(), &config).await?;
+            } else {
+                assert_eq!(result?, expected_ids());
+            }
+        }
+        panic!("query did not complete");
+    }
+
+    async fn drop_after_cancellation() -> HdbResult<()> {
+        info!("a connection with a cancelled roundtrip is closed without a disconnect request");
+        let mock = start_mock()?;
+        let connection = Connection::new(mock.url()).await?;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), ids(&connection, SLOW_QUERY))
+                .await
+                .is_err()
+        );
+        drop(connection);
+        // the server replies to the slow query before it reads the next request
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(mock.disconnect_count(), 0);
+
+        info!("an intact connection sends a disconnect request when it is dropped");
+        drop(Connection::new(mock.url()).await?);
+        for _ in 0..100 {
+            if mock.disconnect_count() == 1 {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("no disconnect request was received");
+    }
+
+    fn is_cancellation_error<T>(result: &HdbResult<T>) -> bool {
+        matches!(
+            result,
+            Err(HdbError::ConnectionBroken { source: Some(source) })
+                if matches!(**source, HdbError::Io { ref source }
+                    if source.kind() == std::io::ErrorKind::Interrupted)
+        )
+    }
+
+    // Resolves to None if the wrapped future is pending more often than the given number of times;
+    // the wrapped future is then dropped.
+    struct CancelAfter<F> {
+        inner: Pin<Box<F>>,
+        pendings_left: usize,
+    }
+    impl<F: Future> CancelAfter<F> {
+        fn new(inner: F, pendings: usize) -> Self {
+            Self {
+                inner: Box::pin(inner),
+                pendings_left: pendings,
+            }
+        }
+    }
+    impl<F: Future> Future for CancelAfter<F> {
+        type Output = Option<F::Output>;
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            match self.inner.as_mut().poll(cx) {
+                Poll::Ready(output) => Poll::Ready(Some(output)),
+                Poll::Pending if self.pendings_left == 0 => Poll::Ready(None),
+                Poll::Pending => {
+                    self.pendings_left -= 1;
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start()?;
+        let result_set = MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+            .with_rows(expected_ids().into_iter().map(|i| vec![HdbValue::INT(i)]));
+        mock.on_statement(QUERY, MockResponse::ResultSet(result_set.clone()));
+        mock.on_statement(
+            SLOW_QUERY,
+            MockResponse::delayed(
+                Duration::from_millis(300),
+                MockResponse::ResultSet(result_set),
+            ),
+        );
+        Ok(mock)
+    }
+
+    fn expected_ids() -> Vec<i32> {
+        (1..=10).collect()
+    }
+
+    async fn ids(connection: &Connection, query: &str) -> HdbResult<Vec<i32>> {
+        connection.query(query).await?.try_into().await
+    }
+}
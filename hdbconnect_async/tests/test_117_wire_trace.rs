@@ -0,0 +1,137 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(feature = "mock_server")]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, HdbError, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+    use std::path::{Path, PathBuf};
+
+    const QUERY: &str = "select ID from NUMBERS";
+    const CHANGE_PASSWORD: &str = "ALTER USER MOCKUSER PASSWORD \"Secret1\"";
+    const UNKNOWN: &str = "select * from UNKNOWN";
+
+    #[tokio::test] // cargo test --features mock_server --test test_117_wire_trace -- --nocapture
+    async fn test_117_wire_trace() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        let mock = start_mock()?;
+        let dir =
+            std::env::temp_dir().join(format!("hdbconnect_async_test_117_{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let recorded = record(&mock, &dir).await?;
+        let path = trace_file(&dir)?;
+        redaction(&path)?;
+        replay(&mock, &path, &recorded).await?;
+        deviation(&path).await?;
+
+        std::fs::remove_dir_all(&dir).ok();
+        Ok(())
+    }
+
+    fn config() -> ConnectionConfiguration {
+        ConnectionConfiguration::default().with_fetch_size(3)
+    }
+
+    async fn record(mock: &MockServer, dir: &Path) -> HdbResult<Workload> {
+        info!("record a session");
+        let config = config().with_wire_trace_dir(Some(dir.to_path_buf()));
+        assert_eq!(config.wire_trace_dir(), Some(dir));
+        let connection = Connection::with_configuration(mock.url(), &config).await?;
+        workload(&connection).await
+    }
+
+    fn trace_file(dir: &Path) -> HdbResult<PathBuf> {
+        let files: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].extension().unwrap(), "hdbwire");
+        Ok(files[0].clone())
+    }
+
+    fn redaction(path: &Path) -> HdbResult<()> {
+        info!("authentication data and passwords are redacted");
+        let trace = String::from_utf8_lossy(&std::fs::read(path)?).to_string();
+        assert!(trace.contains("MOCKUSER"));
+        assert!(trace.contains("SCRAMSHA256"));
+        assert!(trace.contains(QUERY));
+        assert!(trace.contains("ALTER USER MOCKUSER PASSWORD***"));
+        assert!(!trace.contains("Secret1"));
+        Ok(())
+    }
+
+    async fn replay(mock: &MockServer, path: &Path, recorded: &Workload) -> HdbResult<()> {
+        info!("replay the session offline");
+        let connection_count = mock.connection_count();
+        let executions = mock.executions().len();
+        let connection = Connection::replay_wire_trace(path, &config()).await?;
+        assert_eq!(&workload(&connection).await?, recorded);
+        drop(connection);
+        assert_eq!(mock.connection_count(), connection_count);
+        assert_eq!(mock.executions().len(), executions);
+        Ok(())
+    }
+
+    async fn deviation(path: &Path) -> HdbResult<()> {
+        info!("a replay that deviates from the trace fails");
+        let connection = Connection::replay_wire_trace(path, &config()).await?;
+        assert!(matches!(
+            connection.prepare(QUERY).await,
+            Err(HdbError::ConnectionBroken { .. })
+        ));
+
+        info!("a file that is not a wire trace is rejected");
+        let other = path.with_extension("txt");
+        std::fs::write(&other, "this is not a wire trace")?;
+        assert!(matches!(
+            Connection::replay_wire_trace(&other, &config()).await,
+            Err(HdbError::Usage(_))
+        ));
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Workload {
+        ids: Vec<i32>,
+        prepared_ids: Vec<i32>,
+        error: String,
+    }
+
+    async fn workload(connection: &Connection) -> HdbResult<Workload> {
+        let ids: Vec<i32> = connection.query(QUERY).await?.try_into().await?;
+        connection.exec(CHANGE_PASSWORD).await?;
+        let error = connection.query(UNKNOWN).await.unwrap_err().to_string();
+        let prepared_ids: Vec<i32> = connection
+            .prepare(QUERY)
+            .await?
+            .execute(&())
+            .await?
+            .into_result_set()?
+            .try_into()
+            .await?;
+        Ok(Workload {
+            ids,
+            prepared_ids,
+            error,
+        })
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            QUERY,
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=10).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        mock.on_statement(CHANGE_PASSWORD, MockResponse::Success);
+        Ok(mock)
+    }
+}
@@ -0,0 +1,202 @@
+extern crate serde;
+
+mod test_utils;
+
+#[cfg(all(feature = "mock_server", feature = "tracing"))]
+mod a {
+    use hdbconnect_async::{
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        Connection, ConnectionConfiguration, HdbResult, HdbValue, TypeId,
+    };
+    use log::info;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+    const QUERY: &str = "select ID from NUMBERS";
+    const UNKNOWN: &str = "select * from UNKNOWN";
+
+    #[tokio::test] // cargo test --features mock_server,tracing --test test_118_tracing -- --nocapture
+    async fn test_118_tracing() -> HdbResult<()> {
+        let _log_handle = super::test_utils::init_logger();
+
+        let mock = start_mock()?;
+        let spans = Spans::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(spans.clone()));
+
+        let connection = Connection::with_configuration(
+            mock.url(),
+            &ConnectionConfiguration::default().with_fetch_size(3),
+        )
+        .await?;
+        connect(&spans);
+        query(&mock, &connection, &spans).await?;
+        prepared_statement(&connection, &spans).await?;
+        error(&connection, &spans).await;
+        Ok(())
+    }
+
+    fn connect(spans: &Spans) {
+        info!("the connect roundtrips are traced, too");
+        let spans = spans.take();
+        assert_eq!(spans[0]["db.operation.name"], "Authenticate");
+        assert!(!spans[0].contains_key("db.hana.connection_id"));
+        assert!(spans.iter().any(|span| span["otel.name"] == "Connect"));
+    }
+
+    async fn query(mock: &MockServer, connection: &Connection, spans: &Spans) -> HdbResult<()> {
+        info!("a query, with its fetch roundtrips");
+        let ids: Vec<i32> = connection.query(QUERY).await?.try_into().await?;
+        assert_eq!(ids.len(), 10);
+
+        let spans = spans.take();
+        let operations: Vec<&str> = spans
+            .iter()
+            .map(|span| span["db.operation.name"].as_str())
+            .collect();
+        assert_eq!(
+            operations,
+            ["ExecuteDirect", "FetchNext", "FetchNext", "FetchNext"]
+        );
+
+        let execute_direct = &spans[0];
+        assert_eq!(execute_direct["otel.kind"], "client");
+        assert_eq!(execute_direct["db.system.name"], "sap.hana");
+        assert_eq!(execute_direct["server.port"], mock.port().to_string());
+        assert_eq!(
+            execute_direct["db.hana.connection_id"],
+            connection.id().await.to_string()
+        );
+        let hash = &execute_direct["db.hana.statement_hash"];
+        assert_eq!(hash.len(), 16);
+        assert!(!execute_direct.contains_key("db.hana.sub_roundtrip"));
+        assert!(!execute_direct.contains_key("otel.status_code"));
+        for field in [
+            "db.hana.request.size",
+            "db.hana.reply.size",
+            "db.hana.server.proc_time_us",
+        ] {
+            assert!(
+                execute_direct[field].parse::<i64>().unwrap() >= 0,
+                "{field}"
+            );
+        }
+
+        for fetch in &spans[1..] {
+            assert_eq!(fetch["db.hana.sub_roundtrip"], "fetch");
+            assert_eq!(&fetch["db.hana.statement_hash"], hash);
+            assert!(fetch.contains_key("db.hana.server.proc_time_us"));
+        }
+        Ok(())
+    }
+
+    async fn prepared_statement(connection: &Connection, spans: &Spans) -> HdbResult<()> {
+        info!("the roundtrips of a prepared statement carry the hash of its SQL text");
+        let hash = hash_of_query(connection, spans).await?;
+        let mut statement = connection.prepare(QUERY).await?;
+        let ids: Vec<i32> = statement
+            .execute(&())
+            .await?
+            .into_result_set()?
+            .try_into()
+            .await?;
+        assert_eq!(ids.len(), 10);
+
+        let spans = spans.take();
+        let operations: Vec<&str> = spans
+            .iter()
+            .map(|span| span["db.operation.name"].as_str())
+            .collect();
+        assert_eq!(
+            operations,
+            ["Prepare", "Execute", "FetchNext", "FetchNext", "FetchNext"]
+        );
+        for span in &spans {
+            assert_eq!(span["db.hana.statement_hash"], hash);
+        }
+        Ok(())
+    }
+
+    async fn error(connection: &Connection, spans: &Spans) {
+        info!("a failing roundtrip has the error status and the server's error code");
+        connection.query(UNKNOWN).await.unwrap_err();
+        // the prepared statement of prepared_statement() is dropped in a spawned task
+        let spans: Vec<_> = spans
+            .take()
+            .into_iter()
+            .filter(|span| span["db.operation.name"] != "DropStatementId")
+            .collect();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0]["otel.status_code"], "ERROR");
+        assert_eq!(spans[0]["db.response.status_code"], "257");
+        assert_eq!(spans[0]["error.type"], "257");
+    }
+
+    // The statement hash of QUERY, taken from the span of an ExecuteDirect.
+    async fn hash_of_query(connection: &Connection, spans: &Spans) -> HdbResult<String> {
+        let _ids: Vec<i32> = connection.query(QUERY).await?.try_into().await?;
+        let spans = spans.take();
+        Ok(spans
+            .first()
+            .map(|span| span["db.hana.statement_hash"].clone())
+            .unwrap_or_default())
+    }
+
+    fn start_mock() -> HdbResult<MockServer> {
+        let mock = MockServer::start()?;
+        mock.on_statement(
+            QUERY,
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![MockColumn::new("ID", TypeId::INT)])
+                    .with_rows((1..=10).map(|i| vec![HdbValue::INT(i)])),
+            ),
+        );
+        Ok(mock)
+    }
+
+    // Collects the fields of the closed spans.
+    #[derive(Clone, Default)]
+    struct Spans(Arc<Mutex<Vec<HashMap<String, String>>>>);
+    impl Spans {
+        fn take(&self) -> Vec<HashMap<String, String>> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Spans {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            ctx.span(id).unwrap().extensions_mut().insert(fields);
+        }
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut extensions = span.extensions_mut();
+            values.record(extensions.get_mut::<Fields>().unwrap());
+        }
+        fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let fields = span.extensions_mut().remove::<Fields>().unwrap();
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+
+    #[derive(Default)]
+    struct Fields(HashMap<String, String>);
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod blob;
+mod cancel_handle;
 mod clob;
 mod connection;
 mod hdb_response;
@@ -8,6 +9,7 @@ mod prepared_statement;
 mod result_set;
 
 pub use blob::BLob;
+pub use cancel_handle::CancelHandle;
 pub use clob::CLob;
 pub use connection::Connection;
 pub use hdb_response::HdbResponse;
@@ -1,7 +1,7 @@
 use crate::{
     a_sync::HdbResponse,
     base::{new_am_async, InternalReturnValue, PreparedStatementCore, AM},
-    conn::{AmConnCore, CursorHoldability},
+    conn::{rewrite_sql, session_setup, AmConnCore, CursorHoldability, Interception},
     impl_err,
     protocol::{
         parts::{
@@ -10,9 +10,9 @@ use crate::{
         MessageType, Part, PartKind, Request, ServerUsage,
     },
     types_impl::lob::async_lob_writer,
-    usage_err, ConnectionConfiguration, HdbResult,
+    usage_err, ConnectionConfiguration, HdbResult, TraceContext,
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 /// Allows injection-safe SQL execution and repeated calls of the same statement
 /// with different parameters with as few roundtrips as possible.
@@ -75,11 +75,13 @@ use std::sync::Arc;
 #[derive(Clone, Debug)]
 pub struct PreparedStatement {
     am_ps_core: AM<PreparedStatementCore>,
+    sql: String,
     config: ConnectionConfiguration,
     server_usage: ServerUsage,
     a_descriptors: Arc<ParameterDescriptors>,
     o_a_rsmd: Option<Arc<ResultSetMetadata>>,
     batch: ParameterRows<'static>,
+    o_trace_context: Option<TraceContext>,
     _o_table_location: Option<Vec<i32>>,
 }
 
@@ -130,12 +132,17 @@ impl<'a> PreparedStatement {
     /// Several variants of `HdbError` can occur.
     pub async fn execute<T: serde::ser::Serialize>(&mut self, input: &T) -> HdbResult<HdbResponse> {
         trace!("PreparedStatement::execute()");
-        if self.a_descriptors.has_in() {
+        let o_rows = if self.a_descriptors.has_in() {
             let mut par_rows = ParameterRows::new();
             par_rows.push(input, &self.a_descriptors)?;
-            return self.execute_parameter_rows(Some(par_rows)).await;
-        }
-        self.execute_parameter_rows(None).await
+            Some(par_rows)
+        } else {
+            None
+        };
+        let o_interception = self.begin_interception(o_rows.as_ref())?;
+        let result = self.execute_parameter_rows(o_rows).await;
+        end_interception(o_interception, &self.server_usage, &result);
+        result
     }
 
     /// Consumes the given `HdbValue`s as a row of parameters for immediate execution.
@@ -189,10 +196,27 @@ impl<'a> PreparedStatement {
     pub async fn execute_row(
         &'a mut self,
         hdb_values: Vec<HdbValue<'a>>,
+    ) -> HdbResult<HdbResponse> {
+        let row: &[&[HdbValue]] = if self.a_descriptors.has_in() {
+            &[&hdb_values]
+        } else {
+            &[]
+        };
+        let o_interception = Interception::begin(self.config.interceptor(), &self.sql, row)?;
+        let result = self.execute_row_impl(hdb_values).await;
+        end_interception(o_interception, &self.server_usage, &result);
+        result
+    }
+
+    async fn execute_row_impl(
+        &'a mut self,
+        hdb_values: Vec<HdbValue<'a>>,
     ) -> HdbResult<HdbResponse> {
         if self.a_descriptors.has_in() {
             let ps_core_guard = self.am_ps_core.lock_async().await;
             let mut request = Request::new(MessageType::Execute, self.config.command_options());
+            request.set_query_timeout(self.config.query_timeout());
+            request.set_trace_context(self.o_trace_context.clone());
 
             request.push(Part::StatementId(ps_core_guard.statement_id));
 
@@ -246,7 +270,10 @@ impl<'a> PreparedStatement {
             let (mut internal_return_values, replytype) = (
                 main_reply
                     .parts
-                    .into_internal_return_values_async(&ps_core_guard.am_conn_core, None)
+                    .into_internal_return_values_async(
+                        &ps_core_guard.am_conn_core,
+                        Some(&mut self.server_usage),
+                    )
                     .await?,
                 main_reply.replytype,
             );
@@ -280,12 +307,15 @@ impl<'a> PreparedStatement {
                 }
             }
 
-            // inject statement id
+            // inject statement id, and propagate the cursor type
             for rv in &mut internal_return_values {
                 if let InternalReturnValue::RsState((rs_state, _a_rsmd)) = rv {
                     rs_state
                         .inject_ps_core_async(Arc::clone(&self.am_ps_core))
                         .await?;
+                    if self.config.is_scrollable_cursor() {
+                        rs_state.set_scrollable();
+                    }
                 }
             }
             HdbResponse::try_new(internal_return_values, replytype)
@@ -359,7 +389,10 @@ impl<'a> PreparedStatement {
             self.batch.count()
         );
         std::mem::swap(&mut self.batch, &mut batch2);
-        self.execute_parameter_rows(Some(batch2)).await
+        let o_interception = self.begin_interception(Some(&batch2))?;
+        let result = self.execute_parameter_rows(Some(batch2)).await;
+        end_interception(o_interception, &self.server_usage, &result);
+        result
     }
 
     /// Descriptors of all parameters of the prepared statement (in, out, inout).
@@ -368,6 +401,15 @@ impl<'a> PreparedStatement {
         Arc::clone(&self.a_descriptors)
     }
 
+    // Calls `before_execute` of the interceptor, if any.
+    fn begin_interception(
+        &self,
+        o_rows: Option<&ParameterRows>,
+    ) -> HdbResult<Option<Interception>> {
+        let rows: Vec<&[HdbValue]> = o_rows.map(|rows| rows.rows().collect()).unwrap_or_default();
+        Interception::begin(self.config.interceptor(), &self.sql, &rows)
+    }
+
     async fn execute_parameter_rows(
         &mut self,
         o_rows: Option<ParameterRows<'_>>,
@@ -376,6 +418,8 @@ impl<'a> PreparedStatement {
 
         let ps_core_guard = self.am_ps_core.lock_async().await;
         let mut request = Request::new(MessageType::Execute, self.config.command_options());
+        request.set_query_timeout(self.config.query_timeout());
+        request.set_trace_context(self.o_trace_context.clone());
         request.push(Part::StatementId(ps_core_guard.statement_id));
         if let Some(rows) = o_rows {
             request.push(Part::ParameterRows(rows));
@@ -390,15 +434,21 @@ impl<'a> PreparedStatement {
                 &mut None,
             )
             .await?
-            .into_internal_return_values_async(&ps_core_guard.am_conn_core, None)
+            .into_internal_return_values_async(
+                &ps_core_guard.am_conn_core,
+                Some(&mut self.server_usage),
+            )
             .await?;
 
-        // inject statement id
+        // inject statement id, and propagate the cursor type
         for rv in &mut internal_return_values {
             if let InternalReturnValue::RsState((rs_state, _a_rsmd)) = rv {
                 rs_state
                     .inject_ps_core_async(Arc::clone(&self.am_ps_core))
                     .await?;
+                if self.config.is_scrollable_cursor() {
+                    rs_state.set_scrollable();
+                }
             }
         }
 
@@ -423,6 +473,44 @@ impl<'a> PreparedStatement {
         Ok(self.config.cursor_holdability())
     }
 
+    /// Defines whether the statement's result sets are produced with scrollable cursors.
+    pub fn set_scrollable_cursor(&mut self, scrollable: bool) {
+        self.config.set_scrollable_cursor(scrollable);
+    }
+    /// Returns whether the statement's result sets are produced with scrollable cursors.
+    #[must_use]
+    pub fn is_scrollable_cursor(&self) -> bool {
+        self.config.is_scrollable_cursor()
+    }
+
+    /// Sets the timeout after which the server cancels the execution of this statement;
+    /// the execution then fails with `HdbError::Cancelled`, and the connection remains usable.
+    ///
+    /// By default, the query timeout of the connection applies.
+    /// HANA works with full seconds, so the timeout is rounded up.
+    pub fn set_query_timeout(&mut self, query_timeout: Option<Duration>) {
+        self.config.set_query_timeout(query_timeout);
+    }
+    /// Returns the statement's query timeout.
+    #[must_use]
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.config.query_timeout()
+    }
+
+    /// Sets the W3C trace context that is propagated to the server with the executions
+    /// of this statement.
+    ///
+    /// By default, the trace context of the connection applies.
+    /// See [`TraceContext`] for details.
+    pub fn set_trace_context(&mut self, trace_context: Option<TraceContext>) {
+        self.o_trace_context = trace_context;
+    }
+    /// Returns the statement's trace context.
+    #[must_use]
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.o_trace_context.as_ref()
+    }
+
     /// Provides information about the the server-side resource consumption that
     /// is related to this `PreparedStatement` object.
     #[must_use]
@@ -431,24 +519,31 @@ impl<'a> PreparedStatement {
     }
 
     // Prepare a statement.
-    pub(crate) async fn try_new(am_conn_core: AmConnCore, stmt: &str) -> HdbResult<Self> {
+    pub(crate) async fn try_new(
+        am_conn_core: AmConnCore,
+        stmt: &str,
+        placeholder_names: Vec<String>,
+    ) -> HdbResult<Self> {
         let config = am_conn_core.lock_async().await.configuration().clone();
+        let sql = rewrite_sql(config.interceptor(), stmt).into_owned();
+        am_conn_core
+            .set_up_session_async(session_setup(config.interceptor(), &sql))
+            .await?;
         let mut request = Request::new(MessageType::Prepare, config.command_options());
-        request.push(Part::Command(stmt));
+        request.push(Part::Command(&sql));
 
         let reply = am_conn_core.send_async(request).await?;
 
         let mut o_table_location: Option<Vec<i32>> = None;
         let mut o_stmt_id: Option<u64> = None;
-        let mut a_descriptors: Arc<ParameterDescriptors> =
-            Arc::new(ParameterDescriptors::default());
+        let mut descriptors = ParameterDescriptors::default();
         let mut o_a_rsmd: Option<Arc<ResultSetMetadata>> = None;
         let mut server_usage = ServerUsage::default();
 
         for part in reply.parts {
             match part {
-                Part::ParameterMetadata(descriptors) => {
-                    a_descriptors = Arc::new(descriptors);
+                Part::ParameterMetadata(pds) => {
+                    descriptors = pds;
                 }
                 Part::StatementId(id) => {
                     o_stmt_id = Some(id);
@@ -482,18 +577,35 @@ impl<'a> PreparedStatement {
             am_conn_core,
             statement_id,
         });
+        if !placeholder_names.is_empty() {
+            descriptors.set_placeholder_names(placeholder_names)?;
+        }
+        let a_descriptors = Arc::new(descriptors);
         debug!(
             "PreparedStatement created with parameter descriptors = {:?}",
             a_descriptors
         );
         Ok(Self {
             am_ps_core,
+            sql,
             config,
             server_usage,
             batch: ParameterRows::new(),
             a_descriptors,
             o_a_rsmd,
+            o_trace_context: None,
             _o_table_location: o_table_location,
         })
     }
 }
+
+// Calls `after_execute` or `on_error` of the interceptor, if any.
+fn end_interception(
+    o_interception: Option<Interception>,
+    server_usage: &ServerUsage,
+    result: &HdbResult<HdbResponse>,
+) {
+    if let Some(interception) = o_interception {
+        interception.end(result.as_ref().map(HdbResponse::outcome), server_usage);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    base::{RsState, XMutexed},
+    base::{RsState, Scroll, XMutexed},
     protocol::{parts::ResultSetMetadata, ServerUsage},
     HdbResult, HdbValue, Row, Rows,
 };
@@ -212,6 +212,89 @@ impl ResultSet {
             .await
     }
 
+    /// Moves the cursor to the given row and returns it,
+    /// or None if the row does not exist.
+    ///
+    /// Row numbers start with 1; negative row numbers count backwards from the end,
+    /// so `-1` denotes the last row.
+    /// The rows that follow the returned row are fetched along with it,
+    /// so that subsequent calls to `next_row` continue from there.
+    ///
+    /// Requires a scrollable cursor, see `Connection::set_scrollable_cursor`
+    /// and `PreparedStatement::set_scrollable_cursor`.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if `row` is 0, or if the result set is already closed.
+    ///
+    /// Several other variants of `HdbError` are possible.
+    pub async fn fetch_absolute(&mut self, row: i32) -> HdbResult<Option<Row>> {
+        self.state
+            .lock_async()
+            .await
+            .scroll_async(&self.metadata, Scroll::Absolute(row))
+            .await
+    }
+
+    /// Moves the cursor by the given number of rows, relative to the row that was
+    /// returned last, and returns the row it lands on,
+    /// or None if the row does not exist.
+    ///
+    /// `fetch_relative(1)` is equivalent to `next_row`, negative values move backwards.
+    /// Moving forwards within the rows that were already fetched needs no roundtrip.
+    ///
+    /// Requires a scrollable cursor, see `Connection::set_scrollable_cursor`
+    /// and `PreparedStatement::set_scrollable_cursor`.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if the result set is already closed.
+    ///
+    /// Several other variants of `HdbError` are possible.
+    pub async fn fetch_relative(&mut self, delta: i32) -> HdbResult<Option<Row>> {
+        self.state
+            .lock_async()
+            .await
+            .scroll_async(&self.metadata, Scroll::Relative(delta))
+            .await
+    }
+
+    /// Moves the cursor to the first row and returns it, or None if the result set is empty.
+    ///
+    /// Requires a scrollable cursor, see `Connection::set_scrollable_cursor`
+    /// and `PreparedStatement::set_scrollable_cursor`.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if the result set is already closed.
+    ///
+    /// Several other variants of `HdbError` are possible.
+    pub async fn fetch_first(&mut self) -> HdbResult<Option<Row>> {
+        self.state
+            .lock_async()
+            .await
+            .scroll_async(&self.metadata, Scroll::First)
+            .await
+    }
+
+    /// Moves the cursor to the last row and returns it, or None if the result set is empty.
+    ///
+    /// Requires a scrollable cursor, see `Connection::set_scrollable_cursor`
+    /// and `PreparedStatement::set_scrollable_cursor`.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if the result set is already closed.
+    ///
+    /// Several other variants of `HdbError` are possible.
+    pub async fn fetch_last(&mut self) -> HdbResult<Option<Row>> {
+        self.state
+            .lock_async()
+            .await
+            .scroll_async(&self.metadata, Scroll::Last)
+            .await
+    }
+
     /// Fetches all not yet transported result lines from the server.
     ///
     /// Bigger result sets are typically not transported in one roundtrip from the database;
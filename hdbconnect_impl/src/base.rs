@@ -1,5 +1,6 @@
 mod hdb_error;
 mod internal_returnvalue;
+mod named_placeholders;
 mod prepared_statement_core;
 mod row;
 mod rows;
@@ -19,8 +20,9 @@ pub use {
 };
 pub(crate) use {
     internal_returnvalue::InternalReturnValue,
+    named_placeholders::replace_named_placeholders,
     prepared_statement_core::PreparedStatementCore,
     rs_core::RsCore,
-    rs_state::RsState,
+    rs_state::{RsState, Scroll},
     xmutexed::{XMutexed, AM, OAM},
 };
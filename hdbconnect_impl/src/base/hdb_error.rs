@@ -24,6 +24,15 @@ pub enum HdbError {
         // backtrace: Backtrace,
     },
 
+    /// The password of the user is initial or has expired, and the server
+    /// accepts the logon only together with a password change;
+    /// see `Connection::new_with_password_change()`.
+    #[error("Logon requires a password change")]
+    PasswordChangeRequired {
+        /// The causing server error (code 414).
+        source: ServerError,
+    },
+
     /// Deserialization of a `ResultSet`, a `Row`, a single `HdbValue`,
     /// or an `OutputParameter` failed (methods `try_into()`).
     #[error("Error occured in deserialization")]
@@ -72,6 +81,16 @@ pub enum HdbError {
         // backtrace: Backtrace,
     },
 
+    /// The execution of a statement was cancelled by the database server, either
+    /// because its query timeout expired or because it was cancelled with a `CancelHandle`.
+    ///
+    /// The connection remains usable; the open transaction was rolled back by the server.
+    #[error("Statement execution was cancelled")]
+    Cancelled {
+        /// The causing server error (code 139).
+        source: ServerError,
+    },
+
     /// Decompression
     #[error("Decompression failed")]
     Decompression {
@@ -143,6 +162,32 @@ pub enum HdbError {
         second: Box<HdbError>,
     },
 
+    /// The connection was broken, and session recovery
+    /// (see `ConnectionConfiguration::set_session_recovery`) could not repeat the failed
+    /// request safely.
+    ///
+    /// If `session_recovered` is true, the connection was re-established and can be used further,
+    /// but the failed request may or may not have been executed by the server.
+    /// Otherwise a transaction with uncommitted changes was open; its changes are lost,
+    /// and the connection will be re-established with the next request.
+    #[error("Connection was broken and could not be recovered safely: {reason}")]
+    RecoveryUnsafe {
+        /// Why the request was not repeated.
+        reason: &'static str,
+        /// Whether the connection was re-established.
+        session_recovered: bool,
+        /// The error that was caused by the broken connection.
+        source: Box<HdbError>,
+    },
+
+    /// The network connection could not be established with any of the hosts
+    /// of a `ConnectParams` with multiple hosts.
+    #[error("Connection failed with all hosts: {}", display_attempts(.attempts))]
+    AllHostsFailed {
+        /// The addresses that were tried, in their order, with the respective error.
+        attempts: Vec<(String, HdbError)>,
+    },
+
     /// Error caused by wrong usage.
     #[error("Wrong usage: {}", _0)]
     Usage(std::borrow::Cow<'static, str>),
@@ -186,6 +231,12 @@ impl HdbError {
         match self {
             Self::DbError {
                 source: server_error,
+            }
+            | Self::PasswordChangeRequired {
+                source: server_error,
+            }
+            | Self::Cancelled {
+                source: server_error,
             } => Some(server_error),
             _ => None,
         }
@@ -199,7 +250,9 @@ impl HdbError {
             Self::Deserialization { source } => Some(source),
             Self::Serialization { source } => Some(source),
             Self::ConnParams { source } | Self::TlsInit { source } => Some(&**source),
-            Self::DbError { source } => Some(source),
+            Self::DbError { source }
+            | Self::PasswordChangeRequired { source }
+            | Self::Cancelled { source } => Some(source),
             Self::Decompression { source } => Some(source),
             Self::TlsInvalidDnsName { source } => Some(source),
             Self::Io { source } => Some(source),
@@ -230,6 +283,14 @@ impl HdbError {
     }
 }
 
+fn display_attempts(attempts: &[(String, HdbError)]) -> String {
+    attempts
+        .iter()
+        .map(|(addr, e)| format!("{addr}: {}", e.display_with_inner()))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[cfg(feature = "sync")]
 impl<G> From<std::sync::PoisonError<G>> for HdbError {
     fn from(_error: std::sync::PoisonError<G>) -> Self {
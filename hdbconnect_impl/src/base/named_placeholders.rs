@@ -0,0 +1,129 @@
+use crate::{usage_err, HdbResult};
+
+// Replaces the named placeholders (`:name` or `@name`) in the given SQL text with `?`,
+// and returns the rewritten text together with the names, in the order of their occurrence.
+//
+// String literals, quoted identifiers, and comments are left untouched.
+// A name starts with an ASCII letter or an underscore, which can be followed by
+// ASCII letters, digits, and underscores.
+// Named placeholders must not be mixed with positional ones.
+pub(crate) fn replace_named_placeholders(sql: &str) -> HdbResult<(String, Vec<String>)> {
+    let bytes = sql.as_bytes();
+    let mut rewritten = String::with_capacity(sql.len());
+    let mut names = Vec::<String>::new();
+    let mut has_positional = false;
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => i = end_of_quoted(bytes, i + 1, quote),
+            b'-' if bytes.get(i + 1) == Some(&b'-') => i = end_of(bytes, i + 2, b"\n"),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = end_of(bytes, i + 2, b"*/"),
+            b'?' => {
+                has_positional = true;
+                i += 1;
+            }
+            b':' | b'@'
+                if bytes.get(i + 1).is_some_and(|b| is_name_start(*b))
+                    && (i == 0 || !is_name_part(bytes[i - 1])) =>
+            {
+                let end = (i + 2..bytes.len())
+                    .find(|j| !is_name_part(bytes[*j]))
+                    .unwrap_or(bytes.len());
+                rewritten.push_str(&sql[copied..i]);
+                rewritten.push('?');
+                names.push(sql[i + 1..end].to_string());
+                copied = end;
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    if has_positional && !names.is_empty() {
+        return Err(usage_err!(
+            "named placeholders (like :{}) and positional placeholders (?) must not be mixed",
+            names[0]
+        ));
+    }
+    rewritten.push_str(&sql[copied..]);
+    Ok((rewritten, names))
+}
+
+fn is_name_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_'
+}
+fn is_name_part(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+// Returns the position after the closing quote; a doubled quote is an escaped quote.
+fn end_of_quoted(bytes: &[u8], mut i: usize, quote: u8) -> usize {
+    while i < bytes.len() {
+        if bytes[i] != quote {
+            i += 1;
+        } else if bytes.get(i + 1) == Some(&quote) {
+            i += 2;
+        } else {
+            return i + 1;
+        }
+    }
+    bytes.len()
+}
+
+// Returns the position after the given terminator, or the end of the text.
+fn end_of(bytes: &[u8], start: usize, terminator: &[u8]) -> usize {
+    bytes[start..]
+        .windows(terminator.len())
+        .position(|window| window == terminator)
+        .map_or(bytes.len(), |pos| start + pos + terminator.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::replace_named_placeholders;
+    use crate::HdbError;
+
+    fn replace(sql: &str) -> (String, Vec<String>) {
+        replace_named_placeholders(sql).unwrap()
+    }
+
+    #[test]
+    fn test_named_placeholders() {
+        assert_eq!(
+            replace("select * from T where ID = :id and NAME = @name or ID = :id"),
+            (
+                "select * from T where ID = ? and NAME = ? or ID = ?".to_string(),
+                vec!["id".to_string(), "name".to_string(), "id".to_string()]
+            )
+        );
+        assert_eq!(
+            replace("insert into T values (:_first,:second_2)"),
+            (
+                "insert into T values (?,?)".to_string(),
+                vec!["_first".to_string(), "second_2".to_string()]
+            )
+        );
+
+        // literals, quoted identifiers, and comments are not touched
+        let sql = "select ':no', 'it''s :no', \"col:no\", \"a\"\"@no\" from T -- :no\n\
+                   /* @no\n :no */ where X = :yes";
+        let (rewritten, names) = replace(sql);
+        assert_eq!(rewritten, sql.replace(":yes", "?"));
+        assert_eq!(names, ["yes"]);
+
+        // neither positional parameters, nor things that only look like placeholders
+        for sql in [
+            "select * from T where ID = ?",
+            "select a:b, :1, :, @ from T",
+            "select 'unterminated :no",
+            "select 1 from T /* unterminated :no",
+        ] {
+            assert_eq!(replace(sql), (sql.to_string(), Vec::<String>::new()));
+        }
+
+        assert!(matches!(
+            replace_named_placeholders("select * from T where ID = :id and NAME = ?"),
+            Err(HdbError::Usage(_))
+        ));
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     conn::{AmConnCore, CommandOptions},
     impl_err,
     protocol::{
-        parts::{Parts, StatementContext},
+        parts::{FetchOptions, Parts, StatementContext},
         MessageType, Part, PartAttributes, PartKind, ReplyType, Request,
     },
     usage_err, HdbResult, ResultSetMetadata, Row, Rows, ServerUsage,
@@ -15,14 +15,31 @@ use super::new_am_async;
 #[cfg(feature = "sync")]
 use super::new_am_sync;
 
+// The positioning requests of a scrollable cursor.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Scroll {
+    First,
+    Last,
+    // row number, starting with 1; negative values count backwards from the end
+    Absolute(i32),
+    // offset to the row that was returned last
+    Relative(i32),
+}
+
 // the references to the connection (core) and the prepared statement (core)
 // ensure that these are not dropped before all missing content is fetched
+//
+// The buffered rows (row_iter, then next_rows) are the rows that follow the row that was
+// returned last; they are contiguous, but with a scrollable cursor they need not be contiguous
+// with the rows that were returned before.
+// The server's cursor is positioned on the last buffered row.
 #[derive(Debug)]
 pub(crate) struct RsState {
     next_rows: Vec<Row>,
     row_iter: <Vec<Row> as IntoIterator>::IntoIter,
     server_usage: ServerUsage,
     o_am_rscore: OAM<RsCore>,
+    scrollable: bool,
 }
 
 impl RsState {
@@ -38,6 +55,7 @@ impl RsState {
             row_iter: Vec::<Row>::new().into_iter(),
             server_usage: ServerUsage::default(),
             o_am_rscore: Some(new_am_sync(RsCore::new(am_conn_core, attrs, rs_id))),
+            scrollable: false,
         };
         if let Some(stmt_ctx) = o_stmt_ctx {
             new_instance.server_usage.update(
@@ -61,6 +79,7 @@ impl RsState {
             row_iter: Vec::<Row>::new().into_iter(),
             server_usage: ServerUsage::default(),
             o_am_rscore: Some(new_am_async(RsCore::new(am_conn_core, attrs, rs_id))),
+            scrollable: false,
         };
         if let Some(stmt_ctx) = o_stmt_ctx {
             new_instance.server_usage.update(
@@ -101,6 +120,12 @@ impl RsState {
         Ok(())
     }
 
+    // Result sets with a scrollable cursor are kept open after the last packet was fetched,
+    // since the cursor can still be moved backwards.
+    pub(crate) fn set_scrollable(&mut self) {
+        self.scrollable = true;
+    }
+
     pub(crate) fn update_server_usage(&mut self, stmt_ctx: &StatementContext) {
         self.server_usage.update(
             stmt_ctx.server_processing_time(),
@@ -302,7 +327,7 @@ impl RsState {
 
         let mut drop_rs_core = false;
         if let Some(ref am_rscore) = self.o_am_rscore {
-            drop_rs_core = am_rscore.lock_sync()?.attributes().is_last_packet();
+            drop_rs_core = !self.scrollable && am_rscore.lock_sync()?.attributes().is_last_packet();
         }
         if drop_rs_core {
             self.o_am_rscore = None;
@@ -338,7 +363,8 @@ impl RsState {
 
         let mut drop_rs_core = false;
         if let Some(ref am_rscore) = self.o_am_rscore {
-            drop_rs_core = am_rscore.lock_async().await.attributes().is_last_packet();
+            drop_rs_core =
+                !self.scrollable && am_rscore.lock_async().await.attributes().is_last_packet();
         }
         if drop_rs_core {
             self.o_am_rscore = None;
@@ -346,13 +372,138 @@ impl RsState {
         Ok(())
     }
 
+    #[cfg(feature = "sync")]
+    pub(crate) fn scroll_sync(
+        &mut self,
+        a_rsmd: &Arc<ResultSetMetadata>,
+        scroll: Scroll,
+    ) -> HdbResult<Option<Row>> {
+        trace!("ResultSet::scroll({scroll:?})");
+        if !self.scrollable {
+            return Err(usage_err!(
+                "result set was not produced with a scrollable cursor"
+            ));
+        }
+        if let Some(row) = self.scroll_within_buffer(scroll) {
+            return Ok(Some(row));
+        }
+        let (am_conn_core, result_set_id) = {
+            let Some(ref am_rscore) = self.o_am_rscore else {
+                return Err(usage_err!("result set is already closed"));
+            };
+            let rs_core = am_rscore.lock_sync()?;
+            (rs_core.am_conn_core().clone(), rs_core.result_set_id())
+        };
+        let fetch_size = { am_conn_core.lock_sync()?.configuration().fetch_size() };
+
+        let request = self.scroll_request(scroll, result_set_id, fetch_size)?;
+        let mut reply =
+            am_conn_core.full_send_sync(request, Some(a_rsmd), None, &mut Some(self))?;
+        reply.assert_expected_reply_type(ReplyType::Fetch)?;
+        reply.parts.pop_if_kind(PartKind::ResultSet);
+        Ok(self.next_row_no_fetch())
+    }
+    #[cfg(feature = "async")]
+    pub(crate) async fn scroll_async(
+        &mut self,
+        a_rsmd: &Arc<ResultSetMetadata>,
+        scroll: Scroll,
+    ) -> HdbResult<Option<Row>> {
+        trace!("ResultSet::scroll({scroll:?})");
+        if !self.scrollable {
+            return Err(usage_err!(
+                "result set was not produced with a scrollable cursor"
+            ));
+        }
+        if let Some(row) = self.scroll_within_buffer(scroll) {
+            return Ok(Some(row));
+        }
+        let (am_conn_core, result_set_id) = {
+            let Some(ref am_rscore) = self.o_am_rscore else {
+                return Err(usage_err!("result set is already closed"));
+            };
+            let rs_core = am_rscore.lock_async().await;
+            (rs_core.am_conn_core().clone(), rs_core.result_set_id())
+        };
+        let fetch_size = { am_conn_core.lock_async().await.configuration().fetch_size() };
+
+        let request = self.scroll_request(scroll, result_set_id, fetch_size)?;
+        let mut reply = am_conn_core
+            .full_send_async(request, Some(a_rsmd), None, &mut Some(self))
+            .await?;
+        reply.assert_expected_reply_type(ReplyType::Fetch)?;
+        reply.parts.pop_if_kind(PartKind::ResultSet);
+        Ok(self.next_row_no_fetch())
+    }
+
+    // Forward moves to a row that is already buffered need no roundtrip.
+    fn scroll_within_buffer(&mut self, scroll: Scroll) -> Option<Row> {
+        match scroll {
+            Scroll::Relative(delta)
+                if usize::try_from(delta).is_ok_and(|delta| delta > 0 && delta <= self.len()) =>
+            {
+                for _ in 1..delta {
+                    self.next_row_no_fetch();
+                }
+                self.next_row_no_fetch()
+            }
+            _ => None,
+        }
+    }
+
+    // Builds the request for moving the server's cursor, and discards the buffered rows;
+    // the reply brings the target row and the rows that follow it.
+    fn scroll_request(
+        &mut self,
+        scroll: Scroll,
+        result_set_id: u64,
+        fetch_size: u32,
+    ) -> HdbResult<Request<'static>> {
+        let (message_type, o_position, fetch_size) = match scroll {
+            Scroll::First => (MessageType::FetchFirst, None, fetch_size),
+            // with the last row, there is nothing more to fetch
+            Scroll::Last => (MessageType::FetchLast, None, 1),
+            Scroll::Absolute(0) => {
+                return Err(usage_err!(
+                    "row numbers start with 1, negative row numbers count from the end"
+                ));
+            }
+            Scroll::Absolute(row) => (MessageType::FetchAbsolute, Some(row), fetch_size),
+            Scroll::Relative(delta) => {
+                // the server's cursor is on the last buffered row
+                let offset = i32::try_from(self.len())
+                    .ok()
+                    .and_then(|buffered| delta.checked_sub(buffered))
+                    .ok_or_else(|| usage_err!("offset {delta} is out of range"))?;
+                (MessageType::FetchRelative, Some(offset), fetch_size)
+            }
+        };
+        debug!(
+            "ResultSet::scroll() with {message_type:?}, position = {o_position:?}, \
+             fetch_size = {fetch_size}"
+        );
+        let mut request = Request::new(message_type, CommandOptions::EMPTY);
+        request.push(Part::ResultSetId(result_set_id));
+        if let Some(position) = o_position {
+            request.push(Part::FetchOptions(FetchOptions::for_position(position)));
+        }
+        request.push(Part::FetchSize(fetch_size));
+
+        self.row_iter = Vec::<Row>::new().into_iter();
+        self.next_rows.clear();
+        Ok(request)
+    }
+
     #[cfg(feature = "sync")]
     pub(crate) fn is_complete_sync(&self) -> HdbResult<bool> {
         if let Some(ref am_rscore) = self.o_am_rscore {
             let rs_core = am_rscore.lock_sync()?;
             let attributes = rs_core.attributes();
+            // a scrollable cursor that was moved before the first row has no row,
+            // but can still be fetched from
             if (!attributes.is_last_packet())
-                && (attributes.row_not_found() || attributes.result_set_is_closed())
+                && ((attributes.row_not_found() && !self.scrollable)
+                    || attributes.result_set_is_closed())
             {
                 Err(impl_err!(
                     "ResultSet attributes inconsistent: incomplete, but already closed on server",
@@ -368,8 +519,10 @@ impl RsState {
     pub async fn is_complete_async(&self) -> HdbResult<bool> {
         if let Some(ref am_rscore) = self.o_am_rscore {
             let rs_core = am_rscore.lock_async().await;
+            // a scrollable cursor that was moved before the first row has no row,
+            // but can still be fetched from
             if (!rs_core.attributes().is_last_packet())
-                && (rs_core.attributes().row_not_found()
+                && ((rs_core.attributes().row_not_found() && !self.scrollable)
                     || rs_core.attributes().result_set_is_closed())
             {
                 Err(impl_err!(
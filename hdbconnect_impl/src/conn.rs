@@ -7,27 +7,65 @@ mod connection_configuration;
 mod connection_core;
 mod connection_statistics;
 mod initial_request;
+mod interceptor;
 mod params;
+#[cfg(feature = "tracing")]
+mod roundtrip_span;
+mod roundtrip_statistics;
+mod session_recovery;
 mod session_state;
+#[cfg(feature = "alpha_routing")]
+mod statement_routing;
 mod tcp_client;
+mod trace_context;
+mod wire_trace;
 
 pub mod url;
 
 pub(crate) use {
-    am_conn_core::AmConnCore, command_options::CommandOptions, connection_core::ConnectionCore,
-    params::Compression, tcp_client::TcpClient,
+    am_conn_core::AmConnCore,
+    command_options::CommandOptions,
+    connection_core::ConnectionCore,
+    interceptor::{rewrite_sql, session_setup, Interception},
+    params::Compression,
+    tcp_client::TcpClient,
+    wire_trace::Tee,
 };
 pub use {
     command_options::CursorHoldability,
     connection_configuration::ConnectionConfiguration,
     connection_statistics::ConnectionStatistics,
+    interceptor::{ExecutionOutcome, Interceptor},
     params::{
-        connect_params::{ConnectParams, ServerCerts},
+        bearer_token::{BearerToken, BearerTokenKind},
+        client_certificate::ClientCertificate,
+        connect_params::{ConnectParams, HostSelection, ServerCerts},
         connect_params_builder::ConnectParamsBuilder,
+        credential_provider::CredentialProvider,
         into_connect_params::IntoConnectParams,
         into_connect_params_builder::IntoConnectParamsBuilder,
+        proxy::{Proxy, ProxyProtocol},
     },
+    roundtrip_statistics::{
+        LatencyHistogram, MessageTypeStatistics, RoundtripStatistics, SharedStatistics,
+    },
+    trace_context::TraceContext,
+};
+
+#[cfg(feature = "async")]
+pub use params::credential_provider::AsyncCredentialProvider;
+
+#[cfg(feature = "mock_server")]
+pub(crate) use authentication::{
+    rsa_decrypt_oaep_sha1, scram_pdkdf2_sha256, scram_sha256, RsaPublicKey,
 };
+#[cfg(feature = "mock_server")]
+pub(crate) use tcp_client::accept_websocket_sync;
+#[cfg(feature = "dissector")]
+pub(crate) use wire_trace::read_wire_trace;
 
 use authentication::AuthenticationResult;
+use session_recovery::LiveStatements;
 use session_state::SessionState;
+#[cfg(feature = "alpha_routing")]
+use statement_routing::StatementRouting;
@@ -3,11 +3,11 @@ use crate::{
     conn::{ConnectionConfiguration, ConnectionCore},
     protocol::{
         parts::ResultSetMetadata,
-        {Reply, Request},
+        {MessageType, Part, Reply, Request},
     },
     ConnectParams, HdbError, HdbResult, ParameterDescriptors,
 };
-use std::{sync::Arc, time::Instant};
+use std::{path::Path, sync::Arc, time::Instant};
 
 #[derive(Clone, Debug)]
 pub(crate) struct AmConnCore(AM<ConnectionCore>);
@@ -52,6 +52,29 @@ impl AmConnCore {
         Ok(Self(crate::base::new_am_async(conn_core)))
     }
 
+    #[cfg(feature = "sync")]
+    pub fn try_new_replay_sync(path: &Path, config: &ConnectionConfiguration) -> HdbResult<Self> {
+        let conn_core = ConnectionCore::try_new_replay_sync(path, config)?;
+        debug!("replaying the wire trace {}", path.display());
+        Ok(Self(crate::base::new_am_sync(conn_core)))
+    }
+    #[cfg(feature = "async")]
+    pub async fn try_new_replay_async(
+        path: &Path,
+        config: &ConnectionConfiguration,
+    ) -> HdbResult<Self> {
+        let conn_core = ConnectionCore::try_new_replay_async(path, config).await?;
+        debug!("replaying the wire trace {}", path.display());
+        Ok(Self(crate::base::new_am_async(conn_core)))
+    }
+
+    #[cfg(feature = "dissector")]
+    pub fn new_offline() -> HdbResult<Self> {
+        Ok(Self(crate::base::new_am_sync(
+            ConnectionCore::new_offline()?
+        )))
+    }
+
     #[cfg(feature = "sync")]
     pub fn lock_sync(&self) -> std::sync::LockResult<std::sync::MutexGuard<ConnectionCore>> {
         self.0.lock_sync()
@@ -70,6 +93,55 @@ impl AmConnCore {
         self.full_send_async(request, None, None, &mut None).await
     }
 
+    // Executes the session setup statements of the interceptor,
+    // unless they were executed last in this session.
+    #[cfg(feature = "sync")]
+    pub(crate) fn set_up_session_sync(
+        &self,
+        o_session_setup: Option<Vec<String>>,
+    ) -> HdbResult<()> {
+        let Some(session_setup) = o_session_setup else {
+            return Ok(());
+        };
+        let command_options = {
+            let conn_core = self.lock_sync()?;
+            if conn_core.session_setup() == session_setup.as_slice() {
+                return Ok(());
+            }
+            conn_core.configuration().command_options()
+        };
+        for stmt in &session_setup {
+            let mut request = Request::new(MessageType::ExecuteDirect, command_options);
+            request.push(Part::Command(stmt));
+            self.send_sync(request)?;
+        }
+        self.lock_sync()?.set_session_setup(session_setup);
+        Ok(())
+    }
+    #[cfg(feature = "async")]
+    pub(crate) async fn set_up_session_async(
+        &self,
+        o_session_setup: Option<Vec<String>>,
+    ) -> HdbResult<()> {
+        let Some(session_setup) = o_session_setup else {
+            return Ok(());
+        };
+        let command_options = {
+            let conn_core = self.lock_async().await;
+            if conn_core.session_setup() == session_setup.as_slice() {
+                return Ok(());
+            }
+            conn_core.configuration().command_options()
+        };
+        for stmt in &session_setup {
+            let mut request = Request::new(MessageType::ExecuteDirect, command_options);
+            request.push(Part::Command(stmt));
+            self.send_async(request).await?;
+        }
+        self.lock_async().await.set_session_setup(session_setup);
+        Ok(())
+    }
+
     #[cfg(feature = "sync")]
     pub(crate) fn full_send_sync(
         &self,
@@ -84,11 +156,26 @@ impl AmConnCore {
         );
         let start = Instant::now();
         let mut conn_core = self.lock_sync()?;
+        #[cfg(feature = "alpha_routing")]
+        if let Some(reply) = conn_core.route_sync(&mut request, Some(self), o_a_descriptors) {
+            return reply;
+        }
         conn_core.augment_request(&mut request);
 
-        let reply = conn_core.roundtrip_sync(&request, Some(self), o_a_rsmd, o_a_descriptors, o_rs);
+        let mut reply =
+            conn_core.roundtrip_sync(&request, Some(self), o_a_rsmd, o_a_descriptors, o_rs);
+        if let Err(e @ HdbError::ConnectionBroken { .. }) = reply {
+            reply = if conn_core.configuration().is_session_recovery() {
+                conn_core.recover_sync(&mut request, e)?;
+                warn!("full_send_sync(): repeating request after session recovery...");
+                conn_core.roundtrip_sync(&request, Some(self), o_a_rsmd, o_a_descriptors, o_rs)
+            } else {
+                Err(e)
+            };
+        }
         match reply {
-            Ok(reply) => {
+            Ok(mut reply) => {
+                conn_core.register_prepared_statement(&request, &mut reply);
                 trace!(
                     "full_send_sync() took {} ms",
                     Instant::now().duration_since(start).as_millis(),
@@ -134,13 +221,32 @@ impl AmConnCore {
         );
         let start = Instant::now();
         let mut conn_core = self.lock_async().await;
+        #[cfg(feature = "alpha_routing")]
+        if let Some(reply) = conn_core
+            .route_async(&mut request, Some(self), o_a_descriptors)
+            .await
+        {
+            return reply;
+        }
         conn_core.augment_request(&mut request);
 
-        let reply = conn_core
+        let mut reply = conn_core
             .roundtrip_async(&request, Some(self), o_a_rsmd, o_a_descriptors, o_rs)
             .await;
+        if let Err(e @ HdbError::ConnectionBroken { .. }) = reply {
+            reply = if conn_core.configuration().is_session_recovery() {
+                conn_core.recover_async(&mut request, e).await?;
+                warn!("full_send_async(): repeating request after session recovery...");
+                conn_core
+                    .roundtrip_async(&request, Some(self), o_a_rsmd, o_a_descriptors, o_rs)
+                    .await
+            } else {
+                Err(e)
+            };
+        }
         match reply {
-            Ok(reply) => {
+            Ok(mut reply) => {
+                conn_core.register_prepared_statement(&request, &mut reply);
                 trace!(
                     "full_send_async() took {} ms",
                     Instant::now().duration_since(start).as_millis(),
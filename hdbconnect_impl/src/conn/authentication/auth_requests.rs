@@ -1,9 +1,14 @@
 use crate::{
-    conn::{authentication::Authenticator, CommandOptions, ConnectionCore},
+    conn::{
+        authentication::{Authenticator, SessionCookie},
+        CommandOptions, ConnectionCore,
+    },
     impl_err,
     protocol::{
-        parts::{AuthFields, ClientContext, ConnOptId, ConnectOptionsPart, DbConnectInfo},
-        MessageType, Part, Reply, ReplyType, Request,
+        parts::{
+            AuthFields, ClientContext, ConnOptId, ConnectOptionsPart, DbConnectInfo, Severity,
+        },
+        MessageType, Part, PartKind, Reply, ReplyType, Request,
     },
     HdbError, HdbResult,
 };
@@ -111,11 +116,15 @@ fn second_request(
 
     debug!("authenticating with {}", chosen_authenticator.name());
 
+    let client_proof = chosen_authenticator.client_proof(server_challenge_data, db_password)?;
     let mut auth_fields = AuthFields::with_capacity(3);
     {
-        auth_fields.push_string(db_user);
+        match chosen_authenticator.logon_name() {
+            Some(logon_name) => auth_fields.push(logon_name.to_vec()),
+            None => auth_fields.push_string(db_user),
+        }
         auth_fields.push(chosen_authenticator.name_as_bytes());
-        auth_fields.push(chosen_authenticator.client_proof(server_challenge_data, db_password)?);
+        auth_fields.push(client_proof);
     }
     request2.push(Part::Auth(auth_fields));
 
@@ -132,11 +141,27 @@ fn second_request(
     Ok(request2)
 }
 
+// The server error with which the server requires a password change;
+// the session is then opened, but only allows changing the password.
+const PASSWORD_CHANGE_REQUIRED: i32 = 414;
+
 fn evaluate_second_response(
-    reply: Reply,
+    mut reply: Reply,
     chosen_authenticator: &(dyn Authenticator + Send + Sync),
     conn_core: &mut ConnectionCore,
 ) -> HdbResult<()> {
+    let mut o_password_change_required = None;
+    if let Some(Part::Error(server_errors)) = reply.parts.remove_first_of_kind(PartKind::Error) {
+        for server_error in server_errors {
+            if server_error.code() == PASSWORD_CHANGE_REQUIRED {
+                o_password_change_required = Some(server_error);
+            } else if *server_error.severity() != Severity::Warning {
+                return Err(HdbError::Authentication {
+                    source: Box::new(HdbError::from(server_error)),
+                });
+            }
+        }
+    }
     reply.assert_expected_reply_type(ReplyType::Nil)?;
 
     conn_core.set_session_id(reply.session_id());
@@ -152,12 +177,76 @@ fn evaluate_second_response(
             Part::Auth(mut af) => match (af.pop(), af.pop(), af.pop()) {
                 (Some(server_proof), Some(method), None) => {
                     chosen_authenticator.evaluate_second_response(&method, &server_proof)?;
+                    if let Some(cookie) = chosen_authenticator.issued_session_cookie(&server_proof)
+                    {
+                        let logon_name = chosen_authenticator.logon_name().map_or_else(
+                            || conn_core.connect_params().dbuser().as_bytes().to_vec(),
+                            <[u8]>::to_vec,
+                        );
+                        conn_core.set_session_cookie(SessionCookie::new(logon_name, cookie));
+                    }
                 }
                 (_, _, _) => return Err(impl_err!("Expected 2 authfields")),
             },
             _ => warn!("second_auth_request: ignoring unexpected part = {:?}", part),
         }
     }
+    match o_password_change_required {
+        Some(server_error) => Err(HdbError::PasswordChangeRequired {
+            source: server_error,
+        }),
+        None => Ok(()),
+    }
+}
+
+// Changes the password of the user within a session that the server opened
+// with a required password change.
+fn password_change_request(db_user: &str, new_password: &SecUtf8) -> String {
+    format!(
+        "ALTER USER {} PASSWORD \"{}\"",
+        sql_identifier(db_user),
+        new_password.unsecure().replace('"', "\"\"")
+    )
+}
+
+// Plain identifiers are left unquoted, so that the database normalizes them to upper case
+// as with the logon; others are quoted
+fn sql_identifier(name: &str) -> String {
+    if !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+    {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+#[cfg(feature = "sync")]
+pub(crate) fn change_password_sync(
+    conn_core: &mut ConnectionCore,
+    new_password: &SecUtf8,
+) -> HdbResult<()> {
+    debug!("changing the password, as required by the server");
+    let sql = password_change_request(conn_core.connect_params().dbuser(), new_password);
+    let mut request = Request::new(MessageType::ExecuteDirect, CommandOptions::EMPTY);
+    request.push(Part::Command(&sql));
+    conn_core.roundtrip_sync(&request, None, None, None, &mut None)?;
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+pub(crate) async fn change_password_async(
+    conn_core: &mut ConnectionCore,
+    new_password: &SecUtf8,
+) -> HdbResult<()> {
+    debug!("changing the password, as required by the server");
+    let sql = password_change_request(conn_core.connect_params().dbuser(), new_password);
+    let mut request = Request::new(MessageType::ExecuteDirect, CommandOptions::EMPTY);
+    request.push(Part::Command(&sql));
+    conn_core
+        .roundtrip_async(&request, None, None, None, &mut None)
+        .await?;
     Ok(())
 }
 
@@ -165,18 +254,20 @@ fn evaluate_second_response(
 pub(crate) fn second_auth_request_sync(
     conn_core: &mut ConnectionCore,
     chosen_authenticator: &mut (dyn Authenticator + Send + Sync),
+    password: &SecUtf8,
     server_challenge_data: &[u8],
     reconnect: bool,
 ) -> HdbResult<()> {
     let second_request = second_request(
         conn_core.connect_params().dbuser(),
-        conn_core.connect_params().password(),
+        password,
         conn_core.connect_options().for_server(),
         &mut *chosen_authenticator,
         server_challenge_data,
         reconnect,
     )?;
 
+    // errors in the reply are evaluated in evaluate_second_response()
     let reply = conn_core
         .roundtrip_sync(&second_request, None, None, None, &mut None)
         .map_err(|e| HdbError::Authentication {
@@ -189,18 +280,20 @@ pub(crate) fn second_auth_request_sync(
 pub(crate) async fn second_auth_request_async(
     conn_core: &mut ConnectionCore,
     chosen_authenticator: &mut (dyn Authenticator + Send + Sync),
+    password: &SecUtf8,
     server_challenge_data: &[u8],
     reconnect: bool,
 ) -> HdbResult<()> {
     let second_request = second_request(
         conn_core.connect_params().dbuser(),
-        conn_core.connect_params().password(),
+        password,
         conn_core.connect_options().for_server(),
         &mut *chosen_authenticator,
         server_challenge_data,
         reconnect,
     )?;
 
+    // errors in the reply are evaluated in evaluate_second_response()
     let reply = conn_core
         .roundtrip_async(&second_request, None, None, None, &mut None)
         .await
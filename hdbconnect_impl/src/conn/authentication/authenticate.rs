@@ -1,42 +1,98 @@
 #[cfg(feature = "async")]
-use super::{first_auth_request_async, second_auth_request_async};
+use super::{change_password_async, first_auth_request_async, second_auth_request_async};
 #[cfg(feature = "sync")]
-use super::{first_auth_request_sync, second_auth_request_sync};
+use super::{change_password_sync, first_auth_request_sync, second_auth_request_sync};
 
 use crate::{
     conn::{
-        authentication::{Authenticator, FirstAuthResponse, ScramPbkdf2Sha256, ScramSha256},
+        authentication::{
+            Authenticator, BearerTokenAuthenticator, FirstAuthResponse, Ldap, Replayed,
+            ScramPbkdf2Sha256, ScramSha256, SessionCookie, SessionCookieAuthenticator, X509,
+        },
         ConnectionCore,
     },
     impl_err,
     protocol::parts::DbConnectInfo,
-    HdbResult,
+    HdbError, HdbResult,
 };
+use secstr::SecUtf8;
 
 #[must_use]
 pub(crate) enum AuthenticationResult {
     Ok,
     Redirect(DbConnectInfo),
+    // The session cookie was not accepted (e.g. because it expired) and is discarded;
+    // the client has to log on again, on a new connection, with its other credentials.
+    SessionCookieRejected,
 }
 
-// Do the authentication.
+// The authenticators that are proposed to the server, depending on the configured credentials.
+//
+// With a client certificate or a bearer token, the password is optional; if then the password
+// is not given, the pw-related authenticators are not added to the list.
+// LDAP is proposed along with SCRAM, if a password is given; the server selects it
+// for users whose password is checked by an LDAP server.
 //
-// Manages a list of supported authenticators.
-// So far we only support two; if more are implemented, the password might
-// become optional; if then the password is not given, the pw-related
-// authenticators mut not be added to the list.
+// A session cookie that was issued to an earlier logon with the same connect parameters
+// is proposed exclusively. The replay of a wire trace uses the method that was recorded.
+fn authenticators(
+    conn_core: &ConnectionCore,
+    o_session_cookie: Option<&SessionCookie>,
+    password: &SecUtf8,
+) -> HdbResult<Vec<Box<dyn Authenticator + Send + Sync>>> {
+    if conn_core.is_replay() {
+        return Ok(Replayed::boxed_authenticators());
+    }
+    if let Some(session_cookie) = o_session_cookie {
+        return Ok(vec![SessionCookieAuthenticator::boxed_authenticator(
+            session_cookie.clone(),
+        )]);
+    }
+    let connect_params = conn_core.connect_params();
+    let mut authenticators = Vec::<Box<dyn Authenticator + Send + Sync>>::with_capacity(5);
+    // Gss, SapLogon,
+    if let Some(client_certificate) = connect_params.client_certificate() {
+        authenticators.push(X509::boxed_authenticator(client_certificate)?);
+    }
+    if let Some(bearer_token) = connect_params.bearer_token() {
+        authenticators.push(BearerTokenAuthenticator::boxed_authenticator(bearer_token)?);
+    }
+    if authenticators.is_empty() || !password.unsecure().is_empty() {
+        authenticators.push(ScramSha256::boxed_authenticator());
+        authenticators.push(ScramPbkdf2Sha256::boxed_authenticator());
+    }
+    if !password.unsecure().is_empty() {
+        authenticators.push(Ldap::boxed_authenticator()?);
+    }
+    Ok(authenticators)
+}
+
+// Do the authentication.
 #[cfg(feature = "sync")]
 pub(crate) fn authenticate_sync(
     conn_core: &mut ConnectionCore,
     reconnect: bool,
 ) -> HdbResult<AuthenticationResult> {
     trace!("authenticate()");
+    let o_session_cookie = conn_core.connect_params().session_cookies().get();
+    let result = authenticate_with_sync(conn_core, o_session_cookie.as_ref(), reconnect);
+    session_cookie_rejected(conn_core, o_session_cookie.as_ref(), result)
+}
+
+#[cfg(feature = "sync")]
+fn authenticate_with_sync(
+    conn_core: &mut ConnectionCore,
+    o_session_cookie: Option<&SessionCookie>,
+    reconnect: bool,
+) -> HdbResult<AuthenticationResult> {
+    // A credential provider is asked for the current password, unless a cookie is used
+    let password = match o_session_cookie {
+        Some(_) => SecUtf8::from(""),
+        None => conn_core.connect_params().current_password_sync()?,
+    };
+
     // Propose some authenticators...
-    let authenticators: [Box<dyn Authenticator + Send + Sync>; 2] = [
-        // Cookie,  Gss, Saml, SapLogon, Jwt, Ldap,
-        ScramSha256::boxed_authenticator(),
-        ScramPbkdf2Sha256::boxed_authenticator(),
-    ];
+    let authenticators = authenticators(conn_core, o_session_cookie, &password)?;
 
     // ...with the first request.
     match first_auth_request_sync(conn_core, &authenticators)? {
@@ -47,7 +103,25 @@ pub(crate) fn authenticate_sync(
                 .find(|authenticator| authenticator.name() == selected)
                 .ok_or_else(|| impl_err!("None of the available authenticators was accepted"))?;
             // ...and use it for the second request
-            second_auth_request_sync(conn_core, &mut *authenticator, &server_challenge, reconnect)?;
+            let result = second_auth_request_sync(
+                conn_core,
+                &mut *authenticator,
+                &password,
+                &server_challenge,
+                reconnect,
+            );
+            // ...and change the password if the server requires it and a new one is given
+            let o_new_password = conn_core.connect_params().new_password().cloned();
+            let changed = match (result, o_new_password) {
+                (Err(HdbError::PasswordChangeRequired { .. }), Some(new_password)) => {
+                    change_password_sync(conn_core, &new_password)?;
+                    true
+                }
+                (result, _) => result.map(|()| false)?,
+            };
+            conn_core
+                .connect_params_mut()
+                .conclude_password_change(changed);
             conn_core.set_authenticated();
             trace!("session_id: {}", conn_core.session_id());
             Ok(AuthenticationResult::Ok)
@@ -64,12 +138,25 @@ pub(crate) async fn authenticate_async(
     reconnect: bool,
 ) -> HdbResult<AuthenticationResult> {
     trace!("authenticate()");
+    let o_session_cookie = conn_core.connect_params().session_cookies().get();
+    let result = authenticate_with_async(conn_core, o_session_cookie.as_ref(), reconnect).await;
+    session_cookie_rejected(conn_core, o_session_cookie.as_ref(), result)
+}
+
+#[cfg(feature = "async")]
+async fn authenticate_with_async(
+    conn_core: &mut ConnectionCore,
+    o_session_cookie: Option<&SessionCookie>,
+    reconnect: bool,
+) -> HdbResult<AuthenticationResult> {
+    // A credential provider is asked for the current password, unless a cookie is used
+    let password = match o_session_cookie {
+        Some(_) => SecUtf8::from(""),
+        None => conn_core.connect_params().current_password_async().await?,
+    };
+
     // Propose some authenticators...
-    let authenticators: [Box<dyn Authenticator + Send + Sync>; 2] = [
-        // Cookie,  Gss, Saml, SapLogon, Jwt, Ldap,
-        ScramSha256::boxed_authenticator(),
-        ScramPbkdf2Sha256::boxed_authenticator(),
-    ];
+    let authenticators = authenticators(conn_core, o_session_cookie, &password)?;
 
     // ...with the first request.
     match first_auth_request_async(conn_core, &authenticators).await? {
@@ -80,8 +167,26 @@ pub(crate) async fn authenticate_async(
                 .find(|authenticator| authenticator.name() == selected)
                 .ok_or_else(|| impl_err!("None of the available authenticators was accepted"))?;
             // ...and use it for the second request
-            second_auth_request_async(conn_core, &mut *authenticator, &server_challenge, reconnect)
-                .await?;
+            let result = second_auth_request_async(
+                conn_core,
+                &mut *authenticator,
+                &password,
+                &server_challenge,
+                reconnect,
+            )
+            .await;
+            // ...and change the password if the server requires it and a new one is given
+            let o_new_password = conn_core.connect_params().new_password().cloned();
+            let changed = match (result, o_new_password) {
+                (Err(HdbError::PasswordChangeRequired { .. }), Some(new_password)) => {
+                    change_password_async(conn_core, &new_password).await?;
+                    true
+                }
+                (result, _) => result.map(|()| false)?,
+            };
+            conn_core
+                .connect_params_mut()
+                .conclude_password_change(changed);
             conn_core.set_authenticated();
             trace!("session_id: {}", conn_core.session_id());
             Ok(AuthenticationResult::Ok)
@@ -91,3 +196,26 @@ pub(crate) async fn authenticate_async(
         }
     }
 }
+
+// If the server refuses a session cookie, the cookie is discarded, and the logon is repeated
+// without it; other errors are reported as they are.
+fn session_cookie_rejected(
+    conn_core: &ConnectionCore,
+    o_session_cookie: Option<&SessionCookie>,
+    result: HdbResult<AuthenticationResult>,
+) -> HdbResult<AuthenticationResult> {
+    match (result, o_session_cookie) {
+        (
+            Err(e @ (HdbError::DbError { .. } | HdbError::Authentication { .. })),
+            Some(session_cookie),
+        ) => {
+            debug!("session cookie was not accepted ({e}), logging on without it");
+            conn_core
+                .connect_params()
+                .session_cookies()
+                .discard(session_cookie);
+            Ok(AuthenticationResult::SessionCookieRejected)
+        }
+        (result, _) => result,
+    }
+}
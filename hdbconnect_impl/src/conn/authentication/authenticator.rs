@@ -15,6 +15,16 @@ pub(crate) trait Authenticator {
     ) -> HdbResult<Vec<u8>>;
     fn verify_server(&self, server_proof: &[u8]) -> HdbResult<()>;
 
+    // The user for the second request, if it differs from the configured database user.
+    fn logon_name(&self) -> Option<&[u8]> {
+        None
+    }
+
+    // A session cookie that the server issued with its final reply, if the method supports it.
+    fn issued_session_cookie(&self, _server_proof: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
     fn evaluate_second_response(&self, method: &[u8], server_proof: &[u8]) -> HdbResult<()> {
         if method == self.name().as_bytes() {
             self.verify_server(server_proof)
@@ -0,0 +1,70 @@
+use crate::{
+    conn::authentication::Authenticator, impl_err, BearerToken, BearerTokenKind, HdbResult,
+};
+use secstr::SecUtf8;
+
+// Authentication with a JSON Web Token (method JWT) or a SAML assertion (method SAML):
+// the client sends the token as client challenge, the server responds with the name
+// of the database user that it has derived from the token,
+// and the second request confirms the logon for that user.
+pub(crate) struct BearerTokenAuthenticator {
+    kind: BearerTokenKind,
+    token: SecUtf8,
+    logon_name: Option<Vec<u8>>,
+}
+impl BearerTokenAuthenticator {
+    // A token provider is asked for a fresh token whenever a connection is (re-)established
+    pub fn boxed_authenticator(
+        bearer_token: &BearerToken,
+    ) -> HdbResult<Box<dyn Authenticator + Send + Sync>> {
+        Ok(Box::new(Self {
+            kind: bearer_token.kind(),
+            token: bearer_token.token()?,
+            logon_name: None,
+        }))
+    }
+}
+
+impl Authenticator for BearerTokenAuthenticator {
+    fn name(&self) -> &'static str {
+        match self.kind {
+            BearerTokenKind::Jwt => "JWT",
+            BearerTokenKind::Saml => "SAML",
+        }
+    }
+
+    fn name_as_bytes(&self) -> Vec<u8> {
+        self.name().as_bytes().to_owned()
+    }
+
+    fn client_challenge(&self) -> &[u8] {
+        self.token.unsecure().as_bytes()
+    }
+
+    // `server_data` is the logon name; the proof is empty
+    fn client_proof(&mut self, server_data: &[u8], _password: &SecUtf8) -> HdbResult<Vec<u8>> {
+        if server_data.is_empty() {
+            return Err(impl_err!("{}: no logon name received", self.name()));
+        }
+        debug!(
+            "{}: logging on as {}",
+            self.name(),
+            String::from_utf8_lossy(server_data)
+        );
+        self.logon_name = Some(server_data.to_vec());
+        Ok(Vec::new())
+    }
+
+    fn logon_name(&self) -> Option<&[u8]> {
+        self.logon_name.as_deref()
+    }
+
+    // `server_proof` is a session cookie, or empty
+    fn verify_server(&self, _server_proof: &[u8]) -> HdbResult<()> {
+        Ok(())
+    }
+
+    fn issued_session_cookie(&self, server_proof: &[u8]) -> Option<Vec<u8>> {
+        (!server_proof.is_empty()).then(|| server_proof.to_vec())
+    }
+}
@@ -1,6 +1,11 @@
+use crate::{impl_err, usage_err, HdbResult};
 use hmac::{Hmac, Mac};
+use num_bigint::BigUint;
 use pbkdf2::pbkdf2;
+use rand::{rng, RngCore};
+use rustls::pki_types::{pem::PemObject, SubjectPublicKeyInfoDer};
 use secstr::SecUtf8;
+use sha1::Sha1;
 use sha2::{Digest, Sha256};
 
 pub(crate) fn scram_sha256(
@@ -96,3 +101,165 @@ pub fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
     }
     bytes
 }
+
+// DER encoding of the algorithm identifier rsaEncryption (1.2.840.113549.1.1.1)
+const RSA_ENCRYPTION_OID: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+const SHA1_SIZE: usize = 20;
+
+// An RSA public key, as the server sends it with the LDAP authentication.
+pub(crate) struct RsaPublicKey {
+    modulus: BigUint,
+    exponent: BigUint,
+}
+impl RsaPublicKey {
+    // Parses a PEM-encoded SubjectPublicKeyInfo (`-----BEGIN PUBLIC KEY-----`).
+    pub fn from_pem(pem: &[u8]) -> HdbResult<Self> {
+        let spki = SubjectPublicKeyInfoDer::from_pem_slice(pem)
+            .map_err(|e| impl_err!("RSA public key cannot be parsed: {e}"))?;
+        Self::from_spki_der(&spki)
+    }
+
+    // SEQUENCE { SEQUENCE { OID, NULL }, BIT STRING { SEQUENCE { INTEGER n, INTEGER e } } }
+    fn from_spki_der(der: &[u8]) -> HdbResult<Self> {
+        let (spki, _) = der_element(der, 0x30)?;
+        let (algorithm, rest) = der_element(spki, 0x30)?;
+        let (oid, _) = der_element(algorithm, 0x06)?;
+        if oid != RSA_ENCRYPTION_OID {
+            return Err(impl_err!("public key is not an RSA key"));
+        }
+        let (bit_string, _) = der_element(rest, 0x03)?;
+        let Some((0, rsa_public_key)) = bit_string.split_first() else {
+            return Err(impl_err!("RSA public key: unexpected bit string"));
+        };
+        let (rsa_public_key, _) = der_element(rsa_public_key, 0x30)?;
+        let (modulus, rest) = der_element(rsa_public_key, 0x02)?;
+        let (exponent, _) = der_element(rest, 0x02)?;
+        Ok(Self {
+            modulus: BigUint::from_bytes_be(modulus),
+            exponent: BigUint::from_bytes_be(exponent),
+        })
+    }
+
+    // The size of the modulus, in bytes.
+    fn size(&self) -> usize {
+        usize::try_from(self.modulus.bits().div_ceil(8)).unwrap_or(usize::MAX)
+    }
+
+    // RSAES-OAEP encryption (RFC 8017) with SHA-1, MGF1 and an empty label.
+    pub fn encrypt_oaep_sha1(&self, message: &[u8]) -> HdbResult<Vec<u8>> {
+        let mut seed = [0_u8; SHA1_SIZE];
+        rng().fill_bytes(&mut seed);
+        self.encrypt_oaep_sha1_with_seed(message, &seed)
+    }
+
+    pub fn encrypt_oaep_sha1_with_seed(
+        &self,
+        message: &[u8],
+        seed: &[u8; SHA1_SIZE],
+    ) -> HdbResult<Vec<u8>> {
+        let k = self.size();
+        if message.len() + 2 * SHA1_SIZE + 2 > k {
+            return Err(usage_err!(
+                "message with {} bytes is too long for an RSA key with {k} bytes",
+                message.len()
+            ));
+        }
+        // DB = lHash || PS || 0x01 || M
+        let mut data_block = Sha1::digest([]).to_vec();
+        data_block.resize(k - message.len() - SHA1_SIZE - 2, 0);
+        data_block.push(0x01);
+        data_block.extend_from_slice(message);
+
+        let masked_data_block = xor(&data_block, &mgf1_sha1(seed, data_block.len()));
+        let masked_seed = xor(seed, &mgf1_sha1(&masked_data_block, SHA1_SIZE));
+
+        // EM = 0x00 || maskedSeed || maskedDB
+        let mut encoded_message = Vec::<u8>::with_capacity(k);
+        encoded_message.push(0);
+        encoded_message.extend_from_slice(&masked_seed);
+        encoded_message.extend_from_slice(&masked_data_block);
+
+        let cipher = BigUint::from_bytes_be(&encoded_message)
+            .modpow(&self.exponent, &self.modulus)
+            .to_bytes_be();
+        let mut buf = vec![0_u8; k - cipher.len()];
+        buf.extend_from_slice(&cipher);
+        Ok(buf)
+    }
+}
+
+// RSAES-OAEP decryption with SHA-1, MGF1 and an empty label; None if the cipher is invalid.
+#[cfg(any(test, feature = "mock_server"))]
+pub(crate) fn rsa_decrypt_oaep_sha1(
+    public_key: &RsaPublicKey,
+    private_exponent: &BigUint,
+    cipher: &[u8],
+) -> Option<Vec<u8>> {
+    let k = public_key.size();
+    let plain = BigUint::from_bytes_be(cipher)
+        .modpow(private_exponent, &public_key.modulus)
+        .to_bytes_be();
+    if cipher.len() != k || k < 2 * SHA1_SIZE + 2 || plain.len() > k - 1 {
+        return None;
+    }
+    // EM without the leading zero bytes
+    let mut encoded_message = vec![0_u8; k - 1 - plain.len()];
+    encoded_message.extend_from_slice(&plain);
+    let (masked_seed, masked_data_block) = encoded_message.split_at(SHA1_SIZE);
+    let seed = xor(masked_seed, &mgf1_sha1(masked_data_block, SHA1_SIZE));
+    let data_block = xor(
+        masked_data_block,
+        &mgf1_sha1(&seed, masked_data_block.len()),
+    );
+
+    let (label_hash, rest) = data_block.split_at(SHA1_SIZE);
+    if label_hash != Sha1::digest([]).as_slice() {
+        return None;
+    }
+    let separator = rest.iter().position(|b| *b != 0)?;
+    (rest[separator] == 0x01).then(|| rest[separator + 1..].to_vec())
+}
+
+// Mask generation function MGF1 (RFC 8017) with SHA-1.
+fn mgf1_sha1(seed: &[u8], len: usize) -> Vec<u8> {
+    let mut mask = Vec::<u8>::with_capacity(len + SHA1_SIZE);
+    let mut counter = 0_u32;
+    while mask.len() < len {
+        let mut sha = Sha1::new();
+        sha.update(seed);
+        sha.update(counter.to_be_bytes());
+        mask.extend_from_slice(&sha.finalize());
+        counter += 1;
+    }
+    mask.truncate(len);
+    mask
+}
+
+// Returns the content of the DER element with the given tag, and the remaining bytes.
+fn der_element(der: &[u8], tag: u8) -> HdbResult<(&[u8], &[u8])> {
+    let truncated = || impl_err!("RSA public key: truncated DER element");
+    let (&found_tag, rest) = der.split_first().ok_or_else(truncated)?;
+    if found_tag != tag {
+        return Err(impl_err!(
+            "RSA public key: found DER tag {found_tag:#04x}, expected {tag:#04x}"
+        ));
+    }
+    let (&first, mut rest) = rest.split_first().ok_or_else(truncated)?;
+    let len = if first < 0x80 {
+        usize::from(first)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count > std::mem::size_of::<usize>() || rest.len() < count {
+            return Err(truncated());
+        }
+        let (len_bytes, remainder) = rest.split_at(count);
+        rest = remainder;
+        len_bytes
+            .iter()
+            .fold(0_usize, |len, b| (len << 8) | usize::from(*b))
+    };
+    if rest.len() < len {
+        return Err(truncated());
+    }
+    Ok(rest.split_at(len))
+}
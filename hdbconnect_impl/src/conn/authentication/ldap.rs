@@ -0,0 +1,225 @@
+use crate::{
+    conn::authentication::{crypto_util::RsaPublicKey, Authenticator},
+    impl_err,
+    protocol::parts::AuthFields,
+    HdbResult,
+};
+use rand::{rng, RngCore};
+use secstr::SecUtf8;
+
+const CLIENT_NONCE_SIZE: usize = 64;
+const SERVER_NONCE_SIZE: usize = 64;
+// The password is sent RSA-encrypted
+const CAPABILITIES: u8 = 0x01;
+
+// Authentication of a user whose password is checked by an LDAP server:
+// the client sends a nonce, the server responds with its own nonce and its RSA public key,
+// the client responds with the password and the server nonce, encrypted with that key.
+pub(crate) struct Ldap {
+    client_nonce: Vec<u8>,
+    client_challenge: Vec<u8>,
+}
+impl Ldap {
+    pub fn boxed_authenticator() -> HdbResult<Box<dyn Authenticator + Send + Sync>> {
+        let mut client_nonce = vec![0_u8; CLIENT_NONCE_SIZE];
+        rng().fill_bytes(&mut client_nonce);
+        Ok(Box::new(Self::with_client_nonce(client_nonce)?))
+    }
+
+    fn with_client_nonce(client_nonce: Vec<u8>) -> HdbResult<Self> {
+        let mut challenge_fields = AuthFields::with_capacity(2);
+        challenge_fields.push(client_nonce.clone());
+        challenge_fields.push(vec![CAPABILITIES]);
+        let mut client_challenge = Vec::<u8>::with_capacity(challenge_fields.size());
+        challenge_fields.emit(&mut client_challenge)?;
+        Ok(Self {
+            client_nonce,
+            client_challenge,
+        })
+    }
+
+    // `server_data` is an AuthFields with the client nonce, the server nonce,
+    // the PEM-encoded public key of the server, and the capabilities
+    fn parse_server_data(&self, server_data: &[u8]) -> HdbResult<(Vec<u8>, RsaPublicKey)> {
+        let mut af = AuthFields::parse(&mut std::io::Cursor::new(server_data))?;
+        let (Some(capabilities), Some(public_key), Some(server_nonce), Some(client_nonce), None) =
+            (af.pop(), af.pop(), af.pop(), af.pop(), af.pop())
+        else {
+            return Err(impl_err!("LDAP: expected 4 auth fields"));
+        };
+        if client_nonce != self.client_nonce {
+            return Err(impl_err!("LDAP: server returned a wrong client nonce"));
+        }
+        if server_nonce.len() != SERVER_NONCE_SIZE {
+            return Err(impl_err!(
+                "LDAP: server nonce has {} bytes, expected {SERVER_NONCE_SIZE}",
+                server_nonce.len()
+            ));
+        }
+        if capabilities != [CAPABILITIES] {
+            return Err(impl_err!(
+                "LDAP: unsupported server capabilities {capabilities:?}"
+            ));
+        }
+        Ok((server_nonce, RsaPublicKey::from_pem(&public_key)?))
+    }
+}
+
+// The encrypted message: the password, a zero byte, and the server nonce
+fn password_message(password: &SecUtf8, server_nonce: &[u8]) -> Vec<u8> {
+    let password = password.unsecure().as_bytes();
+    let mut message = Vec::<u8>::with_capacity(password.len() + 1 + server_nonce.len());
+    message.extend_from_slice(password);
+    message.push(0);
+    message.extend_from_slice(server_nonce);
+    message
+}
+
+impl Authenticator for Ldap {
+    fn name(&self) -> &'static str {
+        "LDAP"
+    }
+
+    fn name_as_bytes(&self) -> Vec<u8> {
+        self.name().as_bytes().to_owned()
+    }
+
+    fn client_challenge(&self) -> &[u8] {
+        &self.client_challenge
+    }
+
+    // the proof is an AuthFields with the encrypted password
+    fn client_proof(&mut self, server_data: &[u8], password: &SecUtf8) -> HdbResult<Vec<u8>> {
+        let (server_nonce, public_key) = self.parse_server_data(server_data)?;
+        let encrypted_password =
+            public_key.encrypt_oaep_sha1(&password_message(password, &server_nonce))?;
+
+        let mut proof_fields = AuthFields::with_capacity(1);
+        proof_fields.push(encrypted_password);
+        let mut buf = Vec::<u8>::with_capacity(proof_fields.size());
+        proof_fields.emit(&mut buf)?;
+        Ok(buf)
+    }
+
+    // The server does not prove anything
+    fn verify_server(&self, _server_proof: &[u8]) -> HdbResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{password_message, Ldap, CAPABILITIES};
+    use crate::{
+        conn::authentication::{
+            authenticator::Authenticator,
+            crypto_util::{rsa_decrypt_oaep_sha1, RsaPublicKey},
+        },
+        protocol::parts::AuthFields,
+    };
+    use num_bigint::BigUint;
+    use secstr::SecUtf8;
+
+    // A 1024-bit key pair, and a cipher that was created with python's `cryptography`
+    // (and verified by decrypting it with the private key)
+    const PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDiTDpdra3t47PlEOxIuVxFDRkD
+vyzFjyfFT3g9TjJyvEp+45AgAhUtWElfC2KWGhYcJfQlgiGPlX+M8/PvxuajEanM
+1iVS+kVMpImjZMmoipQ3lTjf/EX3cmSh9ATg1Nwz2igm0mDZsGSgSXH+vXv/Q0Pi
+rtNVhNV4zMzKVsN/xQIDAQAB
+-----END PUBLIC KEY-----
+";
+    const PRIVATE_EXPONENT: &str = "\
+        64373bd5c69772f556f287870c41c241f625f0c9d5eafb95b2449a4c13f83565\
+        7b5c2c2ce46fe7aef81d2e111dacb98ab7167bc3ac986e3cd78ab5727eea47b2\
+        741b98307b32a51aa389857bfd08fd5226a3905ef9384e85b0231dd32400e96f\
+        70c79a4b597ab3155e16cacf78aacb5061edcdf2596f233e542a68c5bb23a835";
+    // OAEP seed a0..b3, message "Secret1", a zero byte, and the nonce 00..3f
+    const CIPHER: &str = "\
+        5f7d50e7640865bf11cd4ede11af1b846a7a5f8d5c817a975f63623c58446071\
+        84c42c61a184b85b96a74b936cd17a9c82faf26036d8cfb1889693eedc925830\
+        9c35430cc14e097072e6a6438a9a93b1cec02bac42ed7643e2665de59d8b6909\
+        6af7d392eb09ea1519ea7f3bcb52246243775ff9ad06d63d156d7d416b3c33d3";
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        BigUint::parse_bytes(hex.as_bytes(), 16)
+            .unwrap()
+            .to_bytes_be()
+    }
+
+    fn server_nonce() -> Vec<u8> {
+        (0..64).collect()
+    }
+
+    // cargo test conn::authentication::ldap::tests::test_oaep_vector -- --nocapture
+    #[test]
+    fn test_oaep_vector() {
+        info!("test RSA-OAEP encryption against a known vector");
+        let public_key = RsaPublicKey::from_pem(PUBLIC_KEY.as_bytes()).unwrap();
+        let message = password_message(&SecUtf8::from("Secret1"), &server_nonce());
+        let seed: [u8; 20] = std::array::from_fn(|i| 0xa0 + u8::try_from(i).unwrap());
+        let cipher = public_key
+            .encrypt_oaep_sha1_with_seed(&message, &seed)
+            .unwrap();
+        assert_eq!(cipher, from_hex(CIPHER));
+
+        let private_exponent = BigUint::from_bytes_be(&from_hex(PRIVATE_EXPONENT));
+        assert_eq!(
+            rsa_decrypt_oaep_sha1(&public_key, &private_exponent, &cipher),
+            Some(message)
+        );
+
+        info!("test that the message size is limited by the key size");
+        assert!(public_key.encrypt_oaep_sha1(&[0_u8; 87]).is_err());
+        assert!(public_key.encrypt_oaep_sha1(&[0_u8; 86]).is_ok());
+    }
+
+    // cargo test conn::authentication::ldap::tests::test_client_proof -- --nocapture
+    #[test]
+    fn test_client_proof() {
+        info!("test the client proof for a server challenge");
+        let client_nonce: Vec<u8> = (100..164).collect();
+        let mut ldap = Ldap::with_client_nonce(client_nonce.clone()).unwrap();
+
+        let mut challenge_fields =
+            AuthFields::parse(&mut std::io::Cursor::new(ldap.client_challenge())).unwrap();
+        assert_eq!(challenge_fields.pop(), Some(vec![CAPABILITIES]));
+        assert_eq!(challenge_fields.pop(), Some(client_nonce.clone()));
+        assert_eq!(challenge_fields.pop(), None);
+
+        let server_data = |client_nonce: &[u8], public_key: &str| {
+            let mut fields = AuthFields::with_capacity(4);
+            fields.push(client_nonce.to_vec());
+            fields.push(server_nonce());
+            fields.push(public_key.as_bytes().to_vec());
+            fields.push(vec![CAPABILITIES]);
+            let mut buf = Vec::<u8>::new();
+            fields.emit(&mut buf).unwrap();
+            buf
+        };
+        let password = SecUtf8::from("Secret1");
+        let proof = ldap
+            .client_proof(&server_data(&client_nonce, PUBLIC_KEY), &password)
+            .unwrap();
+
+        // the encryption is randomized, so the proof is checked by decrypting it
+        let mut proof_fields = AuthFields::parse(&mut std::io::Cursor::new(proof)).unwrap();
+        let cipher = proof_fields.pop().unwrap();
+        assert_eq!(proof_fields.pop(), None);
+        assert_eq!(cipher.len(), 128);
+        let public_key = RsaPublicKey::from_pem(PUBLIC_KEY.as_bytes()).unwrap();
+        let private_exponent = BigUint::from_bytes_be(&from_hex(PRIVATE_EXPONENT));
+        assert_eq!(
+            rsa_decrypt_oaep_sha1(&public_key, &private_exponent, &cipher),
+            Some(password_message(&password, &server_nonce()))
+        );
+
+        info!("test that unexpected server data are rejected");
+        assert!(ldap
+            .client_proof(&server_data(&server_nonce(), PUBLIC_KEY), &password)
+            .is_err());
+        assert!(ldap
+            .client_proof(&server_data(&client_nonce, "no key"), &password)
+            .is_err());
+    }
+}
@@ -1,24 +1,41 @@
 mod auth_requests;
 mod authenticate;
 mod authenticator;
+mod bearer_token;
 mod crypto_util;
+mod ldap;
+mod replayed;
 mod scram_pbkdf2_sha256;
 mod scram_sha256;
+mod session_cookie;
+mod x509;
 
 #[cfg(feature = "sync")]
 pub(super) use self::{
-    auth_requests::{first_auth_request_sync, second_auth_request_sync},
+    auth_requests::{change_password_sync, first_auth_request_sync, second_auth_request_sync},
     authenticate::authenticate_sync,
 };
 
 #[cfg(feature = "async")]
 pub(super) use self::{
-    auth_requests::{first_auth_request_async, second_auth_request_async},
+    auth_requests::{change_password_async, first_auth_request_async, second_auth_request_async},
     authenticate::authenticate_async,
 };
 
+#[cfg(feature = "mock_server")]
+pub(crate) use self::crypto_util::{
+    rsa_decrypt_oaep_sha1, scram_pdkdf2_sha256, scram_sha256, RsaPublicKey,
+};
+
 pub(super) use self::{
-    auth_requests::FirstAuthResponse, authenticate::AuthenticationResult,
-    authenticator::Authenticator, scram_pbkdf2_sha256::ScramPbkdf2Sha256,
+    auth_requests::FirstAuthResponse,
+    authenticate::AuthenticationResult,
+    authenticator::Authenticator,
+    bearer_token::BearerTokenAuthenticator,
+    ldap::Ldap,
+    replayed::{Replayed, METHOD_NAMES},
+    scram_pbkdf2_sha256::ScramPbkdf2Sha256,
     scram_sha256::ScramSha256,
+    session_cookie::{SessionCookie, SessionCookieAuthenticator, SessionCookieJar},
+    x509::X509,
 };
@@ -0,0 +1,116 @@
+use crate::{conn::authentication::Authenticator, HdbResult};
+use secstr::{SecUtf8, SecVec};
+use std::sync::{Arc, Mutex, PoisonError};
+
+// A session cookie that the server issued with a successful logon, together with
+// the name of the database user for whom it was issued.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SessionCookie {
+    logon_name: Vec<u8>,
+    cookie: SecVec<u8>,
+}
+impl SessionCookie {
+    pub fn new(logon_name: Vec<u8>, cookie: Vec<u8>) -> Self {
+        Self {
+            logon_name,
+            cookie: SecVec::new(cookie),
+        }
+    }
+}
+
+// Keeps the latest session cookie for all connections that are opened
+// with clones of the same ConnectParams, i.e., with `Connection::spawn()`,
+// by the connection pools, and on reconnects.
+#[derive(Clone, Default)]
+pub(crate) struct SessionCookieJar(Arc<Mutex<Option<SessionCookie>>>);
+impl SessionCookieJar {
+    pub fn get(&self) -> Option<SessionCookie> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn set(&self, session_cookie: SessionCookie) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(session_cookie);
+    }
+
+    // Removes the given cookie, unless it was replaced meanwhile
+    pub fn discard(&self, session_cookie: &SessionCookie) {
+        let mut guard = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if guard.as_ref() == Some(session_cookie) {
+            *guard = None;
+        }
+    }
+}
+impl std::fmt::Debug for SessionCookieJar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionCookieJar")
+            .field("filled", &self.get().is_some())
+            .finish()
+    }
+}
+// The jar is state, not configuration, and is thus ignored in comparisons
+impl PartialEq for SessionCookieJar {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+impl Eq for SessionCookieJar {}
+
+// Re-authentication with a session cookie (method SessionCookie): the client sends the cookie,
+// followed by its client id, and confirms the logon for the user for whom the cookie was
+// issued; this avoids in particular the expensive key derivation of SCRAM-PBKDF2-SHA256.
+pub(crate) struct SessionCookieAuthenticator {
+    session_cookie: SessionCookie,
+    client_challenge: Vec<u8>,
+}
+impl SessionCookieAuthenticator {
+    pub const NAME: &'static str = "SessionCookie";
+
+    pub fn boxed_authenticator(
+        session_cookie: SessionCookie,
+    ) -> Box<dyn Authenticator + Send + Sync> {
+        let mut client_challenge = session_cookie.cookie.unsecure().to_vec();
+        client_challenge.extend_from_slice(client_id().as_bytes());
+        Box::new(Self {
+            session_cookie,
+            client_challenge,
+        })
+    }
+}
+
+impl Authenticator for SessionCookieAuthenticator {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn name_as_bytes(&self) -> Vec<u8> {
+        self.name().as_bytes().to_owned()
+    }
+
+    fn client_challenge(&self) -> &[u8] {
+        &self.client_challenge
+    }
+
+    // the cookie was already checked with the first request; the proof is empty
+    fn client_proof(&mut self, _server_data: &[u8], _password: &SecUtf8) -> HdbResult<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn logon_name(&self) -> Option<&[u8]> {
+        Some(&self.session_cookie.logon_name)
+    }
+
+    fn verify_server(&self, _server_proof: &[u8]) -> HdbResult<()> {
+        Ok(())
+    }
+}
+
+// The client id with which the server associates the cookie: "<pid>@<client host>"
+fn client_id() -> String {
+    let host = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "localhost".to_string());
+    format!("{}@{host}", std::process::id())
+}
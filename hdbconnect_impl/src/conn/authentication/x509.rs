@@ -0,0 +1,127 @@
+use crate::{
+    conn::authentication::Authenticator, impl_err, protocol::parts::AuthFields, usage_err,
+    ClientCertificate, HdbResult,
+};
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer},
+    ClientConfig, SignatureScheme,
+};
+use secstr::SecUtf8;
+use std::sync::Arc;
+
+const SERVER_NONCE_SIZE: usize = 64;
+
+// The schemes with which the signature can be created; the server verifies it
+// with the public key of the client certificate.
+const SIGNATURE_SCHEMES: [SignatureScheme; 4] = [
+    SignatureScheme::RSA_PKCS1_SHA256,
+    SignatureScheme::ECDSA_NISTP256_SHA256,
+    SignatureScheme::ECDSA_NISTP384_SHA384,
+    SignatureScheme::ED25519,
+];
+
+// Authentication with a client certificate: the server sends a nonce, the client
+// responds with its certificate chain and a signature over the chain and the nonce.
+pub(crate) struct X509 {
+    certificates: Vec<CertificateDer<'static>>,
+    private_key: PrivateKeyDer<'static>,
+}
+impl X509 {
+    pub fn boxed_authenticator(
+        client_certificate: &ClientCertificate,
+    ) -> HdbResult<Box<dyn Authenticator + Send + Sync>> {
+        let (certificates, private_key) = client_certificate.load()?;
+        Ok(Box::new(Self {
+            certificates,
+            private_key,
+        }))
+    }
+
+    fn sign(&self, message: &[u8]) -> HdbResult<Vec<u8>> {
+        // installs the default crypto provider from the crate features, if necessary
+        let crypto_provider = Arc::clone(ClientConfig::builder().crypto_provider());
+        let signing_key = crypto_provider
+            .key_provider
+            .load_private_key(self.private_key.clone_key())
+            .map_err(|e| usage_err!("private key of the client certificate not usable: {e}"))?;
+        let signer = signing_key
+            .choose_scheme(&SIGNATURE_SCHEMES)
+            .ok_or_else(|| {
+                usage_err!(
+                    "private key of the client certificate has an unsupported algorithm: {:?}",
+                    signing_key.algorithm()
+                )
+            })?;
+        signer
+            .sign(message)
+            .map_err(|e| impl_err!("signing with the client certificate failed: {e}"))
+    }
+}
+
+impl Authenticator for X509 {
+    fn name(&self) -> &'static str {
+        "X509"
+    }
+
+    fn name_as_bytes(&self) -> Vec<u8> {
+        self.name().as_bytes().to_owned()
+    }
+
+    fn client_challenge(&self) -> &[u8] {
+        &[]
+    }
+
+    // `server_data` is the server nonce;
+    // the proof is an AuthFields with the client certificate, an AuthFields with the
+    // further certificates of the chain (or an empty field), and the signature
+    fn client_proof(&mut self, server_data: &[u8], _password: &SecUtf8) -> HdbResult<Vec<u8>> {
+        if server_data.len() != SERVER_NONCE_SIZE {
+            return Err(impl_err!(
+                "X509: server nonce has {} bytes, expected {SERVER_NONCE_SIZE}",
+                server_data.len()
+            ));
+        }
+        let (certificate, chain) = self
+            .certificates
+            .split_first()
+            .ok_or_else(|| impl_err!("X509: no client certificate"))?;
+
+        let mut message = certificate.to_vec();
+        let chain_field = if chain.is_empty() {
+            Vec::new()
+        } else {
+            let mut chain_fields = AuthFields::with_capacity(chain.len());
+            for chain_certificate in chain {
+                message.extend_from_slice(chain_certificate);
+                chain_fields.push(chain_certificate.to_vec());
+            }
+            let mut buf = Vec::<u8>::with_capacity(chain_fields.size());
+            chain_fields.emit(&mut buf)?;
+            buf
+        };
+        message.extend_from_slice(server_data);
+
+        let mut proof_fields = AuthFields::with_capacity(3);
+        proof_fields.push(certificate.to_vec());
+        proof_fields.push(chain_field);
+        proof_fields.push(self.sign(&message)?);
+        let mut buf = Vec::<u8>::with_capacity(proof_fields.size());
+        proof_fields.emit(&mut buf)?;
+        Ok(buf)
+    }
+
+    // `server_proof` is an AuthFields with the name of the database user
+    // to which the certificate is mapped
+    fn verify_server(&self, server_proof: &[u8]) -> HdbResult<()> {
+        if !server_proof.is_empty() {
+            let mut af = AuthFields::parse(&mut std::io::Cursor::new(server_proof))?;
+            if let Some(logon_name) = af.pop() {
+                debug!(
+                    "X509: logged on as {}",
+                    String::from_utf8_lossy(&logon_name)
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, PartialOrd, Ord, Hash)]
     pub(crate) struct CommandOptions: u8 {
         const HOLD_CURSORS_OVER_COMMIT = 0b0000_1000;
+        const SCROLLABLE_CURSOR_ON = 0b0010_0000;
         const HOLD_CURSORS_OVER_ROLLBACK = 0b0100_0000;
         const EMPTY = 0;
         const ALL = !0;
@@ -25,6 +26,22 @@ impl CommandOptions {
         self & CommandOptions::HOLD_CURSORS_OVER_ROLLBACK
             == CommandOptions::HOLD_CURSORS_OVER_ROLLBACK
     }
+    // Returns if result sets are to be produced with scrollable cursors.
+    pub(crate) fn is_scrollable_cursor_on(self) -> bool {
+        self & CommandOptions::SCROLLABLE_CURSOR_ON == CommandOptions::SCROLLABLE_CURSOR_ON
+    }
+    // Replaces the holdability flags, and keeps the other flags.
+    pub(crate) fn with_holdability(self, holdability: CursorHoldability) -> Self {
+        (self & CommandOptions::SCROLLABLE_CURSOR_ON) | holdability.into()
+    }
+    // Sets or removes the flag for scrollable cursors, and keeps the other flags.
+    pub(crate) fn with_scrollable_cursor(self, scrollable: bool) -> Self {
+        if scrollable {
+            self | CommandOptions::SCROLLABLE_CURSOR_ON
+        } else {
+            self - CommandOptions::SCROLLABLE_CURSOR_ON
+        }
+    }
     pub(crate) fn as_u8(self) -> u8 {
         self.bits()
     }
@@ -99,5 +116,16 @@ mod test {
             s.as_str(),
             "\"HOLD_CURSORS_OVER_COMMIT | HOLD_CURSORS_OVER_ROLLBACK\""
         );
+
+        let co4 = CommandOptions::default().with_scrollable_cursor(true);
+        assert!(co4.is_scrollable_cursor_on());
+        assert_eq!(co4.as_u8(), 0b0010_1000);
+        let co5 = co4.with_holdability(CursorHoldability::Rollback);
+        assert!(co5.is_scrollable_cursor_on());
+        assert!(matches!(
+            CursorHoldability::from(co5),
+            CursorHoldability::Rollback
+        ));
+        assert_eq!(co5.with_scrollable_cursor(false), co2);
     }
 }
@@ -1,5 +1,13 @@
-use super::command_options::{CommandOptions, CursorHoldability};
-use std::time::Duration;
+use super::{
+    command_options::{CommandOptions, CursorHoldability},
+    Interceptor, SharedStatistics,
+};
+use debug_ignore::DebugIgnore;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 // docu is written at re-exports of frontend crates (hdbconnect/lib.rs, hdbconnect_async/lib.rs)
 #[derive(Debug, Clone, Deserialize)]
@@ -12,10 +20,39 @@ pub struct ConnectionConfiguration {
     max_buffer_size: usize,
     min_compression_size: usize,
     read_timeout: Option<Duration>,
+    #[serde(default)]
+    query_timeout: Option<Duration>,
+    #[serde(default = "default_connect_timeout")]
+    connect_timeout: Option<Duration>,
+    #[serde(default)]
+    write_timeout: Option<Duration>,
+    #[serde(default = "default_tcp_nodelay")]
+    tcp_nodelay: bool,
+    #[serde(default)]
+    tcp_keepalive: Option<Duration>,
+    #[serde(default)]
+    tcp_keepalive_retries: Option<u32>,
+    #[serde(default)]
+    session_recovery: bool,
+    #[serde(default)]
+    wire_trace_dir: Option<PathBuf>,
+    #[serde(skip)]
+    shared_statistics: Option<SharedStatistics>,
+    #[serde(skip)]
+    interceptor: Option<DebugIgnore<Arc<dyn Interceptor>>>,
+}
+
+fn default_connect_timeout() -> Option<Duration> {
+    ConnectionConfiguration::DEFAULT_CONNECT_TIMEOUT
+}
+fn default_tcp_nodelay() -> bool {
+    ConnectionConfiguration::DEFAULT_TCP_NODELAY
 }
 
 impl Default for ConnectionConfiguration {
     /// Auto-commit is on, `HOLD_CURSORS_OVER_COMMIT` is on, `HOLD_CURSORS_OVER_ROLLBACK` is off,
+    /// cursors are not scrollable, session recovery is off, TCP keepalive is off,
+    /// no wire trace is recorded, no shared statistics are collected, no interceptor is used,
     /// the other config parameters have the default value defined by the respective constant.
     fn default() -> Self {
         Self {
@@ -27,6 +64,16 @@ impl Default for ConnectionConfiguration {
             max_buffer_size: Self::DEFAULT_MAX_BUFFER_SIZE,
             min_compression_size: Self::DEFAULT_MIN_COMPRESSION_SIZE,
             read_timeout: Self::DEFAULT_READ_TIMEOUT,
+            query_timeout: Self::DEFAULT_QUERY_TIMEOUT,
+            connect_timeout: Self::DEFAULT_CONNECT_TIMEOUT,
+            write_timeout: Self::DEFAULT_WRITE_TIMEOUT,
+            tcp_nodelay: Self::DEFAULT_TCP_NODELAY,
+            tcp_keepalive: None,
+            tcp_keepalive_retries: None,
+            session_recovery: false,
+            wire_trace_dir: None,
+            shared_statistics: None,
+            interceptor: None,
         }
     }
 }
@@ -78,6 +125,28 @@ impl ConnectionConfiguration {
     /// and a new connection will be needed to continue working.
     pub const DEFAULT_READ_TIMEOUT: Option<std::time::Duration> = None;
 
+    /// By default, no query timeout is applied.
+    ///
+    /// A query timeout is sent to HANA with each statement execution;
+    /// HANA cancels the execution if it runs longer, and the connection remains usable.
+    pub const DEFAULT_QUERY_TIMEOUT: Option<std::time::Duration> = None;
+
+    /// By default, establishing the network connection to a host is given up after 30 seconds.
+    ///
+    /// Without a connect timeout, connecting to an unreachable host can block for minutes,
+    /// until the operating system gives up.
+    pub const DEFAULT_CONNECT_TIMEOUT: Option<std::time::Duration> = Some(Duration::from_secs(30));
+
+    /// By default, no write timeout is applied.
+    ///
+    /// Like with the read timeout, the physical connection to HANA will be dropped
+    /// if the write timeout kicks in.
+    pub const DEFAULT_WRITE_TIMEOUT: Option<std::time::Duration> = None;
+
+    /// By default, `TCP_NODELAY` is set, i.e., requests are sent without delay
+    /// (Nagle's algorithm is disabled).
+    pub const DEFAULT_TCP_NODELAY: bool = true;
+
     /// Returns whether the connection uses auto-commit.
     #[must_use]
     pub fn is_auto_commit(&self) -> bool {
@@ -101,12 +170,31 @@ impl ConnectionConfiguration {
     }
     /// Sets the cursor holdability.
     pub fn set_cursor_holdability(&mut self, holdability: CursorHoldability) {
-        self.command_options = holdability.into();
+        self.command_options = self.command_options.with_holdability(holdability);
     }
     /// Builder method for setting the cursor holdability.
     #[must_use]
     pub fn with_cursor_holdability(mut self, holdability: CursorHoldability) -> Self {
-        self.command_options = holdability.into();
+        self.command_options = self.command_options.with_holdability(holdability);
+        self
+    }
+
+    /// Returns whether result sets are produced with scrollable cursors.
+    #[must_use]
+    pub fn is_scrollable_cursor(&self) -> bool {
+        self.command_options.is_scrollable_cursor_on()
+    }
+    /// Defines whether result sets are produced with scrollable cursors.
+    ///
+    /// Only result sets with a scrollable cursor support the methods
+    /// `fetch_absolute()`, `fetch_relative()`, `fetch_first()`, and `fetch_last()`.
+    pub fn set_scrollable_cursor(&mut self, scrollable: bool) {
+        self.command_options = self.command_options.with_scrollable_cursor(scrollable);
+    }
+    /// Builder method for defining whether result sets are produced with scrollable cursors.
+    #[must_use]
+    pub fn with_scrollable_cursor(mut self, scrollable: bool) -> Self {
+        self.command_options = self.command_options.with_scrollable_cursor(scrollable);
         self
     }
     pub(crate) fn command_options(&self) -> CommandOptions {
@@ -225,4 +313,240 @@ impl ConnectionConfiguration {
         self.read_timeout = read_timeout;
         self
     }
+
+    /// Returns the connection's query timeout.
+    #[must_use]
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.query_timeout
+    }
+    /// Sets the timeout after which HANA cancels the execution of a statement;
+    /// HANA works with full seconds, so the timeout is rounded up.
+    ///
+    /// See [`ConnectionConfiguration::DEFAULT_QUERY_TIMEOUT`].
+    pub fn set_query_timeout(&mut self, query_timeout: Option<Duration>) {
+        self.query_timeout = query_timeout;
+    }
+    /// Builder-method for setting the connection's query timeout.
+    #[must_use]
+    pub fn with_query_timeout(mut self, query_timeout: Option<Duration>) -> Self {
+        self.query_timeout = query_timeout;
+        self
+    }
+
+    /// Returns the connection's connect timeout.
+    #[must_use]
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+    /// Sets the timeout for establishing the network connection to a single host.
+    ///
+    /// A connect timeout that is specified with the `ConnectParams` takes precedence.
+    ///
+    /// See [`ConnectionConfiguration::DEFAULT_CONNECT_TIMEOUT`].
+    pub fn set_connect_timeout(&mut self, connect_timeout: Option<Duration>) {
+        self.connect_timeout = connect_timeout;
+    }
+    /// Builder-method for setting the connection's connect timeout.
+    #[must_use]
+    pub fn with_connect_timeout(mut self, connect_timeout: Option<Duration>) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Returns the connection's write timeout.
+    #[must_use]
+    pub fn write_timeout(&self) -> Option<Duration> {
+        self.write_timeout
+    }
+    /// Sets the timeout for sending a request.
+    ///
+    /// A write timeout that is specified with the `ConnectParams` takes precedence.
+    ///
+    /// See [`ConnectionConfiguration::DEFAULT_WRITE_TIMEOUT`].
+    pub fn set_write_timeout(&mut self, write_timeout: Option<Duration>) {
+        self.write_timeout = write_timeout;
+    }
+    /// Builder-method for setting the connection's write timeout.
+    #[must_use]
+    pub fn with_write_timeout(mut self, write_timeout: Option<Duration>) -> Self {
+        self.write_timeout = write_timeout;
+        self
+    }
+
+    /// Returns whether `TCP_NODELAY` is set.
+    #[must_use]
+    pub fn is_tcp_nodelay(&self) -> bool {
+        self.tcp_nodelay
+    }
+    /// Defines whether `TCP_NODELAY` is set.
+    ///
+    /// A setting that is specified with the `ConnectParams` takes precedence.
+    ///
+    /// See [`ConnectionConfiguration::DEFAULT_TCP_NODELAY`].
+    pub fn set_tcp_nodelay(&mut self, tcp_nodelay: bool) {
+        self.tcp_nodelay = tcp_nodelay;
+    }
+    /// Builder-method for defining whether `TCP_NODELAY` is set.
+    #[must_use]
+    pub fn with_tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Returns the interval of TCP keepalive probes, if TCP keepalive is switched on.
+    #[must_use]
+    pub fn tcp_keepalive(&self) -> Option<Duration> {
+        self.tcp_keepalive
+    }
+    /// Switches TCP keepalive on or off.
+    ///
+    /// With `Some(interval)`, keepalive probes are sent after the connection was idle
+    /// for `interval`, and then repeatedly in this interval, so that idle connections,
+    /// like those in a connection pool, are neither dropped by firewalls nor remain
+    /// undetectedly broken.
+    ///
+    /// A setting that is specified with the `ConnectParams` takes precedence.
+    pub fn set_tcp_keepalive(&mut self, tcp_keepalive: Option<Duration>) {
+        self.tcp_keepalive = tcp_keepalive;
+    }
+    /// Builder-method for switching TCP keepalive on or off.
+    #[must_use]
+    pub fn with_tcp_keepalive(mut self, tcp_keepalive: Option<Duration>) -> Self {
+        self.tcp_keepalive = tcp_keepalive;
+        self
+    }
+
+    /// Returns the number of unanswered TCP keepalive probes after which the connection
+    /// is considered broken, if it is configured.
+    #[must_use]
+    pub fn tcp_keepalive_retries(&self) -> Option<u32> {
+        self.tcp_keepalive_retries
+    }
+    /// Sets the number of unanswered TCP keepalive probes after which the connection
+    /// is considered broken; with `None`, the default of the operating system applies.
+    ///
+    /// This setting only has an effect if TCP keepalive is switched on, and is ignored
+    /// on platforms that do not support it.
+    /// A setting that is specified with the `ConnectParams` takes precedence.
+    pub fn set_tcp_keepalive_retries(&mut self, tcp_keepalive_retries: Option<u32>) {
+        self.tcp_keepalive_retries = tcp_keepalive_retries;
+    }
+    /// Builder-method for setting the number of TCP keepalive probes.
+    #[must_use]
+    pub fn with_tcp_keepalive_retries(mut self, tcp_keepalive_retries: Option<u32>) -> Self {
+        self.tcp_keepalive_retries = tcp_keepalive_retries;
+        self
+    }
+
+    /// Returns whether broken connections are recovered transparently.
+    #[must_use]
+    pub fn is_session_recovery(&self) -> bool {
+        self.session_recovery
+    }
+    /// Defines whether broken connections are recovered transparently.
+    ///
+    /// If a request fails because the connection is broken, and no transaction with
+    /// uncommitted changes is open, then the connection is re-established with the original
+    /// connect parameters. The client info is sent again, the settings of the configuration
+    /// (like auto-commit and fetch size) are kept, and the prepared statements that were
+    /// created while session recovery was active are prepared again.
+    /// Finally, the failed request is repeated, if it is idempotent (queries, commit, rollback,
+    /// and prepare).
+    ///
+    /// In all other cases, the failed request returns `HdbError::RecoveryUnsafe`.
+    /// Open result sets and LOB handles cannot be recovered.
+    pub fn set_session_recovery(&mut self, session_recovery: bool) {
+        self.session_recovery = session_recovery;
+    }
+    /// Builder-method for defining whether broken connections are recovered transparently.
+    ///
+    /// See [`ConnectionConfiguration::set_session_recovery`].
+    #[must_use]
+    pub fn with_session_recovery(mut self, session_recovery: bool) -> Self {
+        self.session_recovery = session_recovery;
+        self
+    }
+
+    /// Returns the directory into which wire traces are recorded, if any.
+    #[must_use]
+    pub fn wire_trace_dir(&self) -> Option<&Path> {
+        self.wire_trace_dir.as_deref()
+    }
+    /// Defines a directory into which wire traces are recorded.
+    ///
+    /// Each physical connection then writes all requests and replies, with timestamps
+    /// and part boundaries, into a new file in this directory.
+    /// Authentication data and the SQL text that follows the keyword `PASSWORD` are redacted;
+    /// other data, like parameter values and query results, are recorded as they are.
+    /// A wire trace can be replayed offline with `Connection::replay_wire_trace()`.
+    pub fn set_wire_trace_dir(&mut self, wire_trace_dir: Option<PathBuf>) {
+        self.wire_trace_dir = wire_trace_dir;
+    }
+    /// Builder-method for defining a directory into which wire traces are recorded.
+    ///
+    /// See [`ConnectionConfiguration::set_wire_trace_dir`].
+    #[must_use]
+    pub fn with_wire_trace_dir(mut self, wire_trace_dir: Option<PathBuf>) -> Self {
+        self.wire_trace_dir = wire_trace_dir;
+        self
+    }
+
+    /// Returns the shared statistics into which the roundtrips are recorded, if any.
+    #[must_use]
+    pub fn shared_statistics(&self) -> Option<&SharedStatistics> {
+        self.shared_statistics.as_ref()
+    }
+    /// Defines shared statistics into which the roundtrips of all connections are recorded
+    /// that are created with this configuration (or a clone of it).
+    ///
+    /// This allows aggregating the statistics of the connections of a connection pool.
+    /// The statistics of each single connection are available independently of this setting,
+    /// see `ConnectionStatistics::roundtrips()`.
+    pub fn set_shared_statistics(&mut self, shared_statistics: Option<SharedStatistics>) {
+        self.shared_statistics = shared_statistics;
+    }
+    /// Builder-method for defining shared statistics into which the roundtrips are recorded.
+    ///
+    /// See [`ConnectionConfiguration::set_shared_statistics`].
+    #[must_use]
+    pub fn with_shared_statistics(mut self, shared_statistics: Option<SharedStatistics>) -> Self {
+        self.shared_statistics = shared_statistics;
+        self
+    }
+
+    /// Returns the interceptor, if any.
+    #[must_use]
+    pub fn interceptor(&self) -> Option<&Arc<dyn Interceptor>> {
+        self.interceptor.as_deref()
+    }
+    /// Defines an interceptor whose hooks are invoked around the statement executions
+    /// of all connections that are created with this configuration (or a clone of it).
+    ///
+    /// See [`Interceptor`] for details.
+    pub fn set_interceptor(&mut self, interceptor: Option<Arc<dyn Interceptor>>) {
+        self.interceptor = interceptor.map(DebugIgnore);
+    }
+    /// Builder-method for defining an interceptor.
+    ///
+    /// See [`ConnectionConfiguration::set_interceptor`].
+    #[must_use]
+    pub fn with_interceptor(mut self, interceptor: Option<Arc<dyn Interceptor>>) -> Self {
+        self.interceptor = interceptor.map(DebugIgnore);
+        self
+    }
+
+    // The configuration of the separate connection that cancels a statement execution:
+    // only the socket settings are kept, so that the cancellation is not intercepted,
+    // not counted in the shared statistics, not traced, not recovered, and not timed out.
+    pub(crate) fn for_cancellation(&self) -> Self {
+        Self {
+            read_timeout: self.read_timeout,
+            connect_timeout: self.connect_timeout,
+            write_timeout: self.write_timeout,
+            tcp_nodelay: self.tcp_nodelay,
+            tcp_keepalive: self.tcp_keepalive,
+            tcp_keepalive_retries: self.tcp_keepalive_retries,
+            ..Self::default()
+        }
+    }
 }
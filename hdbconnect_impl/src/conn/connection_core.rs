@@ -1,8 +1,18 @@
+#[cfg(feature = "tracing")]
+use crate::conn::roundtrip_span::RoundtripSpans;
+#[cfg(feature = "async")]
+use crate::conn::tcp_client::SocketSettings;
 use crate::{
     base::RsState,
     conn::{
-        authentication, initial_request, AmConnCore, AuthenticationResult, CommandOptions,
-        ConnectParams, ConnectionConfiguration, ConnectionStatistics, SessionState, TcpClient,
+        authentication::{self, SessionCookie},
+        initial_request,
+        roundtrip_statistics::Roundtrip,
+        tcp_client::ReplayClient,
+        wire_trace::{Direction, WireRecorder},
+        AmConnCore, AuthenticationResult, CommandOptions, ConnectParams, ConnectParamsBuilder,
+        ConnectionConfiguration, ConnectionStatistics, LiveStatements, SessionState, TcpClient,
+        Tee, TraceContext,
     },
     protocol::{
         parts::{
@@ -11,12 +21,20 @@ use crate::{
         },
         MessageType, Part, Reply, ReplyType, Request, ServerUsage,
     },
-    HdbError, HdbResult,
+    usage_err, HdbError, HdbResult,
 };
+#[cfg(feature = "alpha_routing")]
+use crate::{conn::StatementRouting, impl_err, protocol::PartKind};
 use debug_ignore::DebugIgnore;
-#[cfg(feature = "sync")]
-use std::time::Duration;
-use std::{io::Cursor, io::ErrorKind, mem, sync::Arc};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    io::ErrorKind,
+    mem,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 #[doc(hidden)]
 #[derive(Debug)]
@@ -24,7 +42,7 @@ pub(crate) struct ConnectionCore {
     authenticated: bool,
     session_id: i64,
     client_info: ClientInfo,
-    client_info_touched: bool,
+    o_trace_context: Option<TraceContext>,
     statistics: ConnectionStatistics,
     server_usage: ServerUsage,
     config: ConnectionConfiguration,
@@ -32,9 +50,24 @@ pub(crate) struct ConnectionCore {
     statement_sequence: Option<i64>, // statement sequence within the transaction
     connect_options: ConnectOptions,
     topology: Option<Topology>,
+    live_statements: LiveStatements,
+    // the session setup statements of the interceptor that were executed last in this session
+    session_setup: Vec<String>,
+    #[cfg(feature = "alpha_routing")]
+    statement_routing: StatementRouting,
+    // sessions with other index servers of a scale-out system, by volume
+    #[cfg(feature = "alpha_routing")]
+    secondary_sessions: HashMap<i32, ConnectionCore>,
     pub(crate) warnings: Vec<ServerError>,
     tcp_client: TcpClient,
     io_buffer: DebugIgnore<Cursor<Vec<u8>>>,
+    // is true while an async roundtrip is in flight; if it is still true when the next
+    // roundtrip starts, then the future of the previous one was dropped before the reply was read
+    #[cfg(feature = "async")]
+    roundtrip_pending: bool,
+    wire_recorder: Option<WireRecorder>,
+    #[cfg(feature = "tracing")]
+    roundtrip_spans: RoundtripSpans,
 }
 
 impl<'a> ConnectionCore {
@@ -89,6 +122,10 @@ impl<'a> ConnectionCore {
                     debug!("Redirected (2) to {}", redirect_params);
                     conn_core = ConnectionCore::try_new_initialized_sync(redirect_params, config)?;
                 }
+                AuthenticationResult::SessionCookieRejected => {
+                    let params = conn_core.connect_params().clone();
+                    conn_core = ConnectionCore::try_new_initialized_sync(params, config)?;
+                }
             }
         }
     }
@@ -148,6 +185,10 @@ impl<'a> ConnectionCore {
                     conn_core =
                         ConnectionCore::try_new_initialized_async(redirect_params, config).await?;
                 }
+                AuthenticationResult::SessionCookieRejected => {
+                    let params = conn_core.connect_params().clone();
+                    conn_core = ConnectionCore::try_new_initialized_async(params, config).await?;
+                }
             }
         }
     }
@@ -157,28 +198,7 @@ impl<'a> ConnectionCore {
         params: ConnectParams,
         config: &ConnectionConfiguration,
     ) -> HdbResult<Self> {
-        let connect_options =
-            ConnectOptions::new(params.clientlocale(), &get_os_user(), params.compression());
-        let mut tcp_client = TcpClient::try_new_sync(params, config.read_timeout())?;
-        initial_request::send_and_receive_sync(&mut tcp_client)?;
-        Ok(Self {
-            authenticated: false,
-            session_id: 0,
-            statistics: ConnectionStatistics::new(),
-            server_usage: ServerUsage::default(),
-            io_buffer: DebugIgnore::from(Cursor::new(Vec::<u8>::with_capacity(
-                ConnectionConfiguration::MIN_BUFFER_SIZE,
-            ))),
-            config: config.clone(),
-            client_info: ClientInfo::default(),
-            client_info_touched: true,
-            session_state: SessionState::default(),
-            statement_sequence: None,
-            connect_options,
-            topology: None,
-            warnings: Vec::<ServerError>::new(),
-            tcp_client,
-        })
+        Ok(Self::new(connect_sync(params, config)?, config))
     }
 
     #[cfg(feature = "async")]
@@ -186,11 +206,15 @@ impl<'a> ConnectionCore {
         params: ConnectParams,
         config: &ConnectionConfiguration,
     ) -> HdbResult<Self> {
+        Ok(Self::new(connect_async(params, config).await?, config))
+    }
+
+    fn new(tcp_client: TcpClient, config: &ConnectionConfiguration) -> Self {
+        let params = tcp_client.connect_params();
         let connect_options =
             ConnectOptions::new(params.clientlocale(), &get_os_user(), params.compression());
-        let mut tcp_client = TcpClient::try_new_async(params).await?;
-        initial_request::send_and_receive_async(&mut tcp_client).await?;
-        Ok(Self {
+        let wire_recorder = new_wire_recorder(&tcp_client, config);
+        Self {
             authenticated: false,
             session_id: 0,
             statistics: ConnectionStatistics::new(),
@@ -198,27 +222,86 @@ impl<'a> ConnectionCore {
             io_buffer: DebugIgnore::from(Cursor::new(Vec::<u8>::with_capacity(
                 ConnectionConfiguration::MIN_BUFFER_SIZE,
             ))),
+            #[cfg(feature = "async")]
+            roundtrip_pending: false,
             config: config.clone(),
             client_info: ClientInfo::default(),
-            client_info_touched: true,
+            o_trace_context: None,
             session_state: SessionState::default(),
             statement_sequence: None,
             connect_options,
             topology: None,
+            live_statements: LiveStatements::default(),
+            session_setup: Vec::new(),
+            #[cfg(feature = "alpha_routing")]
+            statement_routing: StatementRouting::default(),
+            #[cfg(feature = "alpha_routing")]
+            secondary_sessions: HashMap::new(),
             warnings: Vec::<ServerError>::new(),
             tcp_client,
-        })
+            wire_recorder,
+            #[cfg(feature = "tracing")]
+            roundtrip_spans: RoundtripSpans::default(),
+        }
+    }
+
+    // A connection that replays the given wire trace; the connect parameters are placeholders.
+    #[cfg(feature = "sync")]
+    pub(crate) fn try_new_replay_sync(
+        path: &Path,
+        config: &ConnectionConfiguration,
+    ) -> HdbResult<Self> {
+        let mut conn_core = Self::new(replay_client(path)?, config);
+        match authentication::authenticate_sync(&mut conn_core, false)? {
+            AuthenticationResult::Ok => Ok(conn_core),
+            _ => Err(usage_err!(
+                "the wire trace does not contain a successful logon"
+            )),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn try_new_replay_async(
+        path: &Path,
+        config: &ConnectionConfiguration,
+    ) -> HdbResult<Self> {
+        let mut conn_core = Self::new(replay_client(path)?, config);
+        match authentication::authenticate_async(&mut conn_core, false).await? {
+            AuthenticationResult::Ok => Ok(conn_core),
+            _ => Err(usage_err!(
+                "the wire trace does not contain a successful logon"
+            )),
+        }
+    }
+
+    // A connection core without network connection, for decoding captured messages;
+    // values that refer to the server, like LOB handles, cannot fetch anything.
+    #[cfg(feature = "dissector")]
+    pub(crate) fn new_offline() -> HdbResult<Self> {
+        let params = ConnectParamsBuilder::new()
+            .hostname("offline.invalid")
+            .port(30015)
+            .dbuser("OFFLINE")
+            .password("OFFLINE")
+            .build()?;
+        Ok(Self::new(
+            TcpClient::Dead { params },
+            &ConnectionConfiguration::default(),
+        ))
+    }
+
+    pub(crate) fn is_replay(&self) -> bool {
+        self.tcp_client.is_replay()
     }
 
     #[cfg(feature = "sync")]
     pub(crate) fn reconnect_sync(&mut self) -> HdbResult<()> {
         warn!("Trying to reconnect");
         let mut conn_params = self.tcp_client.connect_params().clone();
+        let session_setup = std::mem::take(&mut self.session_setup);
         loop {
-            let mut tcp_conn =
-                TcpClient::try_new_sync(conn_params.clone(), self.config.read_timeout())?;
-            initial_request::send_and_receive_sync(&mut tcp_conn)?;
-            self.tcp_client = tcp_conn;
+            self.tcp_client = connect_sync(conn_params.clone(), &self.config)?;
+            self.wire_recorder = new_wire_recorder(&self.tcp_client, &self.config);
             self.authenticated = false;
             self.session_id = 0;
             // fetch_size, lob_read_length, lob_write_length are considered automatically
@@ -227,7 +310,7 @@ impl<'a> ConnectionCore {
             match authentication::authenticate_sync(self, true)? {
                 AuthenticationResult::Ok => {
                     debug!("Re-authenticated");
-                    return Ok(());
+                    return self.apply_session_setup_sync(&session_setup);
                 }
                 AuthenticationResult::Redirect(db_connect_info) => {
                     debug!("Redirected");
@@ -236,6 +319,9 @@ impl<'a> ConnectionCore {
                         .connect_params()
                         .redirect(db_connect_info.host()?, db_connect_info.port()?);
                 }
+                AuthenticationResult::SessionCookieRejected => {
+                    conn_params = self.tcp_client.connect_params().clone();
+                }
             }
         }
     }
@@ -244,10 +330,10 @@ impl<'a> ConnectionCore {
     pub(crate) async fn reconnect_async(&mut self) -> HdbResult<()> {
         debug!("Trying to reconnect");
         let mut conn_params = self.tcp_client.connect_params().clone();
+        let session_setup = std::mem::take(&mut self.session_setup);
         loop {
-            let mut tcp_client = TcpClient::try_new_async(conn_params.clone()).await?;
-            initial_request::send_and_receive_async(&mut tcp_client).await?;
-            self.tcp_client = tcp_client;
+            self.tcp_client = connect_async(conn_params.clone(), &self.config).await?;
+            self.wire_recorder = new_wire_recorder(&self.tcp_client, &self.config);
             self.authenticated = false;
             self.session_id = 0;
             // fetch_size, lob_read_length, lob_write_length are considered automatically
@@ -256,7 +342,7 @@ impl<'a> ConnectionCore {
             match authentication::authenticate_async(self, true).await? {
                 AuthenticationResult::Ok => {
                     debug!("Re-authenticated");
-                    return Ok(());
+                    return self.apply_session_setup_async(&session_setup).await;
                 }
                 AuthenticationResult::Redirect(db_connect_info) => {
                     debug!("Redirected");
@@ -265,6 +351,9 @@ impl<'a> ConnectionCore {
                         .connect_params()
                         .redirect(db_connect_info.host()?, db_connect_info.port()?);
                 }
+                AuthenticationResult::SessionCookieRejected => {
+                    conn_params = self.tcp_client.connect_params().clone();
+                }
             }
         }
     }
@@ -275,14 +364,23 @@ impl<'a> ConnectionCore {
             TcpClient::SyncPlain(ref cl) => cl.connect_params(),
             #[cfg(feature = "sync")]
             TcpClient::SyncTls(ref cl) => cl.connect_params(),
+            #[cfg(feature = "sync")]
+            TcpClient::SyncWebSocket(ref cl) => cl.connect_params(),
             #[cfg(feature = "async")]
             TcpClient::AsyncPlain(ref cl) => cl.connect_params(),
             #[cfg(feature = "async")]
             TcpClient::AsyncTls(ref cl) => cl.connect_params(),
+            #[cfg(feature = "async")]
+            TcpClient::AsyncWebSocket(ref cl) => cl.connect_params(),
+            TcpClient::Replay(ref cl) => cl.connect_params(),
             TcpClient::Dead { ref params } => params,
         }
     }
 
+    pub(crate) fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        self.tcp_client.connect_params_mut()
+    }
+
     #[cfg(feature = "sync")]
     pub(crate) fn set_read_timeout_sync(
         &mut self,
@@ -297,31 +395,26 @@ impl<'a> ConnectionCore {
 
     pub(crate) fn set_application<S: AsRef<str>>(&mut self, application: S) {
         self.client_info.set_application(application);
-        self.client_info_touched = true;
     }
 
     pub(crate) fn set_application_version(&mut self, version: &str) {
         self.client_info.set_application_version(version);
-        self.client_info_touched = true;
     }
 
     pub(crate) fn set_application_source(&mut self, source: &str) {
         self.client_info.set_application_source(source);
-        self.client_info_touched = true;
     }
 
     pub(crate) fn set_application_user(&mut self, application_user: &str) {
         self.client_info.set_application_user(application_user);
-        self.client_info_touched = true;
     }
 
-    pub(crate) fn is_client_info_touched(&self) -> bool {
-        self.client_info_touched
+    pub(crate) fn trace_context(&self) -> Option<&TraceContext> {
+        self.o_trace_context.as_ref()
     }
-    pub(crate) fn get_client_info_for_sending(&mut self) -> ClientInfo {
-        debug!("cloning client info for sending");
-        self.client_info_touched = false;
-        self.client_info.clone()
+
+    pub(crate) fn set_trace_context(&mut self, o_trace_context: Option<TraceContext>) {
+        self.o_trace_context = o_trace_context;
     }
 
     pub(crate) fn evaluate_statement_context(&mut self, stmt_ctx: &StatementContext) {
@@ -350,6 +443,49 @@ impl<'a> ConnectionCore {
         &self.config
     }
 
+    pub(crate) fn session_setup(&self) -> &[String] {
+        &self.session_setup
+    }
+
+    pub(crate) fn set_session_setup(&mut self, session_setup: Vec<String>) {
+        self.session_setup = session_setup;
+    }
+
+    // Executes the given session setup statements, unless they were executed last
+    // in this session.
+    #[cfg(feature = "sync")]
+    pub(crate) fn apply_session_setup_sync(&mut self, session_setup: &[String]) -> HdbResult<()> {
+        if self.session_setup != session_setup {
+            self.session_setup.clear();
+            for stmt in session_setup {
+                let mut request =
+                    Request::new(MessageType::ExecuteDirect, self.config.command_options());
+                request.push(Part::Command(stmt));
+                self.roundtrip_sync(&request, None, None, None, &mut None)?;
+            }
+            self.session_setup = session_setup.to_vec();
+        }
+        Ok(())
+    }
+    #[cfg(feature = "async")]
+    pub(crate) async fn apply_session_setup_async(
+        &mut self,
+        session_setup: &[String],
+    ) -> HdbResult<()> {
+        if self.session_setup != session_setup {
+            self.session_setup.clear();
+            for stmt in session_setup {
+                let mut request =
+                    Request::new(MessageType::ExecuteDirect, self.config.command_options());
+                request.push(Part::Command(stmt));
+                self.roundtrip_async(&request, None, None, None, &mut None)
+                    .await?;
+            }
+            self.session_setup = session_setup.to_vec();
+        }
+        Ok(())
+    }
+
     pub(crate) fn configuration_mut(&mut self) -> &mut ConnectionConfiguration {
         &mut self.config
     }
@@ -380,6 +516,13 @@ impl<'a> ConnectionCore {
         self.authenticated = true;
     }
 
+    // The cookie is kept with the connect parameters, and is thus also available
+    // for reconnects, for spawned connections, and for pooled connections.
+    pub(crate) fn set_session_cookie(&self, session_cookie: SessionCookie) {
+        debug!("received a session cookie");
+        self.connect_params().session_cookies().set(session_cookie);
+    }
+
     pub(crate) fn statement_sequence(&self) -> Option<&i64> {
         self.statement_sequence.as_ref()
     }
@@ -430,12 +573,22 @@ impl<'a> ConnectionCore {
     }
 
     pub(crate) fn augment_request(&mut self, request: &mut Request<'a>) {
+        self.live_statements.translate(request);
+        self.add_session_parts(request);
+    }
+
+    fn add_session_parts(&mut self, request: &mut Request<'a>) {
         if self.authenticated {
-            if let Some(ssi_value) = self.statement_sequence() {
-                request.add_statement_context(*ssi_value);
+            request.add_statement_context(self.statement_sequence().copied());
+            // the trace context of a statement applies to its executions,
+            // otherwise the one of the connection
+            if let MessageType::ExecuteDirect | MessageType::Execute = request.message_type() {
+                self.client_info
+                    .set_trace_context(request.trace_context().or(self.o_trace_context.as_ref()));
             }
-            if self.is_client_info_touched() {
-                request.push(Part::ClientInfo(self.get_client_info_for_sending()));
+            if self.client_info.has_changes() {
+                debug!("sending the changed client info");
+                request.push(Part::ClientInfo(self.client_info.take_changes()));
             }
         }
     }
@@ -449,17 +602,46 @@ impl<'a> ConnectionCore {
         o_a_descriptors: Option<&Arc<ParameterDescriptors>>,
         o_rs: &mut Option<&mut RsState>,
     ) -> HdbResult<Reply> {
-        let (session_id, nsn, default_error_handling) =
-            if let MessageType::Authenticate = request.message_type() {
-                (0, 1, false)
-            } else {
-                (self.session_id, self.next_sequence_number(), true)
-            };
+        #[cfg(feature = "tracing")]
+        let span = self.open_roundtrip_span(request);
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        let start = Instant::now();
+        let result =
+            self.roundtrip_sync_impl(request, o_am_conn_core, o_a_rsmd, o_a_descriptors, o_rs);
+        self.record_roundtrip(
+            request,
+            start.elapsed(),
+            &result,
+            #[cfg(feature = "tracing")]
+            &span,
+        );
+        result
+    }
+
+    #[cfg(feature = "sync")]
+    fn roundtrip_sync_impl(
+        &mut self,
+        request: &'a Request<'a>,
+        o_am_conn_core: Option<&AmConnCore>,
+        o_a_rsmd: Option<&Arc<ResultSetMetadata>>,
+        o_a_descriptors: Option<&Arc<ParameterDescriptors>>,
+        o_rs: &mut Option<&mut RsState>,
+    ) -> HdbResult<Reply> {
+        let (session_id, nsn, default_error_handling) = match request.message_type() {
+            MessageType::Authenticate => (0, 1, false),
+            // the reply to the second authentication request is evaluated with the authentication
+            MessageType::Connect => (self.session_id, self.next_sequence_number(), false),
+            _ => (self.session_id, self.next_sequence_number(), true),
+        };
         let compress = self.connect_options().use_compression();
 
+        let mut o_request_copy = self.wire_recorder.is_some().then(Vec::new);
         let w: &mut dyn std::io::Write = match self.tcp_client {
             TcpClient::SyncPlain(ref mut cl) => cl.writer(),
             TcpClient::SyncTls(ref mut cl) => cl.writer(),
+            TcpClient::SyncWebSocket(ref mut cl) => cl.writer(),
+            TcpClient::Replay(ref mut cl) => cl,
             TcpClient::Dead { .. } => return Err(HdbError::ConnectionBroken { source: None }),
             #[cfg(feature = "async")]
             _ => unreachable!("Async connections not supported here"),
@@ -474,7 +656,7 @@ impl<'a> ConnectionCore {
                 o_a_descriptors,
                 &mut self.statistics,
                 &mut self.io_buffer,
-                w,
+                &mut Tee::new(w, o_request_copy.as_mut()),
             )
             .map_err(|e| {
                 info!(
@@ -485,15 +667,19 @@ impl<'a> ConnectionCore {
                     source: Some(Box::new(e)),
                 }
             })?;
+        self.record_wire_message(Direction::Request, o_request_copy);
 
+        let mut o_reply_copy = self.wire_recorder.is_some().then(Vec::new);
         let rdr: &mut dyn std::io::Read = match self.tcp_client {
             TcpClient::SyncPlain(ref mut cl) => cl.reader(),
             TcpClient::SyncTls(ref mut cl) => cl.reader(),
+            TcpClient::SyncWebSocket(ref mut cl) => cl.reader(),
+            TcpClient::Replay(ref mut cl) => cl,
             TcpClient::Dead { .. } => return Err(HdbError::ConnectionBroken { source: None }),
             #[cfg(feature = "async")]
             _ => unreachable!("Async connections not supported here"),
         };
-        let mut reply = match Reply::parse_sync(
+        let result = Reply::parse_sync(
             o_a_rsmd,
             o_a_descriptors,
             o_rs,
@@ -501,8 +687,11 @@ impl<'a> ConnectionCore {
             &mut self.statistics,
             start,
             &mut self.io_buffer,
-            rdr,
-        ) {
+            &mut Tee::new(rdr, o_reply_copy.as_mut()),
+        );
+        // a reply that cannot be parsed is recorded as well
+        self.record_wire_message(Direction::Reply, o_reply_copy);
+        let mut reply = match result {
             Ok(reply) => reply,
             Err(e) => {
                 info!("roundtrip_sync(): TCP connection discarded after \"{e}\"");
@@ -531,56 +720,151 @@ impl<'a> ConnectionCore {
         o_a_descriptors: Option<&Arc<ParameterDescriptors>>,
         o_rs: &mut Option<&mut RsState>,
     ) -> HdbResult<Reply> {
-        let (session_id, nsn, default_error_handling) =
-            if let MessageType::Authenticate = request.message_type() {
-                (0, 1, false)
-            } else {
-                (self.session_id(), self.next_sequence_number(), true)
-            };
+        #[cfg(feature = "tracing")]
+        let span = self.open_roundtrip_span(request);
+        let start = Instant::now();
+        let roundtrip =
+            self.roundtrip_async_impl(request, o_am_conn_core, o_a_rsmd, o_a_descriptors, o_rs);
+        #[cfg(feature = "tracing")]
+        let roundtrip = tracing::Instrument::instrument(roundtrip, span.clone());
+        let result = roundtrip.await;
+        self.record_roundtrip(
+            request,
+            start.elapsed(),
+            &result,
+            #[cfg(feature = "tracing")]
+            &span,
+        );
+        result
+    }
+
+    #[cfg(feature = "async")]
+    #[allow(clippy::too_many_lines)]
+    async fn roundtrip_async_impl(
+        &mut self,
+        request: &'a Request<'a>,
+        o_am_conn_core: Option<&AmConnCore>,
+        o_a_rsmd: Option<&Arc<ResultSetMetadata>>,
+        o_a_descriptors: Option<&Arc<ParameterDescriptors>>,
+        o_rs: &mut Option<&mut RsState>,
+    ) -> HdbResult<Reply> {
+        let (session_id, nsn, default_error_handling) = match request.message_type() {
+            MessageType::Authenticate => (0, 1, false),
+            // the reply to the second authentication request is evaluated with the authentication
+            MessageType::Connect => (self.session_id(), self.next_sequence_number(), false),
+            _ => (self.session_id(), self.next_sequence_number(), true),
+        };
         let compress = self.connect_options().use_compression();
+        if self.roundtrip_pending {
+            // the stream may contain a partially written request or an unread reply
+            info!("roundtrip_async(): TCP connection discarded because a previous roundtrip was cancelled");
+            self.roundtrip_pending = false;
+            self.tcp_client.die();
+            return Err(HdbError::ConnectionBroken {
+                source: Some(Box::new(HdbError::Io {
+                    source: std::io::Error::new(
+                        std::io::ErrorKind::Interrupted,
+                        "a previous request was cancelled before its reply was read",
+                    ),
+                })),
+            });
+        }
+        if self.tcp_client.has_died() {
+            return Err(HdbError::ConnectionBroken { source: None });
+        }
+        let write_timeout =
+            SocketSettings::new(self.tcp_client.connect_params(), &self.config).write_timeout;
+        let mut o_request_copy = self.wire_recorder.is_some().then(Vec::new);
 
-        let start = match self.tcp_client {
-            TcpClient::AsyncPlain(ref mut cl) => {
-                request
-                    .emit_async(
-                        session_id,
-                        nsn,
-                        &self.config,
-                        compress,
-                        o_a_descriptors,
-                        &mut self.statistics,
-                        &mut self.io_buffer,
-                        cl.writer(),
-                    )
-                    .await
-            }
-            TcpClient::AsyncTls(ref mut cl) => {
-                request
-                    .emit_async(
-                        session_id,
-                        nsn,
-                        &self.config,
-                        compress,
-                        o_a_descriptors,
-                        &mut self.statistics,
-                        &mut self.io_buffer,
-                        cl.writer(),
-                    )
-                    .await
+        let emit = async {
+            match self.tcp_client {
+                TcpClient::AsyncPlain(ref mut cl) => {
+                    request
+                        .emit_async(
+                            session_id,
+                            nsn,
+                            &self.config,
+                            compress,
+                            o_a_descriptors,
+                            &mut self.statistics,
+                            &mut self.io_buffer,
+                            &mut Tee::new(cl.writer(), o_request_copy.as_mut()),
+                        )
+                        .await
+                }
+                TcpClient::AsyncTls(ref mut cl) => {
+                    request
+                        .emit_async(
+                            session_id,
+                            nsn,
+                            &self.config,
+                            compress,
+                            o_a_descriptors,
+                            &mut self.statistics,
+                            &mut self.io_buffer,
+                            &mut Tee::new(cl.writer(), o_request_copy.as_mut()),
+                        )
+                        .await
+                }
+                TcpClient::AsyncWebSocket(ref mut cl) => {
+                    request
+                        .emit_async(
+                            session_id,
+                            nsn,
+                            &self.config,
+                            compress,
+                            o_a_descriptors,
+                            &mut self.statistics,
+                            &mut self.io_buffer,
+                            &mut Tee::new(cl.writer(), o_request_copy.as_mut()),
+                        )
+                        .await
+                }
+                TcpClient::Replay(ref mut cl) => {
+                    request
+                        .emit_async(
+                            session_id,
+                            nsn,
+                            &self.config,
+                            compress,
+                            o_a_descriptors,
+                            &mut self.statistics,
+                            &mut self.io_buffer,
+                            cl,
+                        )
+                        .await
+                }
+                TcpClient::Dead { .. } => Err(HdbError::ConnectionBroken { source: None }),
+                #[cfg(feature = "sync")]
+                _ => unreachable!("Sync connections not supported here"),
             }
-            TcpClient::Dead { .. } => return Err(HdbError::ConnectionBroken { source: None }),
-            #[cfg(feature = "sync")]
-            _ => unreachable!("Sync connections not supported here"),
+        };
+        self.roundtrip_pending = true;
+        let start = match write_timeout {
+            None => emit.await,
+            Some(timeout) => tokio::time::timeout(timeout, emit)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(HdbError::Io {
+                        source: std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            format!("connection is broken (connection's write timeout was set to {timeout:?})"),
+                        ),
+                    })
+                }),
         }
         .map_err(|e| {
             info!("roundtrip_async(): TCP connection discarded because write failed with \"{e}\"");
+            self.roundtrip_pending = false;
             self.tcp_client.die();
             HdbError::ConnectionBroken {
                 source: Some(Box::new(e)),
             }
         })?;
+        self.record_wire_message(Direction::Request, o_request_copy);
 
-        let mut reply = if let Some(timeout) = self.config.read_timeout() {
+        let mut o_reply_copy = self.wire_recorder.is_some().then(Vec::new);
+        let reply = if let Some(timeout) = self.config.read_timeout() {
             match tokio::time::timeout(
                 timeout,
                 Reply::parse_async(
@@ -592,6 +876,7 @@ impl<'a> ConnectionCore {
                     &mut self.statistics,
                     &mut self.io_buffer,
                     &mut self.tcp_client,
+                    o_reply_copy.as_mut(),
                 ),
             )
             .await
@@ -614,9 +899,14 @@ impl<'a> ConnectionCore {
                 &mut self.statistics,
                 &mut self.io_buffer,
                 &mut self.tcp_client,
+                o_reply_copy.as_mut(),
             )
             .await
-        }.map_err(|e|{
+        };
+        self.roundtrip_pending = false;
+        // a reply that cannot be parsed is recorded as well
+        self.record_wire_message(Direction::Reply, o_reply_copy);
+        let mut reply = reply.map_err(|e| {
             info!("roundtrip_async(): TCP connection discarded after \"{e}\"");
             self.tcp_client.die();
             connection_broken(e, self.config.read_timeout())
@@ -633,17 +923,424 @@ impl<'a> ConnectionCore {
         Ok(reply)
     }
 
+    #[cfg(feature = "tracing")]
+    fn open_roundtrip_span(&self, request: &Request) -> tracing::Span {
+        self.roundtrip_spans.open(
+            request,
+            self.connect_options.try_get_connection_id(),
+            self.connect_params(),
+        )
+    }
+
+    // Adds the roundtrip to the statistics of the connection and to the shared statistics,
+    // reports it to the metrics facade, and closes its span.
+    fn record_roundtrip(
+        &mut self,
+        request: &Request,
+        latency: Duration,
+        result: &HdbResult<Reply>,
+        #[cfg(feature = "tracing")] span: &tracing::Span,
+    ) {
+        let sizes = self.statistics.take_last_sizes();
+        let roundtrip = Roundtrip::new(request.message_type(), latency, result, sizes);
+        self.statistics.add_roundtrip(&roundtrip);
+        if let Some(shared_statistics) = self.config.shared_statistics() {
+            shared_statistics.add(&roundtrip);
+        }
+        #[cfg(feature = "metrics")]
+        roundtrip.report_metrics();
+        #[cfg(feature = "tracing")]
+        self.roundtrip_spans.close(span, request, result, sizes);
+    }
+
+    fn record_wire_message(&mut self, direction: Direction, o_message: Option<Vec<u8>>) {
+        if let (Some(wire_recorder), Some(message)) = (self.wire_recorder.as_mut(), o_message) {
+            wire_recorder.record(direction, &message);
+        }
+    }
+
+    // Remembers prepared statements for a later session recovery,
+    // and for statement routing.
+    pub(crate) fn register_prepared_statement(&mut self, request: &Request, reply: &mut Reply) {
+        if let MessageType::Prepare = request.message_type() {
+            if self.config.is_session_recovery() {
+                self.live_statements
+                    .register(request, reply, &self.session_setup);
+            }
+            #[cfg(feature = "alpha_routing")]
+            self.statement_routing
+                .register(request, reply, &self.session_setup);
+        }
+    }
+
+    // Sends the execution of a prepared statement directly to a secondary session
+    // with the index server that hosts the affected data (feature `alpha_routing`);
+    // returns None if the request is to be sent on this session.
+    #[cfg(all(feature = "alpha_routing", feature = "sync"))]
+    pub(crate) fn route_sync(
+        &mut self,
+        request: &mut Request<'a>,
+        o_am_conn_core: Option<&AmConnCore>,
+        o_a_descriptors: Option<&Arc<ParameterDescriptors>>,
+    ) -> Option<HdbResult<Reply>> {
+        for (volume_id, statement_id) in self.statement_routing.forget(request) {
+            if let Some(secondary) = self.secondary_sessions.get_mut(&volume_id) {
+                let request = drop_statement_request(statement_id);
+                secondary
+                    .roundtrip_sync(&request, None, None, None, &mut None)
+                    .ok();
+            }
+        }
+        let (client_id, volume_id) = self.routing_target(request)?;
+        let statement_id = match self.prepare_on_secondary_sync(client_id, volume_id) {
+            Ok(statement_id) => statement_id,
+            Err(e) => return self.routing_failed(volume_id, &e),
+        };
+
+        let secondary = self.secondary_sessions.get_mut(&volume_id)?;
+        request.set_statement_id(statement_id);
+        secondary.add_session_parts(request);
+        let result = secondary
+            .roundtrip_sync(request, o_am_conn_core, None, o_a_descriptors, &mut None)
+            .and_then(|reply| secondary.take_session_parts(reply));
+        request.remove_session_parts();
+        request.set_statement_id(client_id);
+        self.discard_broken_secondary(volume_id);
+        Some(result)
+    }
+
+    #[cfg(all(feature = "alpha_routing", feature = "async"))]
+    pub(crate) async fn route_async(
+        &mut self,
+        request: &mut Request<'a>,
+        o_am_conn_core: Option<&AmConnCore>,
+        o_a_descriptors: Option<&Arc<ParameterDescriptors>>,
+    ) -> Option<HdbResult<Reply>> {
+        for (volume_id, statement_id) in self.statement_routing.forget(request) {
+            if let Some(secondary) = self.secondary_sessions.get_mut(&volume_id) {
+                let request = drop_statement_request(statement_id);
+                secondary
+                    .roundtrip_async(&request, None, None, None, &mut None)
+                    .await
+                    .ok();
+            }
+        }
+        let (client_id, volume_id) = self.routing_target(request)?;
+        let statement_id = match self.prepare_on_secondary_async(client_id, volume_id).await {
+            Ok(statement_id) => statement_id,
+            Err(e) => return self.routing_failed(volume_id, &e),
+        };
+
+        let secondary = self.secondary_sessions.get_mut(&volume_id)?;
+        request.set_statement_id(statement_id);
+        secondary.add_session_parts(request);
+        let result = match secondary
+            .roundtrip_async(request, o_am_conn_core, None, o_a_descriptors, &mut None)
+            .await
+        {
+            Ok(reply) => secondary.take_session_parts(reply),
+            Err(e) => Err(e),
+        };
+        request.remove_session_parts();
+        request.set_statement_id(client_id);
+        self.discard_broken_secondary(volume_id);
+        Some(result)
+    }
+
+    // Transactions are bound to a session, so only auto-committed executions are routed.
+    #[cfg(feature = "alpha_routing")]
+    fn routing_target(&mut self, request: &Request) -> Option<(u64, i32)> {
+        if !self.config.is_auto_commit() {
+            return None;
+        }
+        let current_volume = self.topology.as_ref()?.current_volume();
+        self.statement_routing
+            .target(request)
+            .filter(|(_, volume_id)| Some(*volume_id) != current_volume)
+    }
+
+    // Opens the secondary session if necessary, and prepares the statement on it
+    // if necessary; returns the statement id on the secondary session.
+    #[cfg(all(feature = "alpha_routing", feature = "sync"))]
+    fn prepare_on_secondary_sync(&mut self, client_id: u64, volume_id: i32) -> HdbResult<u64> {
+        if !self.secondary_sessions.contains_key(&volume_id) {
+            let params = self.secondary_params(volume_id)?;
+            debug!("Opening secondary session to {params} for volume {volume_id}");
+            let secondary = ConnectionCore::try_new_sync(params, &self.config)
+                .inspect_err(|_| self.statement_routing.set_unreachable(volume_id))?;
+            self.secondary_sessions.insert(volume_id, secondary);
+        }
+        if let Some(statement_id) = self.statement_routing.secondary_id(client_id, volume_id) {
+            return Ok(statement_id);
+        }
+
+        let (sql, command_options, session_setup) = self
+            .statement_routing
+            .statement(client_id)
+            .ok_or_else(|| impl_err!("statement {client_id} is not routable"))?;
+        let mut request = Request::new(MessageType::Prepare, command_options);
+        request.push(Part::Command(&sql));
+        let secondary = self
+            .secondary_sessions
+            .get_mut(&volume_id)
+            .ok_or_else(|| impl_err!("no secondary session for volume {volume_id}"))?;
+        secondary.apply_session_setup_sync(&session_setup)?;
+        secondary.add_session_parts(&mut request);
+        let reply = secondary.roundtrip_sync(&request, None, None, None, &mut None)?;
+        let statement_id = secondary
+            .take_session_parts(reply)
+            .and_then(|reply| statement_id_of(&reply))?;
+        self.statement_routing
+            .set_secondary_id(client_id, volume_id, statement_id);
+        Ok(statement_id)
+    }
+
+    #[cfg(all(feature = "alpha_routing", feature = "async"))]
+    async fn prepare_on_secondary_async(
+        &mut self,
+        client_id: u64,
+        volume_id: i32,
+    ) -> HdbResult<u64> {
+        if !self.secondary_sessions.contains_key(&volume_id) {
+            let params = self.secondary_params(volume_id)?;
+            debug!("Opening secondary session to {params} for volume {volume_id}");
+            let secondary = match ConnectionCore::try_new_async(params, &self.config).await {
+                Ok(secondary) => secondary,
+                Err(e) => {
+                    self.statement_routing.set_unreachable(volume_id);
+                    return Err(e);
+                }
+            };
+            self.secondary_sessions.insert(volume_id, secondary);
+        }
+        if let Some(statement_id) = self.statement_routing.secondary_id(client_id, volume_id) {
+            return Ok(statement_id);
+        }
+
+        let (sql, command_options, session_setup) = self
+            .statement_routing
+            .statement(client_id)
+            .ok_or_else(|| impl_err!("statement {client_id} is not routable"))?;
+        let mut request = Request::new(MessageType::Prepare, command_options);
+        request.push(Part::Command(&sql));
+        let secondary = self
+            .secondary_sessions
+            .get_mut(&volume_id)
+            .ok_or_else(|| impl_err!("no secondary session for volume {volume_id}"))?;
+        secondary.apply_session_setup_async(&session_setup).await?;
+        secondary.add_session_parts(&mut request);
+        let reply = secondary
+            .roundtrip_async(&request, None, None, None, &mut None)
+            .await?;
+        let statement_id = secondary
+            .take_session_parts(reply)
+            .and_then(|reply| statement_id_of(&reply))?;
+        self.statement_routing
+            .set_secondary_id(client_id, volume_id, statement_id);
+        Ok(statement_id)
+    }
+
+    // The connect parameters for the index server that hosts the given volume.
+    #[cfg(feature = "alpha_routing")]
+    fn secondary_params(&self, volume_id: i32) -> HdbResult<ConnectParams> {
+        let (host, port) = self
+            .topology
+            .as_ref()
+            .and_then(|topology| topology.host_of_volume(volume_id))
+            .ok_or_else(|| impl_err!("volume {volume_id} is not part of the topology"))?;
+        Ok(self.connect_params().redirect(&host, port))
+    }
+
+    // Nothing was executed yet, so the request can be sent on this session.
+    #[cfg(feature = "alpha_routing")]
+    fn routing_failed(&mut self, volume_id: i32, e: &HdbError) -> Option<HdbResult<Reply>> {
+        warn!("Routing to volume {volume_id} failed, using the primary session: {e}");
+        self.discard_broken_secondary(volume_id);
+        None
+    }
+
+    // A broken secondary session is closed; it is opened again when needed.
+    // Failed requests are not repeated, because they might have been executed already.
+    #[cfg(feature = "alpha_routing")]
+    fn discard_broken_secondary(&mut self, volume_id: i32) {
+        if self
+            .secondary_sessions
+            .get(&volume_id)
+            .is_some_and(ConnectionCore::is_broken)
+        {
+            self.secondary_sessions.remove(&volume_id);
+            self.statement_routing.forget_volume(volume_id);
+        }
+    }
+
+    // Evaluates the session-related parts of a reply of a secondary session,
+    // and removes them, so that they do not affect the primary session.
+    #[cfg(feature = "alpha_routing")]
+    fn take_session_parts(&mut self, mut reply: Reply) -> HdbResult<Reply> {
+        while let Some(part) = reply.parts.remove_first_of_kind(PartKind::StatementContext) {
+            if let Part::StatementContext(ref stmt_ctx) = part {
+                self.evaluate_statement_context(stmt_ctx);
+            }
+        }
+        while let Some(part) = reply.parts.remove_first_of_kind(PartKind::TransactionFlags) {
+            if let Part::TransactionFlags(ta_flags) = part {
+                self.evaluate_ta_flags(ta_flags)?;
+            }
+        }
+        Ok(reply)
+    }
+
+    // Re-establishes a broken session, and prepares the request for being repeated;
+    // fails with HdbError::RecoveryUnsafe if the request must not be repeated.
+    #[cfg(feature = "sync")]
+    pub(crate) fn recover_sync(
+        &mut self,
+        request: &mut Request<'a>,
+        error: HdbError,
+    ) -> HdbResult<()> {
+        let error = self.check_transaction_for_recovery(error)?;
+        let is_idempotent = self.live_statements.is_idempotent(request);
+        let session_setup = self.session_setup.clone();
+        self.reconnect_sync()?;
+        self.reset_session_state();
+
+        // every statement is prepared again with the session setup it was prepared with
+        let mut renamed = HashMap::<u64, u64>::new();
+        for (client_id, sql, command_options, stmt_setup) in self.live_statements.to_prepare() {
+            let mut prepare_request = Request::new(MessageType::Prepare, command_options);
+            prepare_request.push(Part::Command(&sql));
+            let result = self
+                .apply_session_setup_sync(&stmt_setup)
+                .and_then(|()| self.roundtrip_sync(&prepare_request, None, None, None, &mut None));
+            self.take_over_statement_id(client_id, result, &mut renamed)?;
+        }
+        self.apply_session_setup_sync(&session_setup)?;
+        self.prepare_repetition(request, is_idempotent, &renamed, error)
+    }
+    #[cfg(feature = "async")]
+    pub(crate) async fn recover_async(
+        &mut self,
+        request: &mut Request<'a>,
+        error: HdbError,
+    ) -> HdbResult<()> {
+        let error = self.check_transaction_for_recovery(error)?;
+        let is_idempotent = self.live_statements.is_idempotent(request);
+        let session_setup = self.session_setup.clone();
+        self.reconnect_async().await?;
+        self.reset_session_state();
+
+        // every statement is prepared again with the session setup it was prepared with
+        let mut renamed = HashMap::<u64, u64>::new();
+        for (client_id, sql, command_options, stmt_setup) in self.live_statements.to_prepare() {
+            let mut prepare_request = Request::new(MessageType::Prepare, command_options);
+            prepare_request.push(Part::Command(&sql));
+            let result = match self.apply_session_setup_async(&stmt_setup).await {
+                Ok(()) => {
+                    self.roundtrip_async(&prepare_request, None, None, None, &mut None)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            self.take_over_statement_id(client_id, result, &mut renamed)?;
+        }
+        self.apply_session_setup_async(&session_setup).await?;
+        self.prepare_repetition(request, is_idempotent, &renamed, error)
+    }
+
+    // Uncommitted changes are lost with the session; this is reported to the application,
+    // the next request will then re-establish the session.
+    fn check_transaction_for_recovery(&mut self, error: HdbError) -> HdbResult<HdbError> {
+        if !self.config.is_auto_commit() && self.session_state.has_uncommitted_changes() {
+            warn!("session recovery is not possible, uncommitted changes are lost");
+            self.reset_session_state();
+            Err(HdbError::RecoveryUnsafe {
+                reason: "a transaction with uncommitted changes was open",
+                session_recovered: false,
+                source: Box::new(error),
+            })
+        } else {
+            Ok(error)
+        }
+    }
+
+    fn reset_session_state(&mut self) {
+        self.session_state = SessionState::default();
+        self.statement_sequence = None;
+        // replay the client info with the next request
+        self.client_info.mark_all_changed();
+    }
+
+    fn take_over_statement_id(
+        &mut self,
+        client_id: u64,
+        result: HdbResult<Reply>,
+        renamed: &mut HashMap<u64, u64>,
+    ) -> HdbResult<()> {
+        match result {
+            Ok(reply) => {
+                let o_statement_id = reply.parts.ref_inner().iter().find_map(|part| match part {
+                    Part::StatementId(id) => Some(*id),
+                    _ => None,
+                });
+                if let Some(statement_id) = o_statement_id {
+                    if let Some(previous_id) =
+                        self.live_statements.set_current_id(client_id, statement_id)
+                    {
+                        renamed.insert(previous_id, statement_id);
+                    }
+                }
+                Ok(())
+            }
+            Err(e @ HdbError::ConnectionBroken { .. }) => Err(e),
+            Err(e) => {
+                warn!("prepared statement cannot be recovered: {e}");
+                self.live_statements.forget(client_id);
+                Ok(())
+            }
+        }
+    }
+
+    fn prepare_repetition(
+        &mut self,
+        request: &mut Request<'a>,
+        is_idempotent: bool,
+        renamed: &HashMap<u64, u64>,
+        error: HdbError,
+    ) -> HdbResult<()> {
+        if !is_idempotent {
+            return Err(HdbError::RecoveryUnsafe {
+                reason: "the request is not idempotent",
+                session_recovered: true,
+                source: Box::new(error),
+            });
+        }
+        if let Some(statement_id) = request
+            .statement_id()
+            .and_then(|previous_id| renamed.get(&previous_id))
+        {
+            request.set_statement_id(*statement_id);
+        }
+        request.remove_session_parts();
+        self.add_session_parts(request);
+        Ok(())
+    }
+
     pub(crate) fn is_broken(&self) -> bool {
+        #[cfg(feature = "async")]
+        if self.roundtrip_pending {
+            return true;
+        }
         self.tcp_client.has_died()
     }
 }
 
 impl Drop for ConnectionCore {
-    // try to send a disconnect to the database, ignore all errors
+    // try to send a disconnect to the database, ignore all errors;
+    // after an interrupted roundtrip the stream is in an undefined state and is just closed
     fn drop(&mut self) {
         debug!("Drop of ConnectionCore, session_id = {}", self.session_id);
         #[cfg(any(feature = "sync", feature = "async"))]
-        if self.authenticated {
+        if self.authenticated && !self.is_broken() {
             let request = Request::new_for_disconnect();
             let session_id = self.session_id();
             let nsn = self.next_sequence_number();
@@ -652,7 +1349,8 @@ impl Drop for ConnectionCore {
                 let w: &mut dyn std::io::Write = match self.tcp_client {
                     TcpClient::SyncPlain(ref mut cl) => cl.writer() as &mut dyn std::io::Write,
                     TcpClient::SyncTls(ref mut cl) => cl.writer() as &mut dyn std::io::Write,
-                    TcpClient::Dead { .. } => return,
+                    TcpClient::SyncWebSocket(ref mut cl) => cl.writer(),
+                    TcpClient::Dead { .. } | TcpClient::Replay(_) => return,
                     #[cfg(feature = "async")]
                     _ => unreachable!("Async connections not supported here"),
                 };
@@ -714,7 +1412,22 @@ impl Drop for ConnectionCore {
                                 .await
                                 .ok();
                         }
-                        TcpClient::Dead { .. } => {}
+                        TcpClient::AsyncWebSocket(ref mut cl) => {
+                            request
+                                .emit_async(
+                                    session_id,
+                                    nsn,
+                                    &config,
+                                    false,
+                                    None,
+                                    &mut ConnectionStatistics::new(),
+                                    &mut io_buffer,
+                                    cl.writer(),
+                                )
+                                .await
+                                .ok();
+                        }
+                        TcpClient::Dead { .. } | TcpClient::Replay(_) => {}
                         #[cfg(feature = "sync")]
                         _ => unreachable!("Sync connections not supported here"),
                     }
@@ -725,6 +1438,106 @@ impl Drop for ConnectionCore {
     }
 }
 
+// Opens a wire trace for a new physical connection, if a trace directory is configured;
+// replayed connections are not recorded.
+fn new_wire_recorder(
+    tcp_client: &TcpClient,
+    config: &ConnectionConfiguration,
+) -> Option<WireRecorder> {
+    let dir = config.wire_trace_dir()?;
+    if tcp_client.is_replay() {
+        return None;
+    }
+    WireRecorder::try_new(dir)
+        .map_err(|e| warn!("cannot record a wire trace in {}: {e}", dir.display()))
+        .ok()
+}
+
+fn replay_client(path: &Path) -> HdbResult<TcpClient> {
+    let params = ConnectParamsBuilder::new()
+        .hostname("replay.invalid")
+        .port(30015)
+        .dbuser("REPLAY")
+        .password("REPLAY")
+        .build()?;
+    Ok(TcpClient::Replay(ReplayClient::try_new(path, params)?))
+}
+
+// Establishes the network connection and exchanges the initial request;
+// with multiple hosts, these are tried one after the other.
+#[cfg(feature = "sync")]
+fn connect_sync(params: ConnectParams, config: &ConnectionConfiguration) -> HdbResult<TcpClient> {
+    let mut attempts = Vec::new();
+    for candidate in params.failover_candidates() {
+        let addr = candidate.addr().to_string();
+        match TcpClient::try_new_sync(candidate, config).and_then(|mut tcp_client| {
+            initial_request::send_and_receive_sync(&mut tcp_client)?;
+            Ok(tcp_client)
+        }) {
+            Ok(tcp_client) => return Ok(tcp_client),
+            Err(e) => {
+                warn!("Connecting to {addr} failed: {e}");
+                attempts.push((addr, e));
+            }
+        }
+    }
+    Err(failover_error(attempts))
+}
+
+#[cfg(feature = "async")]
+async fn connect_async(
+    params: ConnectParams,
+    config: &ConnectionConfiguration,
+) -> HdbResult<TcpClient> {
+    let mut attempts = Vec::new();
+    for candidate in params.failover_candidates() {
+        let addr = candidate.addr().to_string();
+        let result = match TcpClient::try_new_async(candidate, config).await {
+            Ok(mut tcp_client) => initial_request::send_and_receive_async(&mut tcp_client)
+                .await
+                .map(|()| tcp_client),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(tcp_client) => return Ok(tcp_client),
+            Err(e) => {
+                warn!("Connecting to {addr} failed: {e}");
+                attempts.push((addr, e));
+            }
+        }
+    }
+    Err(failover_error(attempts))
+}
+
+#[cfg(feature = "alpha_routing")]
+fn drop_statement_request(statement_id: u64) -> Request<'static> {
+    let mut request = Request::new(MessageType::DropStatementId, CommandOptions::EMPTY);
+    request.push(Part::StatementId(statement_id));
+    request
+}
+
+#[cfg(feature = "alpha_routing")]
+fn statement_id_of(reply: &Reply) -> HdbResult<u64> {
+    reply
+        .parts
+        .ref_inner()
+        .iter()
+        .find_map(|part| match part {
+            Part::StatementId(id) => Some(*id),
+            _ => None,
+        })
+        .ok_or_else(|| impl_err!("No StatementId received"))
+}
+
+// With a single host, its error is reported as is.
+fn failover_error(mut attempts: Vec<(String, HdbError)>) -> HdbError {
+    if attempts.len() == 1 {
+        attempts.remove(0).1
+    } else {
+        HdbError::AllHostsFailed { attempts }
+    }
+}
+
 fn get_os_user() -> String {
     let os_user = username::get_user_name().unwrap_or_default();
     trace!("OS user: {}", os_user);
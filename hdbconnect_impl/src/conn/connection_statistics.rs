@@ -1,3 +1,8 @@
+use crate::{
+    conn::{roundtrip_statistics::Roundtrip, RoundtripStatistics},
+    protocol::MESSAGE_AND_SEGMENT_HEADER_SIZE,
+};
+
 // docu is written at re-exports of frontend crates (hdbconnect/lib.rs, hdbconnect_async/lib.rs)
 #[derive(Debug, Clone)]
 pub struct ConnectionStatistics {
@@ -13,7 +18,23 @@ pub struct ConnectionStatistics {
     created_at: time::OffsetDateTime,
     last_reset_at: time::OffsetDateTime,
     wait_time: std::time::Duration,
+    roundtrips: RoundtripStatistics,
+    o_last_request_size: Option<MessageSize>,
+    o_last_reply_size: Option<MessageSize>,
+}
+// The uncompressed size of the parts of a message, and their size on the wire if they were compressed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MessageSize {
+    pub parts_size: usize,
+    pub o_compressed_size: Option<usize>,
+}
+impl MessageSize {
+    // The size of the message on the wire, including the message and segment header.
+    pub fn wire_size(self) -> usize {
+        MESSAGE_AND_SEGMENT_HEADER_SIZE + self.o_compressed_size.unwrap_or(self.parts_size)
+    }
 }
+
 impl Default for ConnectionStatistics {
     fn default() -> Self {
         let timestamp = time::OffsetDateTime::now_utc();
@@ -30,6 +51,9 @@ impl Default for ConnectionStatistics {
             compressed_replies_uncompressed_size: 0,
             shrinked_oversized_buffer_count: 0,
             wait_time: std::time::Duration::default(),
+            roundtrips: RoundtripStatistics::default(),
+            o_last_request_size: None,
+            o_last_reply_size: None,
         }
     }
 }
@@ -71,12 +95,43 @@ impl ConnectionStatistics {
         self.compressed_replies_uncompressed_size +=
             u64::try_from(uncompressed_parts_size).unwrap(/*OK*/);
     }
+    // Remembers the size of the last request and reply, until the roundtrip is recorded.
+    pub(crate) fn set_last_request_size(
+        &mut self,
+        parts_size: usize,
+        o_compressed_size: Option<usize>,
+    ) {
+        self.o_last_request_size = Some(MessageSize {
+            parts_size,
+            o_compressed_size,
+        });
+    }
+    pub(crate) fn set_last_reply_size(
+        &mut self,
+        parts_size: usize,
+        o_compressed_size: Option<usize>,
+    ) {
+        self.o_last_reply_size = Some(MessageSize {
+            parts_size,
+            o_compressed_size,
+        });
+    }
+    pub(crate) fn take_last_sizes(&mut self) -> (Option<MessageSize>, Option<MessageSize>) {
+        (
+            self.o_last_request_size.take(),
+            self.o_last_reply_size.take(),
+        )
+    }
+
     pub(crate) fn add_wait_time(&mut self, wait_time: std::time::Duration) {
         self.wait_time += wait_time;
     }
     pub(crate) fn add_buffer_shrinking(&mut self) {
         self.shrinked_oversized_buffer_count += 1;
     }
+    pub(crate) fn add_roundtrip(&mut self, roundtrip: &Roundtrip) {
+        self.roundtrips.add(roundtrip);
+    }
 
     /// Returns the number of roundtrips to the database that were done through this connection
     /// since the last reset.
@@ -128,6 +183,12 @@ impl ConnectionStatistics {
     pub fn compressed_replies_uncompressed_size(&self) -> u64 {
         self.compressed_replies_uncompressed_size
     }
+
+    /// Returns detailed statistics about the roundtrips, by message type.
+    #[must_use]
+    pub fn roundtrips(&self) -> &RoundtripStatistics {
+        &self.roundtrips
+    }
 }
 
 impl std::fmt::Display for ConnectionStatistics {
@@ -171,6 +232,27 @@ impl std::fmt::Display for ConnectionStatistics {
                     / self.compressed_replies_compressed_size as f64
             )?;
         }
+        writeln!(f, "Roundtrips")?;
+        for (name, stats) in self.roundtrips.message_types() {
+            writeln!(
+                f,
+                "  - {name:<22}{:>6} (errors: {}, latency: {:?})",
+                stats.count(),
+                stats.error_count(),
+                stats.latency().sum()
+            )?;
+        }
+        writeln!(
+            f,
+            "Bytes sent/received:      {}/{}",
+            self.roundtrips.bytes_sent(),
+            self.roundtrips.bytes_received()
+        )?;
+        writeln!(
+            f,
+            "Fetches per result set:   {:.2}",
+            self.roundtrips.fetch_roundtrips_per_result_set()
+        )?;
         Ok(())
     }
 }
@@ -22,7 +22,12 @@ pub(crate) fn send_and_receive_sync(sync_tcp_connection: &mut TcpClient) -> HdbR
                 source: Box::new(e),
             })?;
         }
-        TcpClient::Dead { .. } => unreachable!(),
+        TcpClient::SyncWebSocket(ref mut wc) => {
+            emit_initial_request_sync(wc.writer()).map_err(|e| HdbError::Initialization {
+                source: Box::new(e),
+            })?;
+        }
+        TcpClient::Dead { .. } | TcpClient::Replay(_) => unreachable!(),
         #[cfg(feature = "async")]
         _ => unreachable!("Async connections not supported here"),
     }
@@ -40,7 +45,12 @@ pub(crate) fn send_and_receive_sync(sync_tcp_connection: &mut TcpClient) -> HdbR
                 source: Box::new(e),
             })
         }
-        TcpClient::Dead { .. } => unreachable!(),
+        TcpClient::SyncWebSocket(ref mut wc) => {
+            util_sync::skip_bytes(8, wc.reader()).map_err(|e| HdbError::Initialization {
+                source: Box::new(e),
+            })
+        }
+        TcpClient::Dead { .. } | TcpClient::Replay(_) => unreachable!(),
         #[cfg(feature = "async")]
         _ => unreachable!("Async connections not supported here"),
     }
@@ -68,7 +78,14 @@ pub(crate) async fn send_and_receive_async(tcp_client: &mut TcpClient) -> HdbRes
                     source: Box::new(e),
                 })?;
         }
-        TcpClient::Dead { .. } => unreachable!(),
+        TcpClient::AsyncWebSocket(ref mut wa) => {
+            emit_initial_request_async(wa.writer()).await.map_err(|e| {
+                HdbError::Initialization {
+                    source: Box::new(e),
+                }
+            })?;
+        }
+        TcpClient::Dead { .. } | TcpClient::Replay(_) => unreachable!(),
         #[cfg(feature = "sync")]
         _ => unreachable!("Sync connections not supported here"),
     }
@@ -90,7 +107,14 @@ pub(crate) async fn send_and_receive_async(tcp_client: &mut TcpClient) -> HdbRes
                     source: Box::new(e),
                 })
         }
-        TcpClient::Dead { .. } => unreachable!(),
+        TcpClient::AsyncWebSocket(wa) => {
+            util_async::skip_bytes(8, wa.reader())
+                .await
+                .map_err(|e| HdbError::Initialization {
+                    source: Box::new(e),
+                })
+        }
+        TcpClient::Dead { .. } | TcpClient::Replay(_) => unreachable!(),
         #[cfg(feature = "sync")]
         _ => unreachable!("Sync connections not supported here"),
     }?;
@@ -0,0 +1,209 @@
+use crate::{HdbError, HdbResult, HdbValue, OutputParameters, ResultSetMetadata, ServerUsage};
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Hooks that are invoked around the statement executions of a connection.
+///
+/// An interceptor allows attaching cross-cutting behavior to all database calls,
+/// like audit logging, adding hints to the SQL text, or collecting per-statement metrics,
+/// without wrapping the API.
+/// It is registered with
+/// [`ConnectionConfiguration::with_interceptor`](crate::ConnectionConfiguration::with_interceptor)
+/// and applies to all connections that are created with this configuration,
+/// and to their prepared statements.
+///
+/// All methods have a default implementation that does nothing.
+///
+/// The hooks are invoked for the executions with `Connection::statement` (and the methods
+/// that are based on it, like `query`, `dml`, or `exec`), and with the `execute` methods
+/// of `PreparedStatement`.
+/// Fetching the rows of result sets and reading or writing LOBs happens in later roundtrips,
+/// which are not intercepted.
+///
+/// The hooks are called while the connection is not locked, so they can
+/// take their time; they must not use the same connection, though.
+/// Statements that prepare the session for a statement, like a tenant-specific
+/// `SET SCHEMA`, are provided with [`Interceptor::session_setup`] instead;
+/// the driver executes them on the connection.
+pub trait Interceptor: Send + Sync {
+    /// Returns a replacement for the given SQL text, or `None` to keep it.
+    ///
+    /// Is called before a statement is executed directly, and before a statement is prepared;
+    /// the executions of a prepared statement use the SQL text that was prepared.
+    fn rewrite_sql(&self, sql: &str) -> Option<String> {
+        let _ = sql;
+        None
+    }
+
+    /// Returns the statements that set up the session for the given statement,
+    /// like `SET SCHEMA TENANT_A` for a tenant-specific schema,
+    /// or `None` to leave the session as it is.
+    ///
+    /// Is called with the (rewritten) SQL text before a statement is executed directly,
+    /// after [`Interceptor::before_execute`], and before a statement is prepared;
+    /// the executions of a prepared statement use the session setup of its preparation.
+    ///
+    /// The driver executes the returned statements on the connection, unless they are
+    /// the ones it executed last on this connection. They are not intercepted themselves.
+    /// If one of them fails, the error is returned to the caller,
+    /// and the statement is not executed or prepared.
+    /// After a session recovery, the session setup is executed again in the new session.
+    fn session_setup(&self, sql: &str) -> Option<Vec<String>> {
+        let _ = sql;
+        None
+    }
+
+    /// Is called before a statement is executed, with the (rewritten) SQL text
+    /// and the parameter values, as one slice per parameter row.
+    ///
+    /// Direct executions and executions of prepared statements without input parameters
+    /// have no parameter rows; batch executions have several.
+    ///
+    /// # Errors
+    ///
+    /// An error prevents the execution and is returned to the caller;
+    /// [`Interceptor::on_error`] is not called then.
+    fn before_execute(&self, sql: &str, params: &[&[HdbValue<'_>]]) -> HdbResult<()> {
+        let _ = (sql, params);
+        Ok(())
+    }
+
+    /// Is called after a statement was executed successfully, with a view of
+    /// the response, the server-side resource consumption, and the elapsed time.
+    ///
+    /// The view shows everything the response contains when it is handed over to the caller,
+    /// except for the rows of result sets, which the caller fetches later.
+    /// It is not the `HdbResponse` itself, because the hooks are shared by
+    /// the sync and the async driver, whose responses differ.
+    ///
+    /// The resource consumption is that of the prepared statement for its executions,
+    /// and that of the connection for direct executions; it is reported as zero
+    /// if the connection cannot be accessed anymore.
+    fn after_execute(
+        &self,
+        sql: &str,
+        outcome: &ExecutionOutcome<'_>,
+        server_usage: &ServerUsage,
+        elapsed: Duration,
+    ) {
+        let _ = (sql, outcome, server_usage, elapsed);
+    }
+
+    /// Is called after the execution of a statement failed.
+    fn on_error(&self, sql: &str, error: &HdbError, elapsed: Duration) {
+        let _ = (sql, error, elapsed);
+    }
+}
+
+/// A view of the response to a successful statement execution,
+/// as it is handed over to [`Interceptor::after_execute`].
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionOutcome<'r> {
+    result_set_metadata: Vec<Arc<ResultSetMetadata>>,
+    affected_rows: Vec<usize>,
+    o_output_parameters: Option<&'r OutputParameters>,
+}
+impl<'r> ExecutionOutcome<'r> {
+    pub(crate) fn add_result_set(&mut self, metadata: Arc<ResultSetMetadata>) {
+        self.result_set_metadata.push(metadata);
+    }
+    pub(crate) fn add_affected_rows(&mut self, affected_rows: &[usize]) {
+        self.affected_rows.extend_from_slice(affected_rows);
+    }
+    pub(crate) fn set_output_parameters(&mut self, output_parameters: &'r OutputParameters) {
+        self.o_output_parameters = Some(output_parameters);
+    }
+
+    /// Returns the number of result sets in the response.
+    #[must_use]
+    pub fn result_set_count(&self) -> usize {
+        self.result_set_metadata.len()
+    }
+
+    /// Returns the metadata of the result sets in the response.
+    #[must_use]
+    pub fn result_set_metadata(&self) -> &[Arc<ResultSetMetadata>] {
+        &self.result_set_metadata
+    }
+
+    /// Returns the numbers of affected rows, one for each parameter row of a DML statement.
+    #[must_use]
+    pub fn affected_rows(&self) -> &[usize] {
+        &self.affected_rows
+    }
+
+    /// Returns true if the response contains output parameters of a procedure call.
+    #[must_use]
+    pub fn has_output_parameters(&self) -> bool {
+        self.o_output_parameters.is_some()
+    }
+
+    /// Returns the output parameters of a procedure call, if the response contains them.
+    #[must_use]
+    pub fn output_parameters(&self) -> Option<&'r OutputParameters> {
+        self.o_output_parameters
+    }
+}
+
+// Applies the rewriting hook of the interceptor, if any.
+pub(crate) fn rewrite_sql<'s>(
+    o_interceptor: Option<&Arc<dyn Interceptor>>,
+    sql: &'s str,
+) -> Cow<'s, str> {
+    o_interceptor
+        .and_then(|interceptor| interceptor.rewrite_sql(sql))
+        .map_or(Cow::Borrowed(sql), Cow::Owned)
+}
+
+// Applies the session setup hook of the interceptor, if any.
+pub(crate) fn session_setup(
+    o_interceptor: Option<&Arc<dyn Interceptor>>,
+    sql: &str,
+) -> Option<Vec<String>> {
+    o_interceptor.and_then(|interceptor| interceptor.session_setup(sql))
+}
+
+// A statement execution that is observed by an interceptor.
+pub(crate) struct Interception {
+    interceptor: Arc<dyn Interceptor>,
+    sql: String,
+    start: Instant,
+}
+impl Interception {
+    // Calls `before_execute`, if an interceptor is configured.
+    pub(crate) fn begin(
+        o_interceptor: Option<&Arc<dyn Interceptor>>,
+        sql: &str,
+        params: &[&[HdbValue<'_>]],
+    ) -> HdbResult<Option<Self>> {
+        o_interceptor
+            .map(|interceptor| {
+                interceptor.before_execute(sql, params)?;
+                Ok(Self {
+                    interceptor: Arc::clone(interceptor),
+                    sql: sql.to_string(),
+                    start: Instant::now(),
+                })
+            })
+            .transpose()
+    }
+
+    // Calls `after_execute` or `on_error`.
+    pub(crate) fn end(
+        self,
+        result: Result<ExecutionOutcome<'_>, &HdbError>,
+        server_usage: &ServerUsage,
+    ) {
+        let elapsed = self.start.elapsed();
+        match result {
+            Ok(outcome) => {
+                self.interceptor
+                    .after_execute(&self.sql, &outcome, server_usage, elapsed);
+            }
+            Err(e) => self.interceptor.on_error(&self.sql, e, elapsed),
+        }
+    }
+}
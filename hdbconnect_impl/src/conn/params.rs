@@ -1,9 +1,15 @@
+pub mod bearer_token;
+pub mod client_certificate;
 pub mod connect_params;
 pub mod connect_params_builder;
 pub mod cp_url;
+pub mod credential_provider;
 pub mod into_connect_params;
 pub mod into_connect_params_builder;
+pub mod proxy;
+pub(crate) mod socket_options;
 pub(crate) mod tls;
+pub(crate) mod user_store;
 
 #[derive(Debug, Clone, Default, Copy, Eq, PartialEq, Deserialize)]
 pub(crate) enum Compression {
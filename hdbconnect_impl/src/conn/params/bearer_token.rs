@@ -0,0 +1,145 @@
+use crate::{HdbError, HdbResult};
+use secstr::SecUtf8;
+use std::sync::Arc;
+
+// The signature of a callback that supplies bearer tokens
+type TokenProvider =
+    dyn Fn() -> Result<String, Box<dyn std::error::Error + Send + Sync>> + Send + Sync;
+
+/// A bearer token with which a user logs on, with the authentication method JWT or SAML,
+/// instead of a password.
+///
+/// The database determines the user from the token; the database user needs thus not
+/// be specified.
+/// Tokens typically expire well before the connections that were opened with them.
+/// A token can therefore be supplied by a callback, which is called whenever a connection
+/// is established or re-established (e.g., with session recovery, or after a redirect).
+///
+/// A bearer token can be configured with
+/// [`ConnectParamsBuilder::bearer_token`](crate::ConnectParamsBuilder::bearer_token).
+///
+/// ```rust
+/// use hdbconnect::{BearerToken, ConnectParams};
+/// # fn token_from_idp() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+/// #     Ok(String::new())
+/// # }
+///
+/// let connect_params = ConnectParams::builder()
+///     .hostname("the_host")
+///     .port(2222)
+///     .bearer_token(BearerToken::jwt_provider(token_from_idp))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct BearerToken {
+    kind: BearerTokenKind,
+    source: TokenSource,
+}
+
+/// The kind of a [`BearerToken`], which determines the authentication method.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BearerTokenKind {
+    /// A JSON Web Token, for the authentication method JWT.
+    Jwt,
+    /// A SAML assertion, for the authentication method SAML.
+    Saml,
+}
+
+#[derive(Clone)]
+enum TokenSource {
+    Fixed(SecUtf8),
+    Provider(Arc<TokenProvider>),
+}
+
+impl BearerToken {
+    /// A fixed JSON Web Token.
+    #[must_use]
+    pub fn jwt<T: AsRef<str>>(token: T) -> Self {
+        Self::fixed(BearerTokenKind::Jwt, token.as_ref())
+    }
+
+    /// A fixed SAML assertion.
+    #[must_use]
+    pub fn saml<T: AsRef<str>>(assertion: T) -> Self {
+        Self::fixed(BearerTokenKind::Saml, assertion.as_ref())
+    }
+
+    /// JSON Web Tokens that are supplied by the given callback.
+    pub fn jwt_provider<F>(provider: F) -> Self
+    where
+        F: Fn() -> Result<String, Box<dyn std::error::Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        Self::with_provider(BearerTokenKind::Jwt, Arc::new(provider))
+    }
+
+    /// SAML assertions that are supplied by the given callback.
+    pub fn saml_provider<F>(provider: F) -> Self
+    where
+        F: Fn() -> Result<String, Box<dyn std::error::Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        Self::with_provider(BearerTokenKind::Saml, Arc::new(provider))
+    }
+
+    /// The kind of the token.
+    #[must_use]
+    pub fn kind(&self) -> BearerTokenKind {
+        self.kind
+    }
+
+    // The current token; a provider is asked for a fresh one.
+    pub(crate) fn token(&self) -> HdbResult<SecUtf8> {
+        match self.source {
+            TokenSource::Fixed(ref token) => Ok(token.clone()),
+            TokenSource::Provider(ref provider) => {
+                provider().map(SecUtf8::from).map_err(HdbError::conn_params)
+            }
+        }
+    }
+
+    fn fixed(kind: BearerTokenKind, token: &str) -> Self {
+        Self {
+            kind,
+            source: TokenSource::Fixed(SecUtf8::from(token)),
+        }
+    }
+
+    fn with_provider(kind: BearerTokenKind, provider: Arc<TokenProvider>) -> Self {
+        Self {
+            kind,
+            source: TokenSource::Provider(provider),
+        }
+    }
+}
+
+impl std::fmt::Debug for BearerToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BearerToken")
+            .field("kind", &self.kind)
+            .field(
+                "source",
+                match self.source {
+                    TokenSource::Fixed(_) => &"fixed",
+                    TokenSource::Provider(_) => &"provider",
+                },
+            )
+            .finish()
+    }
+}
+
+// Providers are equal only if they are the same
+impl PartialEq for BearerToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && match (&self.source, &other.source) {
+                (TokenSource::Fixed(token), TokenSource::Fixed(other_token)) => {
+                    token == other_token
+                }
+                (TokenSource::Provider(provider), TokenSource::Provider(other_provider)) => {
+                    Arc::ptr_eq(provider, other_provider)
+                }
+                _ => false,
+            }
+    }
+}
+impl Eq for BearerToken {}
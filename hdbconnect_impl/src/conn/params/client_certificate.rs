@@ -0,0 +1,103 @@
+use crate::{usage_err, HdbResult};
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use secstr::SecUtf8;
+
+/// Expresses where the client certificate and its private key are read from.
+///
+/// A client certificate is presented to the server during the TLS handshake (mutual TLS),
+/// and it allows database users that are mapped to the certificate to log on
+/// without a password (X.509 authentication).
+///
+/// Certificates and keys are expected in PEM format.
+/// The certificate chain starts with the client certificate, optionally followed
+/// by intermediate certificates; the private key can be in PKCS#1, PKCS#8, or SEC1 format.
+/// Certificates and key can also be provided together, in a single file or environment variable,
+/// which is then given for both.
+///
+/// A client certificate can be configured with
+/// [`ConnectParamsBuilder::client_certificate`](crate::ConnectParamsBuilder::client_certificate),
+/// or with the URL options
+/// [`client_certificate_file`](crate::url::CLIENT_CERTIFICATE_FILE) and
+/// [`client_key_file`](crate::url::CLIENT_KEY_FILE), or
+/// [`client_certificate_env`](crate::url::CLIENT_CERTIFICATE_ENV) and
+/// [`client_key_env`](crate::url::CLIENT_KEY_ENV).
+///
+/// ```rust
+/// use hdbconnect::{ClientCertificate, ConnectParams, ServerCerts};
+///
+/// let connect_params = ConnectParams::builder()
+///     .hostname("the_host")
+///     .port(2222)
+///     .dbuser("my_user")
+///     .tls_with(ServerCerts::RootCertificates)
+///     .client_certificate(ClientCertificate::Files(
+///         "/etc/hana/client.pem".to_string(),
+///         "/etc/hana/client.key".to_string(),
+///     ))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(from = "ClientCertificateDef")]
+pub enum ClientCertificate {
+    /// Certificate chain and private key are read from the specified files.
+    Files(String, String),
+    /// Certificate chain and private key are read from the specified environment variables.
+    Environment(String, String),
+    /// Certificate chain and private key are given directly.
+    Direct(String, SecUtf8),
+}
+
+impl ClientCertificate {
+    // The certificate chain and the private key, in DER format.
+    pub(crate) fn load(&self) -> HdbResult<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+        let (certificates, private_key) = match self {
+            Self::Files(cert_file, key_file) => {
+                (read_file(cert_file)?, SecUtf8::from(read_file(key_file)?))
+            }
+            Self::Environment(cert_var, key_var) => {
+                (read_env(cert_var)?, SecUtf8::from(read_env(key_var)?))
+            }
+            Self::Direct(certificates, private_key) => (certificates.clone(), private_key.clone()),
+        };
+
+        let certificates = CertificateDer::pem_slice_iter(certificates.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| usage_err!("client certificate cannot be parsed: {e}"))?;
+        if certificates.is_empty() {
+            return Err(usage_err!("no client certificate found"));
+        }
+        let private_key = PrivateKeyDer::from_pem_slice(private_key.unsecure().as_bytes())
+            .map_err(|e| usage_err!("private key of the client certificate not found: {e}"))?;
+        Ok((certificates, private_key))
+    }
+}
+
+fn read_file(path: &str) -> HdbResult<String> {
+    std::fs::read_to_string(path).map_err(|e| usage_err!("cannot read file {path}: {e}"))
+}
+
+fn read_env(env_var: &str) -> HdbResult<String> {
+    std::env::var(env_var).map_err(|e| usage_err!("environment variable {env_var}: {e}"))
+}
+
+// Deserialization helper; SecUtf8 is not deserializable
+#[derive(Deserialize)]
+enum ClientCertificateDef {
+    Files(String, String),
+    Environment(String, String),
+    Direct(String, String),
+}
+impl From<ClientCertificateDef> for ClientCertificate {
+    fn from(def: ClientCertificateDef) -> Self {
+        match def {
+            ClientCertificateDef::Files(cert_file, key_file) => Self::Files(cert_file, key_file),
+            ClientCertificateDef::Environment(cert_var, key_var) => {
+                Self::Environment(cert_var, key_var)
+            }
+            ClientCertificateDef::Direct(certificates, private_key) => {
+                Self::Direct(certificates, SecUtf8::from(private_key))
+            }
+        }
+    }
+}
@@ -1,20 +1,30 @@
-use std::io::{Cursor, Write};
+use std::{
+    io::{Cursor, Write},
+    time::Duration,
+};
 
-use super::{tls::Tls, Compression};
+use super::{socket_options::SocketOptions, tls::Tls, Compression};
 use crate::{
     url::{self, HDBSQL, HDBSQLS},
-    ServerCerts,
+    ClientCertificate, HostSelection, ServerCerts,
 };
 
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 pub(crate) fn format_as_url(
-    addr: &str,
+    hosts: &[(String, u16)],
     dbuser: &str,
     database: Option<&str>,
     network_group: Option<&str>,
     tls: &Tls,
     clientlocale: Option<&str>,
     compression: Compression,
+    host_selection: HostSelection,
+    connect_timeout: Option<Duration>,
+    socket_options: &SocketOptions,
+    proxy: Option<&str>,
+    websocket_path: Option<&str>,
+    client_certificate: Option<&ClientCertificate>,
+    tls_server_name: Option<&str>,
 ) -> String {
     let mut buf = Cursor::new(Vec::<u8>::with_capacity(200));
     write!(
@@ -22,10 +32,14 @@ pub(crate) fn format_as_url(
         "{}://{}@{}",
         match tls {
             Tls::Off => HDBSQL,
-            Tls::Insecure | Tls::Secure(_) => HDBSQLS,
+            Tls::Insecure | Tls::Secure(_) | Tls::Custom(_) => HDBSQLS,
         },
         dbuser,
-        addr,
+        hosts
+            .iter()
+            .map(|(host, port)| format!("{host}:{port}"))
+            .collect::<Vec<_>>()
+            .join(","),
     )
     .ok();
 
@@ -33,6 +47,13 @@ pub(crate) fn format_as_url(
         && network_group.is_none()
         && matches!(tls, Tls::Off)
         && clientlocale.is_none()
+        && host_selection == HostSelection::Ordered
+        && connect_timeout.is_none()
+        && socket_options.is_empty()
+        && proxy.is_none()
+        && websocket_path.is_none()
+        && client_certificate.is_none()
+        && tls_server_name.is_none()
     {
     } else {
         // write URL options
@@ -49,7 +70,8 @@ pub(crate) fn format_as_url(
         }
 
         match tls {
-            Tls::Off => {}
+            // a custom client configuration cannot be expressed in a URL
+            Tls::Off | Tls::Custom(_) => {}
             Tls::Insecure => {
                 write!(
                     &mut buf,
@@ -89,6 +111,24 @@ pub(crate) fn format_as_url(
                             )
                             .ok();
                         }
+                        ServerCerts::CertificateSha256(pin) => {
+                            write!(
+                                &mut buf,
+                                "{}{}={pin}",
+                                sep.next().unwrap(),
+                                UrlOpt::TlsCertificateSha256
+                            )
+                            .ok();
+                        }
+                        ServerCerts::PublicKeySha256(pin) => {
+                            write!(
+                                &mut buf,
+                                "{}{}={pin}",
+                                sep.next().unwrap(),
+                                UrlOpt::TlsPublicKeySha256
+                            )
+                            .ok();
+                        }
                         ServerCerts::Direct(_s) => {
                             panic!("NOT SUPPORTED IN URLs");
                         }
@@ -97,6 +137,16 @@ pub(crate) fn format_as_url(
             }
         }
 
+        if let Some(server_name) = tls_server_name {
+            write!(
+                &mut buf,
+                "{}{}={server_name}",
+                sep.next().unwrap(),
+                UrlOpt::TlsServerName
+            )
+            .ok();
+        }
+
         if let Some(cl) = clientlocale {
             write!(
                 &mut buf,
@@ -114,6 +164,114 @@ pub(crate) fn format_as_url(
                 write!(&mut buf, "{}{}", sep.next().unwrap(), UrlOpt::NoCompression).ok();
             }
         }
+
+        match host_selection {
+            HostSelection::Ordered => {}
+            HostSelection::Random => {
+                write!(
+                    &mut buf,
+                    "{}{}={}",
+                    sep.next().unwrap(),
+                    UrlOpt::HostSelection,
+                    url::HOST_SELECTION_RANDOM
+                )
+                .ok();
+            }
+        }
+
+        if let Some(timeout) = connect_timeout {
+            write!(
+                &mut buf,
+                "{}{}={}",
+                sep.next().unwrap(),
+                UrlOpt::ConnectTimeout,
+                timeout.as_millis()
+            )
+            .ok();
+        }
+
+        if let Some(timeout) = socket_options.write_timeout {
+            write!(
+                &mut buf,
+                "{}{}={}",
+                sep.next().unwrap(),
+                UrlOpt::WriteTimeout,
+                timeout.as_millis()
+            )
+            .ok();
+        }
+
+        if let Some(nodelay) = socket_options.tcp_nodelay {
+            write!(
+                &mut buf,
+                "{}{}={nodelay}",
+                sep.next().unwrap(),
+                UrlOpt::TcpNodelay
+            )
+            .ok();
+        }
+
+        if let Some(interval) = socket_options.tcp_keepalive {
+            write!(
+                &mut buf,
+                "{}{}={}",
+                sep.next().unwrap(),
+                UrlOpt::TcpKeepalive,
+                interval.as_millis()
+            )
+            .ok();
+        }
+
+        if let Some(retries) = socket_options.tcp_keepalive_retries {
+            write!(
+                &mut buf,
+                "{}{}={retries}",
+                sep.next().unwrap(),
+                UrlOpt::TcpKeepaliveRetries
+            )
+            .ok();
+        }
+
+        if let Some(proxy) = proxy {
+            write!(&mut buf, "{}{}={proxy}", sep.next().unwrap(), UrlOpt::Proxy).ok();
+        }
+
+        if let Some(path) = websocket_path {
+            write!(
+                &mut buf,
+                "{}{}={path}",
+                sep.next().unwrap(),
+                UrlOpt::WebSocketPath
+            )
+            .ok();
+        }
+
+        match client_certificate {
+            Some(ClientCertificate::Files(cert_file, key_file)) => {
+                write!(
+                    &mut buf,
+                    "{}{}={cert_file}{}{}={key_file}",
+                    sep.next().unwrap(),
+                    UrlOpt::ClientCertificateFile,
+                    sep.next().unwrap(),
+                    UrlOpt::ClientKeyFile,
+                )
+                .ok();
+            }
+            Some(ClientCertificate::Environment(cert_var, key_var)) => {
+                write!(
+                    &mut buf,
+                    "{}{}={cert_var}{}{}={key_var}",
+                    sep.next().unwrap(),
+                    UrlOpt::ClientCertificateEnv,
+                    sep.next().unwrap(),
+                    UrlOpt::ClientKeyEnv,
+                )
+                .ok();
+            }
+            // the private key does not belong into a URL
+            Some(ClientCertificate::Direct(..)) | None => {}
+        }
     }
 
     String::from_utf8_lossy(&buf.into_inner()).to_string()
@@ -124,12 +282,27 @@ pub(crate) enum UrlOpt {
     TlsCertificateEnv,
     // TlsCertificateDirect,
     TlsCertificateMozilla,
+    TlsCertificateSha256,
+    TlsPublicKeySha256,
+    TlsServerName,
     InsecureOmitServerCheck,
     ClientLocale,
     ClientLocaleFromEnv,
     Database,
     NetworkGroup,
     NoCompression,
+    HostSelection,
+    ConnectTimeout,
+    WriteTimeout,
+    TcpNodelay,
+    TcpKeepalive,
+    TcpKeepaliveRetries,
+    Proxy,
+    WebSocketPath,
+    ClientCertificateFile,
+    ClientKeyFile,
+    ClientCertificateEnv,
+    ClientKeyEnv,
 }
 impl UrlOpt {
     pub fn from(s: &str) -> Option<Self> {
@@ -137,12 +310,27 @@ impl UrlOpt {
             url::TLS_CERTIFICATE_DIR => Some(UrlOpt::TlsCertificateDir),
             url::TLS_CERTIFICATE_ENV => Some(UrlOpt::TlsCertificateEnv),
             url::USE_MOZILLAS_ROOT_CERTIFICATES => Some(UrlOpt::TlsCertificateMozilla),
+            url::TLS_CERTIFICATE_SHA256 => Some(UrlOpt::TlsCertificateSha256),
+            url::TLS_PUBLIC_KEY_SHA256 => Some(UrlOpt::TlsPublicKeySha256),
+            url::TLS_SERVER_NAME => Some(UrlOpt::TlsServerName),
             url::INSECURE_OMIT_SERVER_CERTIFICATE_CHECK => Some(UrlOpt::InsecureOmitServerCheck),
             url::CLIENT_LOCALE => Some(UrlOpt::ClientLocale),
             url::CLIENT_LOCALE_FROM_ENV => Some(UrlOpt::ClientLocaleFromEnv),
             url::DATABASE => Some(UrlOpt::Database),
             url::NETWORK_GROUP => Some(UrlOpt::NetworkGroup),
             url::NO_COMPRESSION => Some(UrlOpt::NoCompression),
+            url::HOST_SELECTION => Some(UrlOpt::HostSelection),
+            url::CONNECT_TIMEOUT => Some(UrlOpt::ConnectTimeout),
+            url::WRITE_TIMEOUT => Some(UrlOpt::WriteTimeout),
+            url::TCP_NODELAY => Some(UrlOpt::TcpNodelay),
+            url::TCP_KEEPALIVE => Some(UrlOpt::TcpKeepalive),
+            url::TCP_KEEPALIVE_RETRIES => Some(UrlOpt::TcpKeepaliveRetries),
+            url::PROXY => Some(UrlOpt::Proxy),
+            url::WEBSOCKET_PATH => Some(UrlOpt::WebSocketPath),
+            url::CLIENT_CERTIFICATE_FILE => Some(UrlOpt::ClientCertificateFile),
+            url::CLIENT_KEY_FILE => Some(UrlOpt::ClientKeyFile),
+            url::CLIENT_CERTIFICATE_ENV => Some(UrlOpt::ClientCertificateEnv),
+            url::CLIENT_KEY_ENV => Some(UrlOpt::ClientKeyEnv),
             _ => None,
         }
     }
@@ -156,12 +344,27 @@ impl std::fmt::Display for UrlOpt {
                 UrlOpt::TlsCertificateDir => url::TLS_CERTIFICATE_DIR,
                 UrlOpt::TlsCertificateEnv => url::TLS_CERTIFICATE_ENV,
                 UrlOpt::TlsCertificateMozilla => url::USE_MOZILLAS_ROOT_CERTIFICATES,
+                UrlOpt::TlsCertificateSha256 => url::TLS_CERTIFICATE_SHA256,
+                UrlOpt::TlsPublicKeySha256 => url::TLS_PUBLIC_KEY_SHA256,
+                UrlOpt::TlsServerName => url::TLS_SERVER_NAME,
                 UrlOpt::InsecureOmitServerCheck => url::INSECURE_OMIT_SERVER_CERTIFICATE_CHECK,
                 UrlOpt::ClientLocale => url::CLIENT_LOCALE,
                 UrlOpt::ClientLocaleFromEnv => url::CLIENT_LOCALE_FROM_ENV,
                 UrlOpt::Database => url::DATABASE,
                 UrlOpt::NetworkGroup => url::NETWORK_GROUP,
                 UrlOpt::NoCompression => url::NO_COMPRESSION,
+                UrlOpt::HostSelection => url::HOST_SELECTION,
+                UrlOpt::ConnectTimeout => url::CONNECT_TIMEOUT,
+                UrlOpt::WriteTimeout => url::WRITE_TIMEOUT,
+                UrlOpt::TcpNodelay => url::TCP_NODELAY,
+                UrlOpt::TcpKeepalive => url::TCP_KEEPALIVE,
+                UrlOpt::TcpKeepaliveRetries => url::TCP_KEEPALIVE_RETRIES,
+                UrlOpt::Proxy => url::PROXY,
+                UrlOpt::WebSocketPath => url::WEBSOCKET_PATH,
+                UrlOpt::ClientCertificateFile => url::CLIENT_CERTIFICATE_FILE,
+                UrlOpt::ClientKeyFile => url::CLIENT_KEY_FILE,
+                UrlOpt::ClientCertificateEnv => url::CLIENT_CERTIFICATE_ENV,
+                UrlOpt::ClientKeyEnv => url::CLIENT_KEY_ENV,
             }
         )
     }
@@ -0,0 +1,132 @@
+use crate::{HdbError, HdbResult};
+#[cfg(feature = "async")]
+use async_trait::async_trait;
+use secstr::SecUtf8;
+use std::sync::Arc;
+
+/// Supplies the password with which a connection logs on, instead of a fixed password.
+///
+/// The provider is asked for the current password whenever a connection is established
+/// or re-established (e.g., with session recovery, or after a redirect),
+/// so that passwords that are rotated in a secret store are picked up
+/// by new connections, and by the connection pools, without a restart.
+///
+/// A credential provider can be configured with
+/// [`ConnectParamsBuilder::credential_provider`](crate::ConnectParamsBuilder::credential_provider).
+/// Closures with the signature of [`CredentialProvider::password`] implement this trait.
+///
+/// ```rust
+/// use hdbconnect::ConnectParams;
+/// # fn password_from_vault() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+/// #     Ok(String::new())
+/// # }
+///
+/// let connect_params = ConnectParams::builder()
+///     .hostname("the_host")
+///     .port(2222)
+///     .dbuser("my_user")
+///     .credential_provider(password_from_vault)
+///     .build()
+///     .unwrap();
+/// ```
+pub trait CredentialProvider: Send + Sync {
+    /// The current password.
+    ///
+    /// # Errors
+    ///
+    /// Any error, e.g. if the secret store is not reachable; the logon then fails
+    /// with `HdbError::ConnParams`.
+    fn password(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+impl<F> CredentialProvider for F
+where
+    F: Fn() -> Result<String, Box<dyn std::error::Error + Send + Sync>> + Send + Sync,
+{
+    fn password(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self()
+    }
+}
+
+/// Supplies the password with which an async connection logs on, instead of a fixed password.
+///
+/// Like [`CredentialProvider`], but for secret stores that are accessed asynchronously.
+/// An async credential provider can only be used with async connections;
+/// these can also use a [`CredentialProvider`], which then should not block for long.
+///
+/// An async credential provider can be configured with
+/// [`ConnectParamsBuilder::async_credential_provider`](crate::ConnectParamsBuilder::async_credential_provider).
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait AsyncCredentialProvider: Send + Sync {
+    /// The current password.
+    ///
+    /// # Errors
+    ///
+    /// Any error, e.g. if the secret store is not reachable; the logon then fails
+    /// with `HdbError::ConnParams`.
+    async fn password(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+// The configured provider, in one of its flavors
+#[derive(Clone)]
+pub(crate) enum CredentialSource {
+    Sync(Arc<dyn CredentialProvider>),
+    #[cfg(feature = "async")]
+    Async(Arc<dyn AsyncCredentialProvider>),
+}
+impl CredentialSource {
+    // A fresh password, for a sync connection.
+    #[cfg(feature = "sync")]
+    pub(crate) fn password_sync(&self) -> HdbResult<SecUtf8> {
+        match self {
+            Self::Sync(provider) => provider
+                .password()
+                .map(SecUtf8::from)
+                .map_err(HdbError::conn_params),
+            #[cfg(feature = "async")]
+            Self::Async(_) => Err(crate::usage_err!(
+                "An AsyncCredentialProvider cannot be used with sync connections"
+            )),
+        }
+    }
+
+    // A fresh password, for an async connection.
+    #[cfg(feature = "async")]
+    pub(crate) async fn password_async(&self) -> HdbResult<SecUtf8> {
+        match self {
+            Self::Sync(provider) => provider.password(),
+            Self::Async(provider) => provider.password().await,
+        }
+        .map(SecUtf8::from)
+        .map_err(HdbError::conn_params)
+    }
+}
+
+impl std::fmt::Debug for CredentialSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Sync(_) => "CredentialProvider",
+            #[cfg(feature = "async")]
+            Self::Async(_) => "AsyncCredentialProvider",
+        })
+    }
+}
+
+// Providers are equal only if they are the same
+impl PartialEq for CredentialSource {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Sync(provider), Self::Sync(other_provider)) => {
+                Arc::ptr_eq(provider, other_provider)
+            }
+            #[cfg(feature = "async")]
+            (Self::Async(provider), Self::Async(other_provider)) => {
+                Arc::ptr_eq(provider, other_provider)
+            }
+            #[cfg(feature = "async")]
+            _ => false,
+        }
+    }
+}
+impl Eq for CredentialSource {}
@@ -1,4 +1,4 @@
-use crate::{ConnectParams, ConnectParamsBuilder, HdbError, HdbResult, IntoConnectParamsBuilder};
+use crate::{ConnectParams, ConnectParamsBuilder, HdbResult, IntoConnectParamsBuilder};
 use url::Url;
 
 /// A trait implemented by types that can be converted into a `ConnectParams`.
@@ -37,9 +37,7 @@ impl IntoConnectParams for &ConnectParamsBuilder {
 
 impl IntoConnectParams for &str {
     fn into_connect_params(self) -> HdbResult<ConnectParams> {
-        Url::parse(self)
-            .map_err(|e| HdbError::conn_params(Box::new(e)))?
-            .into_connect_params()
+        self.into_connect_params_builder()?.build()
     }
 }
 
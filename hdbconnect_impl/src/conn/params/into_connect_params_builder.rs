@@ -1,8 +1,10 @@
-use super::cp_url::UrlOpt;
+use super::{cp_url::UrlOpt, user_store::UserStoreLocation};
 use crate::{
-    url::{HDBSQL, HDBSQLS},
-    usage_err, ConnectParamsBuilder, HdbError, HdbResult, ServerCerts,
+    url::{self as hdb_url, HDBSQL, HDBSQLS},
+    usage_err, ClientCertificate, ConnectParamsBuilder, HdbError, HdbResult, HostSelection, Proxy,
+    ServerCerts,
 };
+use std::{borrow::Cow, time::Duration};
 use url::Url;
 
 /// A trait implemented by types that can be converted into a `ConnectParamsBuilder`.
@@ -33,9 +35,79 @@ impl IntoConnectParamsBuilder for ConnectParamsBuilder {
 
 impl IntoConnectParamsBuilder for &str {
     fn into_connect_params_builder(self) -> HdbResult<ConnectParamsBuilder> {
-        Url::parse(self)
+        let (url, failover_hosts) = split_off_failover_hosts(self);
+        let mut builder = Url::parse(&url)
             .map_err(|e| HdbError::conn_params(Box::new(e)))?
-            .into_connect_params_builder()
+            .into_connect_params_builder()?;
+        for host_and_port in failover_hosts {
+            let (host, port) = parse_host_and_port(host_and_port)?;
+            builder.add_host(host, port);
+        }
+        Ok(builder)
+    }
+}
+
+// Url accepts only a single host, so the additional hosts are cut out before parsing.
+fn split_off_failover_hosts(url: &str) -> (Cow<'_, str>, Vec<&str>) {
+    let Some(authority_start) = url.find("://").map(|i| i + 3) else {
+        return (Cow::Borrowed(url), Vec::new());
+    };
+    let authority_end = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| authority_start + i);
+    let hosts_start = url[authority_start..authority_end]
+        .rfind('@')
+        .map_or(authority_start, |i| authority_start + i + 1);
+    let Some(first_comma) = url[hosts_start..authority_end]
+        .find(',')
+        .map(|i| hosts_start + i)
+    else {
+        return (Cow::Borrowed(url), Vec::new());
+    };
+    (
+        Cow::Owned(format!("{}{}", &url[..first_comma], &url[authority_end..])),
+        url[first_comma + 1..authority_end].split(',').collect(),
+    )
+}
+
+pub(super) fn parse_host_and_port(host_and_port: &str) -> HdbResult<(String, u16)> {
+    host_and_port
+        .rsplit_once(':')
+        .filter(|(host, _)| !host.is_empty())
+        .and_then(|(host, port)| Some((host.to_string(), port.parse::<u16>().ok()?)))
+        .ok_or_else(|| usage_err!("host '{host_and_port}' is not given in the form <host>:<port>"))
+}
+
+// The key of `<scheme>://@userstore/<key>`
+fn user_store_key(url: &Url) -> HdbResult<Option<&str>> {
+    if !url
+        .host_str()
+        .is_some_and(|host| host.eq_ignore_ascii_case(hdb_url::USER_STORE))
+        || !url.username().is_empty()
+        || url.port().is_some()
+    {
+        return Ok(None);
+    }
+    match url.path().trim_matches('/') {
+        "" => Err(usage_err!(
+            "a URL with '@{}' requires a key, as in '{HDBSQL}://@{}/<key>'",
+            hdb_url::USER_STORE,
+            hdb_url::USER_STORE
+        )),
+        key => Ok(Some(key)),
+    }
+}
+
+fn parse_host_selection(value: &str) -> HdbResult<HostSelection> {
+    match value {
+        hdb_url::HOST_SELECTION_ORDERED => Ok(HostSelection::Ordered),
+        hdb_url::HOST_SELECTION_RANDOM => Ok(HostSelection::Random),
+        _ => Err(usage_err!(
+            "option '{}' supports only the values '{}' and '{}'",
+            hdb_url::HOST_SELECTION,
+            hdb_url::HOST_SELECTION_ORDERED,
+            hdb_url::HOST_SELECTION_RANDOM
+        )),
     }
 }
 
@@ -47,101 +119,195 @@ impl IntoConnectParamsBuilder for String {
 
 impl IntoConnectParamsBuilder for Url {
     fn into_connect_params_builder(self) -> HdbResult<ConnectParamsBuilder> {
+        url_into_connect_params_builder(&self, &UserStoreLocation::from_env)
+    }
+}
+
+// The user store is only located if the URL refers to it.
+#[allow(clippy::too_many_lines)]
+pub(super) fn url_into_connect_params_builder(
+    url: &Url,
+    user_store_location: &dyn Fn() -> HdbResult<UserStoreLocation>,
+) -> HdbResult<ConnectParamsBuilder> {
+    let mut builder = if let Some(key) = user_store_key(url)? {
+        ConnectParamsBuilder::from_user_store_key_at(key, &user_store_location()?)?
+    } else {
         let mut builder = ConnectParamsBuilder::new();
-        self.host_str().map(|host| builder.hostname(host));
-        self.port().map(|port| builder.port(port));
+        url.host_str().map(|host| builder.hostname(host));
+        url.port().map(|port| builder.port(port));
 
-        let dbuser = self.username();
+        let dbuser = url.username();
         if !dbuser.is_empty() {
             builder.dbuser(dbuser);
         }
-        self.password().map(|pw| builder.password(pw));
+        url.password().map(|pw| builder.password(pw));
+        builder
+    };
 
-        // authoritative switch between protocols:
-        let use_tls = match self.scheme() {
-            HDBSQL => false,
-            HDBSQLS => true,
-            _ => {
-                return Err(usage_err!(
-                    "Unknown protocol '{}', only 'hdbsql' and 'hdbsqls' are supported",
-                    self.scheme()
-                ));
+    // authoritative switch between protocols:
+    let use_tls = match url.scheme() {
+        HDBSQL => false,
+        HDBSQLS => true,
+        _ => {
+            return Err(usage_err!(
+                "Unknown protocol '{}', only 'hdbsql' and 'hdbsqls' are supported",
+                url.scheme()
+            ));
+        }
+    };
+
+    let mut insecure_option = false;
+    let mut server_certs = Vec::<ServerCerts>::new();
+    let mut client_certificate_files = (None, None);
+    let mut client_certificate_env = (None, None);
+
+    for (name, value) in url.query_pairs() {
+        match UrlOpt::from(name.as_ref()) {
+            Some(UrlOpt::ClientLocale) => {
+                builder.clientlocale(&value);
+            }
+            Some(UrlOpt::ClientLocaleFromEnv) => {
+                std::env::var(value.to_string())
+                    .ok()
+                    .map(|s| builder.clientlocale(s));
+            }
+            Some(UrlOpt::TlsCertificateDir) => {
+                server_certs.push(ServerCerts::Directory(value.to_string()));
+            }
+            Some(UrlOpt::TlsCertificateEnv) => {
+                server_certs.push(ServerCerts::Environment(value.to_string()));
+            }
+            Some(UrlOpt::TlsCertificateMozilla) => {
+                server_certs.push(ServerCerts::RootCertificates);
+            }
+            Some(UrlOpt::TlsCertificateSha256) => {
+                server_certs.push(ServerCerts::CertificateSha256(value.to_string()));
+            }
+            Some(UrlOpt::TlsPublicKeySha256) => {
+                server_certs.push(ServerCerts::PublicKeySha256(value.to_string()));
+            }
+            Some(UrlOpt::TlsServerName) => {
+                builder.tls_server_name(&value);
+            }
+            Some(UrlOpt::InsecureOmitServerCheck) => {
+                insecure_option = true;
+            }
+            Some(UrlOpt::Database) => {
+                builder.dbname(&value);
+            }
+            Some(UrlOpt::NetworkGroup) => {
+                builder.network_group(&value);
+            }
+            Some(UrlOpt::NoCompression) => {
+                builder.always_uncompressed(true);
             }
-        };
-
-        let mut insecure_option = false;
-        let mut server_certs = Vec::<ServerCerts>::new();
-
-        for (name, value) in self.query_pairs() {
-            match UrlOpt::from(name.as_ref()) {
-                Some(UrlOpt::ClientLocale) => {
-                    builder.clientlocale(&value);
-                }
-                Some(UrlOpt::ClientLocaleFromEnv) => {
-                    std::env::var(value.to_string())
-                        .ok()
-                        .map(|s| builder.clientlocale(s));
-                }
-                Some(UrlOpt::TlsCertificateDir) => {
-                    server_certs.push(ServerCerts::Directory(value.to_string()));
-                }
-                Some(UrlOpt::TlsCertificateEnv) => {
-                    server_certs.push(ServerCerts::Environment(value.to_string()));
-                }
-                Some(UrlOpt::TlsCertificateMozilla) => {
-                    server_certs.push(ServerCerts::RootCertificates);
-                }
-                Some(UrlOpt::InsecureOmitServerCheck) => {
-                    insecure_option = true;
-                }
-                Some(UrlOpt::Database) => {
-                    builder.dbname(&value);
-                }
-                Some(UrlOpt::NetworkGroup) => {
-                    builder.network_group(&value);
-                }
-                Some(UrlOpt::NoCompression) => {
-                    builder.always_uncompressed(true);
-                }
-                None => {
-                    return Err(usage_err!("option '{name}' not supported"));
-                }
+            Some(UrlOpt::HostSelection) => {
+                builder.host_selection(parse_host_selection(&value)?);
             }
+            Some(UrlOpt::ConnectTimeout) => {
+                builder.connect_timeout(Duration::from_millis(value.parse().map_err(|_| {
+                    usage_err!("option '{name}' requires a number of milliseconds")
+                })?));
+            }
+            Some(UrlOpt::WriteTimeout) => {
+                builder.write_timeout(Duration::from_millis(value.parse().map_err(|_| {
+                    usage_err!("option '{name}' requires a number of milliseconds")
+                })?));
+            }
+            Some(UrlOpt::TcpNodelay) => {
+                builder.tcp_nodelay(value.parse().map_err(|_| {
+                    usage_err!("option '{name}' supports only the values 'true' and 'false'")
+                })?);
+            }
+            Some(UrlOpt::TcpKeepalive) => {
+                builder.tcp_keepalive(Duration::from_millis(value.parse().map_err(|_| {
+                    usage_err!("option '{name}' requires a number of milliseconds")
+                })?));
+            }
+            Some(UrlOpt::TcpKeepaliveRetries) => {
+                builder.tcp_keepalive_retries(
+                    value
+                        .parse()
+                        .map_err(|_| usage_err!("option '{name}' requires a number"))?,
+                );
+            }
+            Some(UrlOpt::Proxy) => {
+                builder.proxy(Proxy::from_url(&value)?);
+            }
+            Some(UrlOpt::WebSocketPath) => {
+                builder.websocket_path(&value);
+            }
+            Some(UrlOpt::ClientCertificateFile) => {
+                client_certificate_files.0 = Some(value.to_string());
+            }
+            Some(UrlOpt::ClientKeyFile) => {
+                client_certificate_files.1 = Some(value.to_string());
+            }
+            Some(UrlOpt::ClientCertificateEnv) => {
+                client_certificate_env.0 = Some(value.to_string());
+            }
+            Some(UrlOpt::ClientKeyEnv) => {
+                client_certificate_env.1 = Some(value.to_string());
+            }
+            None => {
+                return Err(usage_err!("option '{name}' not supported"));
+            }
+        }
+    }
+
+    match (client_certificate_files, client_certificate_env) {
+        ((None, None), (None, None)) => {}
+        ((Some(cert_file), Some(key_file)), (None, None)) => {
+            builder.client_certificate(ClientCertificate::Files(cert_file, key_file));
+        }
+        ((None, None), (Some(cert_var), Some(key_var))) => {
+            builder.client_certificate(ClientCertificate::Environment(cert_var, key_var));
+        }
+        _ => {
+            return Err(usage_err!(
+                "A client certificate requires either both url-options '{}' and '{}', \
+                     or both url-options '{}' and '{}'",
+                hdb_url::CLIENT_CERTIFICATE_FILE,
+                hdb_url::CLIENT_KEY_FILE,
+                hdb_url::CLIENT_CERTIFICATE_ENV,
+                hdb_url::CLIENT_KEY_ENV,
+            ));
         }
+    }
 
-        if use_tls {
-            if insecure_option {
-                if !server_certs.is_empty() {
-                    return Err(usage_err!(
-                        "Use either the url-options 'tls_certificate_dir', 'tls_certificate_env', \
+    if use_tls {
+        if insecure_option {
+            if !server_certs.is_empty() {
+                return Err(usage_err!(
+                    "Use either the url-options 'tls_certificate_dir', 'tls_certificate_env', \
                         'tls_certificate_direct' and 'use_mozillas_root_certificates' \
                         to specify the access to the server certificate,\
                         or use 'insecure_omit_server_certificate_check' to not verify the server's \
                         identity, which is not recommended in most situations",
-                    ));
-                }
-                builder.tls_without_server_verification();
-            } else {
-                if server_certs.is_empty() {
-                    return Err(usage_err!(
-                        "Using 'hdbsqls' requires at least one of the url-options \
+                ));
+            }
+            builder.tls_without_server_verification();
+        } else {
+            if server_certs.is_empty() {
+                return Err(usage_err!(
+                    "Using 'hdbsqls' requires at least one of the url-options \
                         'tls_certificate_dir', 'tls_certificate_env', 'tls_certificate_direct', \
-                        'use_mozillas_root_certificates', or 'insecure_omit_server_certificate_check'",
-                    ));
-                }
-                for cert in server_certs {
-                    builder.tls_with(cert);
-                }
-            }
-        } else if insecure_option || !server_certs.is_empty() {
-            return Err(usage_err!(
-                "Using 'hdbsql' is not possible with any of the url-options \
+                        'use_mozillas_root_certificates', 'tls_certificate_sha256', \
+                        'tls_public_key_sha256', or 'insecure_omit_server_certificate_check'",
+                ));
+            }
+            for cert in server_certs {
+                builder.tls_with(cert);
+            }
+        }
+    } else if insecure_option || !server_certs.is_empty() {
+        return Err(usage_err!(
+            "Using 'hdbsql' is not possible with any of the url-options \
                     'tls_certificate_dir', 'tls_certificate_env', 'tls_certificate_direct', \
                     'use_mozillas_root_certificates', or 'insecure_omit_server_certificate_check'; \
                     consider using 'hdbsqls' instead",
-            ));
-        }
-
-        Ok(builder)
+        ));
     }
+
+    Ok(builder)
 }
@@ -0,0 +1,205 @@
+use super::into_connect_params_builder::parse_host_and_port;
+use crate::{usage_err, HdbError, HdbResult};
+use secstr::SecUtf8;
+use url::form_urlencoded::byte_serialize;
+
+/// A proxy through which the network connections to the database are tunneled.
+///
+/// HTTP proxies are used with the method `CONNECT`, SOCKS5 proxies with the command `CONNECT`.
+/// In both cases, the proxy resolves the name of the database host,
+/// and the proxy does not see the content of TLS connections,
+/// which run end-to-end between the driver and the database.
+///
+/// A proxy can be configured with
+/// [`ConnectParamsBuilder::proxy`](crate::ConnectParamsBuilder::proxy),
+/// or with the URL option [`proxy`](crate::url::PROXY).
+///
+/// ```rust
+/// use hdbconnect::{ConnectParams, Proxy};
+///
+/// let connect_params = ConnectParams::builder()
+///     .hostname("the_host")
+///     .port(2222)
+///     .dbuser("my_user")
+///     .password("my_passwd")
+///     .proxy(Proxy::socks5("egress.corp", 1080).with_credentials("proxy_user", "proxy_passwd"))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Proxy {
+    protocol: ProxyProtocol,
+    host: String,
+    port: u16,
+    credentials: Option<(String, SecUtf8)>,
+}
+
+/// The protocol that is used for opening a tunnel through a [`Proxy`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProxyProtocol {
+    /// HTTP, with the method `CONNECT`.
+    Http,
+    /// SOCKS version 5, with the command `CONNECT`.
+    Socks5,
+}
+impl ProxyProtocol {
+    fn scheme(self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Socks5 => "socks5",
+        }
+    }
+}
+
+impl Proxy {
+    /// An HTTP proxy.
+    #[must_use]
+    pub fn http<H: AsRef<str>>(host: H, port: u16) -> Self {
+        Self::new(ProxyProtocol::Http, host.as_ref(), port)
+    }
+
+    /// A SOCKS5 proxy.
+    #[must_use]
+    pub fn socks5<H: AsRef<str>>(host: H, port: u16) -> Self {
+        Self::new(ProxyProtocol::Socks5, host.as_ref(), port)
+    }
+
+    fn new(protocol: ProxyProtocol, host: &str, port: u16) -> Self {
+        Self {
+            protocol,
+            host: host.to_string(),
+            port,
+            credentials: None,
+        }
+    }
+
+    /// Sets the credentials with which the driver authenticates at the proxy.
+    ///
+    /// They are sent with HTTP basic authentication, or with the SOCKS5 username/password
+    /// authentication, respectively.
+    #[must_use]
+    pub fn with_credentials<U: AsRef<str>, P: AsRef<str>>(mut self, user: U, password: P) -> Self {
+        self.credentials = Some((user.as_ref().to_string(), SecUtf8::from(password.as_ref())));
+        self
+    }
+
+    /// Parses a proxy URL of the form `<protocol>://[<user>[:<password>]@]<host>:<port>`,
+    /// where `<protocol>` is `http` or `socks5`.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if the URL is not of the described form.
+    pub fn from_url(url: &str) -> HdbResult<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| usage_err!("proxy '{url}' is not given in the form of a url"))?;
+        let protocol = match scheme {
+            "http" => ProxyProtocol::Http,
+            "socks5" | "socks5h" => ProxyProtocol::Socks5,
+            _ => {
+                return Err(usage_err!(
+                    "unknown proxy protocol '{scheme}', only 'http' and 'socks5' are supported"
+                ));
+            }
+        };
+        let rest = rest.trim_end_matches('/');
+        let (o_userinfo, host_and_port) = match rest.rsplit_once('@') {
+            Some((userinfo, host_and_port)) => (Some(userinfo), host_and_port),
+            None => (None, rest),
+        };
+        let (host, port) = parse_host_and_port(host_and_port)?;
+        let proxy = Self::new(protocol, &host, port);
+        Ok(match o_userinfo {
+            Some(userinfo) => {
+                let (user, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+                proxy.with_credentials(user, password)
+            }
+            None => proxy,
+        })
+    }
+
+    /// The protocol.
+    #[must_use]
+    pub fn protocol(&self) -> ProxyProtocol {
+        self.protocol
+    }
+
+    /// The host.
+    #[must_use]
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// The port.
+    #[must_use]
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// The user with which the driver authenticates at the proxy.
+    #[must_use]
+    pub fn user(&self) -> Option<&str> {
+        self.credentials.as_ref().map(|(user, _)| user.as_str())
+    }
+
+    pub(crate) fn credentials(&self) -> Option<(&str, &SecUtf8)> {
+        self.credentials
+            .as_ref()
+            .map(|(user, password)| (user.as_str(), password))
+    }
+
+    pub(crate) fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    // The value of the URL option; the credentials are encoded such that they survive
+    // the decoding of the URL options.
+    pub(crate) fn to_url_option(&self, with_password: bool) -> String {
+        let userinfo = match self.credentials {
+            Some((ref user, ref password)) if with_password => format!(
+                "{}:{}@",
+                byte_serialize(user.as_bytes()).collect::<String>(),
+                byte_serialize(password.unsecure().as_bytes()).collect::<String>()
+            ),
+            Some((ref user, _)) => {
+                format!("{}@", byte_serialize(user.as_bytes()).collect::<String>())
+            }
+            None => String::new(),
+        };
+        format!(
+            "{}://{userinfo}{}:{}",
+            self.protocol.scheme(),
+            self.host,
+            self.port
+        )
+    }
+}
+
+impl TryFrom<String> for Proxy {
+    type Error = HdbError;
+    fn try_from(url: String) -> HdbResult<Self> {
+        Self::from_url(&url)
+    }
+}
+
+impl std::fmt::Display for Proxy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.user() {
+            Some(user) => write!(
+                f,
+                "{}://{user}@{}:{}",
+                self.protocol.scheme(),
+                self.host,
+                self.port
+            ),
+            None => write!(
+                f,
+                "{}://{}:{}",
+                self.protocol.scheme(),
+                self.host,
+                self.port
+            ),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+// Socket options that are specified with the connect parameters (e.g. in the URL);
+// they take precedence over the respective settings of the `ConnectionConfiguration`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize)]
+pub(crate) struct SocketOptions {
+    #[serde(default)]
+    pub(crate) write_timeout: Option<Duration>,
+    #[serde(default)]
+    pub(crate) tcp_nodelay: Option<bool>,
+    #[serde(default)]
+    pub(crate) tcp_keepalive: Option<Duration>,
+    #[serde(default)]
+    pub(crate) tcp_keepalive_retries: Option<u32>,
+}
+impl SocketOptions {
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
@@ -1,4 +1,16 @@
-use crate::ServerCerts;
+use crate::{usage_err, HdbResult, ServerCerts};
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WebPkiServerVerifier,
+    },
+    crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    CertificateError, ClientConfig, DigitallySignedStruct, SignatureScheme,
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use webpki::EndEntityCert;
 
 /// Describes whether and how TLS is to be used.
 #[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
@@ -10,4 +22,171 @@ pub(crate) enum Tls {
     Insecure,
     /// TLS with server validation
     Secure(Vec<ServerCerts>),
+    /// TLS with a client configuration that is provided by the application
+    #[serde(skip)]
+    Custom(CustomClientConfig),
+}
+
+// A client configuration that is provided by the application.
+#[derive(Clone)]
+pub(crate) struct CustomClientConfig(pub(crate) Arc<ClientConfig>);
+impl std::fmt::Debug for CustomClientConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomClientConfig")
+    }
+}
+// Configurations are equal only if they are the same
+impl PartialEq for CustomClientConfig {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl Eq for CustomClientConfig {}
+
+// Parses the hex representation of a SHA-256 hash, with or without colons.
+pub(crate) fn parse_sha256_pin(pin: &str) -> HdbResult<[u8; 32]> {
+    let digits: Vec<u8> = pin.bytes().filter(|b| *b != b':').collect();
+    let invalid = || usage_err!("'{pin}' is not a SHA-256 hash in hex representation");
+    if digits.len() != 64 || !digits.iter().all(u8::is_ascii_hexdigit) {
+        return Err(invalid());
+    }
+    let mut hash = [0_u8; 32];
+    for (byte, pair) in hash.iter_mut().zip(digits.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).map_err(|_| invalid())?, 16)
+            .map_err(|_| invalid())?;
+    }
+    Ok(hash)
+}
+
+// Accepts the server certificate if its SHA-256 hash, or the SHA-256 hash of its
+// SubjectPublicKeyInfo, is pinned.
+//
+// If root certificates are configured as well, the certificate chain and the server name
+// are validated in addition; otherwise the pin alone identifies the server.
+#[derive(Debug)]
+pub(crate) struct PinningVerifier {
+    certificate_pins: Vec<[u8; 32]>,
+    public_key_pins: Vec<[u8; 32]>,
+    o_webpki_verifier: Option<Arc<WebPkiServerVerifier>>,
+    crypto_provider: Arc<CryptoProvider>,
+}
+impl PinningVerifier {
+    pub(crate) fn new(
+        certificate_pins: Vec<[u8; 32]>,
+        public_key_pins: Vec<[u8; 32]>,
+        o_webpki_verifier: Option<Arc<WebPkiServerVerifier>>,
+        crypto_provider: Arc<CryptoProvider>,
+    ) -> Self {
+        Self {
+            certificate_pins,
+            public_key_pins,
+            o_webpki_verifier,
+            crypto_provider,
+        }
+    }
+
+    fn is_pinned(&self, end_entity: &CertificateDer<'_>) -> Result<bool, rustls::Error> {
+        if self
+            .certificate_pins
+            .iter()
+            .any(|pin| pin.as_slice() == Sha256::digest(end_entity).as_slice())
+        {
+            return Ok(true);
+        }
+        if self.public_key_pins.is_empty() {
+            return Ok(false);
+        }
+        let certificate = EndEntityCert::try_from(end_entity)
+            .map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding))?;
+        let public_key_hash = Sha256::digest(certificate.subject_public_key_info());
+        Ok(self
+            .public_key_pins
+            .iter()
+            .any(|pin| pin.as_slice() == public_key_hash.as_slice()))
+    }
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Some(ref webpki_verifier) = self.o_webpki_verifier {
+            webpki_verifier.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                ocsp_response,
+                now,
+            )?;
+        }
+        if self.is_pinned(end_entity)? {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.crypto_provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.crypto_provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.crypto_provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_sha256_pin;
+
+    #[test]
+    fn test_parse_sha256_pin() {
+        let hash = parse_sha256_pin(
+            "EA:62:F9:2F:B1:49:EF:D8:8A:01:AA:21:35:85:86:D3:\
+             72:E7:72:FA:13:4A:CC:09:B6:46:05:62:3A:39:50:A3",
+        )
+        .unwrap();
+        assert_eq!(hash[0], 0xea);
+        assert_eq!(hash[31], 0xa3);
+        assert_eq!(
+            parse_sha256_pin("ea62f92fb149efd88a01aa21358586d372e772fa134acc09b64605623a3950a3")
+                .unwrap(),
+            hash
+        );
+        assert!(parse_sha256_pin("ea62f92f").is_err());
+        assert!(parse_sha256_pin(&"x".repeat(64)).is_err());
+    }
 }
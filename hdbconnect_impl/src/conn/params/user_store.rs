@@ -0,0 +1,413 @@
+// Reading the connection data of a key from the SAP HANA secure user store (`hdbuserstore`).
+//
+// The secure user store is an SSFS ("secure storage in file system"), which consists of
+// a data file SSFS_HDB.DAT and a key file SSFS_HDB.KEY.
+// The files are found in the folders that are given with the environment variables
+// RSEC_SSFS_DATAPATH and RSEC_SSFS_KEYPATH, or else in <home>/.hdb/<short hostname>.
+//
+// The key file contains the preamble "RSecSSFsKey" (11 bytes), a type byte,
+// the 24-byte key, a timestamp (8 bytes), and the user and host (24 bytes each)
+// that created the store.
+//
+// The data file is a sequence of records, each of which consists of a header of 176 bytes
+// and the data:
+//
+//   offset  size  content
+//        0    12  preamble "RSecSSFsData"
+//       12     4  length of the record, including the header (big endian)
+//       16     1  type
+//       17     7  filler
+//       24    64  name of the record, padded with blanks
+//       88     8  timestamp
+//       96    24  user, padded with blanks
+//      120    24  host, padded with blanks
+//      144     1  is_deleted
+//      145     1  is_stored_as_plaintext
+//      146     1  is_binary_data
+//      147     9  filler
+//      156    20  HMAC-SHA1 of the record (with this field zeroed), keyed with the SSFS key
+//      176     *  data
+//
+// Encrypted data are decrypted with Triple-DES (EDE3) in CBC mode with a zero IV;
+// the decrypted payload consists of the SHA-1 hash (20 bytes) and the length (4 bytes,
+// big endian) of the plain data, followed by the plain data and zero padding.
+//
+// A user store key KEY is represented by the records HDB/KEY/ENV (one or more
+// <host>:<port>, separated by ';' or ','), HDB/KEY/USER, HDB/KEY/PASSWORD,
+// and optionally HDB/KEY/DATABASE; if a record name occurs repeatedly, the last one counts.
+use super::into_connect_params_builder::parse_host_and_port;
+use crate::{usage_err, HdbResult};
+use cbc::cipher::{block_padding::NoPadding, BlockDecryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use secstr::SecUtf8;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+
+const DATA_FILE: &str = "SSFS_HDB.DAT";
+const KEY_FILE: &str = "SSFS_HDB.KEY";
+const DATA_PATH_VAR: &str = "RSEC_SSFS_DATAPATH";
+const KEY_PATH_VAR: &str = "RSEC_SSFS_KEYPATH";
+
+const KEY_PREAMBLE: &[u8] = b"RSecSSFsKey";
+const KEY_SIZE: usize = 24;
+const KEY_FILE_SIZE: usize = 92;
+
+const RECORD_PREAMBLE: &[u8] = b"RSecSSFsData";
+const RECORD_HEADER_SIZE: usize = 176;
+const NAME_RANGE: std::ops::Range<usize> = 24..88;
+const IS_DELETED: usize = 144;
+const IS_PLAINTEXT: usize = 145;
+const HMAC_RANGE: std::ops::Range<usize> = 156..176;
+const HASH_SIZE: usize = 20;
+
+type TdesCbcDecryptor = cbc::Decryptor<des::TdesEde3>;
+
+// The connection data of a user store key.
+#[derive(Debug)]
+pub(crate) struct UserStoreEntry {
+    pub hosts: Vec<(String, u16)>,
+    pub user: String,
+    pub password: SecUtf8,
+    pub database: Option<String>,
+}
+
+// The folders with the data file and the key file of a user store.
+#[derive(Debug)]
+pub(crate) struct UserStoreLocation {
+    data_dir: PathBuf,
+    key_dir: PathBuf,
+}
+impl UserStoreLocation {
+    // The standard location.
+    pub(crate) fn from_env() -> HdbResult<Self> {
+        Ok(Self {
+            data_dir: ssfs_dir(DATA_PATH_VAR)?,
+            key_dir: ssfs_dir(KEY_PATH_VAR)?,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn in_dir(dir: &Path) -> Self {
+        Self {
+            data_dir: dir.to_path_buf(),
+            key_dir: dir.to_path_buf(),
+        }
+    }
+}
+
+// Reads the given key from the user store at the given location.
+pub(crate) fn read_user_store_key(
+    key: &str,
+    location: &UserStoreLocation,
+) -> HdbResult<UserStoreEntry> {
+    read_user_store_key_from(
+        &location.data_dir.join(DATA_FILE),
+        &location.key_dir.join(KEY_FILE),
+        key,
+    )
+}
+
+pub(crate) fn read_user_store_key_from(
+    data_file: &Path,
+    key_file: &Path,
+    key: &str,
+) -> HdbResult<UserStoreEntry> {
+    let ssfs_key = parse_key_file(&read_file(key_file)?)?;
+    let records = parse_data_file(&read_file(data_file)?, &ssfs_key)?;
+
+    let key = key.to_uppercase();
+    let value = |field: &str| {
+        let name = format!("HDB/{key}/{field}");
+        records
+            .iter()
+            .rev()
+            .find(|(record_name, _)| *record_name == name)
+            .map(|(_, data)| {
+                String::from_utf8(data.clone())
+                    .map_err(|_| usage_err!("user store: record {name} is not valid UTF-8"))
+            })
+            .transpose()
+    };
+
+    let env = value("ENV")?
+        .ok_or_else(|| usage_err!("user store: key {key} not found in {}", data_file.display()))?;
+    let hosts = env
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|host_and_port| !host_and_port.is_empty())
+        .map(parse_host_and_port)
+        .collect::<HdbResult<Vec<(String, u16)>>>()?;
+    if hosts.is_empty() {
+        return Err(usage_err!("user store: key {key} has no host"));
+    }
+    Ok(UserStoreEntry {
+        hosts,
+        user: value("USER")?.unwrap_or_default(),
+        password: SecUtf8::from(value("PASSWORD")?.unwrap_or_default()),
+        database: value("DATABASE")?.filter(|database| !database.is_empty()),
+    })
+}
+
+fn ssfs_dir(env_var: &str) -> HdbResult<PathBuf> {
+    if let Ok(dir) = std::env::var(env_var) {
+        return Ok(PathBuf::from(dir));
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map_err(|_| usage_err!("user store: home directory not found, set {env_var}"))?;
+    Ok(Path::new(&home).join(".hdb").join(short_hostname()))
+}
+
+fn short_hostname() -> String {
+    let hostname = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .unwrap_or_default();
+    hostname
+        .trim()
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn read_file(path: &Path) -> HdbResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| usage_err!("user store: cannot read {}: {e}", path.display()))
+}
+
+fn parse_key_file(bytes: &[u8]) -> HdbResult<[u8; KEY_SIZE]> {
+    if bytes.len() < KEY_FILE_SIZE || !bytes.starts_with(KEY_PREAMBLE) {
+        return Err(usage_err!("user store: key file is corrupt"));
+    }
+    let mut key = [0_u8; KEY_SIZE];
+    key.copy_from_slice(&bytes[KEY_PREAMBLE.len() + 1..KEY_PREAMBLE.len() + 1 + KEY_SIZE]);
+    Ok(key)
+}
+
+// The names and the plain data of the records that are not deleted.
+fn parse_data_file(bytes: &[u8], ssfs_key: &[u8; KEY_SIZE]) -> HdbResult<Vec<(String, Vec<u8>)>> {
+    let mut records = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        if rest.len() < RECORD_HEADER_SIZE || !rest.starts_with(RECORD_PREAMBLE) {
+            return Err(usage_err!("user store: data file is corrupt"));
+        }
+        let length = u32::from_be_bytes([rest[12], rest[13], rest[14], rest[15]]) as usize;
+        if length < RECORD_HEADER_SIZE || length > rest.len() {
+            return Err(usage_err!("user store: data file is corrupt"));
+        }
+        let (record, tail) = rest.split_at(length);
+        rest = tail;
+
+        if record_hmac(record, ssfs_key)? != record[HMAC_RANGE] {
+            return Err(usage_err!(
+                "user store: data file was not written with the given key file"
+            ));
+        }
+        if record[IS_DELETED] != 0 {
+            continue;
+        }
+        let name = String::from_utf8_lossy(&record[NAME_RANGE])
+            .trim_end_matches([' ', '\0'])
+            .to_string();
+        let data = &record[RECORD_HEADER_SIZE..];
+        let plain_data = if record[IS_PLAINTEXT] == 0 {
+            decrypt(data, ssfs_key)?
+        } else {
+            data.to_vec()
+        };
+        records.push((name, plain_data));
+    }
+    Ok(records)
+}
+
+fn record_hmac(record: &[u8], ssfs_key: &[u8; KEY_SIZE]) -> HdbResult<Vec<u8>> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(ssfs_key)
+        .map_err(|_| usage_err!("user store: invalid key"))?;
+    mac.update(&record[..HMAC_RANGE.start]);
+    mac.update(&[0_u8; HASH_SIZE]);
+    mac.update(&record[HMAC_RANGE.end..]);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn decrypt(data: &[u8], ssfs_key: &[u8; KEY_SIZE]) -> HdbResult<Vec<u8>> {
+    let corrupt = || usage_err!("user store: encrypted record is corrupt");
+    let mut buf = data.to_vec();
+    let payload = TdesCbcDecryptor::new(ssfs_key.into(), &[0_u8; 8].into())
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .map_err(|_| corrupt())?;
+    if payload.len() < HASH_SIZE + 4 {
+        return Err(corrupt());
+    }
+    let (hash, rest) = payload.split_at(HASH_SIZE);
+    let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+    let plain_data = rest.get(4..4 + length).ok_or_else(corrupt)?;
+    if Sha1::digest(plain_data).as_slice() != hash {
+        return Err(usage_err!(
+            "user store: encrypted record cannot be decrypted with the given key file"
+        ));
+    }
+    Ok(plain_data.to_vec())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use cbc::cipher::BlockEncryptMut;
+
+    type TdesCbcEncryptor = cbc::Encryptor<des::TdesEde3>;
+
+    const TEST_KEY: [u8; KEY_SIZE] = *b"0123456789abcdefGHIJKLMN";
+
+    // Writes a user store with the given records (name, value, encrypted) into the folder.
+    pub(crate) fn write_user_store(dir: &Path, records: &[(&str, &str, bool)]) {
+        std::fs::create_dir_all(dir).unwrap();
+        let mut key_file = KEY_PREAMBLE.to_vec();
+        key_file.push(1);
+        key_file.extend_from_slice(&TEST_KEY);
+        key_file.resize(KEY_FILE_SIZE, b' ');
+        std::fs::write(dir.join(KEY_FILE), key_file).unwrap();
+
+        let mut data_file = Vec::new();
+        for (name, value, encrypted) in records {
+            data_file.extend(record(name, value.as_bytes(), *encrypted, false));
+        }
+        std::fs::write(dir.join(DATA_FILE), data_file).unwrap();
+    }
+
+    fn record(name: &str, value: &[u8], encrypted: bool, deleted: bool) -> Vec<u8> {
+        let data = if encrypted {
+            let mut payload = Sha1::digest(value).to_vec();
+            payload.extend_from_slice(&u32::try_from(value.len()).unwrap().to_be_bytes());
+            payload.extend_from_slice(value);
+            payload.resize(payload.len().div_ceil(8) * 8, 0);
+            let length = payload.len();
+            TdesCbcEncryptor::new(&TEST_KEY.into(), &[0_u8; 8].into())
+                .encrypt_padded_mut::<NoPadding>(&mut payload, length)
+                .unwrap()
+                .to_vec()
+        } else {
+            value.to_vec()
+        };
+        let mut record = RECORD_PREAMBLE.to_vec();
+        record.extend_from_slice(
+            &u32::try_from(RECORD_HEADER_SIZE + data.len())
+                .unwrap()
+                .to_be_bytes(),
+        );
+        record.resize(NAME_RANGE.start, 0);
+        record.extend_from_slice(format!("{name:<64}").as_bytes());
+        record.resize(IS_DELETED, b' ');
+        record.extend_from_slice(&[u8::from(deleted), u8::from(!encrypted), 0]);
+        record.resize(RECORD_HEADER_SIZE, 0);
+        record.extend_from_slice(&data);
+        let hmac = record_hmac(&record, &TEST_KEY).unwrap();
+        record[HMAC_RANGE].copy_from_slice(&hmac);
+        record
+    }
+
+    #[test]
+    fn test_user_store() {
+        let dir =
+            std::env::temp_dir().join(format!("hdbconnect_user_store_{}", std::process::id()));
+        write_user_store(
+            &dir,
+            &[
+                ("HDB/MYKEY/ENV", "the_host:30015;other_host:30015", false),
+                ("HDB/MYKEY/USER", "SCOTT", true),
+                ("HDB/MYKEY/PASSWORD", "first secret", true),
+                ("HDB/MYKEY/PASSWORD", "Tiger Secret 1234567", true),
+                ("HDB/MYKEY/DATABASE", "HXE", true),
+                ("HDB/OTHER/ENV", "some_host:30041", true),
+            ],
+        );
+        let data_file = dir.join(DATA_FILE);
+        let key_file = dir.join(KEY_FILE);
+
+        let entry = read_user_store_key_from(&data_file, &key_file, "mykey").unwrap();
+        assert_eq!(
+            entry.hosts,
+            vec![
+                ("the_host".to_string(), 30015),
+                ("other_host".to_string(), 30015)
+            ]
+        );
+        assert_eq!(entry.user, "SCOTT");
+        assert_eq!(entry.password.unsecure(), "Tiger Secret 1234567");
+        assert_eq!(entry.database.as_deref(), Some("HXE"));
+
+        let entry = read_user_store_key_from(&data_file, &key_file, "OTHER").unwrap();
+        assert_eq!(entry.hosts, vec![("some_host".to_string(), 30041)]);
+        assert_eq!(entry.user, "");
+        assert_eq!(entry.database, None);
+
+        assert!(read_user_store_key_from(&data_file, &key_file, "UNKNOWN").is_err());
+
+        // deleted records are ignored
+        let mut data = std::fs::read(&data_file).unwrap();
+        data.extend(record("HDB/MYKEY/USER", b"DELETED", true, true));
+        std::fs::write(&data_file, &data).unwrap();
+        let entry = read_user_store_key_from(&data_file, &key_file, "MYKEY").unwrap();
+        assert_eq!(entry.user, "SCOTT");
+
+        // manipulated records are detected
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        std::fs::write(&data_file, &data).unwrap();
+        assert!(read_user_store_key_from(&data_file, &key_file, "MYKEY").is_err());
+
+        // a wrong key file is detected
+        let mut key = std::fs::read(&key_file).unwrap();
+        key[12] ^= 0xFF;
+        std::fs::write(&key_file, &key).unwrap();
+        write_user_store(&dir, &[("HDB/MYKEY/ENV", "the_host:30015", true)]);
+        std::fs::write(&key_file, &key).unwrap();
+        assert!(read_user_store_key_from(&data_file, &key_file, "MYKEY").is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_user_store_url() {
+        use crate::conn::params::into_connect_params_builder::url_into_connect_params_builder;
+        use crate::ConnectParamsBuilder;
+
+        let dir =
+            std::env::temp_dir().join(format!("hdbconnect_user_store_url_{}", std::process::id()));
+        write_user_store(
+            &dir,
+            &[
+                ("HDB/MYKEY/ENV", "the_host:30015", true),
+                ("HDB/MYKEY/USER", "SCOTT", true),
+                ("HDB/MYKEY/PASSWORD", "Tiger", true),
+            ],
+        );
+        let location = UserStoreLocation::in_dir(&dir);
+        let into_builder = |url: &str| {
+            url_into_connect_params_builder(&url::Url::parse(url).unwrap(), &|| {
+                Ok(UserStoreLocation::in_dir(&dir))
+            })
+        };
+
+        let builder = into_builder("hdbsql://@userstore/MYKEY?client_locale=en_US").unwrap();
+        assert_eq!(builder.get_hostname(), Some("the_host"));
+        assert_eq!(builder.get_port(), Some(30015));
+        assert_eq!(builder.get_dbuser(), Some("SCOTT"));
+        assert_eq!(builder.get_password().unwrap().unsecure(), "Tiger");
+        assert_eq!(builder.get_clientlocale(), Some("en_US"));
+
+        let params = ConnectParamsBuilder::from_user_store_key_at("MYKEY", &location)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(params.dbuser(), "SCOTT");
+
+        assert!(into_builder("hdbsql://@userstore/").is_err());
+        assert!(into_builder("hdbsql://@userstore/NOKEY").is_err());
+        // a real host that happens to be called userstore
+        let builder = into_builder("hdbsql://SCOTT@userstore:30015").unwrap();
+        assert_eq!(builder.get_hostname(), Some("userstore"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
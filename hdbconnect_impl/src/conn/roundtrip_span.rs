@@ -0,0 +1,221 @@
+// Tracing spans for the roundtrips of a connection (feature `tracing`).
+//
+// The field names follow the OpenTelemetry semantic conventions for database client spans
+// where these define a matching attribute; the driver-specific fields use the prefix `db.hana.`.
+// Numeric fields are recorded as i64, because exporters map u64 values to strings.
+//
+// The SQL text itself is not recorded, since it can contain literal values;
+// instead, the spans carry a hash of it, which allows correlating the roundtrips
+// of a statement, including the fetch roundtrips of its result sets.
+use crate::{
+    conn::{connection_statistics::MessageSize, ConnectParams},
+    protocol::{MessageType, Part, Reply, Request},
+    HdbError, HdbResult,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+    time::Duration,
+};
+use tracing::{field::Empty, Span};
+
+// Result sets are closed implicitly by the server after their last row was fetched,
+// so only the most recently opened ones are remembered.
+const MAX_RESULT_SETS: usize = 32;
+
+// Remembers the statement hashes of prepared statements and of open result sets.
+#[derive(Debug, Default)]
+pub(crate) struct RoundtripSpans {
+    statements: HashMap<u64, String>,
+    result_sets: VecDeque<(u64, String)>,
+}
+impl RoundtripSpans {
+    pub(crate) fn open(
+        &self,
+        request: &Request,
+        o_connection_id: Option<u32>,
+        connect_params: &ConnectParams,
+    ) -> Span {
+        let message_type = request.message_type();
+        let o_port = connect_params
+            .addr()
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<i64>().ok());
+        let o_hash = self.statement_hash(request);
+        tracing::info_span!(
+            target: "hdbconnect",
+            "roundtrip",
+            otel.name = ?message_type,
+            otel.kind = "client",
+            otel.status_code = Empty,
+            db.system.name = "sap.hana",
+            db.namespace = connect_params.dbname(),
+            db.operation.name = ?message_type,
+            db.response.status_code = Empty,
+            error.type = Empty,
+            server.address = connect_params.host(),
+            server.port = o_port,
+            db.hana.connection_id = o_connection_id.map(i64::from),
+            db.hana.statement_hash = o_hash.as_deref(),
+            db.hana.sub_roundtrip = sub_roundtrip(message_type),
+            db.hana.request.size = Empty,
+            db.hana.request.compressed_size = Empty,
+            db.hana.reply.size = Empty,
+            db.hana.reply.compressed_size = Empty,
+            db.hana.server.proc_time_us = Empty,
+        )
+    }
+
+    pub(crate) fn close(
+        &mut self,
+        span: &Span,
+        request: &Request,
+        result: &HdbResult<Reply>,
+        (o_request_size, o_reply_size): (Option<MessageSize>, Option<MessageSize>),
+    ) {
+        if let Some(size) = o_request_size {
+            span.record("db.hana.request.size", as_i64(size.parts_size));
+            if let Some(compressed_size) = size.o_compressed_size {
+                span.record("db.hana.request.compressed_size", as_i64(compressed_size));
+            }
+        }
+        if let Some(size) = o_reply_size {
+            span.record("db.hana.reply.size", as_i64(size.parts_size));
+            if let Some(compressed_size) = size.o_compressed_size {
+                span.record("db.hana.reply.compressed_size", as_i64(compressed_size));
+            }
+        }
+
+        match result {
+            Ok(reply) => {
+                if let Some(proc_time) = reply.o_server_proc_time {
+                    span.record("db.hana.server.proc_time_us", as_micros(proc_time));
+                }
+                if let (Some(result_set_id), Some(hash)) =
+                    (reply.o_result_set_id, self.statement_hash(request))
+                {
+                    if self.result_sets.len() == MAX_RESULT_SETS {
+                        self.result_sets.pop_front();
+                    }
+                    self.result_sets.push_back((result_set_id, hash));
+                }
+                if let MessageType::Prepare = request.message_type() {
+                    let o_statement_id =
+                        reply.parts.ref_inner().iter().find_map(|part| match part {
+                            Part::StatementId(statement_id) => Some(*statement_id),
+                            _ => None,
+                        });
+                    if let (Some(statement_id), Some(hash)) =
+                        (o_statement_id, self.statement_hash(request))
+                    {
+                        self.statements.insert(statement_id, hash);
+                    }
+                }
+            }
+            Err(e) => {
+                span.record("otel.status_code", "ERROR");
+                if let Some(server_error) = e.server_error() {
+                    let code = server_error.code().to_string();
+                    span.record("db.response.status_code", code.as_str());
+                    span.record("error.type", code.as_str());
+                } else {
+                    span.record("error.type", error_type(e));
+                }
+            }
+        }
+
+        match request.message_type() {
+            MessageType::DropStatementId => {
+                if let Some(statement_id) = request.statement_id() {
+                    self.statements.remove(&statement_id);
+                }
+            }
+            MessageType::CloseResultSet => {
+                if let Some(result_set_id) = request.result_set_id() {
+                    self.result_sets.retain(|(id, _)| *id != result_set_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn statement_hash(&self, request: &Request) -> Option<String> {
+        match request.message_type() {
+            MessageType::ExecuteDirect | MessageType::Prepare => request.command().map(hash),
+            MessageType::Execute | MessageType::DropStatementId => request
+                .statement_id()
+                .and_then(|statement_id| self.statements.get(&statement_id).cloned()),
+            MessageType::FetchNext
+            | MessageType::FetchAbsolute
+            | MessageType::FetchRelative
+            | MessageType::FetchFirst
+            | MessageType::FetchLast
+            | MessageType::CloseResultSet => request.result_set_id().and_then(|result_set_id| {
+                self.result_sets
+                    .iter()
+                    .find(|(id, _)| *id == result_set_id)
+                    .map(|(_, hash)| hash.clone())
+            }),
+            _ => None,
+        }
+    }
+}
+
+// The first 8 bytes of the SHA-256 hash of the SQL text, in hex.
+fn hash(sql: &str) -> String {
+    Sha256::digest(sql.as_bytes())[..8]
+        .iter()
+        .fold(String::with_capacity(16), |mut s, b| {
+            write!(s, "{b:02x}").unwrap(/*OK*/);
+            s
+        })
+}
+
+fn sub_roundtrip(message_type: MessageType) -> Option<&'static str> {
+    if message_type.is_fetch() {
+        Some("fetch")
+    } else if message_type.is_lob() {
+        Some("lob")
+    } else {
+        None
+    }
+}
+
+// The name of the error variant, which is a low-cardinality value as required for `error.type`.
+fn error_type(e: &HdbError) -> String {
+    let debug = format!("{e:?}");
+    debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn as_i64(size: usize) -> i64 {
+    i64::try_from(size).unwrap_or(i64::MAX)
+}
+
+fn as_micros(duration: Duration) -> i64 {
+    i64::try_from(duration.as_micros()).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{error_type, hash};
+    use crate::HdbError;
+
+    #[test]
+    fn test_hash_and_error_type() {
+        assert_eq!(hash("select 1 from dummy"), hash("select 1 from dummy"));
+        assert_ne!(hash("select 1 from dummy"), hash("select 2 from dummy"));
+        assert_eq!(hash("").len(), 16);
+        assert_eq!(hash(""), "e3b0c44298fc1c14");
+
+        assert_eq!(
+            error_type(&HdbError::ConnectionBroken { source: None }),
+            "ConnectionBroken"
+        );
+        assert_eq!(error_type(&HdbError::Cesu8), "Cesu8");
+    }
+}
@@ -0,0 +1,627 @@
+use crate::{
+    conn::connection_statistics::MessageSize,
+    protocol::{MessageType, Reply},
+    HdbResult,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+// The upper bounds of the buckets of the latency histograms, in microseconds;
+// a last bucket takes the longer latencies.
+const BUCKET_BOUNDS_US: [u64; 14] = [
+    500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000,
+    2_500_000, 5_000_000, 10_000_000,
+];
+const NO_OF_BUCKETS: usize = BUCKET_BOUNDS_US.len() + 1;
+
+/// Statistics about the roundtrips to the database, by message type.
+///
+/// Besides the number and the latency of the roundtrips, they comprise the
+/// server processing time, the transferred bytes, the number of result sets and of the
+/// fetch roundtrips for them, the LOB roundtrips, and the errors reported by the server.
+///
+/// The statistics of a single connection are part of its
+/// [`ConnectionStatistics`](crate::ConnectionStatistics); the statistics of several
+/// connections, e.g. of those of a connection pool, can be collected with
+/// [`SharedStatistics`].
+#[derive(Clone, Debug, Default)]
+pub struct RoundtripStatistics {
+    message_types: BTreeMap<&'static str, MessageTypeStatistics>,
+    bytes_sent: u64,
+    bytes_received: u64,
+    result_set_count: u64,
+    fetch_roundtrip_count: u64,
+    lob_roundtrip_count: u64,
+    server_errors: BTreeMap<i32, u64>,
+}
+
+impl RoundtripStatistics {
+    pub(crate) fn add(&mut self, roundtrip: &Roundtrip) {
+        let message_type = roundtrip.message_type;
+        let stats = self.message_types.entry(message_type.name()).or_default();
+        stats.count += 1;
+        stats.latency.add(roundtrip.latency);
+        if let Some(server_processing_time) = roundtrip.o_server_processing_time {
+            stats.server_processing_time += server_processing_time;
+        }
+        if roundtrip.failed {
+            stats.error_count += 1;
+        }
+        if let Some(code) = roundtrip.o_server_error_code {
+            *self.server_errors.entry(code).or_default() += 1;
+        }
+        self.bytes_sent += roundtrip.bytes_sent;
+        self.bytes_received += roundtrip.bytes_received;
+        self.result_set_count += roundtrip.result_set_count;
+        if message_type.is_fetch() {
+            self.fetch_roundtrip_count += 1;
+        } else if message_type.is_lob() {
+            self.lob_roundtrip_count += 1;
+        }
+    }
+
+    /// Adds the statistics of other roundtrips.
+    pub fn merge(&mut self, other: &Self) {
+        for (name, other_stats) in &other.message_types {
+            self.message_types
+                .entry(name)
+                .or_default()
+                .merge(other_stats);
+        }
+        self.bytes_sent += other.bytes_sent;
+        self.bytes_received += other.bytes_received;
+        self.result_set_count += other.result_set_count;
+        self.fetch_roundtrip_count += other.fetch_roundtrip_count;
+        self.lob_roundtrip_count += other.lob_roundtrip_count;
+        for (code, count) in &other.server_errors {
+            *self.server_errors.entry(*code).or_default() += count;
+        }
+    }
+
+    /// Returns the statistics of the message types that were used, by their names
+    /// (e.g. `ExecuteDirect`, `Execute`, `FetchNext`, `ReadLob`).
+    pub fn message_types(&self) -> impl Iterator<Item = (&str, &MessageTypeStatistics)> {
+        self.message_types
+            .iter()
+            .map(|(name, stats)| (*name, stats))
+    }
+
+    /// Returns the statistics of the message type with the given name, if it was used.
+    #[must_use]
+    pub fn message_type(&self, name: &str) -> Option<&MessageTypeStatistics> {
+        self.message_types.get(name)
+    }
+
+    /// Returns the number of all roundtrips.
+    #[must_use]
+    pub fn roundtrip_count(&self) -> u64 {
+        self.message_types.values().map(|stats| stats.count).sum()
+    }
+
+    /// Returns the number of bytes that were sent to the server, including the message headers.
+    #[must_use]
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Returns the number of bytes that were received from the server,
+    /// including the message headers.
+    #[must_use]
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Returns the number of result sets that were returned by the server.
+    #[must_use]
+    pub fn result_set_count(&self) -> u64 {
+        self.result_set_count
+    }
+
+    /// Returns the number of roundtrips that fetched rows of result sets.
+    #[must_use]
+    pub fn fetch_roundtrip_count(&self) -> u64 {
+        self.fetch_roundtrip_count
+    }
+
+    /// Returns the average number of fetch roundtrips per result set.
+    ///
+    /// A high value indicates that the fetch size is too small for the result sets.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fetch_roundtrips_per_result_set(&self) -> f64 {
+        if self.result_set_count == 0 {
+            0.0
+        } else {
+            self.fetch_roundtrip_count as f64 / self.result_set_count as f64
+        }
+    }
+
+    /// Returns the number of roundtrips that read or wrote LOB data.
+    #[must_use]
+    pub fn lob_roundtrip_count(&self) -> u64 {
+        self.lob_roundtrip_count
+    }
+
+    /// Returns the number of errors that were reported by the server, by error code.
+    pub fn server_errors(&self) -> impl Iterator<Item = (i32, u64)> + '_ {
+        self.server_errors
+            .iter()
+            .map(|(code, count)| (*code, *count))
+    }
+
+    /// Returns the statistics in the Prometheus text exposition format.
+    ///
+    /// The given labels are added to all samples, e.g. for distinguishing
+    /// the statistics of different connection pools.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn prometheus_text(&self, labels: &[(&str, &str)]) -> String {
+        let mut w = PrometheusWriter {
+            text: String::new(),
+            labels,
+        };
+        self.write_message_type_metrics(&mut w).unwrap(/*OK*/);
+        self.write_other_metrics(&mut w).unwrap(/*OK*/);
+        w.text
+    }
+
+    fn write_message_type_metrics(&self, w: &mut PrometheusWriter) -> std::fmt::Result {
+        w.header(
+            "hdbconnect_roundtrips_total",
+            "counter",
+            "Number of roundtrips, by message type.",
+        )?;
+        for (name, stats) in &self.message_types {
+            w.sample(
+                "hdbconnect_roundtrips_total",
+                &[("message_type", name)],
+                stats.count,
+            )?;
+        }
+        w.header(
+            "hdbconnect_roundtrip_errors_total",
+            "counter",
+            "Number of failed roundtrips, by message type.",
+        )?;
+        for (name, stats) in &self.message_types {
+            w.sample(
+                "hdbconnect_roundtrip_errors_total",
+                &[("message_type", name)],
+                stats.error_count,
+            )?;
+        }
+        w.header(
+            "hdbconnect_roundtrip_duration_seconds",
+            "histogram",
+            "Latency of roundtrips, by message type.",
+        )?;
+        for (name, stats) in &self.message_types {
+            for (o_bound, count) in stats.latency.buckets() {
+                let le =
+                    o_bound.map_or_else(|| "+Inf".to_string(), |b| b.as_secs_f64().to_string());
+                w.sample(
+                    "hdbconnect_roundtrip_duration_seconds_bucket",
+                    &[("message_type", name), ("le", &le)],
+                    count,
+                )?;
+            }
+            w.sample(
+                "hdbconnect_roundtrip_duration_seconds_sum",
+                &[("message_type", name)],
+                stats.latency.sum().as_secs_f64(),
+            )?;
+            w.sample(
+                "hdbconnect_roundtrip_duration_seconds_count",
+                &[("message_type", name)],
+                stats.latency.count(),
+            )?;
+        }
+        w.header(
+            "hdbconnect_server_processing_seconds_total",
+            "counter",
+            "Processing time reported by the server, by message type.",
+        )?;
+        for (name, stats) in &self.message_types {
+            w.sample(
+                "hdbconnect_server_processing_seconds_total",
+                &[("message_type", name)],
+                stats.server_processing_time.as_secs_f64(),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_other_metrics(&self, w: &mut PrometheusWriter) -> std::fmt::Result {
+        for (name, help, value) in [
+            (
+                "hdbconnect_sent_bytes_total",
+                "Number of bytes sent to the server.",
+                self.bytes_sent,
+            ),
+            (
+                "hdbconnect_received_bytes_total",
+                "Number of bytes received from the server.",
+                self.bytes_received,
+            ),
+            (
+                "hdbconnect_result_sets_total",
+                "Number of result sets returned by the server.",
+                self.result_set_count,
+            ),
+            (
+                "hdbconnect_fetch_roundtrips_total",
+                "Number of roundtrips that fetched rows of result sets.",
+                self.fetch_roundtrip_count,
+            ),
+            (
+                "hdbconnect_lob_roundtrips_total",
+                "Number of roundtrips that read or wrote LOB data.",
+                self.lob_roundtrip_count,
+            ),
+        ] {
+            w.header(name, "counter", help)?;
+            w.sample(name, &[], value)?;
+        }
+        w.header(
+            "hdbconnect_server_errors_total",
+            "counter",
+            "Number of errors reported by the server, by error code.",
+        )?;
+        for (code, count) in &self.server_errors {
+            w.sample(
+                "hdbconnect_server_errors_total",
+                &[("code", &code.to_string())],
+                count,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Writes metrics in the Prometheus text exposition format.
+struct PrometheusWriter<'a> {
+    text: String,
+    labels: &'a [(&'a str, &'a str)],
+}
+impl PrometheusWriter<'_> {
+    fn header(&mut self, name: &str, kind: &str, help: &str) -> std::fmt::Result {
+        writeln!(self.text, "# HELP {name} {help}")?;
+        writeln!(self.text, "# TYPE {name} {kind}")
+    }
+
+    fn sample<V: std::fmt::Display>(
+        &mut self,
+        name: &str,
+        labels: &[(&str, &str)],
+        value: V,
+    ) -> std::fmt::Result {
+        write!(self.text, "{name}")?;
+        for (i, (label, label_value)) in self.labels.iter().chain(labels).enumerate() {
+            let separator = if i == 0 { '{' } else { ',' };
+            write!(
+                self.text,
+                "{separator}{label}=\"{}\"",
+                escape_label_value(label_value)
+            )?;
+        }
+        if !self.labels.is_empty() || !labels.is_empty() {
+            write!(self.text, "}}")?;
+        }
+        writeln!(self.text, " {value}")
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Statistics about the roundtrips of a single message type.
+#[derive(Clone, Debug, Default)]
+pub struct MessageTypeStatistics {
+    count: u64,
+    error_count: u64,
+    latency: LatencyHistogram,
+    server_processing_time: Duration,
+}
+impl MessageTypeStatistics {
+    fn merge(&mut self, other: &Self) {
+        self.count += other.count;
+        self.error_count += other.error_count;
+        self.latency.merge(&other.latency);
+        self.server_processing_time += other.server_processing_time;
+    }
+
+    /// Returns the number of roundtrips.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the number of roundtrips that failed.
+    #[must_use]
+    pub fn error_count(&self) -> u64 {
+        self.error_count
+    }
+
+    /// Returns the histogram of the latencies, from start of serializing a request
+    /// until the reply was parsed.
+    #[must_use]
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.latency
+    }
+
+    /// Returns the accumulated processing time that the server reported for the roundtrips.
+    ///
+    /// The difference to the accumulated latency is spent in the network and in the client.
+    #[must_use]
+    pub fn server_processing_time(&self) -> Duration {
+        self.server_processing_time
+    }
+}
+
+/// A histogram of latencies, with fixed buckets from 0.5 milliseconds to 10 seconds.
+#[derive(Clone, Debug, Default)]
+pub struct LatencyHistogram {
+    bucket_counts: [u64; NO_OF_BUCKETS],
+    sum: Duration,
+}
+impl LatencyHistogram {
+    fn add(&mut self, latency: Duration) {
+        let index = BUCKET_BOUNDS_US
+            .iter()
+            .position(|bound| latency <= Duration::from_micros(*bound))
+            .unwrap_or(NO_OF_BUCKETS - 1);
+        self.bucket_counts[index] += 1;
+        self.sum += latency;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (count, other_count) in self.bucket_counts.iter_mut().zip(other.bucket_counts) {
+            *count += other_count;
+        }
+        self.sum += other.sum;
+    }
+
+    /// Returns the number of recorded latencies.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.bucket_counts.iter().sum()
+    }
+
+    /// Returns the sum of the recorded latencies.
+    #[must_use]
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// Returns the buckets, as pairs of upper bound and the number of latencies
+    /// that do not exceed the bound (i.e., cumulative counts, as in Prometheus);
+    /// the last bucket has no upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        BUCKET_BOUNDS_US
+            .iter()
+            .map(|bound| Some(Duration::from_micros(*bound)))
+            .chain(std::iter::once(None))
+            .zip(self.bucket_counts.iter().scan(0, |cumulated, count| {
+                *cumulated += count;
+                Some(*cumulated)
+            }))
+    }
+}
+
+/// Roundtrip statistics that are shared by several connections.
+///
+/// All connections that are created with a [`ConnectionConfiguration`](crate::ConnectionConfiguration)
+/// that contains the same `SharedStatistics` (or clones of it) add their roundtrips to it.
+/// This allows e.g. aggregating the statistics of all connections of a connection pool.
+#[derive(Clone, Debug, Default)]
+pub struct SharedStatistics(Arc<Mutex<RoundtripStatistics>>);
+impl SharedStatistics {
+    /// Creates an empty instance.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a snapshot of the collected statistics.
+    #[must_use]
+    pub fn snapshot(&self) -> RoundtripStatistics {
+        self.lock().clone()
+    }
+
+    /// Discards the collected statistics.
+    pub fn reset(&self) {
+        *self.lock() = RoundtripStatistics::default();
+    }
+
+    /// Returns the collected statistics in the Prometheus text exposition format,
+    /// see [`RoundtripStatistics::prometheus_text`].
+    #[must_use]
+    pub fn prometheus_text(&self, labels: &[(&str, &str)]) -> String {
+        self.lock().prometheus_text(labels)
+    }
+
+    pub(crate) fn add(&self, roundtrip: &Roundtrip) {
+        self.lock().add(roundtrip);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RoundtripStatistics> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// What is recorded about a single roundtrip.
+#[derive(Debug)]
+pub(crate) struct Roundtrip {
+    message_type: MessageType,
+    latency: Duration,
+    o_server_processing_time: Option<Duration>,
+    result_set_count: u64,
+    failed: bool,
+    o_server_error_code: Option<i32>,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+impl Roundtrip {
+    pub(crate) fn new(
+        message_type: MessageType,
+        latency: Duration,
+        result: &HdbResult<Reply>,
+        (o_request_size, o_reply_size): (Option<MessageSize>, Option<MessageSize>),
+    ) -> Self {
+        let wire_size = |o_size: Option<MessageSize>| {
+            o_size.map_or(0, |size| {
+                u64::try_from(size.wire_size()).unwrap_or(u64::MAX)
+            })
+        };
+        let (o_server_processing_time, result_set_count) = match result {
+            Ok(reply) if !message_type.is_fetch() => {
+                (reply.o_server_proc_time, reply.result_set_count)
+            }
+            Ok(reply) => (reply.o_server_proc_time, 0),
+            Err(_) => (None, 0),
+        };
+        Self {
+            message_type,
+            latency,
+            o_server_processing_time,
+            result_set_count,
+            failed: result.is_err(),
+            o_server_error_code: result
+                .as_ref()
+                .err()
+                .and_then(|e| e.server_error())
+                .map(crate::ServerError::code),
+            bytes_sent: wire_size(o_request_size),
+            bytes_received: wire_size(o_reply_size),
+        }
+    }
+
+    // Reports the roundtrip to the `metrics` facade, with the metric names of
+    // `RoundtripStatistics::prometheus_text`; since counters of the facade are integral,
+    // the server processing time is reported as a histogram.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn report_metrics(&self) {
+        let message_type = self.message_type.name();
+        metrics::counter!("hdbconnect_roundtrips_total", "message_type" => message_type)
+            .increment(1);
+        metrics::histogram!(
+            "hdbconnect_roundtrip_duration_seconds",
+            "message_type" => message_type
+        )
+        .record(self.latency.as_secs_f64());
+        if let Some(server_processing_time) = self.o_server_processing_time {
+            metrics::histogram!(
+                "hdbconnect_server_processing_duration_seconds",
+                "message_type" => message_type
+            )
+            .record(server_processing_time.as_secs_f64());
+        }
+        if self.failed {
+            metrics::counter!("hdbconnect_roundtrip_errors_total", "message_type" => message_type)
+                .increment(1);
+        }
+        if let Some(code) = self.o_server_error_code {
+            metrics::counter!("hdbconnect_server_errors_total", "code" => code.to_string())
+                .increment(1);
+        }
+        metrics::counter!("hdbconnect_sent_bytes_total").increment(self.bytes_sent);
+        metrics::counter!("hdbconnect_received_bytes_total").increment(self.bytes_received);
+        metrics::counter!("hdbconnect_result_sets_total").increment(self.result_set_count);
+        if self.message_type.is_fetch() {
+            metrics::counter!("hdbconnect_fetch_roundtrips_total").increment(1);
+        } else if self.message_type.is_lob() {
+            metrics::counter!("hdbconnect_lob_roundtrips_total").increment(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LatencyHistogram, Roundtrip, RoundtripStatistics};
+    use crate::{protocol::MessageType, HdbError};
+    use std::time::Duration;
+
+    fn roundtrip(message_type: MessageType, latency_ms: u64, failed: bool) -> Roundtrip {
+        Roundtrip {
+            message_type,
+            latency: Duration::from_millis(latency_ms),
+            o_server_processing_time: Some(Duration::from_micros(300)),
+            result_set_count: u64::from(matches!(message_type, MessageType::ExecuteDirect)),
+            failed,
+            o_server_error_code: failed.then_some(257),
+            bytes_sent: 100,
+            bytes_received: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_statistics() {
+        let mut histogram = LatencyHistogram::default();
+        for latency_us in [100, 500, 501, 20_000, 60_000_000] {
+            histogram.add(Duration::from_micros(latency_us));
+        }
+        let buckets: Vec<_> = histogram.buckets().collect();
+        assert_eq!(buckets.len(), 15);
+        assert_eq!(buckets[0], (Some(Duration::from_micros(500)), 2));
+        assert_eq!(buckets[1], (Some(Duration::from_millis(1)), 3));
+        assert_eq!(buckets[5], (Some(Duration::from_millis(25)), 4));
+        assert_eq!(buckets[14], (None, 5));
+        assert_eq!(histogram.count(), 5);
+
+        let mut stats = RoundtripStatistics::default();
+        stats.add(&roundtrip(MessageType::ExecuteDirect, 2, false));
+        stats.add(&roundtrip(MessageType::FetchNext, 1, false));
+        stats.add(&roundtrip(MessageType::FetchNext, 1, false));
+        stats.add(&roundtrip(MessageType::ReadLob, 1, false));
+        stats.add(&roundtrip(MessageType::Execute, 30, true));
+        let mut merged = stats.clone();
+        merged.merge(&stats);
+
+        assert_eq!(merged.roundtrip_count(), 10);
+        assert_eq!(merged.message_type("FetchNext").unwrap().count(), 4);
+        assert_eq!(merged.message_type("Execute").unwrap().error_count(), 2);
+        assert_eq!(
+            merged
+                .message_type("Execute")
+                .unwrap()
+                .server_processing_time(),
+            Duration::from_micros(600)
+        );
+        assert_eq!(merged.bytes_sent(), 1_000);
+        assert_eq!(merged.bytes_received(), 10_000);
+        assert_eq!(merged.result_set_count(), 2);
+        assert_eq!(merged.fetch_roundtrip_count(), 4);
+        assert!((merged.fetch_roundtrips_per_result_set() - 2.0).abs() < f64::EPSILON);
+        assert_eq!(merged.lob_roundtrip_count(), 2);
+        assert_eq!(merged.server_errors().collect::<Vec<_>>(), vec![(257, 2)]);
+
+        let text = stats.prometheus_text(&[("pool", "a\"b")]);
+        for line in [
+            "# TYPE hdbconnect_roundtrips_total counter",
+            "hdbconnect_roundtrips_total{pool=\"a\\\"b\",message_type=\"FetchNext\"} 2",
+            "hdbconnect_roundtrip_duration_seconds_bucket{pool=\"a\\\"b\",message_type=\"ExecuteDirect\",le=\"0.0025\"} 1",
+            "hdbconnect_roundtrip_duration_seconds_bucket{pool=\"a\\\"b\",message_type=\"ExecuteDirect\",le=\"+Inf\"} 1",
+            "hdbconnect_roundtrip_duration_seconds_count{pool=\"a\\\"b\",message_type=\"Execute\"} 1",
+            "hdbconnect_fetch_roundtrips_total{pool=\"a\\\"b\"} 2",
+            "hdbconnect_server_errors_total{pool=\"a\\\"b\",code=\"257\"} 1",
+        ] {
+            assert!(text.lines().any(|l| l == line), "{line}\n{text}");
+        }
+        assert!(RoundtripStatistics::default()
+            .prometheus_text(&[])
+            .contains("\nhdbconnect_sent_bytes_total 0\n"));
+
+        // the error code is only available for errors that were reported by the server
+        assert!(HdbError::ConnectionBroken { source: None }
+            .server_error()
+            .is_none());
+    }
+}
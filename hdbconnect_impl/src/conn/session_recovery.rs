@@ -0,0 +1,172 @@
+use crate::{
+    conn::CommandOptions,
+    protocol::{MessageType, Part, Reply, ReplyType, Request},
+};
+use std::collections::HashMap;
+
+// Bookkeeping for the transparent recovery of broken sessions
+// (see `ConnectionConfiguration::set_session_recovery`).
+//
+// The live prepared statements are remembered with their SQL text and the session setup
+// under which they were prepared, so that they can be prepared again on a new session.
+// A `PreparedStatement` keeps the statement id it received initially (its client id);
+// requests are translated to the statement id of the current session.
+#[derive(Debug, Default)]
+pub(crate) struct LiveStatements {
+    statements: HashMap<u64, LiveStatement>,
+    next_alias: u64,
+}
+
+#[derive(Debug)]
+struct LiveStatement {
+    sql: String,
+    command_options: CommandOptions,
+    is_query: bool,
+    current_id: u64,
+    session_setup: Vec<String>,
+}
+
+impl LiveStatements {
+    // Remembers the statement of a successful Prepare request.
+    //
+    // Statement ids are unique only within a session, so a statement id of a new session
+    // can collide with the client id of a statement that was re-prepared;
+    // the new statement is then given an alias as client id.
+    pub(crate) fn register(
+        &mut self,
+        request: &Request,
+        reply: &mut Reply,
+        session_setup: &[String],
+    ) {
+        let Some(sql) = request.command() else {
+            return;
+        };
+        let is_query = matches!(
+            reply.replytype,
+            ReplyType::Select | ReplyType::SelectForUpdate
+        );
+        for part in reply.parts.iter_mut() {
+            if let Part::StatementId(ref mut id) = part {
+                let current_id = *id;
+                if self.statements.contains_key(id) {
+                    *id = self.alias();
+                    debug!("statement id {current_id} is represented to the client as {id}");
+                }
+                self.statements.insert(
+                    *id,
+                    LiveStatement {
+                        sql: sql.to_string(),
+                        command_options: request.command_options(),
+                        is_query,
+                        current_id,
+                        session_setup: session_setup.to_vec(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn alias(&mut self) -> u64 {
+        loop {
+            self.next_alias += 1;
+            let alias = u64::MAX - self.next_alias;
+            if !self.statements.contains_key(&alias) {
+                return alias;
+            }
+        }
+    }
+
+    // Replaces the client id in Execute and DropStatementId requests
+    // with the statement id of the current session.
+    pub(crate) fn translate(&mut self, request: &mut Request) {
+        let Some(client_id) = request.statement_id() else {
+            return;
+        };
+        let o_statement = if let MessageType::DropStatementId = request.message_type() {
+            self.statements.remove(&client_id)
+        } else {
+            None
+        };
+        let current_id = o_statement
+            .as_ref()
+            .or_else(|| self.statements.get(&client_id))
+            .map(|statement| statement.current_id);
+        if let Some(current_id) = current_id {
+            request.set_statement_id(current_id);
+        }
+    }
+
+    // The statements that need to be prepared again on a new session:
+    // (client id, SQL text, command options, session setup).
+    pub(crate) fn to_prepare(&self) -> Vec<(u64, String, CommandOptions, Vec<String>)> {
+        self.statements
+            .iter()
+            .map(|(client_id, statement)| {
+                (
+                    *client_id,
+                    statement.sql.clone(),
+                    statement.command_options,
+                    statement.session_setup.clone(),
+                )
+            })
+            .collect()
+    }
+
+    // Takes over the statement id of a new session; returns the previous one.
+    pub(crate) fn set_current_id(&mut self, client_id: u64, current_id: u64) -> Option<u64> {
+        self.statements
+            .get_mut(&client_id)
+            .map(|statement| std::mem::replace(&mut statement.current_id, current_id))
+    }
+
+    pub(crate) fn forget(&mut self, client_id: u64) {
+        self.statements.remove(&client_id);
+    }
+
+    fn is_query(&self, current_id: u64) -> bool {
+        self.statements
+            .values()
+            .any(|statement| statement.current_id == current_id && statement.is_query)
+    }
+
+    // Requests can be repeated on a new session if repeating them cannot duplicate an effect
+    // on the database; this is decided before the session is recovered.
+    pub(crate) fn is_idempotent(&self, request: &Request) -> bool {
+        match request.message_type() {
+            MessageType::Prepare | MessageType::DbConnectInfo => true,
+            MessageType::ExecuteDirect => request.command().is_some_and(|sql| {
+                matches!(
+                    first_keyword(sql).as_str(),
+                    "SELECT" | "WITH" | "COMMIT" | "ROLLBACK"
+                )
+            }),
+            MessageType::Execute => request
+                .statement_id()
+                .is_some_and(|current_id| self.is_query(current_id)),
+            _ => false,
+        }
+    }
+}
+
+fn first_keyword(sql: &str) -> String {
+    sql.trim_start_matches(|c: char| c.is_whitespace() || c == '(')
+        .split(|c: char| !c.is_alphabetic())
+        .next()
+        .unwrap_or_default()
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::first_keyword;
+
+    #[test]
+    fn test_first_keyword() {
+        assert_eq!(first_keyword("select * from dummy"), "SELECT");
+        assert_eq!(first_keyword("\n  (Select 1 from dummy)"), "SELECT");
+        assert_eq!(first_keyword("with x as (select 1 from dummy)"), "WITH");
+        assert_eq!(first_keyword("commit"), "COMMIT");
+        assert_eq!(first_keyword("insert into T values(1)"), "INSERT");
+        assert_eq!(first_keyword(""), "");
+    }
+}
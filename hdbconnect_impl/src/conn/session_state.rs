@@ -21,6 +21,11 @@ impl Default for SessionState {
     }
 }
 impl SessionState {
+    // Changes that would be lost with the session.
+    pub fn has_uncommitted_changes(&self) -> bool {
+        matches!(self.ta_state, TransactionState::WriteTransaction)
+    }
+
     pub fn update(&mut self, transaction_flags: TransactionFlags) {
         for (id, value) in transaction_flags {
             #[allow(clippy::cast_sign_loss)]
@@ -0,0 +1,183 @@
+use crate::{
+    conn::CommandOptions,
+    protocol::{
+        parts::{PartitionInformation, PartitionMethod},
+        MessageType, Part, PartKind, Reply, ReplyType, Request,
+    },
+};
+use std::collections::{HashMap, HashSet};
+
+// Bookkeeping for client-side statement routing (feature `alpha_routing`).
+//
+// When a statement is prepared on a scale-out system, HANA reports the volumes (i.e.,
+// the index servers) on which the affected table is located, and, for partitioned tables,
+// how the rows are distributed over the partitions. The executions of such a statement
+// can then be sent to a secondary session with the index server that hosts the data,
+// rather than to the coordinator, which would forward them.
+// This is done for tables on a single volume and for round-robin partitioned tables;
+// hash partitioned tables are left to the coordinator.
+//
+// A `PreparedStatement` only knows the statement id of the primary session (its client id);
+// the statement is prepared on the secondary sessions when it is routed there first.
+#[derive(Debug, Default)]
+pub(crate) struct StatementRouting {
+    statements: HashMap<u64, RoutedStatement>,
+    // volumes for which no secondary session could be opened
+    unreachable_volumes: HashSet<i32>,
+    round_robin_counter: usize,
+}
+
+#[derive(Debug)]
+struct RoutedStatement {
+    sql: String,
+    command_options: CommandOptions,
+    // the session setup under which the statement was prepared
+    session_setup: Vec<String>,
+    table_location: Vec<i32>,
+    o_partition_information: Option<PartitionInformation>,
+    // the statement ids on the secondary sessions, by volume
+    secondary_ids: HashMap<i32, u64>,
+}
+
+impl StatementRouting {
+    // Remembers the location of the data of a successfully prepared statement.
+    //
+    // Only statements that do not produce a result set are routed, because
+    // their executions are completed with a single roundtrip.
+    pub(crate) fn register(
+        &mut self,
+        request: &Request,
+        reply: &mut Reply,
+        session_setup: &[String],
+    ) {
+        let Some(sql) = request.command() else {
+            return;
+        };
+        let o_partition_information = match reply
+            .parts
+            .remove_first_of_kind(PartKind::PartitionInformation)
+        {
+            Some(Part::PartitionInformation(partition_information)) => Some(partition_information),
+            _ => None,
+        };
+        if matches!(
+            reply.replytype,
+            ReplyType::Select | ReplyType::SelectForUpdate
+        ) {
+            return;
+        }
+
+        let mut o_statement_id = None;
+        let mut table_location = Vec::new();
+        for part in reply.parts.ref_inner() {
+            match part {
+                Part::StatementId(id) => o_statement_id = Some(*id),
+                Part::TableLocation(volumes) => table_location.clone_from(volumes),
+                _ => {}
+            }
+        }
+        if let Some(statement_id) = o_statement_id {
+            if !table_location.is_empty() || o_partition_information.is_some() {
+                self.statements.insert(
+                    statement_id,
+                    RoutedStatement {
+                        sql: sql.to_string(),
+                        command_options: request.command_options(),
+                        session_setup: session_setup.to_vec(),
+                        table_location,
+                        o_partition_information,
+                        secondary_ids: HashMap::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    // Determines the volume to which an Execute request should be sent;
+    // returns the client id of the statement and the volume.
+    //
+    // Executions on hash partitioned tables are not routed, because the driver does not know
+    // the server's partition hash; they go to the coordinator, which forwards them.
+    // Tables without partitioning information are routed only if they are located
+    // on a single volume.
+    pub(crate) fn target(&mut self, request: &Request) -> Option<(u64, i32)> {
+        let MessageType::Execute = request.message_type() else {
+            return None;
+        };
+        let client_id = request.statement_id()?;
+        let statement = self.statements.get(&client_id)?;
+        let volume_id = match statement.o_partition_information {
+            Some(ref partition_information)
+                if partition_information.partition_method() == PartitionMethod::Hash =>
+            {
+                return None;
+            }
+            Some(ref partition_information)
+                if partition_information.partition_method() == PartitionMethod::RoundRobin =>
+            {
+                let volumes: Vec<i32> = partition_information.volumes().collect();
+                if volumes.is_empty() {
+                    return None;
+                }
+                self.round_robin_counter = self.round_robin_counter.wrapping_add(1);
+                volumes[self.round_robin_counter % volumes.len()]
+            }
+            _ => match statement.table_location.as_slice() {
+                [volume_id] => *volume_id,
+                _ => return None,
+            },
+        };
+        (!self.unreachable_volumes.contains(&volume_id)).then_some((client_id, volume_id))
+    }
+
+    // SQL text, command options, and session setup,
+    // for preparing the statement on a secondary session.
+    pub(crate) fn statement(
+        &self,
+        client_id: u64,
+    ) -> Option<(String, CommandOptions, Vec<String>)> {
+        self.statements.get(&client_id).map(|statement| {
+            (
+                statement.sql.clone(),
+                statement.command_options,
+                statement.session_setup.clone(),
+            )
+        })
+    }
+
+    pub(crate) fn secondary_id(&self, client_id: u64, volume_id: i32) -> Option<u64> {
+        self.statements
+            .get(&client_id)
+            .and_then(|statement| statement.secondary_ids.get(&volume_id).copied())
+    }
+
+    pub(crate) fn set_secondary_id(&mut self, client_id: u64, volume_id: i32, statement_id: u64) {
+        if let Some(statement) = self.statements.get_mut(&client_id) {
+            statement.secondary_ids.insert(volume_id, statement_id);
+        }
+    }
+
+    // Forgets the statement of a DropStatementId request;
+    // returns the statement ids on the secondary sessions, by volume.
+    pub(crate) fn forget(&mut self, request: &Request) -> Vec<(i32, u64)> {
+        let MessageType::DropStatementId = request.message_type() else {
+            return Vec::new();
+        };
+        request
+            .statement_id()
+            .and_then(|client_id| self.statements.remove(&client_id))
+            .map(|statement| statement.secondary_ids.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    // Forgets the statement ids of a secondary session that was closed.
+    pub(crate) fn forget_volume(&mut self, volume_id: i32) {
+        for statement in self.statements.values_mut() {
+            statement.secondary_ids.remove(&volume_id);
+        }
+    }
+
+    pub(crate) fn set_unreachable(&mut self, volume_id: i32) {
+        self.unreachable_volumes.insert(volume_id);
+    }
+}
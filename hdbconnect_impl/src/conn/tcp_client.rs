@@ -2,27 +2,42 @@
 mod async_plain_tcp_client;
 #[cfg(feature = "async")]
 mod async_tls_tcp_client;
+#[cfg(feature = "async")]
+mod async_websocket_client;
+mod http;
+mod proxy_tunnel;
+mod replay_client;
 #[cfg(feature = "sync")]
 mod sync_plain_tcp_client;
 #[cfg(feature = "sync")]
 mod sync_tls_tcp_client;
+#[cfg(feature = "sync")]
+mod sync_websocket_client;
+mod websocket;
 
 #[cfg(feature = "sync")]
 use crate::HdbError;
-use crate::{ConnectParams, HdbResult};
+use crate::{ConnectParams, ConnectionConfiguration, HdbResult};
 #[cfg(feature = "async")]
 use async_plain_tcp_client::AsyncPlainTcpClient;
 #[cfg(feature = "async")]
 use async_tls_tcp_client::AsyncTlsTcpClient;
-#[cfg(feature = "sync")]
-use std::time::Duration;
-use std::time::Instant;
+#[cfg(feature = "async")]
+use async_websocket_client::AsyncWebSocketClient;
+pub(crate) use replay_client::ReplayClient;
+use socket2::{SockRef, TcpKeepalive};
+use std::time::{Duration, Instant};
 #[cfg(feature = "sync")]
 use sync_plain_tcp_client::SyncPlainTcpClient;
 #[cfg(feature = "sync")]
 use sync_tls_tcp_client::SyncTlsTcpClient;
+#[cfg(feature = "sync")]
+use sync_websocket_client::SyncWebSocketClient;
+#[cfg(feature = "mock_server")]
+pub(crate) use websocket::accept_sync as accept_websocket_sync;
 
-// A buffered tcp connection, synchronous or asynchronoues, with or without TLS.
+// A buffered tcp connection, synchronous or asynchronoues, with or without TLS,
+// optionally transporting the byte streams in WebSocket messages.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub(crate) enum TcpClient {
@@ -34,6 +49,10 @@ pub(crate) enum TcpClient {
     #[cfg(feature = "sync")]
     SyncTls(SyncTlsTcpClient),
 
+    // A blocking connection that transports the byte streams in WebSocket messages.
+    #[cfg(feature = "sync")]
+    SyncWebSocket(SyncWebSocketClient),
+
     // A buffered async tcp connection without TLS.
     #[cfg(feature = "async")]
     AsyncPlain(AsyncPlainTcpClient),
@@ -42,6 +61,13 @@ pub(crate) enum TcpClient {
     #[cfg(feature = "async")]
     AsyncTls(AsyncTlsTcpClient),
 
+    // An async connection that transports the byte streams in WebSocket messages.
+    #[cfg(feature = "async")]
+    AsyncWebSocket(AsyncWebSocketClient),
+
+    // Answers the requests with the replies of a wire trace.
+    Replay(ReplayClient),
+
     // Needed if communication issues made the Stream unusable
     // (and for being able to send the Drop asynchronously).
     Dead {
@@ -54,17 +80,20 @@ impl TcpClient {
     #[cfg(feature = "sync")]
     pub fn try_new_sync(
         params: ConnectParams,
-        o_timeout: Option<std::time::Duration>,
+        config: &ConnectionConfiguration,
     ) -> HdbResult<Self> {
         let start = Instant::now();
         trace!("TcpClient: Connecting to {:?})", params.addr());
 
-        let mut tcp_conn = if params.is_tls() {
-            Self::SyncTls(SyncTlsTcpClient::try_new(params)?)
+        let settings = SocketSettings::new(&params, config);
+        let mut tcp_conn = if let Some(path) = params.websocket_path().map(str::to_string) {
+            Self::SyncWebSocket(SyncWebSocketClient::try_new(params, &path, &settings)?)
+        } else if params.is_tls() {
+            Self::SyncTls(SyncTlsTcpClient::try_new(params, &settings)?)
         } else {
-            Self::SyncPlain(SyncPlainTcpClient::try_new(params)?)
+            Self::SyncPlain(SyncPlainTcpClient::try_new(params, &settings)?)
         };
-        tcp_conn.set_read_timeout_sync(o_timeout)?;
+        tcp_conn.set_read_timeout_sync(config.read_timeout())?;
         trace!(
             "Connection of type {} is initialized ({} µs)",
             tcp_conn.s_type(),
@@ -76,14 +105,20 @@ impl TcpClient {
     // Constructs a buffered tcp connection, with or without TLS,
     // depending on the given connection parameters.
     #[cfg(feature = "async")]
-    pub async fn try_new_async(params: ConnectParams) -> HdbResult<Self> {
+    pub async fn try_new_async(
+        params: ConnectParams,
+        config: &ConnectionConfiguration,
+    ) -> HdbResult<Self> {
         let start = Instant::now();
         trace!("TcpClient: Connecting to {:?})", params.addr());
 
-        let tcp_conn = if params.is_tls() {
-            Self::AsyncTls(AsyncTlsTcpClient::try_new(params).await?)
+        let settings = SocketSettings::new(&params, config);
+        let tcp_conn = if let Some(path) = params.websocket_path().map(str::to_string) {
+            Self::AsyncWebSocket(AsyncWebSocketClient::try_new(params, &path, &settings).await?)
+        } else if params.is_tls() {
+            Self::AsyncTls(AsyncTlsTcpClient::try_new(params, &settings).await?)
         } else {
-            Self::AsyncPlain(AsyncPlainTcpClient::try_new(params).await?)
+            Self::AsyncPlain(AsyncPlainTcpClient::try_new(params, &settings).await?)
         };
 
         trace!(
@@ -101,10 +136,15 @@ impl TcpClient {
             Self::SyncPlain(_) => "Sync Plain TCP",
             #[cfg(feature = "sync")]
             Self::SyncTls(_) => "Sync TLS TCP",
+            #[cfg(feature = "sync")]
+            Self::SyncWebSocket(_) => "Sync WebSocket",
             #[cfg(feature = "async")]
             Self::AsyncPlain(_) => "Async Plain TCP",
             #[cfg(feature = "async")]
             Self::AsyncTls(_) => "Async TLS TCP",
+            #[cfg(feature = "async")]
+            Self::AsyncWebSocket(_) => "Async WebSocket",
+            Self::Replay(_) => "Replay of a wire trace",
             Self::Dead { .. } => "Physical connection lost",
         }
     }
@@ -115,10 +155,34 @@ impl TcpClient {
             Self::SyncPlain(cl) => cl.connect_params(),
             #[cfg(feature = "sync")]
             Self::SyncTls(cl) => cl.connect_params(),
+            #[cfg(feature = "sync")]
+            Self::SyncWebSocket(cl) => cl.connect_params(),
             #[cfg(feature = "async")]
             Self::AsyncPlain(cl) => cl.connect_params(),
             #[cfg(feature = "async")]
             Self::AsyncTls(cl) => cl.connect_params(),
+            #[cfg(feature = "async")]
+            Self::AsyncWebSocket(cl) => cl.connect_params(),
+            Self::Replay(cl) => cl.connect_params(),
+            Self::Dead { params } => params,
+        }
+    }
+
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        match self {
+            #[cfg(feature = "sync")]
+            Self::SyncPlain(cl) => cl.connect_params_mut(),
+            #[cfg(feature = "sync")]
+            Self::SyncTls(cl) => cl.connect_params_mut(),
+            #[cfg(feature = "sync")]
+            Self::SyncWebSocket(cl) => cl.connect_params_mut(),
+            #[cfg(feature = "async")]
+            Self::AsyncPlain(cl) => cl.connect_params_mut(),
+            #[cfg(feature = "async")]
+            Self::AsyncTls(cl) => cl.connect_params_mut(),
+            #[cfg(feature = "async")]
+            Self::AsyncWebSocket(cl) => cl.connect_params_mut(),
+            Self::Replay(cl) => cl.connect_params_mut(),
             Self::Dead { params } => params,
         }
     }
@@ -131,6 +195,8 @@ impl TcpClient {
         match self {
             Self::SyncPlain(cl) => Ok(cl.set_read_timeout(client_timeout)?),
             Self::SyncTls(cl) => Ok(cl.set_read_timeout(client_timeout)?),
+            Self::SyncWebSocket(cl) => Ok(cl.set_read_timeout(client_timeout)?),
+            Self::Replay(_) => Ok(()),
             Self::Dead { .. } => Err(HdbError::ConnectionBroken { source: None }),
             #[cfg(feature = "async")]
             _ => unimplemented!(),
@@ -146,6 +212,10 @@ impl TcpClient {
     pub(crate) fn has_died(&self) -> bool {
         matches!(*self, Self::Dead { .. })
     }
+
+    pub(crate) fn is_replay(&self) -> bool {
+        matches!(*self, Self::Replay(_))
+    }
 }
 
 impl Drop for TcpClient {
@@ -153,3 +223,146 @@ impl Drop for TcpClient {
         trace!("Drop of TcpClient");
     }
 }
+
+// The socket settings of a connection; those that are specified with the connect params
+// take precedence over those of the connection configuration.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SocketSettings {
+    connect_timeout: Option<Duration>,
+    pub(crate) write_timeout: Option<Duration>,
+    tcp_nodelay: bool,
+    tcp_keepalive: Option<Duration>,
+    tcp_keepalive_retries: Option<u32>,
+}
+impl SocketSettings {
+    pub(crate) fn new(params: &ConnectParams, config: &ConnectionConfiguration) -> Self {
+        let options = params.socket_options();
+        Self {
+            connect_timeout: params.connect_timeout().or(config.connect_timeout()),
+            write_timeout: options.write_timeout.or(config.write_timeout()),
+            tcp_nodelay: options.tcp_nodelay.unwrap_or(config.is_tcp_nodelay()),
+            tcp_keepalive: options.tcp_keepalive.or(config.tcp_keepalive()),
+            tcp_keepalive_retries: options
+                .tcp_keepalive_retries
+                .or(config.tcp_keepalive_retries()),
+        }
+    }
+
+    // Applies the options that sync and async sockets have in common.
+    fn apply(&self, socket: &SockRef) -> std::io::Result<()> {
+        socket.set_tcp_nodelay(self.tcp_nodelay)?;
+        if let Some(interval) = self.tcp_keepalive {
+            let keepalive = TcpKeepalive::new().with_time(interval);
+            #[cfg(any(
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "fuchsia",
+                target_os = "illumos",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "windows",
+            ))]
+            let keepalive = {
+                let keepalive = keepalive.with_interval(interval);
+                match self.tcp_keepalive_retries {
+                    Some(retries) => keepalive.with_retries(retries),
+                    None => keepalive,
+                }
+            };
+            #[cfg(not(any(
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "fuchsia",
+                target_os = "illumos",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "windows",
+            )))]
+            if self.tcp_keepalive_retries.is_some() {
+                debug!("TCP keepalive retries are not supported on this platform");
+            }
+            socket.set_tcp_keepalive(&keepalive)?;
+        }
+        Ok(())
+    }
+}
+
+// Opens a TCP connection to the host of the given params, observing the connect timeout;
+// if a proxy is configured, the connection is tunneled through it.
+#[cfg(feature = "sync")]
+fn connect_sync(
+    params: &ConnectParams,
+    settings: &SocketSettings,
+) -> std::io::Result<std::net::TcpStream> {
+    let tcp_stream = if let Some(proxy) = params.proxy() {
+        let mut tcp_stream = connect_to_addr_sync(&proxy.addr(), settings.connect_timeout)?;
+        tcp_stream.set_read_timeout(settings.connect_timeout)?;
+        tcp_stream.set_write_timeout(settings.connect_timeout)?;
+        proxy_tunnel::open_sync(proxy, params.addr(), &mut tcp_stream)?;
+        tcp_stream.set_read_timeout(None)?;
+        tcp_stream
+    } else {
+        connect_to_addr_sync(params.addr(), settings.connect_timeout)?
+    };
+    tcp_stream.set_write_timeout(settings.write_timeout)?;
+    settings.apply(&SockRef::from(&tcp_stream))?;
+    Ok(tcp_stream)
+}
+
+#[cfg(feature = "sync")]
+fn connect_to_addr_sync(
+    addr: &str,
+    o_timeout: Option<Duration>,
+) -> std::io::Result<std::net::TcpStream> {
+    let Some(timeout) = o_timeout else {
+        return std::net::TcpStream::connect(addr);
+    };
+    let mut o_last_error = None;
+    for socket_addr in std::net::ToSocketAddrs::to_socket_addrs(addr)? {
+        match std::net::TcpStream::connect_timeout(&socket_addr, timeout) {
+            Ok(tcp_stream) => return Ok(tcp_stream),
+            Err(e) => o_last_error = Some(e),
+        }
+    }
+    Err(o_last_error.unwrap_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{addr} could not be resolved"),
+        )
+    }))
+}
+
+// Opens a TCP connection to the host of the given params, observing the connect timeout;
+// if a proxy is configured, the connection is tunneled through it.
+// Async sockets have no write timeout, it is observed when requests are sent.
+#[cfg(feature = "async")]
+async fn connect_async(
+    params: &ConnectParams,
+    settings: &SocketSettings,
+) -> std::io::Result<tokio::net::TcpStream> {
+    let connect = async {
+        match params.proxy() {
+            None => tokio::net::TcpStream::connect(params.addr()).await,
+            Some(proxy) => {
+                let mut tcp_stream = tokio::net::TcpStream::connect(proxy.addr()).await?;
+                proxy_tunnel::open_async(proxy, params.addr(), &mut tcp_stream).await?;
+                Ok(tcp_stream)
+            }
+        }
+    };
+    let tcp_stream = match settings.connect_timeout {
+        None => connect.await?,
+        Some(timeout) => tokio::time::timeout(timeout, connect).await.map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("connecting to {} timed out", params.addr()),
+            )
+        })??,
+    };
+    settings.apply(&SockRef::from(&tcp_stream))?;
+    Ok(tcp_stream)
+}
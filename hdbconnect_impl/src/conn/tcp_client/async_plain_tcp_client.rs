@@ -1,3 +1,4 @@
+use super::SocketSettings;
 use crate::{ConnectParams, HdbResult};
 use tokio::net::TcpStream;
 
@@ -9,8 +10,8 @@ pub(crate) struct AsyncPlainTcpClient {
 }
 
 impl AsyncPlainTcpClient {
-    pub async fn try_new(params: ConnectParams) -> HdbResult<Self> {
-        let tcp_stream = TcpStream::connect(params.addr()).await?;
+    pub async fn try_new(params: ConnectParams, settings: &SocketSettings) -> HdbResult<Self> {
+        let tcp_stream = super::connect_async(&params, settings).await?;
         Ok(Self { params, tcp_stream })
     }
 
@@ -18,6 +19,10 @@ impl AsyncPlainTcpClient {
         &self.params
     }
 
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        &mut self.params
+    }
+
     pub fn writer(&mut self) -> &mut TcpStream {
         &mut self.tcp_stream
     }
@@ -1,6 +1,5 @@
+use super::SocketSettings;
 use crate::{ConnectParams, HdbError, HdbResult};
-use rustls::pki_types::ServerName;
-use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio_rustls::{client::TlsStream, TlsConnector};
 
@@ -11,26 +10,21 @@ pub(crate) struct AsyncTlsTcpClient {
 }
 
 impl AsyncTlsTcpClient {
-    pub async fn try_new(params: ConnectParams) -> HdbResult<Self> {
-        let a_client_config = Arc::new(params.rustls_clientconfig()?.0);
-        let server_name = ServerName::try_from(params.host().to_owned())?;
-
-        let tls_connector = TlsConnector::from(a_client_config);
-
-        let tcp_stream = TcpStream::connect(params.addr()).await?;
-        let tls_stream = tls_connector
-            .connect(server_name, tcp_stream)
-            .await
-            .map_err(|e| HdbError::TlsInit {
-                source: Box::new(e),
-            })?;
-        Ok(AsyncTlsTcpClient { params, tls_stream })
+    pub async fn try_new(params: ConnectParams, settings: &SocketSettings) -> HdbResult<Self> {
+        Ok(AsyncTlsTcpClient {
+            tls_stream: try_new_tls_stream(&params, settings).await?,
+            params,
+        })
     }
 
     pub fn connect_params(&self) -> &ConnectParams {
         &self.params
     }
 
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        &mut self.params
+    }
+
     pub fn writer(&mut self) -> &mut TlsStream<TcpStream> {
         &mut self.tls_stream
     }
@@ -39,3 +33,22 @@ impl AsyncTlsTcpClient {
         &mut self.tls_stream
     }
 }
+
+pub(super) async fn try_new_tls_stream(
+    params: &ConnectParams,
+    settings: &SocketSettings,
+) -> HdbResult<TlsStream<TcpStream>> {
+    let a_client_config = params.rustls_clientconfig()?.0;
+    // also with a proxy, the TLS session is established with the database host
+    let server_name = params.server_name()?;
+
+    let tls_connector = TlsConnector::from(a_client_config);
+
+    let tcp_stream = super::connect_async(params, settings).await?;
+    tls_connector
+        .connect(server_name, tcp_stream)
+        .await
+        .map_err(|e| HdbError::TlsInit {
+            source: Box::new(e),
+        })
+}
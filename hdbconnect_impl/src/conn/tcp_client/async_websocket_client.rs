@@ -0,0 +1,104 @@
+use super::{
+    websocket::{self, WebSocketStream},
+    SocketSettings,
+};
+use crate::{ConnectParams, HdbResult};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::client::TlsStream;
+
+// An async connection that transports the byte streams in WebSocket messages,
+// with or without TLS.
+pub(crate) struct AsyncWebSocketClient {
+    params: ConnectParams,
+    ws_stream: WebSocketStream<AsyncStream>,
+}
+impl std::fmt::Debug for AsyncWebSocketClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "WebSocketClient {{params: {:?}, ... }}", &self.params)
+    }
+}
+impl AsyncWebSocketClient {
+    pub async fn try_new(
+        params: ConnectParams,
+        path: &str,
+        settings: &SocketSettings,
+    ) -> HdbResult<Self> {
+        let stream = if params.is_tls() {
+            AsyncStream::Tls(Box::new(
+                super::async_tls_tcp_client::try_new_tls_stream(&params, settings).await?,
+            ))
+        } else {
+            AsyncStream::Plain(super::connect_async(&params, settings).await?)
+        };
+        Ok(Self {
+            ws_stream: websocket::connect_async(stream, params.addr(), path).await?,
+            params,
+        })
+    }
+
+    pub fn connect_params(&self) -> &ConnectParams {
+        &self.params
+    }
+
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        &mut self.params
+    }
+
+    pub fn writer(&mut self) -> &mut WebSocketStream<AsyncStream> {
+        &mut self.ws_stream
+    }
+
+    pub fn reader(&mut self) -> &mut WebSocketStream<AsyncStream> {
+        &mut self.ws_stream
+    }
+}
+
+pub(crate) enum AsyncStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+impl AsyncRead for AsyncStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(tcp_stream) => Pin::new(tcp_stream).poll_read(cx, buf),
+            Self::Tls(tls_stream) => Pin::new(tls_stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+impl AsyncWrite for AsyncStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(tcp_stream) => Pin::new(tcp_stream).poll_write(cx, buf),
+            Self::Tls(tls_stream) => Pin::new(tls_stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(tcp_stream) => Pin::new(tcp_stream).poll_flush(cx),
+            Self::Tls(tls_stream) => Pin::new(tls_stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(tcp_stream) => Pin::new(tcp_stream).poll_shutdown(cx),
+            Self::Tls(tls_stream) => Pin::new(tls_stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// Pieces of HTTP/1.1 that are needed for opening tunnels through HTTP proxies
+// and for upgrading connections to WebSocket.
+//
+// Response headers are read byte by byte up to the empty line, so that nothing is consumed
+// that the server sends after the header through the tunnel.
+use std::io::{Error, ErrorKind, Result};
+
+const MAX_HEADER_SIZE: usize = 8 * 1024;
+
+#[cfg(any(feature = "sync", feature = "mock_server"))]
+pub(crate) fn read_header_sync<R: std::io::Read>(rdr: &mut R) -> Result<String> {
+    let mut header = Vec::<u8>::new();
+    let mut byte = [0_u8; 1];
+    while !header.ends_with(b"\r\n\r\n") {
+        check_header_size(&header)?;
+        rdr.read_exact(&mut byte)?;
+        header.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&header).to_string())
+}
+
+#[cfg(feature = "async")]
+pub(crate) async fn read_header_async<R: tokio::io::AsyncRead + Unpin>(
+    rdr: &mut R,
+) -> Result<String> {
+    use tokio::io::AsyncReadExt;
+    let mut header = Vec::<u8>::new();
+    let mut byte = [0_u8; 1];
+    while !header.ends_with(b"\r\n\r\n") {
+        check_header_size(&header)?;
+        rdr.read_exact(&mut byte).await?;
+        header.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&header).to_string())
+}
+
+fn check_header_size(header: &[u8]) -> Result<()> {
+    if header.len() < MAX_HEADER_SIZE {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "HTTP header exceeds the maximal size",
+        ))
+    }
+}
+
+// The first line of the header, and its second token, which is the status code of a response,
+// or the path of a request.
+pub(crate) fn first_line(header: &str) -> (&str, Option<&str>) {
+    let line = header.lines().next().unwrap_or_default();
+    (line, line.split_whitespace().nth(1))
+}
+
+// The value of the given header field; field names are case-insensitive.
+pub(crate) fn field<'h>(header: &'h str, name: &str) -> Option<&'h str> {
+    header.lines().skip(1).find_map(|line| {
+        line.split_once(':')
+            .filter(|(field_name, _)| field_name.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    })
+}
+
+// Standard base64 encoding with padding, as used in HTTP basic authentication.
+pub(crate) fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(char::from(
+                    ALPHABET[(triple >> (18 - 6 * i)) as usize & 0x3F],
+                ));
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::{base64, field, first_line};
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(
+            base64(b"Aladdin:open sesame"),
+            "QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+    }
+
+    #[test]
+    fn test_header() {
+        let header = "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                      sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n";
+        assert_eq!(
+            first_line(header),
+            ("HTTP/1.1 101 Switching Protocols", Some("101"))
+        );
+        assert_eq!(field(header, "upgrade"), Some("websocket"));
+        assert_eq!(
+            field(header, "Sec-WebSocket-Accept"),
+            Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=")
+        );
+        assert_eq!(field(header, "Connection"), None);
+        assert_eq!(first_line("garbage"), ("garbage", None));
+    }
+}
@@ -0,0 +1,277 @@
+// Opens a tunnel to the database host through an HTTP or SOCKS5 proxy.
+//
+// The responses of the proxy are read with their exact length,
+// so that nothing is consumed that the database sends through the tunnel.
+use super::http;
+use crate::{Proxy, ProxyProtocol};
+use std::{
+    io::{Error, ErrorKind, Result},
+    net::IpAddr,
+};
+
+const SOCKS_VERSION: u8 = 5;
+const SOCKS_NO_AUTHENTICATION: u8 = 0;
+const SOCKS_USERNAME_PASSWORD: u8 = 2;
+const SOCKS_NO_ACCEPTABLE_METHOD: u8 = 0xFF;
+const SOCKS_CMD_CONNECT: u8 = 1;
+const SOCKS_ATYP_IPV4: u8 = 1;
+const SOCKS_ATYP_DOMAIN: u8 = 3;
+const SOCKS_ATYP_IPV6: u8 = 4;
+
+#[cfg(feature = "sync")]
+pub(crate) fn open_sync<S: std::io::Read + std::io::Write>(
+    proxy: &Proxy,
+    target: &str,
+    stream: &mut S,
+) -> Result<()> {
+    debug!("Opening a tunnel to {target} through {proxy}");
+    match proxy.protocol() {
+        ProxyProtocol::Http => {
+            stream.write_all(&http_connect_request(proxy, target))?;
+            evaluate_http_response(&http::read_header_sync(stream)?)
+        }
+        ProxyProtocol::Socks5 => {
+            stream.write_all(&socks_greeting(proxy))?;
+            let mut method_selection = [0_u8; 2];
+            stream.read_exact(&mut method_selection)?;
+            if evaluate_socks_method_selection(method_selection)? == SOCKS_USERNAME_PASSWORD {
+                stream.write_all(&socks_authentication_request(proxy)?)?;
+                let mut status = [0_u8; 2];
+                stream.read_exact(&mut status)?;
+                evaluate_socks_authentication(status)?;
+            }
+            stream.write_all(&socks_connect_request(target)?)?;
+            let mut reply_head = [0_u8; 5];
+            stream.read_exact(&mut reply_head)?;
+            let mut bound_address = vec![0_u8; evaluate_socks_reply(reply_head)?];
+            stream.read_exact(&mut bound_address)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) async fn open_async<S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin>(
+    proxy: &Proxy,
+    target: &str,
+    stream: &mut S,
+) -> Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    debug!("Opening a tunnel to {target} through {proxy}");
+    match proxy.protocol() {
+        ProxyProtocol::Http => {
+            stream
+                .write_all(&http_connect_request(proxy, target))
+                .await?;
+            evaluate_http_response(&http::read_header_async(stream).await?)
+        }
+        ProxyProtocol::Socks5 => {
+            stream.write_all(&socks_greeting(proxy)).await?;
+            let mut method_selection = [0_u8; 2];
+            stream.read_exact(&mut method_selection).await?;
+            if evaluate_socks_method_selection(method_selection)? == SOCKS_USERNAME_PASSWORD {
+                stream
+                    .write_all(&socks_authentication_request(proxy)?)
+                    .await?;
+                let mut status = [0_u8; 2];
+                stream.read_exact(&mut status).await?;
+                evaluate_socks_authentication(status)?;
+            }
+            stream.write_all(&socks_connect_request(target)?).await?;
+            let mut reply_head = [0_u8; 5];
+            stream.read_exact(&mut reply_head).await?;
+            let mut bound_address = vec![0_u8; evaluate_socks_reply(reply_head)?];
+            stream.read_exact(&mut bound_address).await.map(|_| ())
+        }
+    }
+}
+
+fn http_connect_request(proxy: &Proxy, target: &str) -> Vec<u8> {
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some((user, password)) = proxy.credentials() {
+        request.push_str("Proxy-Authorization: Basic ");
+        request.push_str(&http::base64(
+            format!("{user}:{}", password.unsecure()).as_bytes(),
+        ));
+        request.push_str("\r\n");
+    }
+    request.push_str("\r\n");
+    request.into_bytes()
+}
+
+// Only the status line is relevant; any 2xx status means that the tunnel is open.
+fn evaluate_http_response(header: &str) -> Result<()> {
+    let (status_line, o_status) = http::first_line(header);
+    match o_status {
+        Some(status) if status.starts_with('2') && status.len() == 3 => Ok(()),
+        Some("407") => Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!("HTTP proxy requires authentication: {status_line}"),
+        )),
+        _ => Err(Error::new(
+            ErrorKind::ConnectionRefused,
+            format!("HTTP proxy refused to open the tunnel: {status_line}"),
+        )),
+    }
+}
+
+fn socks_greeting(proxy: &Proxy) -> Vec<u8> {
+    if proxy.credentials().is_some() {
+        vec![
+            SOCKS_VERSION,
+            2,
+            SOCKS_NO_AUTHENTICATION,
+            SOCKS_USERNAME_PASSWORD,
+        ]
+    } else {
+        vec![SOCKS_VERSION, 1, SOCKS_NO_AUTHENTICATION]
+    }
+}
+
+fn evaluate_socks_method_selection(method_selection: [u8; 2]) -> Result<u8> {
+    match method_selection {
+        [SOCKS_VERSION, method @ (SOCKS_NO_AUTHENTICATION | SOCKS_USERNAME_PASSWORD)] => Ok(method),
+        [SOCKS_VERSION, SOCKS_NO_ACCEPTABLE_METHOD] => Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "SOCKS5 proxy accepts none of the offered authentication methods",
+        )),
+        _ => Err(proxy_error("unexpected response of SOCKS5 proxy")),
+    }
+}
+
+// RFC 1929
+fn socks_authentication_request(proxy: &Proxy) -> Result<Vec<u8>> {
+    let (user, password) = proxy.credentials().ok_or_else(|| {
+        Error::new(
+            ErrorKind::PermissionDenied,
+            "SOCKS5 proxy requires authentication, but no credentials are configured",
+        )
+    })?;
+    let mut request = vec![1];
+    for field in [user.as_bytes(), password.unsecure().as_bytes()] {
+        request.push(
+            u8::try_from(field.len())
+                .map_err(|_| proxy_error("SOCKS5 credentials must not exceed 255 bytes"))?,
+        );
+        request.extend_from_slice(field);
+    }
+    Ok(request)
+}
+
+fn evaluate_socks_authentication(status: [u8; 2]) -> Result<()> {
+    if status[1] == 0 {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "SOCKS5 proxy rejected the credentials",
+        ))
+    }
+}
+
+// Host names are passed to the proxy, which resolves them.
+fn socks_connect_request(target: &str) -> Result<Vec<u8>> {
+    let (host, port) = target
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| proxy_error(&format!("invalid target address {target}")))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let mut request = vec![SOCKS_VERSION, SOCKS_CMD_CONNECT, 0];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(SOCKS_ATYP_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(SOCKS_ATYP_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            request.push(SOCKS_ATYP_DOMAIN);
+            request.push(
+                u8::try_from(host.len())
+                    .map_err(|_| proxy_error("host names must not exceed 255 bytes"))?,
+            );
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    Ok(request)
+}
+
+// Evaluates version, reply code, reserved byte, address type, and the first byte of the
+// bound address; returns the number of bytes that remain to be read.
+fn evaluate_socks_reply(reply_head: [u8; 5]) -> Result<usize> {
+    let [version, reply, _, address_type, first] = reply_head;
+    if version != SOCKS_VERSION {
+        return Err(proxy_error("unexpected response of SOCKS5 proxy"));
+    }
+    if reply != 0 {
+        return Err(Error::new(
+            ErrorKind::ConnectionRefused,
+            format!(
+                "SOCKS5 proxy refused to open the tunnel: {}",
+                match reply {
+                    1 => "general failure",
+                    2 => "connection not allowed by ruleset",
+                    3 => "network unreachable",
+                    4 => "host unreachable",
+                    5 => "connection refused",
+                    6 => "TTL expired",
+                    7 => "command not supported",
+                    8 => "address type not supported",
+                    _ => "unknown error",
+                }
+            ),
+        ));
+    }
+    // the bound address, followed by the port
+    match address_type {
+        SOCKS_ATYP_IPV4 => Ok(3 + 2),
+        SOCKS_ATYP_IPV6 => Ok(15 + 2),
+        SOCKS_ATYP_DOMAIN => Ok(usize::from(first) + 2),
+        _ => Err(proxy_error(
+            "unexpected address type in response of SOCKS5 proxy",
+        )),
+    }
+}
+
+fn proxy_error(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{evaluate_http_response, socks_connect_request};
+
+    #[test]
+    fn test_evaluate_http_response() {
+        assert!(evaluate_http_response("HTTP/1.1 200 Connection established\r\n\r\n").is_ok());
+        assert!(evaluate_http_response("HTTP/1.0 200 OK\r\nVia: proxy\r\n\r\n").is_ok());
+        assert_eq!(
+            evaluate_http_response("HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        assert!(evaluate_http_response("HTTP/1.1 403 Forbidden\r\n\r\n").is_err());
+        assert!(evaluate_http_response("garbage\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn test_socks_connect_request() {
+        assert_eq!(
+            socks_connect_request("hana:30015").unwrap(),
+            [5, 1, 0, 3, 4, b'h', b'a', b'n', b'a', 0x75, 0x3F]
+        );
+        assert_eq!(
+            socks_connect_request("10.0.0.1:30015").unwrap(),
+            [5, 1, 0, 1, 10, 0, 0, 1, 0x75, 0x3F]
+        );
+        assert_eq!(
+            socks_connect_request("[::1]:30015").unwrap()[3..20],
+            [4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+        assert!(socks_connect_request("hana").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{
+    conn::wire_trace::{self, Direction, WireRecord},
+    protocol::MessageType,
+    ConnectParams, HdbResult,
+};
+use std::{
+    io::{Cursor, Read, Write},
+    path::Path,
+};
+
+// Answers the requests of a connection with the replies of a wire trace, so that
+// a recorded session can be reproduced offline.
+//
+// The requests are not evaluated; only their message type is compared with that of the
+// recorded request, to detect if the replaying program deviates from the recorded one.
+#[derive(Debug)]
+pub(crate) struct ReplayClient {
+    params: ConnectParams,
+    records: std::vec::IntoIter<WireRecord>,
+    request: Vec<u8>,
+    reply: Cursor<Vec<u8>>,
+}
+impl ReplayClient {
+    pub fn try_new(path: &Path, params: ConnectParams) -> HdbResult<Self> {
+        let records = wire_trace::read_wire_trace(path)?;
+        debug!(
+            "replaying {} messages from {}",
+            records.len(),
+            path.display()
+        );
+        Ok(Self {
+            params,
+            records: records.into_iter(),
+            request: Vec::new(),
+            reply: Cursor::new(Vec::new()),
+        })
+    }
+
+    pub fn connect_params(&self) -> &ConnectParams {
+        &self.params
+    }
+
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        &mut self.params
+    }
+
+    // Provides the reply to the request that was sent last.
+    fn next_reply(&mut self) -> std::io::Result<()> {
+        let mut o_recorded_type = None;
+        loop {
+            match self.records.next() {
+                None => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "the wire trace contains no further reply",
+                    ))
+                }
+                Some(record) if record.direction == Direction::Request => {
+                    o_recorded_type = record.message_type();
+                }
+                Some(record) => {
+                    let o_sent_type = wire_trace::request_message_type(&self.request);
+                    if let (Some(sent), Some(recorded)) = (o_sent_type, o_recorded_type) {
+                        if sent != recorded {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                format!(
+                                    "the replay deviates from the wire trace: \
+                                     a request of type {} was sent, the trace has one of type {}",
+                                    message_type_name(sent),
+                                    message_type_name(recorded)
+                                ),
+                            ));
+                        }
+                    }
+                    self.request.clear();
+                    self.reply = Cursor::new(record.message);
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn message_type_name(val: i8) -> String {
+    MessageType::from_i8(val).map_or_else(|_| val.to_string(), |mt| format!("{mt:?}"))
+}
+
+impl Read for ReplayClient {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.reply.position() >= self.reply.get_ref().len() as u64 {
+            self.next_reply()?;
+        }
+        self.reply.read(buf)
+    }
+}
+
+impl Write for ReplayClient {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.request.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl tokio::io::AsyncRead for ReplayClient {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let count = self.get_mut().read(buf.initialize_unfilled())?;
+        buf.advance(count);
+        std::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "async")]
+impl tokio::io::AsyncWrite for ReplayClient {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        std::task::Poll::Ready(self.get_mut().write(buf))
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+}
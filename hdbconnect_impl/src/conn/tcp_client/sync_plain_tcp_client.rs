@@ -1,3 +1,4 @@
+use super::SocketSettings;
 use crate::{ConnectParams, HdbResult};
 use std::{net::TcpStream, time::Duration};
 
@@ -9,9 +10,9 @@ pub(crate) struct SyncPlainTcpClient {
 
 impl SyncPlainTcpClient {
     // Returns an initialized plain tcp connection
-    pub fn try_new(params: ConnectParams) -> HdbResult<Self> {
+    pub fn try_new(params: ConnectParams, settings: &SocketSettings) -> HdbResult<Self> {
         Ok(Self {
-            tcp_stream: TcpStream::connect(params.addr())?,
+            tcp_stream: super::connect_sync(&params, settings)?,
             params,
         })
     }
@@ -20,6 +21,10 @@ impl SyncPlainTcpClient {
         &self.params
     }
 
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        &mut self.params
+    }
+
     pub(crate) fn set_read_timeout(&mut self, o_duration: Option<Duration>) -> std::io::Result<()> {
         self.tcp_stream.set_read_timeout(o_duration)
     }
@@ -1,6 +1,7 @@
+use super::SocketSettings;
 use crate::{ConnectParams, HdbResult};
-use rustls::{pki_types::ServerName, ClientConnection, StreamOwned};
-use std::{sync::Arc, time::Duration};
+use rustls::{ClientConnection, StreamOwned};
+use std::time::Duration;
 
 pub(crate) struct SyncTlsTcpClient {
     params: ConnectParams,
@@ -12,9 +13,9 @@ impl std::fmt::Debug for SyncTlsTcpClient {
     }
 }
 impl SyncTlsTcpClient {
-    pub fn try_new(params: ConnectParams) -> HdbResult<Self> {
+    pub fn try_new(params: ConnectParams, settings: &SocketSettings) -> HdbResult<Self> {
         Ok(Self {
-            tls_stream: try_new_tls_stream(&params)?,
+            tls_stream: try_new_tls_stream(&params, settings)?,
             params,
         })
     }
@@ -23,6 +24,10 @@ impl SyncTlsTcpClient {
         &self.params
     }
 
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        &mut self.params
+    }
+
     pub(crate) fn set_read_timeout(&mut self, o_duration: Option<Duration>) -> std::io::Result<()> {
         self.tls_stream.sock.set_read_timeout(o_duration)
     }
@@ -36,16 +41,18 @@ impl SyncTlsTcpClient {
     }
 }
 
-fn try_new_tls_stream(
+pub(super) fn try_new_tls_stream(
     params: &ConnectParams,
+    settings: &SocketSettings,
 ) -> HdbResult<StreamOwned<ClientConnection, std::net::TcpStream>> {
-    let a_client_config = Arc::new(params.rustls_clientconfig()?.0);
-    let server_name = ServerName::try_from(params.host().to_owned())?;
+    let a_client_config = params.rustls_clientconfig()?.0;
+    // also with a proxy, the TLS session is established with the database host
+    let server_name = params.server_name()?;
     let client_connection = ClientConnection::new(a_client_config, server_name)?;
     debug!("ClientConnection: {client_connection:?}");
 
     debug!("Connecting to {:?}", params.addr());
-    let tcpstream = std::net::TcpStream::connect(params.addr())?;
+    let tcpstream = super::connect_sync(params, settings)?;
     trace!("tcpstream working");
 
     Ok(StreamOwned::new(client_connection, tcpstream))
@@ -0,0 +1,101 @@
+use super::{
+    websocket::{self, WebSocketStream},
+    SocketSettings,
+};
+use crate::{ConnectParams, HdbResult};
+use rustls::{ClientConnection, StreamOwned};
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+// A blocking connection that transports the byte streams in WebSocket messages,
+// with or without TLS.
+pub(crate) struct SyncWebSocketClient {
+    params: ConnectParams,
+    ws_stream: WebSocketStream<SyncStream>,
+}
+impl std::fmt::Debug for SyncWebSocketClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "WebSocketClient {{params: {:?}, ... }}", &self.params)
+    }
+}
+impl SyncWebSocketClient {
+    pub fn try_new(
+        params: ConnectParams,
+        path: &str,
+        settings: &SocketSettings,
+    ) -> HdbResult<Self> {
+        let stream = if params.is_tls() {
+            SyncStream::Tls(Box::new(super::sync_tls_tcp_client::try_new_tls_stream(
+                &params, settings,
+            )?))
+        } else {
+            SyncStream::Plain(super::connect_sync(&params, settings)?)
+        };
+        Ok(Self {
+            ws_stream: websocket::connect_sync(stream, params.addr(), path)?,
+            params,
+        })
+    }
+
+    pub fn connect_params(&self) -> &ConnectParams {
+        &self.params
+    }
+
+    pub fn connect_params_mut(&mut self) -> &mut ConnectParams {
+        &mut self.params
+    }
+
+    pub(crate) fn set_read_timeout(&mut self, o_duration: Option<Duration>) -> std::io::Result<()> {
+        self.ws_stream
+            .inner()
+            .tcp_stream()
+            .set_read_timeout(o_duration)
+    }
+
+    pub(crate) fn writer(&mut self) -> &mut dyn std::io::Write {
+        &mut self.ws_stream
+    }
+
+    pub(crate) fn reader(&mut self) -> &mut dyn std::io::Read {
+        &mut self.ws_stream
+    }
+}
+
+enum SyncStream {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+impl SyncStream {
+    fn tcp_stream(&self) -> &TcpStream {
+        match self {
+            Self::Plain(tcp_stream) => tcp_stream,
+            Self::Tls(tls_stream) => &tls_stream.sock,
+        }
+    }
+}
+impl Read for SyncStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(tcp_stream) => tcp_stream.read(buf),
+            Self::Tls(tls_stream) => tls_stream.read(buf),
+        }
+    }
+}
+impl Write for SyncStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(tcp_stream) => tcp_stream.write(buf),
+            Self::Tls(tls_stream) => tls_stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(tcp_stream) => tcp_stream.flush(),
+            Self::Tls(tls_stream) => tls_stream.flush(),
+        }
+    }
+}
@@ -0,0 +1,587 @@
+// WebSocket (RFC 6455) framing of the byte streams of requests and replies.
+//
+// Everything that is written until a flush is sent as a single binary message;
+// the payload of the incoming data frames is handed out as a byte stream, independent of
+// the message boundaries. Pings are answered, a close frame ends the stream.
+use super::http;
+use sha1::{Digest, Sha1};
+use std::io::{Error, ErrorKind, Result};
+
+const FIN: u8 = 0x80;
+const MASKED: u8 = 0x80;
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+const MAX_CONTROL_PAYLOAD: usize = 125;
+// values of the 7-bit length field that announce an extended length
+const LENGTH_16_BIT: u8 = 126;
+const LENGTH_64_BIT: u8 = 127;
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+
+pub(crate) struct WebSocketStream<S> {
+    inner: S,
+    // frames that are sent by clients are masked, frames that are sent by servers are not
+    is_client: bool,
+    // bytes that were read from the inner stream, but are not yet decoded
+    raw_in: Vec<u8>,
+    raw_pos: usize,
+    // the frame whose payload is being read
+    o_frame: Option<Frame>,
+    closed: bool,
+    // the payload of the next outgoing message
+    payload_out: Vec<u8>,
+    // encoded frames that are not yet (completely) written to the inner stream
+    frames_out: Vec<u8>,
+    frames_out_pos: usize,
+}
+
+struct Frame {
+    opcode: u8,
+    remaining: usize,
+    o_mask: Option<[u8; 4]>,
+    mask_pos: usize,
+}
+impl Frame {
+    fn unmask(&mut self, payload: &mut [u8]) {
+        if let Some(mask) = self.o_mask {
+            for byte in payload {
+                *byte ^= mask[self.mask_pos % 4];
+                self.mask_pos += 1;
+            }
+        }
+    }
+}
+
+impl<S> WebSocketStream<S> {
+    fn new(inner: S, is_client: bool) -> Self {
+        Self {
+            inner,
+            is_client,
+            raw_in: Vec::new(),
+            raw_pos: 0,
+            o_frame: None,
+            closed: false,
+            payload_out: Vec::new(),
+            frames_out: Vec::new(),
+            frames_out_pos: 0,
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    pub(crate) fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    // Decodes buffered raw bytes into buf; returns None if more raw bytes are needed,
+    // and Some(0) at the end of the stream.
+    fn decode(&mut self, buf: &mut [u8]) -> Result<Option<usize>> {
+        loop {
+            if self.closed {
+                return Ok(Some(0));
+            }
+            let available = &self.raw_in[self.raw_pos..];
+            match self.o_frame {
+                None => {
+                    let Some((frame, header_len)) = parse_frame_header(available)? else {
+                        return Ok(None);
+                    };
+                    self.raw_pos += header_len;
+                    self.o_frame = Some(frame);
+                }
+                Some(ref mut frame)
+                    if frame.opcode == OPCODE_BINARY || frame.opcode == OPCODE_CONTINUATION =>
+                {
+                    if frame.remaining == 0 {
+                        self.o_frame = None;
+                        continue;
+                    }
+                    let n = available.len().min(frame.remaining).min(buf.len());
+                    if n == 0 {
+                        return Ok(None);
+                    }
+                    buf[..n].copy_from_slice(&available[..n]);
+                    frame.unmask(&mut buf[..n]);
+                    frame.remaining -= n;
+                    self.raw_pos += n;
+                    if frame.remaining == 0 {
+                        self.o_frame = None;
+                    }
+                    return Ok(Some(n));
+                }
+                Some(ref mut frame) => {
+                    // control frames are evaluated when they are complete
+                    if available.len() < frame.remaining {
+                        return Ok(None);
+                    }
+                    let mut payload = available[..frame.remaining].to_vec();
+                    frame.unmask(&mut payload);
+                    self.raw_pos += frame.remaining;
+                    let opcode = frame.opcode;
+                    self.o_frame = None;
+                    match opcode {
+                        OPCODE_PING => self.queue_frame(OPCODE_PONG, &payload),
+                        OPCODE_CLOSE => {
+                            debug!("WebSocket connection is closed by the peer");
+                            // echo the status code
+                            self.queue_frame(OPCODE_CLOSE, payload.get(..2).unwrap_or_default());
+                            self.closed = true;
+                        }
+                        _ => {} // pong
+                    }
+                }
+            }
+        }
+    }
+
+    // Makes room for reading more raw bytes, and returns the position to read into.
+    fn prepare_raw_in(&mut self) -> usize {
+        self.raw_in.drain(..self.raw_pos);
+        self.raw_pos = 0;
+        let len = self.raw_in.len();
+        self.raw_in.resize(len + READ_CHUNK_SIZE, 0);
+        len
+    }
+
+    // Removes what prepare_raw_in() added, but was not filled.
+    fn raw_in_filled(&mut self, n: usize) {
+        self.raw_in
+            .truncate(self.raw_in.len() - READ_CHUNK_SIZE + n);
+    }
+
+    fn queue_message(&mut self) {
+        if !self.payload_out.is_empty() {
+            let payload = std::mem::take(&mut self.payload_out);
+            self.queue_frame(OPCODE_BINARY, &payload);
+        }
+    }
+
+    fn queue_frame(&mut self, opcode: u8, payload: &[u8]) {
+        let out = &mut self.frames_out;
+        out.push(FIN | opcode);
+        let mask_bit = if self.is_client { MASKED } else { 0 };
+        let len = payload.len();
+        match (u8::try_from(len), u16::try_from(len)) {
+            (Ok(len), _) if usize::from(len) <= MAX_CONTROL_PAYLOAD => out.push(mask_bit | len),
+            (_, Ok(len)) => {
+                out.push(mask_bit | LENGTH_16_BIT);
+                out.extend_from_slice(&len.to_be_bytes());
+            }
+            _ => {
+                out.push(mask_bit | LENGTH_64_BIT);
+                out.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        if self.is_client {
+            let mask: [u8; 4] = rand::random();
+            out.extend_from_slice(&mask);
+            out.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+        } else {
+            out.extend_from_slice(payload);
+        }
+    }
+
+    fn frames_written(&mut self) {
+        self.frames_out.clear();
+        self.frames_out_pos = 0;
+    }
+}
+
+// Returns the frame and the length of its header, or None if the header is not yet complete.
+fn parse_frame_header(bytes: &[u8]) -> Result<Option<(Frame, usize)>> {
+    let [first, second, ..] = *bytes else {
+        return Ok(None);
+    };
+    let opcode = first & 0x0F;
+    let (length, mut header_len) = match second & 0x7F {
+        LENGTH_16_BIT => match bytes.get(2..4) {
+            Some(length) => (u64::from(u16::from_be_bytes([length[0], length[1]])), 4),
+            None => return Ok(None),
+        },
+        LENGTH_64_BIT => match bytes.get(2..10) {
+            Some(length) => (u64::from_be_bytes(length.try_into().unwrap(/*OK*/)), 10),
+            None => return Ok(None),
+        },
+        length => (u64::from(length), 2),
+    };
+    let o_mask = if second & MASKED == 0 {
+        None
+    } else {
+        match bytes.get(header_len..header_len + 4) {
+            Some(mask) => {
+                header_len += 4;
+                Some(mask.try_into().unwrap(/*OK*/))
+            }
+            None => return Ok(None),
+        }
+    };
+    let remaining = usize::try_from(length)
+        .map_err(|_| invalid_data(format!("WebSocket frame of {length} bytes is too big")))?;
+    match opcode {
+        OPCODE_CONTINUATION | OPCODE_BINARY => {}
+        OPCODE_CLOSE | OPCODE_PING | OPCODE_PONG if remaining <= MAX_CONTROL_PAYLOAD => {}
+        OPCODE_TEXT => return Err(invalid_data("WebSocket text messages are not supported")),
+        _ => {
+            return Err(invalid_data(format!(
+                "invalid WebSocket frame (opcode {opcode}, {remaining} bytes)"
+            )));
+        }
+    }
+    Ok(Some((
+        Frame {
+            opcode,
+            remaining,
+            o_mask,
+            mask_pos: 0,
+        },
+        header_len,
+    )))
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> Error {
+    Error::new(ErrorKind::InvalidData, error)
+}
+
+// The request for upgrading an HTTP connection to WebSocket,
+// and the value of header field Sec-WebSocket-Accept that the response must have.
+fn upgrade_request(host_and_port: &str, path: &str) -> (Vec<u8>, String) {
+    let key = http::base64(&rand::random::<[u8; 16]>());
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: {host_and_port}\r\nUpgrade: websocket\r\n\
+         Connection: Upgrade\r\nSec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n\r\n"
+    );
+    (request.into_bytes(), accept_key(&key))
+}
+
+fn accept_key(key: &str) -> String {
+    let mut sha1 = Sha1::new();
+    sha1.update(key.as_bytes());
+    sha1.update(ACCEPT_GUID.as_bytes());
+    http::base64(&sha1.finalize())
+}
+
+fn evaluate_upgrade_response(header: &str, accept_key: &str) -> Result<()> {
+    match http::first_line(header) {
+        (_, Some("101")) => {}
+        (status_line, _) => {
+            return Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                format!("WebSocket upgrade was refused: {status_line}"),
+            ));
+        }
+    }
+    if http::field(header, "Sec-WebSocket-Accept") == Some(accept_key) {
+        Ok(())
+    } else {
+        Err(invalid_data(
+            "WebSocket upgrade response has a wrong Sec-WebSocket-Accept",
+        ))
+    }
+}
+
+// Upgrades the connection to WebSocket.
+#[cfg(feature = "sync")]
+pub(crate) fn connect_sync<S: std::io::Read + std::io::Write>(
+    mut stream: S,
+    host_and_port: &str,
+    path: &str,
+) -> Result<WebSocketStream<S>> {
+    debug!("Upgrading the connection to {host_and_port} to WebSocket, with path {path}");
+    let (request, accept_key) = upgrade_request(host_and_port, path);
+    stream.write_all(&request)?;
+    stream.flush()?;
+    evaluate_upgrade_response(&http::read_header_sync(&mut stream)?, &accept_key)?;
+    Ok(WebSocketStream::new(stream, true))
+}
+
+// Upgrades the connection to WebSocket.
+#[cfg(feature = "async")]
+pub(crate) async fn connect_async<S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin>(
+    mut stream: S,
+    host_and_port: &str,
+    path: &str,
+) -> Result<WebSocketStream<S>> {
+    use tokio::io::AsyncWriteExt;
+    debug!("Upgrading the connection to {host_and_port} to WebSocket, with path {path}");
+    let (request, accept_key) = upgrade_request(host_and_port, path);
+    stream.write_all(&request).await?;
+    stream.flush().await?;
+    evaluate_upgrade_response(&http::read_header_async(&mut stream).await?, &accept_key)?;
+    Ok(WebSocketStream::new(stream, true))
+}
+
+// Server side of the upgrade, for the mock server; requests with another path are rejected.
+#[cfg(feature = "mock_server")]
+pub(crate) fn accept_sync<S: std::io::Read + std::io::Write>(
+    mut stream: S,
+    path: &str,
+) -> Result<WebSocketStream<S>> {
+    let header = http::read_header_sync(&mut stream)?;
+    match (
+        http::first_line(&header).1,
+        http::field(&header, "Sec-WebSocket-Key"),
+    ) {
+        (Some(requested_path), Some(key)) if requested_path == path => {
+            stream.write_all(
+                format!(
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                     Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                    accept_key(key)
+                )
+                .as_bytes(),
+            )?;
+            stream.flush()?;
+            Ok(WebSocketStream::new(stream, false))
+        }
+        _ => {
+            stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")?;
+            stream.flush()?;
+            Err(Error::new(
+                ErrorKind::NotFound,
+                "WebSocket upgrade request with wrong path",
+            ))
+        }
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "mock_server"))]
+impl<S: std::io::Read + std::io::Write> WebSocketStream<S> {
+    fn write_frames_sync(&mut self) -> Result<()> {
+        self.inner
+            .write_all(&self.frames_out[self.frames_out_pos..])?;
+        self.frames_written();
+        self.inner.flush()
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "mock_server"))]
+impl<S: std::io::Read + std::io::Write> std::io::Read for WebSocketStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let o_n = self.decode(buf)?;
+            if !self.frames_out.is_empty() {
+                self.write_frames_sync()?;
+            }
+            if let Some(n) = o_n {
+                return Ok(n);
+            }
+            let start = self.prepare_raw_in();
+            match self.inner.read(&mut self.raw_in[start..]) {
+                Ok(n) => {
+                    self.raw_in_filled(n);
+                    if n == 0 {
+                        return Ok(0);
+                    }
+                }
+                Err(e) => {
+                    self.raw_in_filled(0);
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "mock_server"))]
+impl<S: std::io::Read + std::io::Write> std::io::Write for WebSocketStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.payload_out.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.queue_message();
+        self.write_frames_sync()
+    }
+}
+
+#[cfg(feature = "async")]
+mod a_sync {
+    use super::WebSocketStream;
+    use std::{
+        io::{Error, ErrorKind, Result},
+        pin::Pin,
+        task::{ready, Context, Poll},
+    };
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    impl<S: AsyncRead + AsyncWrite + Unpin> WebSocketStream<S> {
+        fn poll_write_frames(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            while self.frames_out_pos < self.frames_out.len() {
+                let n = ready!(Pin::new(&mut self.inner)
+                    .poll_write(cx, &self.frames_out[self.frames_out_pos..]))?;
+                if n == 0 {
+                    return Poll::Ready(Err(Error::from(ErrorKind::WriteZero)));
+                }
+                self.frames_out_pos += n;
+            }
+            self.frames_written();
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<usize>> {
+            let start = self.prepare_raw_in();
+            let mut read_buf = ReadBuf::new(&mut self.raw_in[start..]);
+            let result = Pin::new(&mut self.inner).poll_read(cx, &mut read_buf);
+            let n = read_buf.filled().len();
+            self.raw_in_filled(n);
+            result.map_ok(|()| n)
+        }
+    }
+
+    impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for WebSocketStream<S> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<Result<()>> {
+            let this = self.get_mut();
+            if buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            loop {
+                let o_n = this.decode(buf.initialize_unfilled())?;
+                // answers to pings are sent along the way, or with the next flush
+                if !this.frames_out.is_empty() {
+                    if let Poll::Ready(Err(e)) = this.poll_write_frames(cx) {
+                        return Poll::Ready(Err(e));
+                    }
+                }
+                if let Some(n) = o_n {
+                    buf.advance(n);
+                    return Poll::Ready(Ok(()));
+                }
+                if ready!(this.poll_fill(cx))? == 0 {
+                    return Poll::Ready(Ok(()));
+                }
+            }
+        }
+    }
+
+    impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for WebSocketStream<S> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize>> {
+            self.get_mut().payload_out.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            let this = self.get_mut();
+            this.queue_message();
+            ready!(this.poll_write_frames(cx))?;
+            Pin::new(&mut this.inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            ready!(self.as_mut().poll_flush(cx))?;
+            Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        accept_key, evaluate_upgrade_response, WebSocketStream, LENGTH_16_BIT, OPCODE_PING,
+    };
+    use std::io::{Cursor, Read, Write};
+
+    #[test]
+    fn test_accept_key() {
+        // the example of RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        let response = "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                        Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n";
+        assert!(evaluate_upgrade_response(response, "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=").is_ok());
+        assert!(evaluate_upgrade_response(response, "something else").is_err());
+        assert!(evaluate_upgrade_response("HTTP/1.1 404 Not Found\r\n\r\n", "").is_err());
+    }
+
+    // the frames that a client writes with the given payloads, one flush per payload
+    fn client_frames(payloads: &[&[u8]]) -> Vec<u8> {
+        let mut client = WebSocketStream::new(Cursor::new(Vec::<u8>::new()), true);
+        for payload in payloads {
+            client.write_all(payload).unwrap();
+            client.flush().unwrap();
+        }
+        client.inner.into_inner()
+    }
+
+    #[test]
+    fn test_frames() {
+        let big = vec![7_u8; 70_000];
+        let medium = vec![8_u8; 300];
+        let frames = client_frames(&[b"hello", &medium, &big]);
+        // one masked frame per flush, with 7-bit, 16-bit, and 64-bit lengths
+        assert_eq!(&frames[..2], &[0x82, 0x80 | 5]);
+        assert_eq!(&frames[11..15], &[0x82, 0x80 | LENGTH_16_BIT, 1, 44]);
+        assert_eq!(
+            frames.len(),
+            (2 + 4 + 5) + (4 + 4 + 300) + (10 + 4 + 70_000)
+        );
+
+        // the server sees the payloads as a single byte stream, also when it arrives byte-wise
+        let mut expected = b"hello".to_vec();
+        expected.extend_from_slice(&medium);
+        expected.extend_from_slice(&big);
+        let mut server = WebSocketStream::new(Cursor::new(frames.clone()), false);
+        let mut received = Vec::new();
+        server.read_to_end(&mut received).unwrap();
+        assert_eq!(received, expected);
+
+        let mut server = WebSocketStream::new(Cursor::new(Vec::<u8>::new()), false);
+        let mut received = Vec::new();
+        let mut buf = [0_u8; 1000];
+        for byte in frames {
+            server.raw_in.push(byte);
+            while let Some(n) = server.decode(&mut buf).unwrap() {
+                received.extend_from_slice(&buf[..n]);
+            }
+        }
+        assert_eq!(received, expected);
+    }
+
+    #[test]
+    fn test_control_frames() {
+        // a ping between two data frames, and a close frame at the end
+        let mut input = vec![0x82, 2, b'a', b'b'];
+        input.extend_from_slice(&[0x80 | OPCODE_PING, 3, b'p', b'n', b'g']);
+        input.extend_from_slice(&[0x82, 1, b'c', 0x88, 2, 3, 232]);
+        input.extend_from_slice(&[0x82, 1, b'd']);
+
+        let input_len = input.len();
+        let mut client = WebSocketStream::new(Cursor::new(input), true);
+        let mut received = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"abc");
+
+        // the ping was answered with a masked pong, the close frame was echoed
+        let written = client.inner.into_inner();
+        let written = &written[input_len..]; // skip over what was read
+        assert_eq!(written[..2], [0x8A, 0x80 | 3]);
+        let mask = &written[2..6];
+        let pong: Vec<u8> = written[6..9]
+            .iter()
+            .zip(mask.iter().cycle())
+            .map(|(b, m)| b ^ m)
+            .collect();
+        assert_eq!(pong, b"png");
+        assert_eq!(written[9..11], [0x88, 0x80 | 2]);
+
+        // text messages are rejected
+        let mut client = WebSocketStream::new(Cursor::new(vec![0x81, 1, b'x']), true);
+        assert!(client.read(&mut [0_u8; 10]).is_err());
+    }
+}
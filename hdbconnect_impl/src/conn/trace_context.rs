@@ -0,0 +1,188 @@
+use crate::{usage_err, HdbResult};
+
+// The longest tracestate that is accepted; the W3C recommendation requires at least
+// this length to be propagated.
+const MAX_TRACESTATE_LENGTH: usize = 512;
+const MAX_TRACESTATE_MEMBERS: usize = 32;
+
+/// A W3C trace context, which links the statements that are executed on the server
+/// to a distributed trace of the application.
+///
+/// The trace context is sent to the server as the session variables `TRACEPARENT` and
+/// `TRACESTATE`, which are visible e.g. in the monitoring view `M_SESSION_CONTEXT`
+/// and allow correlating the server's traces with the trace of the application.
+///
+/// It can be set for a connection with `Connection::set_trace_context`,
+/// for a single prepared statement with `PreparedStatement::set_trace_context`,
+/// and for a single execution with `Connection::statement_with_trace_context`.
+/// Changed values are sent along with the next statement execution, so updating the trace
+/// context between statements does not cost an extra roundtrip.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceContext {
+    traceparent: String,
+    o_tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Creates a trace context from the value of a `traceparent` header.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if the value is not a valid `traceparent`
+    /// according to the W3C Trace Context recommendation.
+    pub fn new<S: AsRef<str>>(traceparent: S) -> HdbResult<Self> {
+        let traceparent = traceparent.as_ref();
+        validate_traceparent(traceparent).map_err(|reason| {
+            usage_err!("\"{traceparent}\" is not a valid traceparent: {reason}")
+        })?;
+        Ok(Self {
+            traceparent: traceparent.to_string(),
+            o_tracestate: None,
+        })
+    }
+
+    /// Adds the value of a `tracestate` header.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if the value is longer than 512 characters,
+    /// has more than 32 list members, or contains other than printable ASCII characters.
+    pub fn with_tracestate<S: AsRef<str>>(mut self, tracestate: S) -> HdbResult<Self> {
+        let tracestate = tracestate.as_ref();
+        if tracestate.len() > MAX_TRACESTATE_LENGTH {
+            return Err(usage_err!(
+                "the tracestate is longer than {MAX_TRACESTATE_LENGTH} characters"
+            ));
+        }
+        if !tracestate.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+            return Err(usage_err!(
+                "the tracestate \"{tracestate}\" contains invalid characters"
+            ));
+        }
+        if tracestate.split(',').count() > MAX_TRACESTATE_MEMBERS {
+            return Err(usage_err!(
+                "the tracestate has more than {MAX_TRACESTATE_MEMBERS} list members"
+            ));
+        }
+        self.o_tracestate = (!tracestate.trim().is_empty()).then(|| tracestate.to_string());
+        Ok(self)
+    }
+
+    /// The value of the `traceparent` header.
+    #[must_use]
+    pub fn traceparent(&self) -> &str {
+        &self.traceparent
+    }
+
+    /// The value of the `tracestate` header, if any.
+    #[must_use]
+    pub fn tracestate(&self) -> Option<&str> {
+        self.o_tracestate.as_deref()
+    }
+
+    /// The trace id, as 32 lowercase hex digits.
+    #[must_use]
+    pub fn trace_id(&self) -> &str {
+        &self.traceparent[3..35]
+    }
+
+    /// The id of the parent span, as 16 lowercase hex digits.
+    #[must_use]
+    pub fn parent_id(&self) -> &str {
+        &self.traceparent[36..52]
+    }
+}
+
+// version "-" trace-id "-" parent-id "-" trace-flags, all in lowercase hex;
+// versions after 00 can append further fields
+fn validate_traceparent(traceparent: &str) -> Result<(), &'static str> {
+    let is_hex = |s: &str| s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+    let is_zero = |s: &str| s.bytes().all(|b| b == b'0');
+
+    if !traceparent.is_ascii() {
+        return Err("the fields must consist of lowercase hex digits");
+    }
+    if traceparent.len() < 55 {
+        return Err("too short");
+    }
+    let (version, trace_id, parent_id, flags, rest) = (
+        &traceparent[0..2],
+        &traceparent[3..35],
+        &traceparent[36..52],
+        &traceparent[53..55],
+        &traceparent[55..],
+    );
+    if [2, 35, 52].iter().any(|&i| &traceparent[i..=i] != "-") {
+        return Err("the fields must be separated by dashes");
+    }
+    if ![version, trace_id, parent_id, flags]
+        .iter()
+        .all(|s| is_hex(s))
+    {
+        return Err("the fields must consist of lowercase hex digits");
+    }
+    if version == "ff" {
+        return Err("version ff is invalid");
+    }
+    if (version == "00" && !rest.is_empty()) || (!rest.is_empty() && !rest.starts_with('-')) {
+        return Err("unexpected characters after the trace flags");
+    }
+    if is_zero(trace_id) {
+        return Err("the trace id must not be all zeros");
+    }
+    if is_zero(parent_id) {
+        return Err("the parent id must not be all zeros");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::TraceContext;
+
+    const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    #[test]
+    fn test_trace_context() {
+        let trace_context = TraceContext::new(TRACEPARENT).unwrap();
+        assert_eq!(trace_context.traceparent(), TRACEPARENT);
+        assert_eq!(trace_context.trace_id(), "0af7651916cd43dd8448eb211c80319c");
+        assert_eq!(trace_context.parent_id(), "b7ad6b7169203331");
+        assert_eq!(trace_context.tracestate(), None);
+
+        // a future version can have more fields
+        TraceContext::new(format!("01{}-abc", &TRACEPARENT[2..])).unwrap();
+
+        for invalid in [
+            "",
+            &TRACEPARENT[1..],
+            &TRACEPARENT.to_uppercase(),
+            &TRACEPARENT.replace('-', "_"),
+            &format!("{TRACEPARENT}-abc"),
+            &format!("ff{}", &TRACEPARENT[2..]),
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+        ] {
+            assert!(TraceContext::new(invalid).is_err(), "{invalid}");
+        }
+
+        let with_state = trace_context
+            .clone()
+            .with_tracestate("rojo=00f067aa0ba902b7,congo=t61rcWkgMzE")
+            .unwrap();
+        assert_eq!(
+            with_state.tracestate(),
+            Some("rojo=00f067aa0ba902b7,congo=t61rcWkgMzE")
+        );
+        assert_eq!(
+            trace_context.clone().with_tracestate("").unwrap(),
+            trace_context
+        );
+        assert!(trace_context.clone().with_tracestate("a=\u{e4}").is_err());
+        assert!(trace_context
+            .clone()
+            .with_tracestate("a=b,".repeat(33))
+            .is_err());
+        assert!(trace_context.with_tracestate("a".repeat(513)).is_err());
+    }
+}
@@ -0,0 +1,459 @@
+// Recording of the messages that a connection exchanges with the server into a wire trace,
+// from which the session can be replayed offline (see `TcpClient::Replay`).
+//
+// A wire trace file starts with `MAGIC` and the format version (u16), followed by a record
+// for each message, with (all numbers are little-endian)
+//   - the direction (u8: 1 = request, 2 = reply),
+//   - the timestamp (u64: microseconds since the UNIX epoch),
+//   - the number of parts (u16), and for each part its offset and length (u32, u32)
+//     within the message, including the part header, excluding the padding,
+//   - the length of the message (u32), and the message itself.
+//
+// Messages are stored in uncompressed form. The fields of authentication parts, except
+// the user name and the method names, and the SQL text that follows the keyword PASSWORD,
+// are overwritten before a message is stored.
+use super::authentication::METHOD_NAMES;
+use crate::{
+    impl_err,
+    protocol::{parts::length_indicator, PartKind},
+    usage_err, HdbResult,
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Cursor, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const MAGIC: &[u8; 8] = b"HDBWIRE\0";
+const VERSION: u16 = 1;
+const FILE_EXTENSION: &str = "hdbwire";
+
+// Offsets in the message and segment header
+const VARPART_SIZE: usize = 12;
+const COMPRESSION: usize = 22;
+const UNCOMPRESSED_SIZE: usize = 24;
+const NO_OF_PARTS: usize = 40;
+const MESSAGE_TYPE: usize = 45;
+const HEADER_SIZE: usize = 56;
+const MESSAGE_HEADER_SIZE: usize = 32;
+const SEGMENT_HEADER_SIZE: usize = 24;
+const PART_HEADER_SIZE: usize = 16;
+
+const PACKET_OPTION_COMPRESS: u8 = 2;
+const PASSWORD: &[u8] = b"PASSWORD";
+
+static TRACE_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Request,
+    Reply,
+}
+impl Direction {
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Request => 1,
+            Self::Reply => 2,
+        }
+    }
+
+    fn from_u8(val: u8) -> HdbResult<Self> {
+        match val {
+            1 => Ok(Self::Request),
+            2 => Ok(Self::Reply),
+            _ => Err(impl_err!("invalid direction {val} in wire trace")),
+        }
+    }
+}
+
+// A message of a wire trace.
+#[derive(Debug)]
+pub(crate) struct WireRecord {
+    pub(crate) direction: Direction,
+    pub(crate) timestamp: SystemTime,
+    pub(crate) parts: Vec<(u32, u32)>,
+    pub(crate) message: Vec<u8>,
+}
+impl WireRecord {
+    // Prepares a message as it was sent or received for being stored.
+    fn new(direction: Direction, raw: &[u8]) -> HdbResult<Self> {
+        if raw.len() < HEADER_SIZE
+            || raw.len() != MESSAGE_HEADER_SIZE + read_u32_at(raw, VARPART_SIZE) as usize
+        {
+            return Err(impl_err!("incomplete message of {} bytes", raw.len()));
+        }
+        let mut message = raw[..HEADER_SIZE].to_vec();
+        if raw[COMPRESSION] == PACKET_OPTION_COMPRESS {
+            let parts = lz4_flex::block::decompress(
+                &raw[HEADER_SIZE..],
+                read_u32_at(raw, UNCOMPRESSED_SIZE) as usize,
+            )
+            .map_err(|e| impl_err!("decompression failed: {e}"))?;
+            message.extend_from_slice(&parts);
+            let varpart_size = u32::try_from(SEGMENT_HEADER_SIZE + parts.len())
+                .map_err(|_| impl_err!("message too big"))?;
+            message[VARPART_SIZE..VARPART_SIZE + 4].copy_from_slice(&varpart_size.to_le_bytes());
+            message[COMPRESSION..MESSAGE_HEADER_SIZE].fill(0);
+        } else {
+            message.extend_from_slice(&raw[HEADER_SIZE..]);
+        }
+        let parts = redact_parts(direction, &mut message)?;
+        Ok(Self {
+            direction,
+            timestamp: SystemTime::now(),
+            parts,
+            message,
+        })
+    }
+
+    // The message type of a request.
+    pub(crate) fn message_type(&self) -> Option<i8> {
+        match self.direction {
+            Direction::Request => request_message_type(&self.message),
+            Direction::Reply => None,
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn emit(&self, w: &mut dyn Write) -> std::io::Result<()> {
+        let micros = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
+        w.write_u8(self.direction.as_u8())?;
+        w.write_u64::<LittleEndian>(u64::try_from(micros).unwrap_or(u64::MAX))?;
+        w.write_u16::<LittleEndian>(self.parts.len() as u16)?;
+        for (offset, length) in &self.parts {
+            w.write_u32::<LittleEndian>(*offset)?;
+            w.write_u32::<LittleEndian>(*length)?;
+        }
+        w.write_u32::<LittleEndian>(self.message.len() as u32)?;
+        w.write_all(&self.message)?;
+        w.flush()
+    }
+
+    fn parse(rdr: &mut dyn Read) -> HdbResult<Option<Self>> {
+        let direction = match rdr.read_u8() {
+            Ok(val) => Direction::from_u8(val)?,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let timestamp = UNIX_EPOCH + Duration::from_micros(rdr.read_u64::<LittleEndian>()?);
+        let no_of_parts = rdr.read_u16::<LittleEndian>()?;
+        let mut parts = Vec::with_capacity(no_of_parts as usize);
+        for _ in 0..no_of_parts {
+            parts.push((
+                rdr.read_u32::<LittleEndian>()?,
+                rdr.read_u32::<LittleEndian>()?,
+            ));
+        }
+        let mut message = vec![0; rdr.read_u32::<LittleEndian>()? as usize];
+        rdr.read_exact(&mut message)?;
+        Ok(Some(Self {
+            direction,
+            timestamp,
+            parts,
+            message,
+        }))
+    }
+}
+
+// The message type of a request as it was sent, compressed or not.
+pub(crate) fn request_message_type(message: &[u8]) -> Option<i8> {
+    message.get(MESSAGE_TYPE).map(|b| i8::from_le_bytes([*b]))
+}
+
+// Reads all records of a wire trace file.
+pub(crate) fn read_wire_trace(path: &Path) -> HdbResult<Vec<WireRecord>> {
+    let mut rdr = BufReader::new(File::open(path)?);
+    let mut magic = [0_u8; 8];
+    rdr.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(usage_err!("{} is not a wire trace", path.display()));
+    }
+    let version = rdr.read_u16::<LittleEndian>()?;
+    if version != VERSION {
+        return Err(usage_err!(
+            "{} has the unsupported wire trace version {version}",
+            path.display()
+        ));
+    }
+    let mut records = Vec::new();
+    while let Some(record) = WireRecord::parse(&mut rdr)? {
+        records.push(record);
+    }
+    Ok(records)
+}
+
+// Writes the messages of a connection into a new file in the trace directory.
+#[derive(Debug)]
+pub(crate) struct WireRecorder {
+    path: PathBuf,
+    w: BufWriter<File>,
+}
+impl WireRecorder {
+    pub(crate) fn try_new(dir: &Path) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "hdbconnect_{}_{}_{}.{FILE_EXTENSION}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+            std::process::id(),
+            TRACE_FILE_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut w = BufWriter::new(File::create(&path)?);
+        w.write_all(MAGIC)?;
+        w.write_u16::<LittleEndian>(VERSION)?;
+        w.flush()?;
+        debug!("recording the wire trace into {}", path.display());
+        Ok(Self { path, w })
+    }
+
+    // Failures are logged, but do not affect the connection.
+    pub(crate) fn record(&mut self, direction: Direction, message: &[u8]) {
+        if let Err(e) =
+            WireRecord::new(direction, message).and_then(|record| Ok(record.emit(&mut self.w)?))
+        {
+            warn!(
+                "message of {} bytes not recorded in {}: {e}",
+                message.len(),
+                self.path.display()
+            );
+        }
+    }
+}
+
+// Finds the parts of an uncompressed message, and overwrites the confidential data.
+fn redact_parts(direction: Direction, message: &mut [u8]) -> HdbResult<Vec<(u32, u32)>> {
+    let no_of_parts = u16::from_le_bytes([message[NO_OF_PARTS], message[NO_OF_PARTS + 1]]);
+    let mut parts = Vec::with_capacity(no_of_parts as usize);
+    let mut offset = HEADER_SIZE;
+    for _ in 0..no_of_parts {
+        if offset + PART_HEADER_SIZE > message.len() {
+            return Err(impl_err!("part header at {offset} exceeds the message"));
+        }
+        let kind = PartKind::from_i8(i8::from_le_bytes([message[offset]]));
+        let body_start = offset + PART_HEADER_SIZE;
+        let body_end = body_start + read_u32_at(message, offset + 8) as usize;
+        if body_end > message.len() {
+            return Err(impl_err!("part at {offset} exceeds the message"));
+        }
+        parts.push((
+            u32::try_from(offset).map_err(|_| impl_err!("message too big"))?,
+            u32::try_from(body_end - offset).map_err(|_| impl_err!("part too big"))?,
+        ));
+
+        let body = &mut message[body_start..body_end];
+        match (kind, direction) {
+            (Ok(PartKind::Authentication), _) => redact_auth_fields(direction, body)?,
+            (Ok(PartKind::Command), Direction::Request) => redact_password(body),
+            _ => {}
+        }
+        offset = body_end + (8 - (body_end - body_start) % 8) % 8;
+    }
+    Ok(parts)
+}
+
+// Overwrites all authentication fields with zeros, except for the user name
+// (the first field of a request) and the method names.
+fn redact_auth_fields(direction: Direction, body: &mut [u8]) -> HdbResult<()> {
+    let mut rdr = Cursor::new(&*body);
+    let field_count = rdr.read_u16::<LittleEndian>()?;
+    let mut redacted = Vec::new();
+    for i in 0..field_count {
+        let length = length_indicator::parse(rdr.read_u8()?, &mut rdr)?;
+        let start = usize::try_from(rdr.position()).unwrap(/*OK*/);
+        let field = rdr
+            .get_ref()
+            .get(start..start + length)
+            .ok_or_else(|| impl_err!("authentication field exceeds the part"))?;
+        let is_user = i == 0 && direction == Direction::Request;
+        if !is_user && !METHOD_NAMES.iter().any(|name| name.as_bytes() == field) {
+            redacted.push(start..start + length);
+        }
+        rdr.set_position((start + length) as u64);
+    }
+    for range in redacted {
+        body[range].fill(0);
+    }
+    Ok(())
+}
+
+// Overwrites the SQL text after the keyword PASSWORD, if it occurs, with '*'.
+fn redact_password(command: &mut [u8]) {
+    if let Some(pos) = command
+        .windows(PASSWORD.len())
+        .position(|w| w.eq_ignore_ascii_case(PASSWORD))
+    {
+        command[pos + PASSWORD.len()..].fill(b'*');
+    }
+}
+
+fn read_u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap(/*OK*/))
+}
+
+// A reader or a writer that copies the transferred bytes, if a copy is requested.
+pub(crate) struct Tee<'a, T: ?Sized> {
+    inner: &'a mut T,
+    o_copy: Option<&'a mut Vec<u8>>,
+}
+impl<'a, T: ?Sized> Tee<'a, T> {
+    pub(crate) fn new(inner: &'a mut T, o_copy: Option<&'a mut Vec<u8>>) -> Self {
+        Self { inner, o_copy }
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: Read + ?Sized> Read for Tee<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        if let Some(ref mut copy) = self.o_copy {
+            copy.extend_from_slice(&buf[..count]);
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: Write + ?Sized> Write for Tee<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let count = self.inner.write(buf)?;
+        if let Some(ref mut copy) = self.o_copy {
+            copy.extend_from_slice(&buf[..count]);
+        }
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: tokio::io::AsyncRead + Unpin + ?Sized> tokio::io::AsyncRead for Tee<'_, T> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let already_filled = buf.filled().len();
+        let poll = std::pin::Pin::new(&mut *this.inner).poll_read(cx, buf);
+        if let (std::task::Poll::Ready(Ok(())), Some(ref mut copy)) = (&poll, &mut this.o_copy) {
+            copy.extend_from_slice(&buf.filled()[already_filled..]);
+        }
+        poll
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: tokio::io::AsyncWrite + Unpin + ?Sized> tokio::io::AsyncWrite for Tee<'_, T> {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = std::pin::Pin::new(&mut *this.inner).poll_write(cx, buf);
+        if let (std::task::Poll::Ready(Ok(count)), Some(ref mut copy)) = (&poll, &mut this.o_copy) {
+            copy.extend_from_slice(&buf[..*count]);
+        }
+        poll
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut *self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut *self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_u32_at, Direction, WireRecord, HEADER_SIZE};
+
+    // A request with an authentication part and a command part
+    fn request(compressed: bool) -> Vec<u8> {
+        let mut auth = Vec::new();
+        auth.extend_from_slice(&3_u16.to_le_bytes());
+        for field in [&b"ME"[..], b"SCRAMSHA256", b"secret challenge"] {
+            auth.push(u8::try_from(field.len()).unwrap());
+            auth.extend_from_slice(field);
+        }
+        let command = b"alter user ME password \"Secret1\"".to_vec();
+
+        let mut parts = Vec::new();
+        for (kind, body) in [(33_u8, auth), (3, command)] {
+            parts.push(kind);
+            parts.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0]);
+            parts.extend_from_slice(&u32::try_from(body.len()).unwrap().to_le_bytes());
+            parts.extend_from_slice(&[0; 4]);
+            parts.extend_from_slice(&body);
+            parts.resize(parts.len() + (8 - body.len() % 8) % 8, 0);
+        }
+        let uncompressed_size = u32::try_from(24 + parts.len()).unwrap();
+        let body = if compressed {
+            lz4_flex::block::compress(&parts)
+        } else {
+            parts
+        };
+
+        let mut message = vec![0; HEADER_SIZE];
+        let varpart_size = u32::try_from(24 + body.len()).unwrap();
+        message[12..16].copy_from_slice(&varpart_size.to_le_bytes());
+        message[16..20].copy_from_slice(&uncompressed_size.to_le_bytes());
+        if compressed {
+            message[22] = 2;
+            message[24..28].copy_from_slice(&uncompressed_size.to_le_bytes());
+        }
+        message[40] = 2; // number of parts
+        message[45] = 2; // message type ExecuteDirect
+        message.extend_from_slice(&body);
+        message
+    }
+
+    #[test]
+    fn test_wire_record() {
+        let uncompressed = WireRecord::new(Direction::Request, &request(false)).unwrap();
+        let decompressed = WireRecord::new(Direction::Request, &request(true)).unwrap();
+        assert_eq!(decompressed.message, uncompressed.message);
+        assert_eq!(decompressed.parts, vec![(56, 50), (112, 48)]);
+        assert_eq!(decompressed.message_type(), Some(2));
+        assert_eq!(
+            read_u32_at(&decompressed.message, 12) as usize,
+            decompressed.message.len() - 32
+        );
+
+        let message = &decompressed.message;
+        let text = String::from_utf8_lossy(message);
+        assert!(text.contains("ME"));
+        assert!(text.contains("SCRAMSHA256"));
+        assert!(!text.contains("secret challenge"));
+        assert!(text.contains("alter user ME password**********"));
+        assert!(!text.contains("Secret1"));
+
+        let mut buffer = Vec::new();
+        decompressed.emit(&mut buffer).unwrap();
+        let parsed = WireRecord::parse(&mut &buffer[..]).unwrap().unwrap();
+        assert_eq!(parsed.direction, Direction::Request);
+        assert_eq!(parsed.parts, decompressed.parts);
+        assert_eq!(&parsed.message, message);
+
+        assert!(WireRecord::new(Direction::Request, &request(false)[..80]).is_err());
+    }
+}
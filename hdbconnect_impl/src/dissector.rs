@@ -0,0 +1,728 @@
+//! Decoding of captured HANA wire protocol traffic, for diagnosis.
+//!
+//! A [`Dissector`] splits a captured byte stream into the messages that client and server
+//! exchanged, and decodes them with the protocol implementation of the driver:
+//! message and segment headers, the parts with their kinds and attributes, option parts,
+//! and the rows of result sets and of parameters. Compressed messages are decompressed.
+//! Parts that the driver does not interpret, like `SessionVariable`, `WorkloadReplayContext`
+//! or `SQLReplyOptions`, are shown as annotated hex dumps.
+//!
+//! The dissector keeps track of prepared statements and result sets, because their metadata
+//! are needed to decode parameter rows and result set rows; these parts are shown as hex dumps
+//! if the statement was prepared before the capture started.
+//!
+//! The results implement `Display`, for a human-readable form, and `Serialize`, e.g. for JSON.
+//!
+//! This module is used by the `hdbdump` tool, which reads pcap files and hex dumps.
+mod hex;
+mod parts;
+
+use crate::{
+    conn::{read_wire_trace, AmConnCore},
+    impl_err,
+    protocol::{
+        parse_reply_packet_header, parse_request_packet_header, parts::ParameterDescriptors,
+        MessageType, PartKind,
+    },
+    HdbResult, ResultSetMetadata,
+};
+use parts::{PartContext, RawPart};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    path::Path,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub use parts::{DissectedOption, DissectedPart, PartContent};
+
+// Offsets in the message and segment header
+const VARPART_SIZE: usize = 12;
+const NO_OF_SEGMENTS: usize = 20;
+const COMPRESSION: usize = 22;
+const SEGMENT_KIND: usize = 44;
+const MESSAGE_HEADER_SIZE: usize = 32;
+const PACKET_HEADER_SIZE: usize = 56;
+
+// The initial request starts with a filler of four 0xff bytes
+const INITIAL_REQUEST_SIZE: usize = 14;
+const INITIAL_REQUEST_START: [u8; 4] = [0xff; 4];
+const INITIAL_REPLY_SIZE: usize = 8;
+
+/// Decodes the messages of a connection.
+///
+/// Use a separate instance for each connection, since the metadata of prepared statements
+/// and result sets are tracked across the messages.
+#[derive(Debug)]
+pub struct Dissector {
+    am_conn_core: AmConnCore,
+    statements: HashMap<u64, Statement>,
+    result_sets: HashMap<u64, Arc<ResultSetMetadata>>,
+    o_request: Option<RequestContext>,
+}
+
+#[derive(Debug)]
+struct Statement {
+    o_a_rsmd: Option<Arc<ResultSetMetadata>>,
+    o_a_descriptors: Option<Arc<ParameterDescriptors>>,
+}
+
+// What a reply may need to know about its request.
+#[derive(Debug)]
+struct RequestContext {
+    message_type: MessageType,
+    o_statement_id: Option<u64>,
+    o_result_set_id: Option<u64>,
+}
+
+impl Dissector {
+    /// Creates a dissector for a new connection.
+    ///
+    /// # Errors
+    ///
+    /// Only `HdbError::Impl` in unexpected situations.
+    pub fn new() -> HdbResult<Self> {
+        Ok(Self {
+            am_conn_core: AmConnCore::new_offline()?,
+            statements: HashMap::new(),
+            result_sets: HashMap::new(),
+            o_request: None,
+        })
+    }
+
+    /// Decodes the byte stream of a connection.
+    ///
+    /// The stream can contain the messages of both directions, in the order in which they
+    /// were sent, or only those of one direction, and it can start with the initial request
+    /// or the initial reply. Bytes that do not belong to a message are reported as
+    /// [`DissectedItem::Undecodable`], and decoding continues at the next message header.
+    pub fn dissect_stream(&mut self, stream: &[u8]) -> Vec<Dissected> {
+        let mut result = Vec::<Dissected>::new();
+        let mut o_garbage_start = None;
+        let mut pos = 0;
+        while pos < stream.len() {
+            let rest = &stream[pos..];
+            let at_start = result
+                .iter()
+                .all(|d| matches!(d.item, DissectedItem::InitialRequest { .. }));
+            let (length, item) = if result.is_empty()
+                && rest.len() >= INITIAL_REQUEST_SIZE
+                && rest.starts_with(&INITIAL_REQUEST_START)
+            {
+                (
+                    INITIAL_REQUEST_SIZE,
+                    DissectedItem::InitialRequest {
+                        hex: hex::dump(&rest[..INITIAL_REQUEST_SIZE]),
+                    },
+                )
+            } else if let Some(length) = message_length(rest) {
+                if length > rest.len() {
+                    (
+                        rest.len(),
+                        DissectedItem::Undecodable {
+                            reason: format!(
+                                "incomplete message: {length} bytes announced, {} bytes available",
+                                rest.len()
+                            ),
+                            hex: hex::dump(rest),
+                        },
+                    )
+                } else {
+                    (length, self.dissect_message(&rest[..length]))
+                }
+            } else if at_start
+                && o_garbage_start.is_none()
+                && rest.len() > INITIAL_REPLY_SIZE
+                && message_length(&rest[INITIAL_REPLY_SIZE..]).is_some()
+            {
+                (
+                    INITIAL_REPLY_SIZE,
+                    DissectedItem::InitialReply {
+                        hex: hex::dump(&rest[..INITIAL_REPLY_SIZE]),
+                    },
+                )
+            } else {
+                o_garbage_start.get_or_insert(pos);
+                pos += 1;
+                continue;
+            };
+
+            if let Some(start) = o_garbage_start.take() {
+                result.push(Dissected::undecodable(start, &stream[start..pos]));
+            }
+            result.push(Dissected {
+                offset: pos,
+                timestamp: None,
+                length,
+                item,
+            });
+            pos += length;
+        }
+        if let Some(start) = o_garbage_start {
+            result.push(Dissected::undecodable(start, &stream[start..]));
+        }
+        result
+    }
+
+    /// Decodes a wire trace, as it is written by connections for which
+    /// `ConnectionConfiguration::set_wire_trace_dir` was used.
+    ///
+    /// The offsets refer to the concatenation of the recorded messages.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Usage` if the file is not a wire trace, `HdbError::Io` if it cannot be read.
+    pub fn dissect_wire_trace(&mut self, path: &Path) -> HdbResult<Vec<Dissected>> {
+        let mut offset = 0;
+        Ok(read_wire_trace(path)?
+            .into_iter()
+            .map(|record| {
+                let mut dissected = Dissected {
+                    offset,
+                    timestamp: None,
+                    length: record.message.len(),
+                    item: self.dissect_message(&record.message),
+                };
+                dissected.set_timestamp(record.timestamp);
+                offset += record.message.len();
+                dissected
+            })
+            .collect())
+    }
+
+    /// Decodes a single, complete message.
+    pub fn dissect_message(&mut self, message: &[u8]) -> DissectedItem {
+        match message.get(SEGMENT_KIND) {
+            Some(1) => self.dissect_request(message).map(DissectedItem::Request),
+            Some(_) => self.dissect_reply(message).map(DissectedItem::Reply),
+            None => Err(impl_err!("message too short")),
+        }
+        .unwrap_or_else(|e| DissectedItem::Undecodable {
+            reason: e.to_string(),
+            hex: hex::dump(message),
+        })
+    }
+
+    fn dissect_request(&mut self, message: &[u8]) -> HdbResult<DissectedMessage> {
+        let header = parse_request_packet_header(&mut Cursor::new(message))?;
+        let (buffer, o_compressed_size) =
+            part_buffer(message, header.part_buffer_size, header.o_uncompressed_size)?;
+        let (raw_parts, notes) = RawPart::split(usize::from(header.no_of_parts), &buffer);
+
+        let context = RequestContext {
+            message_type: header.message_type,
+            o_statement_id: raw_parts
+                .iter()
+                .find_map(|raw| raw.id_of_kind(PartKind::StatementId)),
+            o_result_set_id: raw_parts
+                .iter()
+                .find_map(|raw| raw.id_of_kind(PartKind::ResultSetId)),
+        };
+        let o_statement = context
+            .o_statement_id
+            .and_then(|id| self.statements.get(&id));
+        let part_context = PartContext {
+            am_conn_core: &self.am_conn_core,
+            o_a_rsmd: None,
+            o_a_descriptors: o_statement.and_then(|stmt| stmt.o_a_descriptors.as_ref()),
+        };
+        let parts = raw_parts
+            .iter()
+            .map(|raw| raw.dissect(&part_context))
+            .collect();
+
+        match context.message_type {
+            MessageType::DropStatementId => {
+                context.o_statement_id.map(|id| self.statements.remove(&id));
+            }
+            MessageType::CloseResultSet => {
+                context
+                    .o_result_set_id
+                    .map(|id| self.result_sets.remove(&id));
+            }
+            _ => {}
+        }
+        self.o_request = Some(context);
+
+        Ok(DissectedMessage {
+            session_id: header.session_id,
+            packet_seq_number: i64::from(header.packet_seq_number),
+            o_compressed_size,
+            segment: DissectedSegment {
+                kind: SegmentKind::Request,
+                function: format!("{:?}", header.message_type),
+                o_auto_commit: Some(header.auto_commit),
+                o_command_options: Some(format!("{:?}", header.command_options)),
+                parts,
+            },
+            notes,
+        })
+    }
+
+    fn dissect_reply(&mut self, message: &[u8]) -> HdbResult<DissectedMessage> {
+        let header = parse_reply_packet_header(&mut Cursor::new(message))?;
+        let (buffer, o_compressed_size) =
+            part_buffer(message, header.part_buffer_size, header.o_uncompressed_size)?;
+        #[allow(clippy::cast_sign_loss)]
+        let (raw_parts, notes) = RawPart::split(header.no_of_parts.max(0) as usize, &buffer);
+
+        // metadata that are contained in the reply itself, or are known from the request
+        let mut o_a_rsmd = None;
+        let mut o_a_descriptors = None;
+        for raw in &raw_parts {
+            match raw.kind() {
+                Some(PartKind::ResultSetMetadata) => {
+                    o_a_rsmd = raw.parse_result_set_metadata().map(Arc::new);
+                }
+                Some(PartKind::ParameterMetadata) => {
+                    o_a_descriptors = raw.parse_parameter_descriptors().map(Arc::new);
+                }
+                _ => {}
+            }
+        }
+        let o_request = self.o_request.take();
+        let o_statement = o_request
+            .as_ref()
+            .and_then(|request| request.o_statement_id)
+            .and_then(|id| self.statements.get(&id));
+        let o_a_rsmd_for_rows = o_a_rsmd
+            .clone()
+            .or_else(|| o_statement.and_then(|stmt| stmt.o_a_rsmd.clone()))
+            .or_else(|| {
+                o_request
+                    .as_ref()
+                    .and_then(|request| request.o_result_set_id)
+                    .and_then(|id| self.result_sets.get(&id).cloned())
+            });
+        let o_a_descriptors_for_output = o_a_descriptors
+            .clone()
+            .or_else(|| o_statement.and_then(|stmt| stmt.o_a_descriptors.clone()));
+
+        let part_context = PartContext {
+            am_conn_core: &self.am_conn_core,
+            o_a_rsmd: o_a_rsmd_for_rows.as_ref(),
+            o_a_descriptors: o_a_descriptors_for_output.as_ref(),
+        };
+        let parts = raw_parts
+            .iter()
+            .map(|raw| raw.dissect(&part_context))
+            .collect();
+
+        if let Some(statement_id) = raw_parts
+            .iter()
+            .find_map(|raw| raw.id_of_kind(PartKind::StatementId))
+        {
+            self.statements.insert(
+                statement_id,
+                Statement {
+                    o_a_rsmd,
+                    o_a_descriptors,
+                },
+            );
+        }
+        if let (Some(result_set_id), Some(a_rsmd)) = (
+            raw_parts
+                .iter()
+                .find_map(|raw| raw.id_of_kind(PartKind::ResultSetId)),
+            o_a_rsmd_for_rows,
+        ) {
+            self.result_sets.insert(result_set_id, a_rsmd);
+        }
+
+        Ok(DissectedMessage {
+            session_id: header.session_id,
+            packet_seq_number: i64::from(header.packet_seq_number),
+            o_compressed_size,
+            segment: DissectedSegment {
+                kind: if header.is_error {
+                    SegmentKind::Error
+                } else {
+                    SegmentKind::Reply
+                },
+                function: format!("{:?}", header.reply_type),
+                o_auto_commit: None,
+                o_command_options: None,
+                parts,
+            },
+            notes,
+        })
+    }
+}
+
+// The length of the message that starts with the given bytes,
+// if they look like a message header.
+fn message_length(bytes: &[u8]) -> Option<usize> {
+    if bytes.len() < PACKET_HEADER_SIZE
+        || i16::from_le_bytes([bytes[NO_OF_SEGMENTS], bytes[NO_OF_SEGMENTS + 1]]) != 1
+        || !matches!(bytes[COMPRESSION], 0 | 2)
+        || !matches!(bytes[SEGMENT_KIND], 1 | 2 | 5)
+    {
+        return None;
+    }
+    let varpart_size = u32::from_le_bytes(bytes[VARPART_SIZE..VARPART_SIZE + 4]
+            .try_into()
+            .unwrap(/*OK*/)) as usize;
+    (varpart_size >= PACKET_HEADER_SIZE - MESSAGE_HEADER_SIZE)
+        .then_some(MESSAGE_HEADER_SIZE + varpart_size)
+}
+
+// The parts of a message, decompressed if necessary,
+// and the compressed size if the message was compressed.
+fn part_buffer(
+    message: &[u8],
+    part_buffer_size: usize,
+    o_uncompressed_size: Option<usize>,
+) -> HdbResult<(Vec<u8>, Option<usize>)> {
+    let parts = message
+        .get(PACKET_HEADER_SIZE..PACKET_HEADER_SIZE + part_buffer_size)
+        .ok_or_else(|| impl_err!("the header announces more parts than the message contains"))?;
+    match o_uncompressed_size {
+        Some(uncompressed_size) => Ok((
+            lz4_flex::block::decompress(parts, uncompressed_size)
+                .map_err(|e| impl_err!("decompression failed: {e}"))?,
+            Some(part_buffer_size),
+        )),
+        None => Ok((parts.to_vec(), None)),
+    }
+}
+
+/// A section of the decoded byte stream.
+#[derive(Debug, Serialize)]
+pub struct Dissected {
+    /// The position in the byte stream.
+    pub offset: usize,
+    /// The time of capture, if known, in RFC 3339 format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// The number of bytes.
+    pub length: usize,
+    /// What was found.
+    #[serde(flatten)]
+    pub item: DissectedItem,
+}
+impl Dissected {
+    fn undecodable(offset: usize, bytes: &[u8]) -> Self {
+        Self {
+            offset,
+            timestamp: None,
+            length: bytes.len(),
+            item: DissectedItem::Undecodable {
+                reason: "no message header found".to_string(),
+                hex: hex::dump(bytes),
+            },
+        }
+    }
+
+    /// Sets the time of capture.
+    pub fn set_timestamp(&mut self, timestamp: SystemTime) {
+        self.timestamp = time::OffsetDateTime::from(timestamp)
+            .format(&time::format_description::well_known::Rfc3339)
+            .ok()
+            .or_else(|| {
+                timestamp
+                    .duration_since(UNIX_EPOCH)
+                    .ok()
+                    .map(|d| format!("{}.{:06}", d.as_secs(), d.subsec_micros()))
+            });
+    }
+}
+
+/// The content of a section of the decoded byte stream.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DissectedItem {
+    /// The bytes with which the client starts the communication.
+    InitialRequest {
+        /// The bytes, as hex dump.
+        hex: Vec<String>,
+    },
+    /// The bytes with which the server answers the initial request.
+    InitialReply {
+        /// The bytes, as hex dump.
+        hex: Vec<String>,
+    },
+    /// A request message.
+    Request(DissectedMessage),
+    /// A reply message.
+    Reply(DissectedMessage),
+    /// Bytes that could not be decoded.
+    Undecodable {
+        /// The reason.
+        reason: String,
+        /// The bytes, as hex dump.
+        hex: Vec<String>,
+    },
+}
+
+/// A decoded message.
+#[derive(Debug, Serialize)]
+pub struct DissectedMessage {
+    /// The session id from the message header.
+    pub session_id: i64,
+    /// The packet sequence number from the message header.
+    pub packet_seq_number: i64,
+    /// The size of the compressed parts, if the message was compressed.
+    #[serde(rename = "compressed_size", skip_serializing_if = "Option::is_none")]
+    pub o_compressed_size: Option<usize>,
+    /// The (only) segment of the message.
+    pub segment: DissectedSegment,
+    /// Inconsistencies that were found.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// A decoded segment.
+#[derive(Debug, Serialize)]
+pub struct DissectedSegment {
+    /// The segment kind.
+    pub kind: SegmentKind,
+    /// The message type of a request, or the function code of a reply.
+    pub function: String,
+    /// Whether the request is to be committed automatically.
+    #[serde(rename = "auto_commit", skip_serializing_if = "Option::is_none")]
+    pub o_auto_commit: Option<bool>,
+    /// The command options of the request.
+    #[serde(rename = "command_options", skip_serializing_if = "Option::is_none")]
+    pub o_command_options: Option<String>,
+    /// The parts of the segment.
+    pub parts: Vec<DissectedPart>,
+}
+
+/// The kind of a segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    /// A request.
+    Request,
+    /// A regular reply.
+    Reply,
+    /// A reply that reports an error.
+    Error,
+}
+
+impl std::fmt::Display for Dissected {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "@{:#08x}", self.offset)?;
+        if let Some(ref timestamp) = self.timestamp {
+            write!(f, " {timestamp}")?;
+        }
+        write!(f, " ({} bytes) ", self.length)?;
+        match self.item {
+            DissectedItem::InitialRequest { ref hex } => {
+                writeln!(f, "initial request")?;
+                hex::write_indented(hex, f)
+            }
+            DissectedItem::InitialReply { ref hex } => {
+                writeln!(f, "initial reply")?;
+                hex::write_indented(hex, f)
+            }
+            DissectedItem::Request(ref message) => write!(f, "REQUEST {message}"),
+            DissectedItem::Reply(ref message) => write!(f, "REPLY {message}"),
+            DissectedItem::Undecodable {
+                ref reason,
+                ref hex,
+            } => {
+                writeln!(f, "undecodable: {reason}")?;
+                hex::write_indented(hex, f)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DissectedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "session_id = {}, packet_seq_number = {}",
+            self.session_id, self.packet_seq_number
+        )?;
+        if let Some(compressed_size) = self.o_compressed_size {
+            write!(f, ", parts compressed to {compressed_size} bytes")?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.segment)?;
+        for note in &self.notes {
+            writeln!(f, "    NOTE: {note}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for DissectedSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "    segment {:?}: {}", self.kind, self.function)?;
+        if let Some(auto_commit) = self.o_auto_commit {
+            write!(f, ", auto_commit = {auto_commit}")?;
+        }
+        if let Some(ref command_options) = self.o_command_options {
+            write!(f, ", command_options = {command_options}")?;
+        }
+        writeln!(f)?;
+        for part in &self.parts {
+            write!(f, "{part}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "mock_server"))]
+mod test {
+    use super::{
+        DissectedItem, Dissector, PartContent, COMPRESSION, MESSAGE_HEADER_SIZE,
+        PACKET_HEADER_SIZE, SEGMENT_KIND, VARPART_SIZE,
+    };
+    use crate::{
+        conn::read_wire_trace,
+        mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+        protocol::MessageType,
+        sync::Connection,
+        ConnectionConfiguration, HdbValue, TypeId,
+    };
+
+    const QUERY: &str = "select ID, NAME from ITEMS";
+
+    #[test]
+    fn test_dissect_wire_trace() {
+        let mock = MockServer::start().unwrap();
+        mock.on_statement(
+            QUERY,
+            MockResponse::ResultSet(
+                MockResultSet::new(vec![
+                    MockColumn::new("ID", TypeId::INT),
+                    MockColumn::new("NAME", TypeId::NVARCHAR),
+                ])
+                .with_rows(
+                    (1..=3).map(|i| vec![HdbValue::INT(i), HdbValue::STRING(format!("item {i}"))]),
+                ),
+            ),
+        );
+        let dir = std::env::temp_dir().join(format!("hdbconnect_dissector_{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        let config = ConnectionConfiguration::default()
+            .with_fetch_size(2)
+            .with_wire_trace_dir(Some(dir.clone()));
+        {
+            let connection = Connection::with_configuration(mock.url(), &config).unwrap();
+            let ids: Vec<(i32, String)> = connection
+                .prepare(QUERY)
+                .unwrap()
+                .execute(&())
+                .unwrap()
+                .into_result_set()
+                .unwrap()
+                .try_into()
+                .unwrap();
+            assert_eq!(ids.len(), 3);
+        }
+        let path = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+
+        let dissected = Dissector::new().unwrap().dissect_wire_trace(&path).unwrap();
+        let messages: Vec<Vec<u8>> = read_wire_trace(&path)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.message)
+            .collect();
+        std::fs::remove_dir_all(&dir).ok();
+        let text: String = dissected.iter().map(ToString::to_string).collect();
+        assert!(
+            dissected
+                .iter()
+                .all(|d| !matches!(d.item, DissectedItem::Undecodable { .. })),
+            "{text}"
+        );
+        assert!(text.contains("REQUEST"), "{text}");
+        assert!(text.contains("segment Request: Prepare"), "{text}");
+        assert!(text.contains(QUERY), "{text}");
+
+        // the rows of the result set are decoded
+        let rows: Vec<&Vec<String>> = dissected
+            .iter()
+            .filter_map(|d| match d.item {
+                DissectedItem::Reply(ref message) => Some(&message.segment.parts),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|part| match part.content {
+                PartContent::Rows { ref rows, .. } if part.kind == "ResultSet" => Some(rows),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        assert_eq!(rows.len(), 3, "{text}");
+        assert_eq!(rows[2], &vec!["3".to_string(), "item 3".to_string()]);
+
+        dissect_as_stream(&messages, dissected.len());
+    }
+
+    fn dissect_as_stream(messages: &[Vec<u8>], no_of_messages: usize) {
+        // the same messages as byte stream, after the initial request and reply,
+        // with the Prepare request in compressed form, and with some garbage in between
+        let mut stream = vec![0xff, 0xff, 0xff, 0xff, 4, 0, 0x14, 4, 0, 1, 0, 1, 1, 1];
+        stream.extend_from_slice(&[4, 20, 0, 4, 1, 0, 0, 0]);
+        for message in messages {
+            if message[SEGMENT_KIND] == 1 && message[45] == MessageType::Prepare as u8 {
+                stream.extend_from_slice(&compressed(message));
+                stream.extend_from_slice(b"garbage");
+            } else {
+                stream.extend_from_slice(message);
+            }
+        }
+        let from_stream = Dissector::new().unwrap().dissect_stream(&stream);
+        let kinds: Vec<&str> = from_stream
+            .iter()
+            .map(|d| match d.item {
+                DissectedItem::InitialRequest { .. } => "initial request",
+                DissectedItem::InitialReply { .. } => "initial reply",
+                DissectedItem::Request(ref message) if message.o_compressed_size.is_some() => {
+                    "compressed request"
+                }
+                DissectedItem::Request(_) => "request",
+                DissectedItem::Reply(_) => "reply",
+                DissectedItem::Undecodable { .. } => "undecodable",
+            })
+            .collect();
+        assert_eq!(
+            kinds[..9],
+            [
+                "initial request",
+                "initial reply",
+                "request",
+                "reply",
+                "request",
+                "reply",
+                "compressed request",
+                "undecodable",
+                "reply"
+            ]
+        );
+        assert_eq!(from_stream.len(), no_of_messages + 3);
+        assert_eq!(from_stream[7].length, b"garbage".len());
+        assert_eq!(
+            from_stream
+                .iter()
+                .map(ToString::to_string)
+                .collect::<String>()
+                .matches(QUERY)
+                .count(),
+            1
+        );
+    }
+
+    fn compressed(message: &[u8]) -> Vec<u8> {
+        let parts = lz4_flex::block::compress(&message[PACKET_HEADER_SIZE..]);
+        let varpart_size =
+            u32::try_from(parts.len() + PACKET_HEADER_SIZE - MESSAGE_HEADER_SIZE).unwrap();
+        let mut result = message[..PACKET_HEADER_SIZE].to_vec();
+        result[COMPRESSION] = 2;
+        result.copy_within(VARPART_SIZE..VARPART_SIZE + 4, 24);
+        result[VARPART_SIZE..VARPART_SIZE + 4].copy_from_slice(&varpart_size.to_le_bytes());
+        result.extend_from_slice(&parts);
+        result
+    }
+}
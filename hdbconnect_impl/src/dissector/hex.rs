@@ -0,0 +1,66 @@
+// Hex dumps in the usual form: offset, 16 bytes in hex, and the printable ASCII characters.
+
+use std::fmt::Write;
+
+// Longer byte sequences are cut off, to keep the output readable.
+const MAX_DUMP_SIZE: usize = 4096;
+const BYTES_PER_LINE: usize = 16;
+
+pub(super) fn dump(bytes: &[u8]) -> Vec<String> {
+    let shown = &bytes[..bytes.len().min(MAX_DUMP_SIZE)];
+    let mut lines: Vec<String> = shown
+        .chunks(BYTES_PER_LINE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut line = format!("{:04x} ", i * BYTES_PER_LINE);
+            for j in 0..BYTES_PER_LINE {
+                if j % 8 == 0 {
+                    line.push(' ');
+                }
+                match chunk.get(j) {
+                    Some(b) => write!(line, "{b:02x} ").unwrap(/*OK*/),
+                    None => line.push_str("   "),
+                }
+            }
+            line.push('|');
+            line.extend(chunk.iter().map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    char::from(*b)
+                } else {
+                    '.'
+                }
+            }));
+            line.push('|');
+            line
+        })
+        .collect();
+    if bytes.len() > shown.len() {
+        lines.push(format!("... {} more bytes", bytes.len() - shown.len()));
+    }
+    lines
+}
+
+pub(super) fn write_indented(lines: &[String], f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    for line in lines {
+        writeln!(f, "        {line}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_dump() {
+        let lines = super::dump(b"HANA\x00\x01 wire protocol");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "0000  48 41 4e 41 00 01 20 77  69 72 65 20 70 72 6f 74 |HANA.. wire prot|"
+        );
+        assert!(lines[1].starts_with("0010  6f 63 6f 6c "));
+        assert!(lines[1].ends_with("|ocol|"));
+
+        let lines = super::dump(&[0_u8; 5000]);
+        assert_eq!(lines.last().unwrap(), "... 904 more bytes");
+    }
+}
@@ -0,0 +1,783 @@
+use super::hex;
+use crate::{
+    base::Row,
+    conn::AmConnCore,
+    protocol::{
+        padsize,
+        parts::{
+            length_indicator, parse_parameter_rows, AuthFields, ClientContext, CommandInfo,
+            ConnectOptionsPart, DbConnectInfo, ExecutionResults, FetchOptions, LobFlags, OptionId,
+            OptionPart, OptionValue, OutputParameters, ParameterDescriptors, PartitionInformation,
+            ReadLobReply, ReadLobRequest, ResultSetMetadata, ServerError, SessionContext,
+            StatementContext, Topology, TransactionFlags, WriteLobReply, WriteLobRequest,
+        },
+        util, util_sync, PartAttributes, PartKind, PART_HEADER_SIZE,
+    },
+    HdbResult,
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::{
+    fmt::{Debug, Write},
+    hash::Hash,
+    io::Cursor,
+    sync::Arc,
+};
+
+// Longer authentication fields (challenges, proofs) are shown shortened.
+const MAX_AUTH_FIELD_HEX: usize = 16;
+
+// Fixed sizes of the structures whose declared lengths and offsets are checked before decoding.
+const FIELD_METADATA_SIZE: usize = 24;
+const PARAMETER_DESCRIPTOR_SIZE: usize = 16;
+const SERVER_ERROR_HEADER_SIZE: usize = 18;
+const EXECUTION_RESULT_SIZE: usize = 4;
+
+// What is known when a part is decoded.
+pub(super) struct PartContext<'a> {
+    pub am_conn_core: &'a AmConnCore,
+    pub o_a_rsmd: Option<&'a Arc<ResultSetMetadata>>,
+    pub o_a_descriptors: Option<&'a Arc<ParameterDescriptors>>,
+}
+
+// A part as it was found in the message, not yet decoded.
+pub(super) struct RawPart<'a> {
+    kind_id: i8,
+    attributes: u8,
+    count: usize,
+    body: &'a [u8],
+}
+impl<'a> RawPart<'a> {
+    // Splits the part buffer of a message into its parts;
+    // inconsistencies with the announced sizes are returned as notes.
+    pub fn split(no_of_parts: usize, buffer: &'a [u8]) -> (Vec<Self>, Vec<String>) {
+        let mut raw_parts = Vec::with_capacity(no_of_parts);
+        let mut notes = Vec::new();
+        let mut pos = 0;
+        for i in 0..no_of_parts {
+            let Some(header) = buffer.get(pos..pos + PART_HEADER_SIZE) else {
+                notes.push(format!(
+                    "{no_of_parts} parts announced, but only {i} parts found"
+                ));
+                return (raw_parts, notes);
+            };
+            let count_i16 = i16::from_le_bytes([header[2], header[3]]);
+            let count_i32 = i32::from_le_bytes(header[4..8].try_into().unwrap(/*OK*/));
+            let arg_size = i32::from_le_bytes(header[8..12].try_into().unwrap(/*OK*/));
+            #[allow(clippy::cast_sign_loss)]
+            let count = i32::from(count_i16).max(count_i32).max(0) as usize;
+            #[allow(clippy::cast_sign_loss)]
+            let arg_size = arg_size.max(0) as usize;
+
+            let body_start = pos + PART_HEADER_SIZE;
+            let body_end = body_start + arg_size;
+            if body_end > buffer.len() {
+                notes.push(format!(
+                    "part {i} announces {arg_size} bytes, but only {} bytes are available",
+                    buffer.len() - body_start
+                ));
+            }
+            #[allow(clippy::cast_possible_wrap)]
+            raw_parts.push(Self {
+                kind_id: header[0] as i8,
+                attributes: header[1],
+                count,
+                body: &buffer[body_start..body_end.min(buffer.len())],
+            });
+            pos = (body_end + padsize(arg_size)).min(buffer.len());
+        }
+        if pos < buffer.len() {
+            notes.push(format!(
+                "{} bytes follow after the last part",
+                buffer.len() - pos
+            ));
+        }
+        (raw_parts, notes)
+    }
+
+    pub fn kind(&self) -> Option<PartKind> {
+        PartKind::from_i8(self.kind_id).ok()
+    }
+
+    // The content of a StatementId or ResultSetId part.
+    pub fn id_of_kind(&self, kind: PartKind) -> Option<u64> {
+        (self.kind() == Some(kind))
+            .then(|| self.body.get(0..8))
+            .flatten()
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap(/*OK*/)))
+    }
+
+    pub fn parse_result_set_metadata(&self) -> Option<ResultSetMetadata> {
+        self.check_sizes(PartKind::ResultSetMetadata).ok()?;
+        ResultSetMetadata::parse(self.count, &mut self.reader()).ok()
+    }
+
+    pub fn parse_parameter_descriptors(&self) -> Option<ParameterDescriptors> {
+        self.check_sizes(PartKind::ParameterMetadata).ok()?;
+        ParameterDescriptors::parse(self.count, &mut self.reader()).ok()
+    }
+
+    // The driver's parsers trust the lengths and offsets in a part, and allocate accordingly;
+    // in a corrupt capture they are checked against the size of the part body first.
+    fn check_sizes(&self, kind: PartKind) -> Result<(), String> {
+        let fits = |item_size: usize| {
+            self.count
+                .checked_mul(item_size)
+                .filter(|size| *size <= self.body.len())
+                .map(|_| ())
+                .ok_or_else(|| {
+                    format!(
+                        "{} items of at least {item_size} bytes do not fit into {} bytes",
+                        self.count,
+                        self.body.len()
+                    )
+                })
+        };
+        match kind {
+            PartKind::ResultSetMetadata => {
+                fits(FIELD_METADATA_SIZE)?;
+                // the offsets of the table, schema, column, and display names
+                let names_size = self.body.len() - self.count * FIELD_METADATA_SIZE;
+                for (i, field) in self.body[..self.count * FIELD_METADATA_SIZE]
+                    .chunks_exact(FIELD_METADATA_SIZE)
+                    .enumerate()
+                {
+                    for offset in field[8..].chunks_exact(4) {
+                        let offset = u32::from_le_bytes(offset.try_into().unwrap(/*OK*/));
+                        if offset != u32::MAX && offset as usize >= names_size {
+                            return Err(format!(
+                                "field {i} refers to a name at offset {offset}, \
+                                 but the names have only {names_size} bytes"
+                            ));
+                        }
+                    }
+                }
+                Ok(())
+            }
+            PartKind::ParameterMetadata => fits(PARAMETER_DESCRIPTOR_SIZE),
+            PartKind::ExecutionResults => fits(EXECUTION_RESULT_SIZE),
+            PartKind::Parameters => fits(1),
+            PartKind::Error => {
+                let mut pos = 0;
+                for i in 0..self.count {
+                    let header = self
+                        .body
+                        .get(pos..pos + SERVER_ERROR_HEADER_SIZE)
+                        .ok_or_else(|| format!("error {i} exceeds the part"))?;
+                    let text_length = i32::from_le_bytes(header[8..12].try_into().unwrap(/*OK*/));
+                    // each error is padded to a multiple of 8 bytes, by 1 to 8 bytes
+                    let size = usize::try_from(text_length)
+                        .ok()
+                        .map(|text_length| SERVER_ERROR_HEADER_SIZE + text_length)
+                        .map(|size| size + 8 - size % 8)
+                        .filter(|size| pos + size <= self.body.len())
+                        .ok_or_else(|| {
+                            format!("error {i} has a text length of {text_length}, which exceeds the part")
+                        })?;
+                    pos += size;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn reader(&self) -> Cursor<Vec<u8>> {
+        Cursor::new(self.body.to_vec())
+    }
+
+    pub fn dissect(&self, part_context: &PartContext) -> DissectedPart {
+        let mut rdr = self.reader();
+        let mut notes = Vec::new();
+        let content = match self.kind() {
+            None => PartContent::hex(self.body, "unknown part kind".to_string()),
+            Some(kind) => match self
+                .check_sizes(kind)
+                .map(|()| self.decode(kind, part_context, &mut rdr))
+            {
+                Ok(Ok(content)) => {
+                    let rest = self.body.len().saturating_sub(rdr_position(&rdr));
+                    if rest > 0 && !matches!(content, PartContent::Hex { .. }) {
+                        notes.push(format!("{rest} bytes were not decoded"));
+                    }
+                    content
+                }
+                Ok(Err(e)) => PartContent::hex(self.body, format!("decoding failed: {e}")),
+                Err(inconsistency) => {
+                    PartContent::hex(self.body, format!("corrupt: {inconsistency}"))
+                }
+            },
+        };
+        DissectedPart {
+            kind: self.kind().map_or_else(
+                || format!("unknown ({})", self.kind_id),
+                |kind| format!("{kind:?}"),
+            ),
+            kind_id: self.kind_id,
+            attributes: format!("{:?}", PartAttributes::new(self.attributes)),
+            count: self.count,
+            size: self.body.len(),
+            content,
+            notes,
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn decode(
+        &self,
+        kind: PartKind,
+        context: &PartContext,
+        rdr: &mut Cursor<Vec<u8>>,
+    ) -> HdbResult<PartContent> {
+        let count = self.count;
+        Ok(match kind {
+            PartKind::Command => PartContent::text(&util::string_from_cesu8(
+                util_sync::parse_bytes(self.body.len(), rdr)?,
+            )?),
+            PartKind::ClientInfo => {
+                let mut options = Vec::new();
+                for _ in 0..count / 2 {
+                    let name = parse_length_and_string(rdr)?;
+                    let value = parse_length_and_string(rdr)?;
+                    options.push(DissectedOption { name, value });
+                }
+                PartContent::Options { options }
+            }
+            // the driver sends the client context with count 1, regardless of its length
+            PartKind::ClientContext => {
+                let mut client_context = ClientContext::parse(count, rdr)?;
+                while rdr_position(rdr) < self.body.len() {
+                    for (id, value) in ClientContext::parse(1, rdr)? {
+                        client_context.insert(id, value);
+                    }
+                }
+                options(client_context)
+            }
+            PartKind::CommandInfo => options(CommandInfo::parse(count, rdr)?),
+            PartKind::ConnectOptions => options(ConnectOptionsPart::parse(count, rdr)?),
+            PartKind::DbConnectInfo => options(DbConnectInfo::parse(count, rdr)?),
+            PartKind::FetchOptions => options(FetchOptions::parse(count, rdr)?),
+            PartKind::LobFlags => options(LobFlags::parse(count, rdr)?),
+            PartKind::SessionContext => options(SessionContext::parse(count, rdr)?),
+            PartKind::StatementContext => options(StatementContext::parse(count, rdr)?),
+            PartKind::TransactionFlags => options(TransactionFlags::parse(count, rdr)?),
+            PartKind::TopologyInformation => PartContent::Options {
+                options: Topology::parse(count, rdr)?
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, line)| {
+                        options_of(line.clone()).into_iter().map(
+                            move |DissectedOption { name, value }| DissectedOption {
+                                name: format!("[{i}] {name}"),
+                                value,
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+            PartKind::FetchSize => {
+                PartContent::text(&format!("{}", rdr.read_u32::<LittleEndian>()?))
+            }
+            PartKind::StatementId | PartKind::ResultSetId => {
+                PartContent::text(&format!("{:#018x}", rdr.read_u64::<LittleEndian>()?))
+            }
+            PartKind::TableLocation => {
+                let mut locations = Vec::new();
+                for _ in 0..count {
+                    locations.push(rdr.read_i32::<LittleEndian>()?.to_string());
+                }
+                PartContent::text(&locations.join(", "))
+            }
+            PartKind::Authentication => PartContent::Text {
+                lines: AuthFields::parse(rdr)?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| format!("[{i}] {}", auth_field(field)))
+                    .collect(),
+            },
+            PartKind::Error => PartContent::Text {
+                lines: ServerError::parse(count, rdr)?
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            },
+            PartKind::ExecutionResults => PartContent::Text {
+                lines: ExecutionResults::parse(count, rdr)?
+                    .into_iter()
+                    .map(|execution_result| execution_result.to_string())
+                    .collect(),
+            },
+            PartKind::ResultSetMetadata => PartContent::Text {
+                lines: ResultSetMetadata::parse(count, rdr)?
+                    .iter()
+                    .map(|fmd| {
+                        format!(
+                            "{}: {} ({}.{}.{}), precision {}, scale {}{}",
+                            fmd.displayname(),
+                            fmd.type_id(),
+                            fmd.schemaname(),
+                            fmd.tablename(),
+                            fmd.columnname(),
+                            fmd.precision(),
+                            fmd.scale(),
+                            if fmd.is_nullable() { ", nullable" } else { "" }
+                        )
+                    })
+                    .collect(),
+            },
+            PartKind::ParameterMetadata => PartContent::Text {
+                lines: ParameterDescriptors::parse(count, rdr)?
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            },
+            PartKind::Parameters => match context.o_a_descriptors {
+                Some(a_descriptors) => {
+                    let (rows, lob_placeholders) = parse_parameter_rows(count, a_descriptors, rdr)?;
+                    PartContent::Rows {
+                        columns: a_descriptors
+                            .iter_in()
+                            .map(|descriptor| descriptor.name().unwrap_or("?").to_string())
+                            .collect(),
+                        rows: rows
+                            .into_iter()
+                            .enumerate()
+                            .map(|(row_idx, row)| {
+                                row.into_iter()
+                                    .enumerate()
+                                    .map(|(col_idx, value)| {
+                                        if lob_placeholders
+                                            .iter()
+                                            .any(|(r, c, _)| *r == row_idx && *c == col_idx)
+                                        {
+                                            "<LOB, see WriteLob>".to_string()
+                                        } else {
+                                            value.to_string()
+                                        }
+                                    })
+                                    .collect()
+                            })
+                            .collect(),
+                    }
+                }
+                None => PartContent::hex(
+                    self.body,
+                    "parameter metadata unknown (statement prepared before the capture started?)"
+                        .to_string(),
+                ),
+            },
+            PartKind::ResultSet => match context.o_a_rsmd {
+                Some(a_rsmd) => {
+                    let mut rows = Vec::new();
+                    for _ in 0..count {
+                        rows.push(
+                            Row::parse_sync(Arc::clone(a_rsmd), &None, context.am_conn_core, rdr)?
+                                .map(|value| value.to_string())
+                                .collect(),
+                        );
+                    }
+                    PartContent::Rows {
+                        columns: a_rsmd
+                            .iter()
+                            .map(|fmd| fmd.displayname().to_string())
+                            .collect(),
+                        rows,
+                    }
+                }
+                None => PartContent::hex(
+                    self.body,
+                    "result set metadata unknown (query executed before the capture started?)"
+                        .to_string(),
+                ),
+            },
+            PartKind::OutputParameters => match context.o_a_descriptors {
+                Some(a_descriptors) => {
+                    let output_parameters = OutputParameters::parse_sync(
+                        Some(context.am_conn_core),
+                        a_descriptors,
+                        rdr,
+                    )?;
+                    let (descriptors, values) = output_parameters.as_descriptors_and_values();
+                    PartContent::Rows {
+                        columns: descriptors
+                            .iter()
+                            .map(|descriptor| descriptor.name().unwrap_or("?").to_string())
+                            .collect(),
+                        rows: vec![values.iter().map(ToString::to_string).collect()],
+                    }
+                }
+                None => PartContent::hex(
+                    self.body,
+                    "parameter metadata unknown (statement prepared before the capture started?)"
+                        .to_string(),
+                ),
+            },
+            PartKind::ReadLobRequest => {
+                let request = ReadLobRequest::parse(rdr)?;
+                PartContent::text(&format!(
+                    "locator_id = {:#018x}, offset = {}, length = {}",
+                    request.locator_id(),
+                    request.offset(),
+                    request.length()
+                ))
+            }
+            PartKind::ReadLobReply => {
+                let reply = ReadLobReply::parse(rdr)?;
+                let locator_id = *reply.locator_id();
+                let (data, is_last_data) = reply.into_data_and_last();
+                PartContent::hex(
+                    &data,
+                    format!("locator_id = {locator_id:#018x}, last_data = {is_last_data}"),
+                )
+            }
+            PartKind::WriteLobRequest => {
+                let (locator_id, offset, data, last_data) = WriteLobRequest::parse(rdr)?;
+                PartContent::hex(
+                    &data,
+                    format!(
+                        "locator_id = {locator_id:#018x}, offset = {offset}, last_data = {last_data}"
+                    ),
+                )
+            }
+            PartKind::WriteLobReply => PartContent::text(
+                &WriteLobReply::parse(count, rdr)?
+                    .into_locator_ids()
+                    .iter()
+                    .map(|id| format!("{id:#018x}"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            PartKind::PartitionInformation => {
+                PartContent::text(&format!("{:#?}", PartitionInformation::parse(rdr)?))
+            }
+            // Parts that the driver does not interpret; many of them are option parts
+            PartKind::CommitOptions
+            | PartKind::FindLobRequest
+            | PartKind::FindLobReply
+            | PartKind::PrintOptions
+            | PartKind::ResultSetOptions
+            | PartKind::SessionVariable
+            | PartKind::SQLReplyOptions
+            | PartKind::TransactionId
+            | PartKind::WorkloadReplayContext
+            | PartKind::XatOptions => {
+                let mut annotation = "not interpreted by the driver".to_string();
+                if let Some(options) = self.guess_options() {
+                    annotation.push_str("; parses as option part: ");
+                    annotation.push_str(
+                        &options
+                            .iter()
+                            .map(|DissectedOption { name, value }| format!("{name} = {value}"))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    );
+                }
+                PartContent::hex(self.body, annotation)
+            }
+        })
+    }
+
+    // Tries to decode the part as option part, which many undocumented parts are.
+    fn guess_options(&self) -> Option<Vec<DissectedOption>> {
+        let mut rdr = self.reader();
+        let option_part = OptionPart::<RawOptionId>::parse(self.count, &mut rdr).ok()?;
+        (self.count > 0 && rdr_position(&rdr) == self.body.len()).then(|| options_of(option_part))
+    }
+}
+
+fn rdr_position(rdr: &Cursor<Vec<u8>>) -> usize {
+    usize::try_from(rdr.position()).unwrap_or(usize::MAX)
+}
+
+fn parse_length_and_string(rdr: &mut dyn std::io::Read) -> HdbResult<String> {
+    let l8 = rdr.read_u8()?;
+    let length = length_indicator::parse(l8, rdr)?;
+    util::string_from_cesu8(util_sync::parse_bytes(length, rdr)?)
+}
+
+fn auth_field(field: &[u8]) -> String {
+    match std::str::from_utf8(field) {
+        Ok(s) if !s.is_empty() && s.chars().all(|c| c.is_ascii_graphic() || c == ' ') => {
+            s.to_string()
+        }
+        _ if field.len() > MAX_AUTH_FIELD_HEX => format!(
+            "{} bytes: {}...",
+            field.len(),
+            hex_string(&field[..MAX_AUTH_FIELD_HEX])
+        ),
+        _ => format!("{} bytes: {}", field.len(), hex_string(field)),
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut s, b| {
+        write!(s, "{b:02x}").unwrap(/*OK*/);
+        s
+    })
+}
+
+fn options<T>(option_part: OptionPart<T>) -> PartContent
+where
+    T: OptionId<T> + Debug + Eq + PartialEq + Hash,
+{
+    PartContent::Options {
+        options: options_of(option_part),
+    }
+}
+
+fn options_of<T>(option_part: OptionPart<T>) -> Vec<DissectedOption>
+where
+    T: OptionId<T> + Debug + Eq + PartialEq + Hash,
+{
+    let mut options: Vec<(T, OptionValue)> = option_part.into_iter().collect();
+    options.sort_by_key(|(id, _)| id.to_u8());
+    options
+        .into_iter()
+        .map(|(id, value)| DissectedOption {
+            name: format!("{id:?}"),
+            value: match value {
+                OptionValue::BSTRING(bytes) => hex_string(&bytes),
+                value => value.to_string(),
+            },
+        })
+        .collect()
+}
+
+// Option id for option parts whose ids are not known.
+#[derive(Eq, PartialEq, Hash)]
+struct RawOptionId(u8);
+impl OptionId<RawOptionId> for RawOptionId {
+    fn from_u8(val: u8) -> Self {
+        Self(val)
+    }
+    fn to_u8(&self) -> u8 {
+        self.0
+    }
+    fn part_type(&self) -> &'static str {
+        "undocumented option part"
+    }
+}
+impl Debug for RawOptionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A decoded part.
+#[derive(Debug, Serialize)]
+pub struct DissectedPart {
+    /// The name of the part kind.
+    pub kind: String,
+    /// The numeric part kind.
+    pub kind_id: i8,
+    /// The part attributes.
+    pub attributes: String,
+    /// The number of arguments, as given in the part header.
+    pub count: usize,
+    /// The size of the part body.
+    pub size: usize,
+    /// The decoded content.
+    pub content: PartContent,
+    /// Inconsistencies that were found.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// The decoded content of a part.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PartContent {
+    /// Textual content.
+    Text {
+        /// The lines of text.
+        lines: Vec<String>,
+    },
+    /// Named values.
+    Options {
+        /// The options, in the order of their ids.
+        options: Vec<DissectedOption>,
+    },
+    /// Values in a tabular structure.
+    Rows {
+        /// The column names.
+        columns: Vec<String>,
+        /// The values, as strings.
+        rows: Vec<Vec<String>>,
+    },
+    /// Content that is not, or cannot be, interpreted.
+    Hex {
+        /// What is known about the content.
+        annotation: String,
+        /// The bytes, as hex dump.
+        hex: Vec<String>,
+    },
+}
+impl PartContent {
+    fn text(s: &str) -> Self {
+        Self::Text {
+            lines: s.lines().map(ToString::to_string).collect(),
+        }
+    }
+    fn hex(bytes: &[u8], annotation: String) -> Self {
+        Self::Hex {
+            annotation,
+            hex: hex::dump(bytes),
+        }
+    }
+}
+
+/// A named value.
+#[derive(Debug, Serialize)]
+pub struct DissectedOption {
+    /// The name.
+    pub name: String,
+    /// The value.
+    pub value: String,
+}
+
+impl std::fmt::Display for DissectedPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "    part {} ({}), attributes {}, count {}, {} bytes",
+            self.kind, self.kind_id, self.attributes, self.count, self.size
+        )?;
+        match self.content {
+            PartContent::Text { ref lines } => {
+                for line in lines {
+                    writeln!(f, "        {line}")?;
+                }
+            }
+            PartContent::Options { ref options } => {
+                for option in options {
+                    writeln!(f, "        {} = {}", option.name, option.value)?;
+                }
+            }
+            PartContent::Rows {
+                ref columns,
+                ref rows,
+            } => {
+                writeln!(f, "        | {} |", columns.join(" | "))?;
+                for row in rows {
+                    writeln!(f, "        | {} |", row.join(" | "))?;
+                }
+            }
+            PartContent::Hex {
+                ref annotation,
+                ref hex,
+            } => {
+                writeln!(f, "        ({annotation})")?;
+                hex::write_indented(hex, f)?;
+            }
+        }
+        for note in &self.notes {
+            writeln!(f, "        NOTE: {note}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PartContent, PartContext, RawPart};
+    use crate::{dissector::Dissector, protocol::PartKind, TypeId};
+
+    #[test]
+    fn test_split() {
+        // a StatementId part, followed by a truncated Command part
+        let mut buffer = vec![10, 0, 1, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0];
+        buffer.extend_from_slice(&0x0123_4567_89ab_cdef_u64.to_le_bytes());
+        buffer.extend_from_slice(&[3, 0, 1, 0, 0, 0, 0, 0, 20, 0, 0, 0, 0, 0, 0, 0]);
+        buffer.extend_from_slice(b"select");
+
+        let (raw_parts, notes) = RawPart::split(3, &buffer);
+        assert_eq!(raw_parts.len(), 2);
+        assert_eq!(
+            raw_parts[0].id_of_kind(PartKind::StatementId),
+            Some(0x0123_4567_89ab_cdef)
+        );
+        assert_eq!(raw_parts[0].id_of_kind(PartKind::ResultSetId), None);
+        assert_eq!(raw_parts[1].kind(), Some(PartKind::Command));
+        assert_eq!(raw_parts[1].body, b"select");
+        assert_eq!(notes.len(), 2);
+    }
+
+    #[test]
+    fn test_corrupt_parts() {
+        let dissector = Dissector::new().unwrap();
+        let context = PartContext {
+            am_conn_core: &dissector.am_conn_core,
+            o_a_rsmd: None,
+            o_a_descriptors: None,
+        };
+        let dissect = |kind: PartKind, count: usize, body: &[u8]| {
+            RawPart {
+                kind_id: kind as i8,
+                attributes: 0,
+                count,
+                body,
+            }
+            .dissect(&context)
+            .content
+        };
+
+        // a column with the name "ID", but with a table name far beyond the part
+        let result_set_metadata = |table_name_offset: u32| {
+            let mut body = vec![2, TypeId::INT as u8, 0, 0, 10, 0, 0, 0];
+            body.extend_from_slice(&table_name_offset.to_le_bytes());
+            body.extend_from_slice(&u32::MAX.to_le_bytes());
+            body.extend_from_slice(&0_u32.to_le_bytes());
+            body.extend_from_slice(&0_u32.to_le_bytes());
+            body.extend_from_slice(&[2, b'I', b'D']);
+            body
+        };
+        let body = result_set_metadata(u32::MAX);
+        let raw = RawPart {
+            kind_id: PartKind::ResultSetMetadata as i8,
+            attributes: 0,
+            count: 1,
+            body: &body,
+        };
+        assert!(raw.parse_result_set_metadata().is_some());
+        assert!(matches!(
+            dissect(PartKind::ResultSetMetadata, 1, &body),
+            PartContent::Text { .. }
+        ));
+        let body = result_set_metadata(0xFFFF_FFF0);
+        let raw = RawPart { body: &body, ..raw };
+        assert!(raw.parse_result_set_metadata().is_none());
+        assert!(matches!(
+            dissect(PartKind::ResultSetMetadata, 1, &body),
+            PartContent::Hex { ref annotation, .. } if annotation.starts_with("corrupt")
+        ));
+        assert!(matches!(
+            dissect(PartKind::ResultSetMetadata, usize::MAX, &body),
+            PartContent::Hex { .. }
+        ));
+
+        // an error with a text of 3 bytes, then with a corrupt text length
+        let error = |text_length: i32| {
+            let mut body = Vec::new();
+            body.extend_from_slice(&257_i32.to_le_bytes());
+            body.extend_from_slice(&0_i32.to_le_bytes());
+            body.extend_from_slice(&text_length.to_le_bytes());
+            body.push(1);
+            body.extend_from_slice(b"HY000bad");
+            body.resize(24, 0);
+            body
+        };
+        assert!(matches!(
+            dissect(PartKind::Error, 1, &error(3)),
+            PartContent::Text { ref lines } if lines.len() == 1
+        ));
+        for text_length in [i32::MAX, -1, 7] {
+            assert!(matches!(
+                dissect(PartKind::Error, 1, &error(text_length)),
+                PartContent::Hex { ref annotation, .. } if annotation.starts_with("corrupt")
+            ));
+        }
+        assert!(matches!(
+            dissect(PartKind::Error, 2, &error(3)),
+            PartContent::Hex { .. }
+        ));
+    }
+}
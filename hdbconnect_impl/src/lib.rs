@@ -34,11 +34,20 @@ pub mod a_sync;
 #[cfg(feature = "sync")]
 pub mod sync;
 
+#[cfg(feature = "mock_server")]
+pub mod mock_server;
+
+#[cfg(feature = "dissector")]
+pub mod dissector;
+
 pub use crate::{
     base::{HdbError, HdbResult, Row, Rows},
     conn::{
-        url, ConnectParams, ConnectParamsBuilder, ConnectionConfiguration, ConnectionStatistics,
-        CursorHoldability, IntoConnectParams, IntoConnectParamsBuilder, ServerCerts,
+        url, BearerToken, BearerTokenKind, ClientCertificate, ConnectParams, ConnectParamsBuilder,
+        ConnectionConfiguration, ConnectionStatistics, CredentialProvider, CursorHoldability,
+        ExecutionOutcome, HostSelection, Interceptor, IntoConnectParams, IntoConnectParamsBuilder,
+        LatencyHistogram, MessageTypeStatistics, Proxy, ProxyProtocol, RoundtripStatistics,
+        ServerCerts, SharedStatistics, TraceContext,
     },
     protocol::parts::{
         ExecutionResult, ExecutionResults, FieldMetadata, HdbValue, OutputParameters,
@@ -49,6 +58,9 @@ pub use crate::{
     serde_db_impl::{time, ToHana},
 };
 
+#[cfg(feature = "async")]
+pub use crate::conn::AsyncCredentialProvider;
+
 pub use serde_db::{de::DeserializationError, ser::SerializationError};
 
 /// Non-standard types that are used to represent database values.
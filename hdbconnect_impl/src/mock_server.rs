@@ -0,0 +1,557 @@
+//! An in-process mock of a HANA server, for testing code that uses the driver
+//! without a real database.
+//!
+//! The [`MockServer`] listens on a local TCP port and answers the requests of the driver
+//! with replies that are built from the same protocol implementation that the driver
+//! uses for parsing, so the mock stays faithful to the wire protocol.
+//! Statements are scripted upfront with their [`MockResponse`]s; statements that
+//! are not scripted fail with a SQL syntax error.
+//! All executions are recorded and can be inspected afterwards.
+//! Long-running statements can be simulated with [`MockResponse::Delayed`];
+//! they can be cancelled and are subject to query timeouts.
+//!
+//! ```rust,ignore
+//! use hdbconnect::{
+//!     mock_server::{MockColumn, MockResponse, MockResultSet, MockServer},
+//!     Connection, HdbValue, TypeId,
+//! };
+//!
+//! let mock = MockServer::start()?;
+//! mock.on_statement(
+//!     "select name from users",
+//!     MockResponse::ResultSet(
+//!         MockResultSet::new(vec![MockColumn::new("NAME", TypeId::NVARCHAR)])
+//!             .with_row(vec![HdbValue::STRING("Alice".to_string())]),
+//!     ),
+//! );
+//!
+//! let connection = Connection::new(mock.url())?;
+//! let names: Vec<String> = connection.query("select name from users")?.try_into()?;
+//! assert_eq!(names, vec!["Alice"]);
+//! ```
+//!
+//! The mock server supports plain TCP connections, optionally upgraded to WebSocket
+//! (see [`MockServerConfig::with_websocket_path`]), and TLS connections
+//! (see [`MockServerConfig::with_tls`]).
+mod script;
+mod session;
+mod wire;
+
+pub use self::script::{
+    MockColumn, MockExecution, MockHost, MockParameter, MockPartitioning, MockResponse,
+    MockResultSet,
+};
+
+use self::script::Script;
+use crate::{ConnectParamsBuilder, HdbResult};
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, PoisonError,
+    },
+    thread::JoinHandle,
+};
+
+/// The authentication method that the [`MockServer`] selects.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MockAuthenticator {
+    /// SCRAM-SHA256.
+    ScramSha256,
+    /// SCRAM-PBKDF2-SHA256.
+    #[default]
+    ScramPbkdf2Sha256,
+    /// X.509, with the certificate that is configured with
+    /// [`MockServerConfig::with_client_certificate`].
+    X509,
+    /// JWT, with the token that is configured with [`MockServerConfig::with_jwt`].
+    Jwt,
+    /// SAML, with the assertion that is configured with
+    /// [`MockServerConfig::with_saml_assertion`].
+    Saml,
+    /// LDAP, with the configured password, which the client sends RSA-encrypted.
+    Ldap,
+    /// `SessionCookie`; the server selects it in place of the configured method
+    /// if session cookies are enabled with [`MockServerConfig::with_session_cookies`]
+    /// and the client proposes it.
+    SessionCookie,
+}
+impl MockAuthenticator {
+    fn name(self) -> &'static str {
+        match self {
+            Self::ScramSha256 => "SCRAMSHA256",
+            Self::ScramPbkdf2Sha256 => "SCRAMPBKDF2SHA256",
+            Self::X509 => "X509",
+            Self::Jwt => "JWT",
+            Self::Saml => "SAML",
+            Self::Ldap => "LDAP",
+            Self::SessionCookie => "SessionCookie",
+        }
+    }
+}
+
+/// Configuration of a [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct MockServerConfig {
+    user: String,
+    password: String,
+    authenticator: MockAuthenticator,
+    iterations: u32,
+    system_id: String,
+    database_name: String,
+    full_version: String,
+    implicit_lob_streaming: bool,
+    lob_chunk_size: usize,
+    websocket_path: Option<String>,
+    // certificate chain and private key, in PEM format
+    tls: Option<(String, String)>,
+    client_certificate: Option<String>,
+    bearer_token: Option<String>,
+    session_cookies: bool,
+    password_change_required: bool,
+}
+impl Default for MockServerConfig {
+    fn default() -> Self {
+        Self {
+            user: "MOCKUSER".to_string(),
+            password: "MockPassword1".to_string(),
+            authenticator: MockAuthenticator::default(),
+            iterations: 15_000,
+            system_id: "MCK".to_string(),
+            database_name: "MCK".to_string(),
+            full_version: "2.00.070.00.1234567890".to_string(),
+            implicit_lob_streaming: false,
+            lob_chunk_size: 1024,
+            websocket_path: None,
+            tls: None,
+            client_certificate: None,
+            bearer_token: None,
+            session_cookies: false,
+            password_change_required: false,
+        }
+    }
+}
+impl MockServerConfig {
+    /// Sets the user and password that the mock server accepts.
+    #[must_use]
+    pub fn with_credentials<U: AsRef<str>, P: AsRef<str>>(mut self, user: U, password: P) -> Self {
+        self.user = user.as_ref().to_string();
+        self.password = password.as_ref().to_string();
+        self
+    }
+
+    /// Sets the authentication method that the mock server selects.
+    #[must_use]
+    pub fn with_authenticator(mut self, authenticator: MockAuthenticator) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+
+    /// Sets the number of PBKDF2 iterations; the driver requires at least 15,000.
+    #[must_use]
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets the system id that is reported to the client.
+    #[must_use]
+    pub fn with_system_id<S: AsRef<str>>(mut self, system_id: S) -> Self {
+        self.system_id = system_id.as_ref().to_string();
+        self
+    }
+
+    /// Sets the database name that is reported to the client.
+    #[must_use]
+    pub fn with_database_name<S: AsRef<str>>(mut self, database_name: S) -> Self {
+        self.database_name = database_name.as_ref().to_string();
+        self
+    }
+
+    /// Sets the full version string that is reported to the client.
+    #[must_use]
+    pub fn with_full_version<S: AsRef<str>>(mut self, full_version: S) -> Self {
+        self.full_version = full_version.as_ref().to_string();
+        self
+    }
+
+    /// Lets the mock server announce implicit LOB streaming.
+    #[must_use]
+    pub fn with_implicit_lob_streaming(mut self, implicit_lob_streaming: bool) -> Self {
+        self.implicit_lob_streaming = implicit_lob_streaming;
+        self
+    }
+
+    /// Sets the number of bytes of a LOB value that are sent along with the result set;
+    /// the remainder is served with LOB read requests.
+    #[must_use]
+    pub fn with_lob_chunk_size(mut self, lob_chunk_size: usize) -> Self {
+        self.lob_chunk_size = lob_chunk_size.max(1);
+        self
+    }
+
+    /// Lets the mock server expect that connections are upgraded to WebSocket on the given
+    /// path; upgrade requests for other paths are rejected.
+    #[must_use]
+    pub fn with_websocket_path<P: AsRef<str>>(mut self, path: P) -> Self {
+        self.websocket_path = Some(path.as_ref().to_string());
+        self
+    }
+
+    /// Lets the mock server accept only TLS connections, with the given server certificate
+    /// (chain) and its private key, both in PEM format.
+    ///
+    /// TLS cannot be combined with WebSocket.
+    #[must_use]
+    pub fn with_tls<C: AsRef<str>, K: AsRef<str>>(
+        mut self,
+        certificate: C,
+        private_key: K,
+    ) -> Self {
+        self.tls = Some((
+            certificate.as_ref().to_string(),
+            private_key.as_ref().to_string(),
+        ));
+        self
+    }
+
+    /// Sets the client certificate (in PEM format) that is mapped to the configured user,
+    /// and lets the mock server select X.509 authentication.
+    #[must_use]
+    pub fn with_client_certificate<C: AsRef<str>>(mut self, certificate: C) -> Self {
+        self.client_certificate = Some(certificate.as_ref().to_string());
+        self.authenticator = MockAuthenticator::X509;
+        self
+    }
+
+    /// Sets the JSON Web Token that is mapped to the configured user,
+    /// and lets the mock server select JWT authentication.
+    #[must_use]
+    pub fn with_jwt<T: AsRef<str>>(mut self, token: T) -> Self {
+        self.bearer_token = Some(token.as_ref().to_string());
+        self.authenticator = MockAuthenticator::Jwt;
+        self
+    }
+
+    /// Sets the SAML assertion that is mapped to the configured user,
+    /// and lets the mock server select SAML authentication.
+    #[must_use]
+    pub fn with_saml_assertion<A: AsRef<str>>(mut self, assertion: A) -> Self {
+        self.bearer_token = Some(assertion.as_ref().to_string());
+        self.authenticator = MockAuthenticator::Saml;
+        self
+    }
+
+    /// Lets the mock server issue session cookies with JWT and SAML logons,
+    /// and accept them for later logons with the method `SessionCookie`.
+    #[must_use]
+    pub fn with_session_cookies(mut self) -> Self {
+        self.session_cookies = true;
+        self
+    }
+
+    /// Lets the mock server require a password change with the logon, as for a user
+    /// with an initial or expired password: the session then only accepts
+    /// `ALTER USER <user> PASSWORD "<new password>"`, after which the new password
+    /// is accepted, without further password change.
+    #[must_use]
+    pub fn with_password_change_required(mut self) -> Self {
+        self.password_change_required = true;
+        self
+    }
+}
+
+// State that is shared between the MockServer handle and the session threads.
+#[derive(Debug)]
+struct Shared {
+    config: MockServerConfig,
+    addr: SocketAddr,
+    script: Mutex<Script>,
+    executions: Mutex<Vec<MockExecution>>,
+    connection_count: AtomicUsize,
+    disconnect_count: AtomicUsize,
+    // clones of the streams of the running sessions, by connection number
+    open_streams: Mutex<HashMap<usize, TcpStream>>,
+    next_session_id: AtomicI64,
+    // the issued session cookies, with the users for whom they were issued
+    session_cookies: Mutex<HashMap<Vec<u8>, String>>,
+    // the password that is currently accepted; initially the configured one
+    password: Mutex<String>,
+    password_change_required: AtomicBool,
+    // the sessions that run a delayed statement, with whether it was cancelled
+    running_statements: Mutex<HashMap<i64, bool>>,
+    statement_cancelled: Condvar,
+    shutdown: AtomicBool,
+}
+
+impl Shared {
+    fn open_streams(&self) -> std::sync::MutexGuard<'_, HashMap<usize, TcpStream>> {
+        self.open_streams
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn session_cookies(&self) -> std::sync::MutexGuard<'_, HashMap<Vec<u8>, String>> {
+        self.session_cookies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn password(&self) -> std::sync::MutexGuard<'_, String> {
+        self.password.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn running_statements(&self) -> std::sync::MutexGuard<'_, HashMap<i64, bool>> {
+        self.running_statements
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A mock HANA server that runs in the current process.
+///
+/// The server is shut down when the `MockServer` is dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    o_acceptor: Option<JoinHandle<()>>,
+}
+impl MockServer {
+    /// Starts a mock server with the default configuration on a free local port.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Io` if the listener cannot be started.
+    pub fn start() -> HdbResult<Self> {
+        Self::start_with(MockServerConfig::default())
+    }
+
+    /// Starts a mock server with the given configuration on a free local port.
+    ///
+    /// # Errors
+    ///
+    /// `HdbError::Io` if the listener cannot be started.
+    pub fn start_with(config: MockServerConfig) -> HdbResult<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            password: Mutex::new(config.password.clone()),
+            password_change_required: AtomicBool::new(config.password_change_required),
+            config,
+            addr,
+            script: Mutex::new(Script::default()),
+            executions: Mutex::new(Vec::new()),
+            connection_count: AtomicUsize::new(0),
+            disconnect_count: AtomicUsize::new(0),
+            open_streams: Mutex::new(HashMap::new()),
+            next_session_id: AtomicI64::new(1),
+            session_cookies: Mutex::new(HashMap::new()),
+            running_statements: Mutex::new(HashMap::new()),
+            statement_cancelled: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let shared2 = Arc::clone(&shared);
+        let acceptor = std::thread::Builder::new()
+            .name(format!("hdb-mock-server-{}", addr.port()))
+            .spawn(move || accept(&listener, &shared2))?;
+        debug!("mock server listens on {addr}");
+        Ok(Self {
+            addr,
+            shared,
+            o_acceptor: Some(acceptor),
+        })
+    }
+
+    /// The socket address the server listens on.
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The port the server listens on.
+    #[must_use]
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// A URL for connecting to the mock server with the configured user,
+    /// the current password, and the configured WebSocket path.
+    ///
+    /// The credentials are not percent-encoded.
+    #[must_use]
+    pub fn url(&self) -> String {
+        let url = format!(
+            "hdbsql://{}:{}@{}",
+            self.shared.config.user,
+            self.shared.password(),
+            self.addr
+        );
+        match self.shared.config.websocket_path {
+            Some(ref path) => format!("{url}?{}={path}", crate::url::WEBSOCKET_PATH),
+            None => url,
+        }
+    }
+
+    /// A `ConnectParamsBuilder` that is prepared with the address, the configured user,
+    /// the current password, and the configured WebSocket path of the mock server.
+    #[must_use]
+    pub fn connect_params_builder(&self) -> ConnectParamsBuilder {
+        let mut builder = ConnectParamsBuilder::new();
+        builder
+            .hostname(self.addr.ip().to_string())
+            .port(self.addr.port())
+            .dbuser(&self.shared.config.user)
+            .password(&*self.shared.password());
+        if let Some(ref path) = self.shared.config.websocket_path {
+            builder.websocket_path(path);
+        }
+        builder
+    }
+
+    /// Scripts the response for a statement without parameters.
+    ///
+    /// The statement text is compared ignoring leading, trailing,
+    /// and repeated whitespace.
+    pub fn on_statement<S: AsRef<str>>(&self, sql: S, response: MockResponse) {
+        self.on_statement_with_parameters(sql, Vec::new(), response);
+    }
+
+    /// Scripts the response for a statement with input parameters.
+    ///
+    /// The parameters are announced to the client when it prepares the statement.
+    pub fn on_statement_with_parameters<S: AsRef<str>>(
+        &self,
+        sql: S,
+        parameters: Vec<MockParameter>,
+        response: MockResponse,
+    ) {
+        self.script()
+            .add_statement(sql.as_ref(), parameters, response);
+    }
+
+    /// Lets the server answer all authentication attempts with a redirect
+    /// to the given host and port, as HANA Cloud does.
+    pub fn redirect_authentication<H: AsRef<str>>(&self, host: H, port: u16) {
+        self.script()
+            .set_redirect_authentication(host.as_ref().to_string(), port);
+    }
+
+    /// Lets the server redirect clients that ask for the given database
+    /// to the given host and port.
+    pub fn redirect_database<D: AsRef<str>, H: AsRef<str>>(&self, db_name: D, host: H, port: u16) {
+        self.script().add_redirect_database(
+            db_name.as_ref().to_string(),
+            host.as_ref().to_string(),
+            port,
+        );
+    }
+
+    /// Lets the server announce the hosts of a scale-out system with the authentication;
+    /// the host with the address of this server is marked as the one of the current session.
+    ///
+    /// Other `MockServer`s can play the other hosts.
+    pub fn set_topology(&self, hosts: Vec<MockHost>) {
+        self.script().set_topology(hosts);
+    }
+
+    /// Lets the server announce the location of the table of a statement
+    /// when the statement is prepared.
+    pub fn set_partitioning<S: AsRef<str>>(&self, sql: S, partitioning: MockPartitioning) {
+        self.script().set_partitioning(sql.as_ref(), partitioning);
+    }
+
+    /// The statement executions that the server received so far, in their order.
+    ///
+    /// Includes direct executions, executions of prepared statements,
+    /// and commits and rollbacks.
+    #[must_use]
+    pub fn executions(&self) -> Vec<MockExecution> {
+        self.shared
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The number of client connections that were accepted so far.
+    #[must_use]
+    pub fn connection_count(&self) -> usize {
+        self.shared.connection_count.load(Ordering::SeqCst)
+    }
+
+    /// The number of disconnect requests that the server received so far.
+    #[must_use]
+    pub fn disconnect_count(&self) -> usize {
+        self.shared.disconnect_count.load(Ordering::SeqCst)
+    }
+
+    /// Closes all client connections abruptly, as a network failure would do.
+    ///
+    /// The server continues to accept new connections.
+    pub fn break_connections(&self) {
+        for (_, stream) in self.shared.open_streams().drain() {
+            stream.shutdown(std::net::Shutdown::Both).ok();
+        }
+    }
+
+    /// Changes the password that the server accepts from now on, as a password rotation
+    /// would do; open sessions are not affected.
+    pub fn change_password<P: AsRef<str>>(&self, password: P) {
+        *self.shared.password() = password.as_ref().to_string();
+    }
+
+    /// Invalidates all session cookies that the server issued so far,
+    /// as their expiration would do.
+    pub fn invalidate_session_cookies(&self) {
+        self.shared.session_cookies().clear();
+    }
+
+    fn script(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.shared
+            .script
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        // unblock the acceptor
+        TcpStream::connect(self.addr).ok();
+        if let Some(acceptor) = self.o_acceptor.take() {
+            acceptor.join().ok();
+        }
+    }
+}
+
+fn accept(listener: &TcpListener, shared: &Arc<Shared>) {
+    for stream in listener.incoming() {
+        if shared.shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        match stream {
+            Ok(stream) => {
+                let connection_number = shared.connection_count.fetch_add(1, Ordering::SeqCst);
+                if let Ok(clone) = stream.try_clone() {
+                    shared.open_streams().insert(connection_number, clone);
+                }
+                let shared = Arc::clone(shared);
+                // sessions end when the client disconnects
+                if let Err(e) = std::thread::Builder::new()
+                    .name("hdb-mock-session".to_string())
+                    .spawn(move || {
+                        session::serve(stream, &shared);
+                        shared.open_streams().remove(&connection_number);
+                    })
+                {
+                    warn!("mock server cannot spawn a session thread: {e}");
+                }
+            }
+            Err(e) => warn!("mock server cannot accept a connection: {e}"),
+        }
+    }
+}
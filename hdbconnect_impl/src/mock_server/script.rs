@@ -0,0 +1,479 @@
+use crate::{
+    protocol::parts::{
+        ParameterBinding, ParameterDescriptor, ParameterDescriptors, ParameterDirection,
+        ResultSetMetadata, Severity,
+    },
+    HdbValue, ServerError, TypeId,
+};
+use std::{collections::HashMap, time::Duration};
+
+/// The response that the [`MockServer`](crate::mock_server::MockServer) sends
+/// when a scripted statement is executed.
+#[derive(Clone, Debug)]
+pub enum MockResponse {
+    /// The statement succeeds without returning rows or a row count, like a DDL statement.
+    Success,
+    /// The statement succeeds and reports the given number of affected rows (per parameter row).
+    AffectedRows(usize),
+    /// The statement returns the given result set.
+    ResultSet(MockResultSet),
+    /// The statement fails with the given server error.
+    Error(ServerError),
+    /// The statement runs for the given time before it responds with the given response.
+    ///
+    /// Meanwhile, its session can be cancelled with `ALTER SYSTEM CANCEL SESSION '<id>'`,
+    /// and a query timeout of the execution can expire;
+    /// the statement then fails with error code 139.
+    Delayed(Duration, Box<MockResponse>),
+}
+impl MockResponse {
+    /// Convenience constructor for a `MockResponse::Error` with the given error code and text.
+    #[must_use]
+    pub fn error<S: Into<String>>(code: i32, text: S) -> Self {
+        Self::Error(ServerError::new(
+            code,
+            0,
+            Severity::Error,
+            b"HY000".to_vec(),
+            text.into(),
+        ))
+    }
+
+    /// Convenience constructor for a `MockResponse::Delayed`.
+    #[must_use]
+    pub fn delayed(duration: Duration, response: Self) -> Self {
+        Self::Delayed(duration, Box::new(response))
+    }
+
+    // The result set that the statement returns, if any.
+    pub(super) fn result_set(&self) -> Option<&MockResultSet> {
+        match self {
+            Self::ResultSet(result_set) => Some(result_set),
+            Self::Delayed(_, response) => response.result_set(),
+            _ => None,
+        }
+    }
+}
+
+/// A result set that is returned by the [`MockServer`](crate::mock_server::MockServer).
+///
+/// LOB columns (`BLOB`, `CLOB`, `NCLOB`) take `HdbValue::BINARY` or `HdbValue::STRING` values;
+/// the mock server sends them in chunks and serves the remaining data
+/// via the regular LOB read requests.
+#[derive(Clone, Debug)]
+pub struct MockResultSet {
+    columns: Vec<MockColumn>,
+    rows: Vec<Vec<HdbValue<'static>>>,
+}
+impl MockResultSet {
+    /// Creates an empty result set with the given columns.
+    #[must_use]
+    pub fn new(columns: Vec<MockColumn>) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row.
+    ///
+    /// The values must match the columns in number and type;
+    /// `HdbValue::NULL` is allowed only for nullable columns.
+    #[must_use]
+    pub fn with_row(mut self, row: Vec<HdbValue<'static>>) -> Self {
+        self.rows.push(row);
+        self
+    }
+
+    /// Adds several rows.
+    #[must_use]
+    pub fn with_rows<I: IntoIterator<Item = Vec<HdbValue<'static>>>>(mut self, rows: I) -> Self {
+        self.rows.extend(rows);
+        self
+    }
+
+    pub(crate) fn columns(&self) -> &[MockColumn] {
+        &self.columns
+    }
+
+    pub(crate) fn rows(&self) -> &[Vec<HdbValue<'static>>] {
+        &self.rows
+    }
+
+    pub(crate) fn metadata(&self) -> ResultSetMetadata {
+        ResultSetMetadata::new_for_server(
+            self.columns
+                .iter()
+                .map(|c| {
+                    (
+                        [
+                            c.schema.as_str(),
+                            c.table.as_str(),
+                            c.name.as_str(),
+                            c.name.as_str(),
+                        ],
+                        if c.nullable {
+                            0b_0000_0010_u8
+                        } else {
+                            0b_0000_0001_u8
+                        },
+                        c.type_id,
+                        c.scale,
+                        c.precision,
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// A column of a [`MockResultSet`].
+#[derive(Clone, Debug)]
+pub struct MockColumn {
+    name: String,
+    table: String,
+    schema: String,
+    type_id: TypeId,
+    nullable: bool,
+    precision: i16,
+    scale: i16,
+}
+impl MockColumn {
+    /// Creates a not-nullable column with the given name and type.
+    #[must_use]
+    pub fn new<S: AsRef<str>>(name: S, type_id: TypeId) -> Self {
+        let (precision, scale) = default_precision_and_scale(type_id);
+        Self {
+            name: name.as_ref().to_string(),
+            table: String::new(),
+            schema: String::new(),
+            type_id,
+            nullable: false,
+            precision,
+            scale,
+        }
+    }
+
+    /// Makes the column nullable.
+    #[must_use]
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
+    /// Sets the table name that is reported in the metadata.
+    #[must_use]
+    pub fn with_table<S: AsRef<str>>(mut self, table: S) -> Self {
+        self.table = table.as_ref().to_string();
+        self
+    }
+
+    /// Sets the schema name that is reported in the metadata.
+    #[must_use]
+    pub fn with_schema<S: AsRef<str>>(mut self, schema: S) -> Self {
+        self.schema = schema.as_ref().to_string();
+        self
+    }
+
+    /// Sets precision and scale, e.g. for DECIMAL columns.
+    #[must_use]
+    pub fn with_precision_and_scale(mut self, precision: i16, scale: i16) -> Self {
+        self.precision = precision;
+        self.scale = scale;
+        self
+    }
+
+    pub(crate) fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub(crate) fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    pub(crate) fn scale(&self) -> i16 {
+        self.scale
+    }
+}
+
+/// A parameter of a statement that is scripted with
+/// [`MockServer::on_statement_with_parameters`](crate::mock_server::MockServer::on_statement_with_parameters).
+#[derive(Clone, Debug)]
+pub struct MockParameter {
+    name: Option<String>,
+    type_id: TypeId,
+    nullable: bool,
+    output: bool,
+    precision: i16,
+    scale: i16,
+}
+impl MockParameter {
+    /// Creates a mandatory unnamed input parameter of the given type.
+    #[must_use]
+    pub fn new(type_id: TypeId) -> Self {
+        let (precision, scale) = default_precision_and_scale(type_id);
+        Self {
+            name: None,
+            type_id,
+            nullable: false,
+            output: false,
+            precision,
+            scale,
+        }
+    }
+
+    /// Makes the parameter an OUT parameter of a procedure call;
+    /// it is reported in the parameter metadata, but the client sends no value for it.
+    #[must_use]
+    pub fn output(mut self) -> Self {
+        self.output = true;
+        self
+    }
+
+    /// Sets the parameter name that is reported in the parameter metadata.
+    #[must_use]
+    pub fn with_name<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.name = Some(name.as_ref().to_string());
+        self
+    }
+
+    /// Makes the parameter nullable.
+    #[must_use]
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
+    /// Sets precision and scale, e.g. for DECIMAL parameters.
+    #[must_use]
+    pub fn with_precision_and_scale(mut self, precision: i16, scale: i16) -> Self {
+        self.precision = precision;
+        self.scale = scale;
+        self
+    }
+
+    fn to_descriptor(&self) -> ParameterDescriptor {
+        ParameterDescriptor::new(
+            self.name.clone(),
+            self.type_id,
+            if self.nullable {
+                ParameterBinding::Optional
+            } else {
+                ParameterBinding::Mandatory
+            },
+            if self.output {
+                ParameterDirection::OUT
+            } else {
+                ParameterDirection::IN
+            },
+            self.precision,
+            self.scale,
+        )
+    }
+}
+
+/// A statement execution that was received by the
+/// [`MockServer`](crate::mock_server::MockServer).
+#[derive(Clone, Debug)]
+pub struct MockExecution {
+    sql: String,
+    parameter_rows: Vec<Vec<HdbValue<'static>>>,
+    session_variables: HashMap<String, String>,
+}
+impl MockExecution {
+    pub(crate) fn new(sql: String, parameter_rows: Vec<Vec<HdbValue<'static>>>) -> Self {
+        Self {
+            sql,
+            parameter_rows,
+            session_variables: HashMap::new(),
+        }
+    }
+
+    /// The executed SQL statement.
+    #[must_use]
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// The parameter rows that were sent with the execution, empty for direct executions.
+    ///
+    /// LOB parameters that were streamed to the server are represented as
+    /// `HdbValue::BINARY` (for BLOBs) or `HdbValue::STRING` (for CLOBs and NCLOBs).
+    #[must_use]
+    pub fn parameter_rows(&self) -> &[Vec<HdbValue<'static>>] {
+        &self.parameter_rows
+    }
+
+    pub(crate) fn parameter_rows_mut(&mut self) -> &mut Vec<Vec<HdbValue<'static>>> {
+        &mut self.parameter_rows
+    }
+
+    /// The value of a session variable at the time of the execution,
+    /// as it was set by the client with the client info (e.g. `APPLICATION` or `TRACEPARENT`).
+    #[must_use]
+    pub fn session_variable(&self, name: &str) -> Option<&str> {
+        self.session_variables.get(name).map(String::as_str)
+    }
+
+    pub(crate) fn set_session_variables(&mut self, session_variables: HashMap<String, String>) {
+        self.session_variables = session_variables;
+    }
+}
+
+/// An index server of a scale-out system, as announced with
+/// [`MockServer::set_topology`](crate::mock_server::MockServer::set_topology).
+#[derive(Clone, Debug)]
+pub struct MockHost {
+    host: String,
+    port: u16,
+    volume_id: i32,
+}
+impl MockHost {
+    /// Creates a host with the given address that hosts the given volume.
+    #[must_use]
+    pub fn new<S: AsRef<str>>(host: S, port: u16, volume_id: i32) -> Self {
+        Self {
+            host: host.as_ref().to_string(),
+            port,
+            volume_id,
+        }
+    }
+
+    pub(crate) fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub(crate) fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub(crate) fn volume_id(&self) -> i32 {
+        self.volume_id
+    }
+}
+
+/// How the table of a statement is distributed over the volumes of a scale-out system,
+/// as announced with
+/// [`MockServer::set_partitioning`](crate::mock_server::MockServer::set_partitioning).
+#[derive(Clone, Debug)]
+pub enum MockPartitioning {
+    /// The table is not partitioned and located on the given volumes.
+    Located(Vec<i32>),
+    /// The table has a round-robin partition on each of the given volumes.
+    RoundRobin(Vec<i32>),
+    /// The table has a hash partition on each of the given volumes;
+    /// the partitioning key consists of the input parameters with the given (zero-based) indexes.
+    /// The driver does not route executions on such tables.
+    Hash {
+        /// Indexes of the input parameters that form the partitioning key.
+        parameters: Vec<i32>,
+        /// The volumes of the partitions.
+        volumes: Vec<i32>,
+    },
+}
+
+// The scripted behavior of a mock server.
+#[derive(Debug, Default)]
+pub(crate) struct Script {
+    statements: HashMap<String, ScriptedStatement>,
+    redirect_authentication: Option<(String, u16)>,
+    redirect_database: HashMap<String, (String, u16)>,
+    topology: Vec<MockHost>,
+    partitioning: HashMap<String, MockPartitioning>,
+}
+impl Script {
+    pub(crate) fn add_statement(
+        &mut self,
+        sql: &str,
+        parameters: Vec<MockParameter>,
+        response: MockResponse,
+    ) {
+        self.statements.insert(
+            normalize(sql),
+            ScriptedStatement {
+                parameters,
+                response,
+            },
+        );
+    }
+
+    pub(crate) fn statement(&self, sql: &str) -> Option<&ScriptedStatement> {
+        self.statements.get(&normalize(sql))
+    }
+
+    pub(crate) fn set_redirect_authentication(&mut self, host: String, port: u16) {
+        self.redirect_authentication = Some((host, port));
+    }
+
+    pub(crate) fn redirect_authentication(&self) -> Option<&(String, u16)> {
+        self.redirect_authentication.as_ref()
+    }
+
+    pub(crate) fn add_redirect_database(&mut self, db_name: String, host: String, port: u16) {
+        self.redirect_database.insert(db_name, (host, port));
+    }
+
+    pub(crate) fn redirect_database(&self, db_name: &str) -> Option<&(String, u16)> {
+        self.redirect_database.get(db_name)
+    }
+
+    pub(crate) fn set_topology(&mut self, hosts: Vec<MockHost>) {
+        self.topology = hosts;
+    }
+
+    pub(crate) fn topology(&self) -> &[MockHost] {
+        &self.topology
+    }
+
+    pub(crate) fn set_partitioning(&mut self, sql: &str, partitioning: MockPartitioning) {
+        self.partitioning.insert(normalize(sql), partitioning);
+    }
+
+    pub(crate) fn partitioning(&self, sql: &str) -> Option<&MockPartitioning> {
+        self.partitioning.get(&normalize(sql))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ScriptedStatement {
+    parameters: Vec<MockParameter>,
+    response: MockResponse,
+}
+impl ScriptedStatement {
+    pub(crate) fn descriptors(&self) -> ParameterDescriptors {
+        ParameterDescriptors::new(
+            self.parameters
+                .iter()
+                .map(MockParameter::to_descriptor)
+                .collect(),
+        )
+    }
+
+    pub(crate) fn response(&self) -> &MockResponse {
+        &self.response
+    }
+}
+
+// Statements are matched ignoring leading and trailing whitespace
+// and differences in the amount of inner whitespace.
+fn normalize(sql: &str) -> String {
+    sql.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn default_precision_and_scale(type_id: TypeId) -> (i16, i16) {
+    match type_id {
+        // scale i16::MAX denotes a floating decimal
+        TypeId::DECIMAL => (38, i16::MAX),
+        TypeId::FIXED8 => (18, 0),
+        TypeId::FIXED12 => (28, 0),
+        TypeId::FIXED16 => (38, 0),
+        TypeId::TINYINT => (3, 0),
+        TypeId::SMALLINT => (5, 0),
+        TypeId::INT => (10, 0),
+        TypeId::BIGINT => (19, 0),
+        TypeId::REAL => (24, 0),
+        TypeId::DOUBLE => (53, 0),
+        TypeId::BOOLEAN => (1, 0),
+        _ => (5000, 0),
+    }
+}
@@ -0,0 +1,1493 @@
+use super::{
+    script::{
+        MockExecution, MockPartitioning, MockResponse, MockResultSet, Script, ScriptedStatement,
+    },
+    wire::{handle_initial_request, MockReply, MockRequest, ReplyPart, RequestPart},
+    MockAuthenticator, MockServerConfig, Shared,
+};
+use crate::{
+    conn::{
+        accept_websocket_sync, rsa_decrypt_oaep_sha1, scram_pdkdf2_sha256, scram_sha256,
+        RsaPublicKey,
+    },
+    impl_err,
+    protocol::{
+        parts::{
+            length_indicator, parse_parameter_rows, AuthFields, ConnOptId, ConnectOptionsPart,
+            DbConnectInfo, DbConnectInfoId, ExecutionResult, ExecutionResults, FetchOptions,
+            OptionValue, PartitionInformation, PartitionMethod, ReadLobReply, ReadLobRequest,
+            Severity, StatementContext, TaFlagId, Topology, TransactionFlags, WriteLobReply,
+            WriteLobRequest,
+        },
+        util_sync, MessageType, PartKind, ReplyType,
+    },
+    types_impl::lob::emit_lob_descriptor,
+    HdbResult, HdbValue, ServerError, TypeId,
+};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use num_bigint::BigUint;
+use rand::{rng, RngCore};
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    ClientConfig, ServerConfig, ServerConnection, StreamOwned,
+};
+use secstr::SecUtf8;
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    net::TcpStream,
+    sync::{atomic::Ordering, Arc, PoisonError},
+    time::{Duration, Instant},
+};
+use webpki::EndEntityCert;
+
+// Part attributes of result set parts
+const LAST_PACKET: u8 = 0b_0000_0001;
+const NEXT_PACKET: u8 = 0b_0000_0010;
+const ROW_NOT_FOUND: u8 = 0b_0000_1000;
+const RESULTSET_IS_CLOSED: u8 = 0b_0001_0000;
+
+const DEFAULT_FETCH_SIZE: usize = 32;
+
+// The key pair with which the LDAP authentication encrypts the password
+const LDAP_PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAyMgayn690j7rKfo+6XT2
+zf101i4Svr3FVNZy64OsgZ+pBqiHSBlujH8NUx9Yyh3EMf+aBNjN0Djnol5fRQUx
++nraKExU01aBsRV0ZgQ+jySnuAE2P/7YjE2XYpSQPgCa7pphef8lkp49bW2msa0F
+udpA05XixxCyio+VRGgRbFUW3odEFxxnXMpe0Xlnhf88sHHZV8Ad70ABGQlMEyq9
+D0T0/h6v4WbA43KgRv3af7UMBYaPkqrtW3NBT7DRVS2wsItMudos4mdj4nJKCF5G
+NXwSdQv4N8Rd08aTIG9UYSYDGQcj/Vdz5ErbSyf7kN30eL9j/dwUac/qH3Kb4UK4
++QIDAQAB
+-----END PUBLIC KEY-----
+";
+const LDAP_PRIVATE_EXPONENT: &str = "\
+    2be167d1879f8eba8784b61757c8634423b78c33a78f1e44694530fa7ddd8eba\
+    c9231663f282f4612a474c3a569a5da50a545561e7cc1b4cc47afceb0f579253\
+    aed3cce8d9c5126be7bacba49c1adc07e3cbd81ff61e894f288a045679abc350\
+    19038e782e2615041662bb0b2a5ba91bb43264cba6336157d3e68b75d6d4affb\
+    0997061dab32ea4b20c785eef7eb3ffd1b46af3041b3dfd6401de256a4ff241e\
+    6f4b859a1c1053497de8b1081cac9dac65fb2b1f3c9ccf5ecde3b37f5b6b1520\
+    bdd795eece1ee0c150deea45aa46bbd2cf9161743e02c29f9f8e00995f6d8a37\
+    b98013df4a3bc23a7ce1e5b31ce0be9612b719337c6c4204707168e29881a0f1";
+
+// Serves a single client connection until the client disconnects.
+pub(super) fn serve(mut stream: TcpStream, shared: &Shared) {
+    let result = match (&shared.config.websocket_path, &shared.config.tls) {
+        (Some(path), _) => accept_websocket_sync(stream, path)
+            .map_err(Into::into)
+            .and_then(|mut ws_stream| Session::new(shared).run(&mut ws_stream)),
+        (None, Some((certificate, private_key))) => accept_tls(stream, certificate, private_key)
+            .and_then(|mut tls_stream| Session::new(shared).run(&mut tls_stream)),
+        (None, None) => Session::new(shared).run(&mut stream),
+    };
+    if let Err(e) = result {
+        debug!("mock server session ended with error: {e}");
+    }
+}
+
+// The TLS handshake is done with the first read of the session.
+fn accept_tls(
+    stream: TcpStream,
+    certificate: &str,
+    private_key: &str,
+) -> HdbResult<StreamOwned<ServerConnection, TcpStream>> {
+    let certificates = CertificateDer::pem_slice_iter(certificate.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| impl_err!("mock server: server certificate cannot be parsed: {e}"))?;
+    let private_key = PrivateKeyDer::from_pem_slice(private_key.as_bytes())
+        .map_err(|e| impl_err!("mock server: private key cannot be parsed: {e}"))?;
+    let server_config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certificates, private_key)
+        .map_err(|e| impl_err!("mock server: TLS configuration failed: {e}"))?;
+    let server_connection = ServerConnection::new(Arc::new(server_config))
+        .map_err(|e| impl_err!("mock server: TLS session cannot be created: {e}"))?;
+    Ok(StreamOwned::new(server_connection, stream))
+}
+
+struct Session<'a> {
+    shared: &'a Shared,
+    id: i64,
+    o_pending_authentication: Option<PendingAuthentication>,
+    authenticated: bool,
+    // only a password change is accepted
+    password_change_pending: bool,
+    fetch_size: usize,
+    // the query timeout of the current execution
+    o_query_timeout: Option<Duration>,
+    next_id: u64,
+    statements: HashMap<u64, PreparedMock>,
+    result_sets: HashMap<u64, OpenResultSet>,
+    lobs: HashMap<u64, ServedLob>,
+    lob_writes: HashMap<u64, LobWrite>,
+    // the session variables that were set with the client info
+    variables: HashMap<String, String>,
+}
+
+struct PendingAuthentication {
+    authenticator: MockAuthenticator,
+    salt: Vec<u8>,
+    server_nonce: Vec<u8>,
+    client_challenge: Vec<u8>,
+}
+
+struct PreparedMock {
+    sql: String,
+    statement: ScriptedStatement,
+}
+
+// The cursor is on the row that was transferred last, counting from 1;
+// 0 is before the first row, and rows().len() + 1 is after the last row.
+struct OpenResultSet {
+    result_set: MockResultSet,
+    scrollable: bool,
+    cursor: usize,
+}
+
+// LOB data that the client can read with ReadLob requests
+struct ServedLob {
+    type_id: TypeId,
+    data: Vec<u8>,
+}
+
+// LOB data that the client sends with WriteLob requests,
+// for a parameter value of a recorded execution
+struct LobWrite {
+    execution: usize,
+    row: usize,
+    column: usize,
+    type_id: TypeId,
+    data: Vec<u8>,
+}
+
+impl<'a> Session<'a> {
+    fn new(shared: &'a Shared) -> Self {
+        Self {
+            shared,
+            id: 0,
+            o_pending_authentication: None,
+            authenticated: false,
+            password_change_pending: false,
+            fetch_size: DEFAULT_FETCH_SIZE,
+            o_query_timeout: None,
+            next_id: 1,
+            statements: HashMap::new(),
+            result_sets: HashMap::new(),
+            lobs: HashMap::new(),
+            lob_writes: HashMap::new(),
+            variables: HashMap::new(),
+        }
+    }
+
+    fn run<S: Read + Write>(&mut self, stream: &mut S) -> HdbResult<()> {
+        handle_initial_request(stream)?;
+        while let Some(request) = MockRequest::read(stream)? {
+            let message_type = request.header.message_type;
+            trace!("mock server received {message_type:?}");
+            if let MessageType::Disconnect = message_type {
+                // the client does not wait for a reply
+                self.shared.disconnect_count.fetch_add(1, Ordering::SeqCst);
+                return Ok(());
+            }
+            if let Some(part) = request.part(PartKind::ClientInfo) {
+                self.update_session_variables(part)?;
+            }
+            let start = Instant::now();
+            let mut reply = self.handle(&request).unwrap_or_else(|e| {
+                warn!("mock server cannot handle {message_type:?}: {e}");
+                error_reply(&ServerError::new(
+                    0,
+                    0,
+                    Severity::Error,
+                    b"HY000".to_vec(),
+                    format!("mock server cannot handle {message_type:?}: {e}"),
+                ))
+            });
+            if !request.header.auto_commit {
+                add_transaction_flags(&mut reply)?;
+            }
+            if self.authenticated && !reply.is_error() && is_statement_related(message_type) {
+                add_statement_context(&mut reply, start.elapsed())?;
+            }
+            reply.emit(self.id, request.header.packet_seq_number, stream)?;
+        }
+        Ok(())
+    }
+
+    // with feature dist_tx, the wildcard also covers the XA message types
+    #[allow(clippy::match_wildcard_for_single_variants)]
+    fn handle(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        match request.header.message_type {
+            MessageType::Authenticate => self.authenticate(request),
+            MessageType::Connect => self.connect(request),
+            MessageType::DbConnectInfo => self.db_connect_info(request),
+            _ if !self.authenticated => Ok(error_reply(&ServerError::new(
+                10,
+                0,
+                Severity::Fatal,
+                b"28000".to_vec(),
+                "session is not authenticated".to_string(),
+            ))),
+            MessageType::ExecuteDirect if self.password_change_pending => {
+                self.change_password(request)
+            }
+            _ if self.password_change_pending => Ok(password_change_required(&self.shared.config)),
+            MessageType::ExecuteDirect => self.execute_direct(request),
+            MessageType::Prepare => self.prepare(request),
+            MessageType::Execute => self.execute(request),
+            MessageType::FetchNext => self.fetch_next(request),
+            MessageType::FetchFirst
+            | MessageType::FetchLast
+            | MessageType::FetchAbsolute
+            | MessageType::FetchRelative => self.scroll(request),
+            MessageType::CloseResultSet => {
+                let rs_id = read_id(request.required_part(PartKind::ResultSetId)?)?;
+                self.result_sets.remove(&rs_id);
+                Ok(MockReply::new(ReplyType::Nil))
+            }
+            MessageType::DropStatementId => {
+                let stmt_id = read_id(request.required_part(PartKind::StatementId)?)?;
+                self.statements.remove(&stmt_id);
+                Ok(MockReply::new(ReplyType::Nil))
+            }
+            MessageType::ReadLob => self.read_lob(request),
+            MessageType::WriteLob => self.write_lob(request),
+            message_type => Err(impl_err!("{message_type:?} is not supported")),
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    // First authentication roundtrip: select the configured method and send the challenge
+    fn authenticate(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        // user, followed by pairs of method name and client challenge
+        let fields = auth_fields(request.required_part(PartKind::Authentication)?)?;
+
+        if let Some((host, port)) = self
+            .shared
+            .script
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .redirect_authentication()
+        {
+            return redirect_reply(host, *port);
+        }
+
+        let client_challenge_of = |authenticator: MockAuthenticator| {
+            fields
+                .get(1..)
+                .unwrap_or_default()
+                .chunks(2)
+                .find(|pair| pair[0] == authenticator.name().as_bytes())
+                .and_then(|pair| pair.get(1))
+        };
+        // a session cookie is preferred, if the client proposes one
+        let (authenticator, o_client_challenge) =
+            match client_challenge_of(MockAuthenticator::SessionCookie) {
+                Some(client_challenge) if self.shared.config.session_cookies => {
+                    (MockAuthenticator::SessionCookie, Some(client_challenge))
+                }
+                _ => (
+                    self.shared.config.authenticator,
+                    client_challenge_of(self.shared.config.authenticator),
+                ),
+            };
+        let Some(client_challenge) = o_client_challenge else {
+            return Ok(authentication_failed());
+        };
+
+        let (salt, server_nonce, challenge_bytes) = match authenticator {
+            MockAuthenticator::X509 => {
+                // the server challenge is just a nonce
+                let mut server_nonce = vec![0_u8; 64];
+                rng().fill_bytes(&mut server_nonce);
+                (Vec::new(), server_nonce.clone(), server_nonce)
+            }
+            MockAuthenticator::Jwt | MockAuthenticator::Saml => {
+                // the token is checked right away, the server challenge is the logon name
+                if self
+                    .shared
+                    .config
+                    .bearer_token
+                    .as_deref()
+                    .map(str::as_bytes)
+                    != Some(client_challenge.as_slice())
+                {
+                    return Ok(authentication_failed());
+                }
+                (
+                    Vec::new(),
+                    Vec::new(),
+                    self.shared.config.user.as_bytes().to_vec(),
+                )
+            }
+            MockAuthenticator::Ldap => match ldap_server_challenge(client_challenge)? {
+                Some(challenge) => challenge,
+                None => return Ok(authentication_failed()),
+            },
+            MockAuthenticator::SessionCookie => {
+                // the client challenge is the cookie, followed by the client id
+                let issued_for_user = self.shared.session_cookies().iter().any(|(cookie, user)| {
+                    client_challenge.starts_with(cookie) && *user == self.shared.config.user
+                });
+                if !issued_for_user {
+                    return Ok(authentication_failed());
+                }
+                (Vec::new(), Vec::new(), Vec::new())
+            }
+            MockAuthenticator::ScramSha256 | MockAuthenticator::ScramPbkdf2Sha256 => {
+                let mut salt = vec![0_u8; 16];
+                rng().fill_bytes(&mut salt);
+                let mut server_nonce = vec![0_u8; 48];
+                rng().fill_bytes(&mut server_nonce);
+
+                let mut server_challenge = AuthFields::with_capacity(3);
+                server_challenge.push(salt.clone());
+                server_challenge.push(server_nonce.clone());
+                if let MockAuthenticator::ScramPbkdf2Sha256 = authenticator {
+                    let mut iterations = Vec::<u8>::with_capacity(4);
+                    iterations.write_u32::<BigEndian>(self.shared.config.iterations)?;
+                    server_challenge.push(iterations);
+                }
+                let mut challenge_bytes = Vec::<u8>::new();
+                server_challenge.emit(&mut challenge_bytes)?;
+                (salt, server_nonce, challenge_bytes)
+            }
+        };
+
+        self.o_pending_authentication = Some(PendingAuthentication {
+            authenticator,
+            salt,
+            server_nonce,
+            client_challenge: client_challenge.clone(),
+        });
+
+        let mut auth_fields = AuthFields::with_capacity(2);
+        auth_fields.push_string(authenticator.name());
+        auth_fields.push(challenge_bytes);
+        let mut reply = MockReply::new(ReplyType::Nil);
+        reply.push(ReplyPart::new(PartKind::Authentication, 1, |w| {
+            auth_fields.emit(w)
+        })?);
+        Ok(reply)
+    }
+
+    // Second authentication roundtrip: verify the client proof and open the session
+    fn connect(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        let fields = auth_fields(request.required_part(PartKind::Authentication)?)?;
+        let config = &self.shared.config;
+        let (Some(pending), [user, method, proof]) =
+            (self.o_pending_authentication.take(), fields.as_slice())
+        else {
+            return Ok(authentication_failed());
+        };
+        if user.as_slice() != config.user.as_bytes()
+            || method.as_slice() != pending.authenticator.name().as_bytes()
+        {
+            return Ok(authentication_failed());
+        }
+
+        let o_server_proof = match pending.authenticator {
+            MockAuthenticator::ScramSha256 | MockAuthenticator::ScramPbkdf2Sha256 => {
+                scram_server_proof(config, &self.shared.password(), &pending, proof)?
+            }
+            MockAuthenticator::X509 => x509_server_proof(config, &pending, proof)?,
+            MockAuthenticator::Ldap => ldap_server_proof(&self.shared.password(), &pending, proof)?,
+            // the server proof is a session cookie, or empty
+            MockAuthenticator::Jwt | MockAuthenticator::Saml => {
+                proof.is_empty().then(|| self.issue_session_cookie())
+            }
+            // the cookie was checked with the first request
+            MockAuthenticator::SessionCookie => proof.is_empty().then(Vec::new),
+        };
+        let Some(server_proof) = o_server_proof else {
+            return Ok(authentication_failed());
+        };
+
+        self.id = self.shared.next_session_id.fetch_add(1, Ordering::Relaxed);
+        self.authenticated = true;
+        self.password_change_pending = self.shared.password_change_required.load(Ordering::SeqCst);
+
+        let mut connect_options = ConnectOptionsPart::default();
+        #[allow(clippy::cast_possible_truncation)]
+        connect_options.insert(ConnOptId::ConnectionID, OptionValue::INT(self.id as i32));
+        connect_options.insert(
+            ConnOptId::SystemID,
+            OptionValue::STRING(config.system_id.clone()),
+        );
+        connect_options.insert(
+            ConnOptId::DatabaseName,
+            OptionValue::STRING(config.database_name.clone()),
+        );
+        connect_options.insert(
+            ConnOptId::FullVersionString,
+            OptionValue::STRING(config.full_version.clone()),
+        );
+        connect_options.insert(
+            ConnOptId::ImplicitLobStreaming,
+            OptionValue::BOOLEAN(config.implicit_lob_streaming),
+        );
+
+        let mut auth_fields = AuthFields::with_capacity(2);
+        auth_fields.push_string(pending.authenticator.name());
+        auth_fields.push(server_proof);
+
+        let mut reply = MockReply::new(ReplyType::Nil);
+        reply.push(ReplyPart::new(
+            PartKind::ConnectOptions,
+            connect_options.len(),
+            |w| connect_options.emit(w),
+        )?);
+        if let Some(topology) = self.topology() {
+            reply.push(ReplyPart::new(
+                PartKind::TopologyInformation,
+                topology.len(),
+                |w| topology.emit(w),
+            )?);
+        }
+        reply.push(ReplyPart::new(PartKind::Authentication, 1, |w| {
+            auth_fields.emit(w)
+        })?);
+        if self.password_change_pending {
+            reply.push(error_part(&password_change_required_error(config))?);
+        }
+        Ok(reply)
+    }
+
+    // The only statement that a session with a required password change accepts
+    fn change_password(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        let sql = command(request)?;
+        let Some((user, new_password)) =
+            password_change(&sql).filter(|(user, _)| *user == self.shared.config.user)
+        else {
+            return Ok(password_change_required(&self.shared.config));
+        };
+        self.record_execution(MockExecution::new(
+            format!("ALTER USER {user} PASSWORD ***"),
+            Vec::new(),
+        ));
+        *self.shared.password() = new_password;
+        self.shared
+            .password_change_required
+            .store(false, Ordering::SeqCst);
+        self.password_change_pending = false;
+        let mut reply = MockReply::new(ReplyType::Ddl);
+        reply.push(execution_results_part(&ExecutionResults::new(vec![
+            ExecutionResult::SuccessNoInfo,
+        ]))?);
+        Ok(reply)
+    }
+
+    fn issue_session_cookie(&self) -> Vec<u8> {
+        if !self.shared.config.session_cookies {
+            return Vec::new();
+        }
+        let mut session_cookie = vec![0_u8; 32];
+        rng().fill_bytes(&mut session_cookie);
+        self.shared
+            .session_cookies()
+            .insert(session_cookie.clone(), self.shared.config.user.clone());
+        session_cookie
+    }
+
+    fn db_connect_info(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        let request_part = request.required_part(PartKind::DbConnectInfo)?;
+        let incoming = DbConnectInfo::parse(request_part.count, &mut request_part.reader())?;
+        let db_name = incoming.get(&DbConnectInfoId::DatabaseName)?.get_string()?;
+
+        let mut db_connect_info = DbConnectInfo::default();
+        match self.script().redirect_database(db_name) {
+            Some((host, port)) => {
+                db_connect_info.insert(
+                    DbConnectInfoId::OnCorrectDatabase,
+                    OptionValue::BOOLEAN(false),
+                );
+                db_connect_info.insert(DbConnectInfoId::Host, OptionValue::STRING(host.clone()));
+                db_connect_info.insert(DbConnectInfoId::Port, OptionValue::INT(i32::from(*port)));
+            }
+            None => {
+                db_connect_info.insert(
+                    DbConnectInfoId::OnCorrectDatabase,
+                    OptionValue::BOOLEAN(true),
+                );
+            }
+        }
+        let mut reply = MockReply::new(ReplyType::Nil);
+        reply.push(ReplyPart::new(
+            PartKind::DbConnectInfo,
+            db_connect_info.len(),
+            |w| db_connect_info.emit(w),
+        )?);
+        Ok(reply)
+    }
+
+    fn execute_direct(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        self.update_fetch_size(request)?;
+        self.update_query_timeout(request)?;
+        let sql = command(request)?;
+
+        if let Some(session_id) = session_to_cancel(&sql) {
+            self.record_execution(MockExecution::new(sql.clone(), Vec::new()));
+            self.cancel_session(session_id);
+            return self.respond(&sql, &MockResponse::Success, 1, true, false);
+        }
+
+        let o_reply_type = match first_keyword(&sql).as_str() {
+            "COMMIT" => Some(ReplyType::Commit),
+            "ROLLBACK" => Some(ReplyType::Rollback),
+            _ => None,
+        };
+        if let Some(reply_type) = o_reply_type {
+            self.record_execution(MockExecution::new(sql, Vec::new()));
+            let mut reply = MockReply::new(reply_type);
+            reply.push(execution_results_part(&ExecutionResults::new(vec![
+                ExecutionResult::SuccessNoInfo,
+            ]))?);
+            return Ok(reply);
+        }
+
+        let Some(statement) = self.scripted_statement(&sql) else {
+            return Ok(not_scripted(&sql));
+        };
+        self.record_execution(MockExecution::new(sql.clone(), Vec::new()));
+        let scrollable = request.header.command_options.is_scrollable_cursor_on();
+        self.respond(&sql, statement.response(), 1, true, scrollable)
+    }
+
+    fn prepare(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        let sql = command(request)?;
+        let Some(statement) = self.scripted_statement(&sql) else {
+            return Ok(not_scripted(&sql));
+        };
+
+        let statement_id = self.next_id();
+        let descriptors = statement.descriptors();
+        let o_result_set_metadata = statement
+            .response()
+            .result_set()
+            .map(MockResultSet::metadata);
+        let o_partitioning = self.script().partitioning(&sql).cloned();
+        self.statements
+            .insert(statement_id, PreparedMock { sql, statement });
+
+        let mut reply = MockReply::new(if o_result_set_metadata.is_some() {
+            ReplyType::Select
+        } else {
+            ReplyType::Nil
+        });
+        reply.push(ReplyPart::new(PartKind::StatementId, 1, |w| {
+            Ok(w.write_u64::<LittleEndian>(statement_id)?)
+        })?);
+        if !descriptors.is_empty() {
+            reply.push(ReplyPart::new(
+                PartKind::ParameterMetadata,
+                descriptors.len(),
+                |w| descriptors.emit(w),
+            )?);
+        }
+        if let Some(result_set_metadata) = o_result_set_metadata {
+            reply.push(ReplyPart::new(
+                PartKind::ResultSetMetadata,
+                result_set_metadata.len(),
+                |w| result_set_metadata.emit(w),
+            )?);
+        }
+        if let Some(partitioning) = o_partitioning {
+            add_partitioning(&mut reply, &partitioning)?;
+        }
+        Ok(reply)
+    }
+
+    fn execute(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        self.update_fetch_size(request)?;
+        self.update_query_timeout(request)?;
+        let statement_id = read_id(request.required_part(PartKind::StatementId)?)?;
+        let Some(prepared) = self.statements.get(&statement_id) else {
+            return Err(impl_err!("unknown statement id {statement_id}"));
+        };
+        let sql = prepared.sql.clone();
+        let response = prepared.statement.response().clone();
+        let descriptors = prepared.statement.descriptors();
+
+        let (parameter_rows, lob_placeholders) = match request.part(PartKind::Parameters) {
+            Some(part) => parse_parameter_rows(part.count, &descriptors, &mut part.reader())?,
+            None => (Vec::new(), Vec::new()),
+        };
+        let no_of_rows = parameter_rows.len().max(1);
+        let execution = self.record_execution(MockExecution::new(sql.clone(), parameter_rows));
+        let scrollable = request.header.command_options.is_scrollable_cursor_on();
+
+        if lob_placeholders.is_empty() {
+            return self.respond(&sql, &response, no_of_rows, false, scrollable);
+        }
+
+        // the client will send the LOB data with subsequent WriteLob requests
+        let mut locator_ids = Vec::<u64>::with_capacity(lob_placeholders.len());
+        for (row, column, type_id) in lob_placeholders {
+            let locator_id = self.next_id();
+            self.lob_writes.insert(
+                locator_id,
+                LobWrite {
+                    execution,
+                    row,
+                    column,
+                    type_id,
+                    data: Vec::new(),
+                },
+            );
+            locator_ids.push(locator_id);
+        }
+        let mut reply = self.respond(&sql, &response, no_of_rows, false, scrollable)?;
+        let write_lob_reply = WriteLobReply::new(locator_ids);
+        reply.push(ReplyPart::new(
+            PartKind::WriteLobReply,
+            write_lob_reply.len(),
+            |w| write_lob_reply.emit(w),
+        )?);
+        Ok(reply)
+    }
+
+    fn fetch_next(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        self.update_fetch_size(request)?;
+        let rs_id = read_id(request.required_part(PartKind::ResultSetId)?)?;
+        let cursor = self.open_result_set(rs_id)?.cursor;
+        let mut reply = MockReply::new(ReplyType::Fetch);
+        reply.push(self.result_set_part(rs_id, row_number(cursor) + 1)?);
+        Ok(reply)
+    }
+
+    // Positions the cursor of a scrollable result set and sends the rows from there on.
+    fn scroll(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        self.update_fetch_size(request)?;
+        let rs_id = read_id(request.required_part(PartKind::ResultSetId)?)?;
+        let open_rs = self.open_result_set(rs_id)?;
+        if !open_rs.scrollable {
+            return Ok(error_reply(&ServerError::new(
+                2048,
+                0,
+                Severity::Error,
+                b"HY000".to_vec(),
+                "column store error: cursor is not scrollable".to_string(),
+            )));
+        }
+        let total = row_number(open_rs.result_set.rows().len());
+        let cursor = row_number(open_rs.cursor);
+        let position = || -> HdbResult<i64> {
+            let part = request.required_part(PartKind::FetchOptions)?;
+            Ok(i64::from(
+                FetchOptions::parse(part.count, &mut part.reader())?.position()?,
+            ))
+        };
+        let target = match request.header.message_type {
+            MessageType::FetchFirst => 1,
+            MessageType::FetchLast => total,
+            MessageType::FetchAbsolute => match position()? {
+                row if row < 0 => total + 1 + row,
+                row => row,
+            },
+            _ => cursor + position()?,
+        };
+        let mut reply = MockReply::new(ReplyType::Fetch);
+        reply.push(self.result_set_part(rs_id, target)?);
+        Ok(reply)
+    }
+
+    fn read_lob(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        let read_lob_request =
+            ReadLobRequest::parse(&mut request.required_part(PartKind::ReadLobRequest)?.reader())?;
+        let locator_id = read_lob_request.locator_id();
+        let lob = self
+            .lobs
+            .get(&locator_id)
+            .ok_or_else(|| impl_err!("unknown locator id {locator_id}"))?;
+
+        // offset and length count bytes for BLOBs and CLOBs,
+        // and 1-2-3-sequences for NCLOBs; offset starts with 1
+        let starts = unit_starts(lob.type_id, &lob.data);
+        let first = usize::try_from(read_lob_request.offset().saturating_sub(1))
+            .unwrap_or(usize::MAX)
+            .min(starts.len());
+        let last = first
+            .saturating_add(read_lob_request.length() as usize)
+            .min(starts.len());
+        let byte_position = |unit: usize| starts.get(unit).copied().unwrap_or(lob.data.len());
+        let data = lob.data[byte_position(first)..byte_position(last)].to_vec();
+        let is_last_data = last == starts.len();
+        if is_last_data {
+            self.lobs.remove(&locator_id);
+        }
+
+        let read_lob_reply = ReadLobReply::new(locator_id, is_last_data, data);
+        let mut reply = MockReply::new(ReplyType::ReadLob);
+        reply.push(ReplyPart::new(PartKind::ReadLobReply, 1, |w| {
+            read_lob_reply.emit(w)
+        })?);
+        Ok(reply)
+    }
+
+    fn write_lob(&mut self, request: &MockRequest) -> HdbResult<MockReply> {
+        let (locator_id, _offset, data, is_last_data) = WriteLobRequest::parse(
+            &mut request.required_part(PartKind::WriteLobRequest)?.reader(),
+        )?;
+        let lob_write = self
+            .lob_writes
+            .get_mut(&locator_id)
+            .ok_or_else(|| impl_err!("unknown locator id {locator_id}"))?;
+        lob_write.data.extend_from_slice(&data);
+
+        if is_last_data {
+            let lob_write = self.lob_writes.remove(&locator_id).unwrap(/*OK*/);
+            let value = match lob_write.type_id {
+                TypeId::BLOB => HdbValue::BINARY(lob_write.data),
+                _ => HdbValue::STRING(
+                    cesu8::from_cesu8(&lob_write.data)
+                        .map_err(|_| impl_err!("received LOB data is not valid cesu8"))?
+                        .to_string(),
+                ),
+            };
+            let mut executions = self
+                .shared
+                .executions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if let Some(slot) = executions
+                .get_mut(lob_write.execution)
+                .and_then(|execution| execution.parameter_rows_mut().get_mut(lob_write.row))
+                .and_then(|row| row.get_mut(lob_write.column))
+            {
+                *slot = value;
+            }
+        }
+
+        let write_lob_reply = WriteLobReply::new(vec![locator_id]);
+        let mut reply = MockReply::new(ReplyType::WriteLob);
+        reply.push(ReplyPart::new(
+            PartKind::WriteLobReply,
+            write_lob_reply.len(),
+            |w| write_lob_reply.emit(w),
+        )?);
+        Ok(reply)
+    }
+
+    // Builds the reply for the execution of a scripted statement.
+    fn respond(
+        &mut self,
+        sql: &str,
+        response: &MockResponse,
+        no_of_rows: usize,
+        with_metadata: bool,
+        scrollable: bool,
+    ) -> HdbResult<MockReply> {
+        match response {
+            MockResponse::Success => {
+                let mut reply = MockReply::new(ReplyType::Ddl);
+                reply.push(execution_results_part(&ExecutionResults::new(vec![
+                    ExecutionResult::SuccessNoInfo,
+                ]))?);
+                Ok(reply)
+            }
+            MockResponse::AffectedRows(count) => {
+                let mut reply = MockReply::new(match first_keyword(sql).as_str() {
+                    "INSERT" => ReplyType::Insert,
+                    "UPDATE" => ReplyType::Update,
+                    "DELETE" => ReplyType::Delete,
+                    _ => ReplyType::Nil,
+                });
+                reply.push(execution_results_part(&ExecutionResults::new(
+                    (0..no_of_rows)
+                        .map(|_| ExecutionResult::RowsAffected(*count))
+                        .collect(),
+                ))?);
+                Ok(reply)
+            }
+            MockResponse::ResultSet(result_set) => {
+                let rs_id = self.next_id();
+                let mut reply = MockReply::new(ReplyType::Select);
+                if with_metadata {
+                    let result_set_metadata = result_set.metadata();
+                    reply.push(ReplyPart::new(
+                        PartKind::ResultSetMetadata,
+                        result_set_metadata.len(),
+                        |w| result_set_metadata.emit(w),
+                    )?);
+                }
+                reply.push(ReplyPart::new(PartKind::ResultSetId, 1, |w| {
+                    Ok(w.write_u64::<LittleEndian>(rs_id)?)
+                })?);
+                self.result_sets.insert(
+                    rs_id,
+                    OpenResultSet {
+                        result_set: result_set.clone(),
+                        scrollable,
+                        cursor: 0,
+                    },
+                );
+                reply.push(self.result_set_part(rs_id, 1)?);
+                Ok(reply)
+            }
+            MockResponse::Error(server_error) => Ok(error_reply(server_error)),
+            MockResponse::Delayed(duration, response) => {
+                if self.run_statement(*duration) {
+                    self.respond(sql, response, no_of_rows, with_metadata, scrollable)
+                } else {
+                    Ok(error_reply(&cancelled_error()))
+                }
+            }
+        }
+    }
+
+    // Lets a statement run for the given time; returns false if the statement is cancelled,
+    // or if its query timeout expires first.
+    fn run_statement(&self, duration: Duration) -> bool {
+        let (run_time, timed_out) = match self.o_query_timeout {
+            Some(query_timeout) if query_timeout < duration => (query_timeout, true),
+            _ => (duration, false),
+        };
+        let mut running_statements = self.shared.running_statements();
+        running_statements.insert(self.id, false);
+        let (mut running_statements, _) = self
+            .shared
+            .statement_cancelled
+            .wait_timeout_while(running_statements, run_time, |running_statements| {
+                running_statements.get(&self.id) == Some(&false)
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        let cancelled = running_statements.remove(&self.id) == Some(true);
+        !(cancelled || timed_out)
+    }
+
+    // Cancels the statement that the given session runs, if any.
+    fn cancel_session(&self, session_id: i64) {
+        if let Some(cancelled) = self.shared.running_statements().get_mut(&session_id) {
+            *cancelled = true;
+            self.shared.statement_cancelled.notify_all();
+        }
+    }
+
+    fn open_result_set(&self, rs_id: u64) -> HdbResult<&OpenResultSet> {
+        self.result_sets
+            .get(&rs_id)
+            .ok_or_else(|| impl_err!("unknown result set id {rs_id}"))
+    }
+
+    // Produces a packet of rows of an open result set, starting with the given row
+    // (counting from 1), and moves the cursor to the last row of the packet.
+    // Result sets with a scrollable cursor stay open until the client closes them.
+    fn result_set_part(&mut self, rs_id: u64, first_row: i64) -> HdbResult<ReplyPart> {
+        let mut open_rs = self
+            .result_sets
+            .remove(&rs_id)
+            .ok_or_else(|| impl_err!("unknown result set id {rs_id}"))?;
+        let total = open_rs.result_set.rows().len();
+        let start = usize::try_from(first_row.saturating_sub(1))
+            .unwrap_or(0)
+            .min(total);
+        let end = if first_row < 1 {
+            start
+        } else {
+            start.saturating_add(self.fetch_size.max(1)).min(total)
+        };
+
+        let mut body = Vec::<u8>::new();
+        for row in &open_rs.result_set.rows()[start..end] {
+            let columns = open_rs.result_set.columns();
+            if row.len() != columns.len() {
+                return Err(impl_err!(
+                    "scripted row has {} values, but result set has {} columns",
+                    row.len(),
+                    columns.len()
+                ));
+            }
+            for (value, column) in row.iter().zip(columns) {
+                if value.is_null() && !column.is_nullable() {
+                    return Err(impl_err!("NULL value in not-nullable column"));
+                }
+                self.emit_value(value, column.type_id(), column.scale(), &mut body)?;
+            }
+        }
+
+        let attributes = if first_row < 1 {
+            open_rs.cursor = 0;
+            ROW_NOT_FOUND
+        } else if start == end && open_rs.scrollable {
+            open_rs.cursor = total + 1;
+            LAST_PACKET | ROW_NOT_FOUND
+        } else {
+            open_rs.cursor = end;
+            if end == total {
+                LAST_PACKET
+            } else {
+                NEXT_PACKET
+            }
+        };
+        let attributes = if open_rs.scrollable || attributes & LAST_PACKET == 0 {
+            self.result_sets.insert(rs_id, open_rs);
+            attributes
+        } else {
+            attributes | RESULTSET_IS_CLOSED
+        };
+        Ok(ReplyPart::new(
+            PartKind::ResultSet,
+            end - start,
+            |w| Ok(w.write_all(&body)?),
+        )?
+        .with_attributes(attributes))
+    }
+
+    fn emit_value(
+        &mut self,
+        value: &HdbValue<'static>,
+        type_id: TypeId,
+        scale: i16,
+        w: &mut dyn Write,
+    ) -> HdbResult<()> {
+        if !matches!(type_id, TypeId::BLOB | TypeId::CLOB | TypeId::NCLOB) {
+            return value.emit_in_result_set(type_id, scale, w);
+        }
+
+        let data = match (value, type_id) {
+            (HdbValue::NULL, _) => return emit_lob_descriptor(type_id, None, w),
+            (HdbValue::BINARY(bytes), TypeId::BLOB) => bytes.clone(),
+            (HdbValue::STRING(s), TypeId::CLOB | TypeId::NCLOB) => cesu8::to_cesu8(s).to_vec(),
+            (HdbValue::STR(s), TypeId::CLOB | TypeId::NCLOB) => cesu8::to_cesu8(s).to_vec(),
+            _ => {
+                return Err(impl_err!(
+                    "cannot send {value:?} as value of type {type_id:?}"
+                ));
+            }
+        };
+        let starts = unit_starts(type_id, &data);
+        let char_length = starts.len() as u64;
+        let byte_length = data.len() as u64;
+
+        // send the first chunk inline, without splitting a surrogate pair
+        let chunk_size = self.shared.config.lob_chunk_size;
+        let mut chunk_units = starts.partition_point(|&start| start < chunk_size);
+        if chunk_units < starts.len()
+            && chunk_units > 0
+            && is_low_surrogate(&data[starts[chunk_units]..])
+        {
+            chunk_units -= 1;
+        }
+        let chunk_end = starts.get(chunk_units).copied().unwrap_or(data.len());
+        let is_last_data = chunk_end == data.len();
+
+        let locator_id = self.next_id();
+        emit_lob_descriptor(
+            type_id,
+            Some((
+                char_length,
+                byte_length,
+                locator_id,
+                &data[..chunk_end],
+                is_last_data,
+            )),
+            w,
+        )?;
+        if !is_last_data {
+            self.lobs.insert(locator_id, ServedLob { type_id, data });
+        }
+        Ok(())
+    }
+
+    // The announced topology, with the host of this server as the one of the current session
+    fn topology(&self) -> Option<Topology> {
+        let script = self.script();
+        let hosts: Vec<(&str, u16, i32, bool)> = script
+            .topology()
+            .iter()
+            .map(|host| {
+                let is_current_session = host.port() == self.shared.addr.port()
+                    && host.host() == self.shared.addr.ip().to_string();
+                (
+                    host.host(),
+                    host.port(),
+                    host.volume_id(),
+                    is_current_session,
+                )
+            })
+            .collect();
+        (!hosts.is_empty()).then(|| Topology::new_for_server(&hosts))
+    }
+
+    fn script(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.shared
+            .script
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn scripted_statement(&self, sql: &str) -> Option<ScriptedStatement> {
+        self.script().statement(sql).cloned()
+    }
+
+    // Returns the index of the recorded execution.
+    fn record_execution(&self, mut execution: MockExecution) -> usize {
+        execution.set_session_variables(self.variables.clone());
+        let mut executions = self
+            .shared
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        executions.push(execution);
+        executions.len() - 1
+    }
+
+    // The client info consists of pairs of names and values; an empty value unsets the variable.
+    fn update_session_variables(&mut self, part: &RequestPart) -> HdbResult<()> {
+        let mut rdr = part.reader();
+        let mut read_string = || -> HdbResult<String> {
+            let l8 = rdr.read_u8()?;
+            let length = length_indicator::parse(l8, &mut rdr)?;
+            Ok(
+                cesu8::from_cesu8(&util_sync::parse_bytes(length, &mut rdr)?)
+                    .map_err(|_| impl_err!("client info is not valid cesu8"))?
+                    .to_string(),
+            )
+        };
+        for _ in 0..part.count / 2 {
+            let (name, value) = (read_string()?, read_string()?);
+            if value.is_empty() {
+                self.variables.remove(&name);
+            } else {
+                self.variables.insert(name, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn update_fetch_size(&mut self, request: &MockRequest) -> HdbResult<()> {
+        if let Some(part) = request.part(PartKind::FetchSize) {
+            self.fetch_size = part.reader().read_u32::<LittleEndian>()? as usize;
+        }
+        Ok(())
+    }
+
+    fn update_query_timeout(&mut self, request: &MockRequest) -> HdbResult<()> {
+        self.o_query_timeout = match request.part(PartKind::StatementContext) {
+            Some(part) => StatementContext::parse(part.count, &mut part.reader())?.query_timeout(),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+fn auth_fields(part: &RequestPart) -> HdbResult<Vec<Vec<u8>>> {
+    let mut auth_fields = AuthFields::parse(&mut part.reader())?;
+    let mut fields = Vec::<Vec<u8>>::new();
+    while let Some(field) = auth_fields.pop() {
+        fields.push(field);
+    }
+    fields.reverse();
+    Ok(fields)
+}
+
+fn command(request: &MockRequest) -> HdbResult<String> {
+    let part = request.required_part(PartKind::Command)?;
+    Ok(cesu8::from_cesu8(&part.body)
+        .map_err(|_| impl_err!("command is not valid cesu8"))?
+        .to_string())
+}
+
+fn read_id(part: &RequestPart) -> HdbResult<u64> {
+    Ok(part.reader().read_u64::<LittleEndian>()?)
+}
+
+fn row_number(count: usize) -> i64 {
+    i64::try_from(count).unwrap_or(i64::MAX)
+}
+
+fn first_keyword(sql: &str) -> String {
+    sql.split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_uppercase()
+}
+
+// Byte positions at which the units start that are used for counting LOB lengths:
+// bytes for BLOBs and CLOBs, 1-2-3-sequences for NCLOBs.
+fn unit_starts(type_id: TypeId, data: &[u8]) -> Vec<usize> {
+    match type_id {
+        TypeId::NCLOB => data
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| matches!(**b, 0x00..=0x7F | 0xC0..=0xDF | 0xE0..=0xEF | 0xF0..=0xF7))
+            .map(|(i, _)| i)
+            .collect(),
+        _ => (0..data.len()).collect(),
+    }
+}
+
+// cesu8 encodes low surrogates as ED B0..BF xx
+fn is_low_surrogate(bytes: &[u8]) -> bool {
+    matches!(bytes, [0xED, 0xB0..=0xBF, ..])
+}
+
+// Without auto-commit, the client learns from the transaction flags
+// whether the transaction has uncommitted changes.
+fn add_transaction_flags(reply: &mut MockReply) -> HdbResult<()> {
+    let flag = match reply.reply_type() {
+        ReplyType::Insert | ReplyType::Update | ReplyType::Delete => TaFlagId::WriteTaStarted,
+        ReplyType::Commit => TaFlagId::Committed,
+        ReplyType::Rollback => TaFlagId::RolledBack,
+        _ => return Ok(()),
+    };
+    let mut transaction_flags = TransactionFlags::default();
+    transaction_flags.insert(flag, OptionValue::BOOLEAN(true));
+    reply.push_front(ReplyPart::new(
+        PartKind::TransactionFlags,
+        transaction_flags.len(),
+        |w| transaction_flags.emit(w),
+    )?);
+    Ok(())
+}
+
+fn is_statement_related(message_type: MessageType) -> bool {
+    !matches!(
+        message_type,
+        MessageType::Authenticate | MessageType::Connect | MessageType::DbConnectInfo
+    )
+}
+
+// Reports the time the mock server needed for handling the request as server processing time
+fn add_statement_context(reply: &mut MockReply, server_processing_time: Duration) -> HdbResult<()> {
+    let mut statement_context = StatementContext::default();
+    statement_context.set_server_processing_time(server_processing_time);
+    reply.insert_before_result_set(ReplyPart::new(
+        PartKind::StatementContext,
+        statement_context.len(),
+        |w| statement_context.emit(w),
+    )?);
+    Ok(())
+}
+
+// Announces the location of the table of a prepared statement
+fn add_partitioning(reply: &mut MockReply, partitioning: &MockPartitioning) -> HdbResult<()> {
+    let o_partition_information = match partitioning {
+        MockPartitioning::Located(volumes) => {
+            reply.push(ReplyPart::new(
+                PartKind::TableLocation,
+                volumes.len(),
+                |w| {
+                    for volume_id in volumes {
+                        w.write_i32::<LittleEndian>(*volume_id)?;
+                    }
+                    Ok(())
+                },
+            )?);
+            None
+        }
+        MockPartitioning::RoundRobin(volumes) => Some((
+            PartitionInformation::new_for_server(PartitionMethod::RoundRobin, volumes),
+            [].as_slice(),
+        )),
+        MockPartitioning::Hash {
+            parameters,
+            volumes,
+        } => Some((
+            PartitionInformation::new_for_server(PartitionMethod::Hash, volumes),
+            parameters.as_slice(),
+        )),
+    };
+    if let Some((partition_information, parameter_indexes)) = o_partition_information {
+        reply.push(ReplyPart::new(PartKind::PartitionInformation, 1, |w| {
+            partition_information.emit(parameter_indexes, w)
+        })?);
+    }
+    Ok(())
+}
+
+fn error_part(server_error: &ServerError) -> HdbResult<ReplyPart> {
+    ReplyPart::new(PartKind::Error, 1, |w| server_error.emit(w))
+}
+
+fn error_reply(server_error: &ServerError) -> MockReply {
+    let mut reply = MockReply::new_error();
+    match error_part(server_error) {
+        Ok(part) => reply.push(part),
+        Err(e) => warn!("mock server cannot serialize server error: {e}"),
+    }
+    reply
+}
+
+fn execution_results_part(execution_results: &ExecutionResults) -> HdbResult<ReplyPart> {
+    ReplyPart::new(PartKind::ExecutionResults, execution_results.len(), |w| {
+        execution_results.emit(w)
+    })
+}
+
+// The (empty) salt, the server nonce, and the server challenge, which adds the server nonce
+// and the public key to the client nonce and the capabilities of the client challenge
+#[allow(clippy::type_complexity)]
+fn ldap_server_challenge(
+    client_challenge: &[u8],
+) -> HdbResult<Option<(Vec<u8>, Vec<u8>, Vec<u8>)>> {
+    let mut client_fields = AuthFields::parse(&mut std::io::Cursor::new(client_challenge))?;
+    let (Some(capabilities), Some(client_nonce)) = (client_fields.pop(), client_fields.pop())
+    else {
+        return Ok(None);
+    };
+    let mut server_nonce = vec![0_u8; 64];
+    rng().fill_bytes(&mut server_nonce);
+
+    let mut server_challenge = AuthFields::with_capacity(4);
+    server_challenge.push(client_nonce);
+    server_challenge.push(server_nonce.clone());
+    server_challenge.push_string(LDAP_PUBLIC_KEY);
+    server_challenge.push(capabilities);
+    let mut challenge_bytes = Vec::<u8>::new();
+    server_challenge.emit(&mut challenge_bytes)?;
+    Ok(Some((Vec::new(), server_nonce, challenge_bytes)))
+}
+
+// An empty server proof, if the client sent the encrypted password
+fn ldap_server_proof(
+    password: &str,
+    pending: &PendingAuthentication,
+    proof: &[u8],
+) -> HdbResult<Option<Vec<u8>>> {
+    let mut proof_fields = AuthFields::parse(&mut std::io::Cursor::new(proof))?;
+    let (Some(cipher), None) = (proof_fields.pop(), proof_fields.pop()) else {
+        return Ok(None);
+    };
+    let public_key = RsaPublicKey::from_pem(LDAP_PUBLIC_KEY.as_bytes())?;
+    let private_exponent = BigUint::parse_bytes(LDAP_PRIVATE_EXPONENT.as_bytes(), 16)
+        .ok_or_else(|| impl_err!("mock server: invalid private exponent"))?;
+
+    // the password, a zero byte, and the server nonce
+    let mut expected = password.as_bytes().to_vec();
+    expected.push(0);
+    expected.extend_from_slice(&pending.server_nonce);
+    Ok(
+        (rsa_decrypt_oaep_sha1(&public_key, &private_exponent, &cipher) == Some(expected))
+            .then(Vec::new),
+    )
+}
+
+// The server proof, if the client proof is correct
+fn scram_server_proof(
+    config: &MockServerConfig,
+    password: &str,
+    pending: &PendingAuthentication,
+    proof: &[u8],
+) -> HdbResult<Option<Vec<u8>>> {
+    let password = SecUtf8::from(password);
+    let (client_proof, server_proof) = match pending.authenticator {
+        MockAuthenticator::ScramPbkdf2Sha256 => scram_pdkdf2_sha256(
+            &pending.salt,
+            &pending.server_nonce,
+            &pending.client_challenge,
+            &password,
+            config.iterations,
+        ),
+        _ => scram_sha256(
+            &pending.salt,
+            &pending.server_nonce,
+            &pending.client_challenge,
+            &password,
+        ),
+    }
+    .map_err(|_| impl_err!("crypto_common::InvalidLength"))?;
+    // the proof is prefixed with its count (u16) and its length (u8)
+    if proof.get(3..) != Some(client_proof.as_slice()) {
+        return Ok(None);
+    }
+
+    match pending.authenticator {
+        MockAuthenticator::ScramPbkdf2Sha256 => {
+            let mut server_proof_fields = AuthFields::with_capacity(1);
+            server_proof_fields.push(server_proof);
+            let mut buf = Vec::<u8>::new();
+            server_proof_fields.emit(&mut buf)?;
+            Ok(Some(buf))
+        }
+        _ => Ok(Some(Vec::new())),
+    }
+}
+
+// The name of the user, if the client sent the configured certificate
+// and signed it, together with its chain and the server nonce
+fn x509_server_proof(
+    config: &MockServerConfig,
+    pending: &PendingAuthentication,
+    proof: &[u8],
+) -> HdbResult<Option<Vec<u8>>> {
+    let Some(ref client_certificate) = config.client_certificate else {
+        return Ok(None);
+    };
+    let expected_certificate = CertificateDer::pem_slice_iter(client_certificate.as_bytes())
+        .next()
+        .ok_or_else(|| impl_err!("mock server: no client certificate configured"))?
+        .map_err(|e| impl_err!("mock server: client certificate cannot be parsed: {e}"))?;
+
+    let mut proof_fields = AuthFields::parse(&mut std::io::Cursor::new(proof))?;
+    let (Some(signature), Some(chain), Some(certificate), None) = (
+        proof_fields.pop(),
+        proof_fields.pop(),
+        proof_fields.pop(),
+        proof_fields.pop(),
+    ) else {
+        return Ok(None);
+    };
+    if certificate != expected_certificate.as_ref() {
+        return Ok(None);
+    }
+
+    let mut message = certificate;
+    if !chain.is_empty() {
+        let mut chain_fields = AuthFields::parse(&mut std::io::Cursor::new(chain))?;
+        let mut chain_certificates = Vec::new();
+        while let Some(chain_certificate) = chain_fields.pop() {
+            chain_certificates.push(chain_certificate);
+        }
+        for chain_certificate in chain_certificates.iter().rev() {
+            message.extend_from_slice(chain_certificate);
+        }
+    }
+    message.extend_from_slice(&pending.server_nonce);
+
+    let end_entity_certificate = EndEntityCert::try_from(&expected_certificate)
+        .map_err(|e| impl_err!("mock server: client certificate not usable: {e}"))?;
+    let crypto_provider = Arc::clone(ClientConfig::builder().crypto_provider());
+    if !crypto_provider
+        .signature_verification_algorithms
+        .all
+        .iter()
+        .any(|algorithm| {
+            end_entity_certificate
+                .verify_signature(*algorithm, &message, &signature)
+                .is_ok()
+        })
+    {
+        return Ok(None);
+    }
+
+    let mut server_proof_fields = AuthFields::with_capacity(1);
+    server_proof_fields.push_string(&config.user);
+    let mut buf = Vec::<u8>::new();
+    server_proof_fields.emit(&mut buf)?;
+    Ok(Some(buf))
+}
+
+fn redirect_reply(host: &str, port: u16) -> HdbResult<MockReply> {
+    let mut reply = MockReply::new_error();
+    reply.push(error_part(&ServerError::new(
+        0,
+        0,
+        Severity::Error,
+        b"HY000".to_vec(),
+        "connection must be redirected".to_string(),
+    ))?);
+    let mut db_connect_info = DbConnectInfo::default();
+    db_connect_info.insert(DbConnectInfoId::Host, OptionValue::STRING(host.to_string()));
+    db_connect_info.insert(DbConnectInfoId::Port, OptionValue::INT(i32::from(port)));
+    reply.push(ReplyPart::new(
+        PartKind::DbConnectInfo,
+        db_connect_info.len(),
+        |w| db_connect_info.emit(w),
+    )?);
+    Ok(reply)
+}
+
+fn authentication_failed() -> MockReply {
+    error_reply(&ServerError::new(
+        10,
+        0,
+        Severity::Fatal,
+        b"28000".to_vec(),
+        "authentication failed".to_string(),
+    ))
+}
+
+fn password_change_required_error(config: &MockServerConfig) -> ServerError {
+    ServerError::new(
+        414,
+        0,
+        Severity::Error,
+        b"HY000".to_vec(),
+        format!(
+            "user is forced to change password: alter password for user {}",
+            config.user
+        ),
+    )
+}
+
+fn password_change_required(config: &MockServerConfig) -> MockReply {
+    error_reply(&password_change_required_error(config))
+}
+
+// The user and the new password of `ALTER USER <user> PASSWORD "<password>"`;
+// unquoted user names are normalized to upper case
+fn password_change(sql: &str) -> Option<(String, String)> {
+    let rest = strip_keyword(sql.trim_start(), "ALTER")?;
+    let rest = strip_keyword(rest, "USER")?;
+    let (user, rest) = if let Some((user, rest)) = quoted(rest) {
+        (user, rest)
+    } else {
+        let end = rest.find(char::is_whitespace)?;
+        (rest[..end].to_uppercase(), &rest[end..])
+    };
+    let rest = strip_keyword(rest.trim_start(), "PASSWORD")?;
+    let (password, rest) = quoted(rest)?;
+    rest.trim().is_empty().then_some((user, password))
+}
+
+fn strip_keyword<'s>(s: &'s str, keyword: &str) -> Option<&'s str> {
+    let rest = s.get(keyword.len()..)?;
+    (s[..keyword.len()].eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
+
+// A double-quoted string, in which double quotes are doubled, and the rest
+fn quoted(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices().peekable();
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        if c == '"' {
+            if chars.peek().is_some_and(|(_, c)| *c == '"') {
+                chars.next();
+            } else {
+                return Some((value, &s[i + 2..]));
+            }
+        }
+        value.push(c);
+    }
+    None
+}
+
+// The session id of `ALTER SYSTEM CANCEL SESSION '<id>'`
+fn session_to_cancel(sql: &str) -> Option<i64> {
+    let rest = strip_keyword(sql.trim_start(), "ALTER")?;
+    let rest = strip_keyword(rest, "SYSTEM")?;
+    let rest = strip_keyword(rest, "CANCEL")?;
+    let rest = strip_keyword(rest, "SESSION")?;
+    rest.trim()
+        .strip_prefix('\'')?
+        .strip_suffix('\'')?
+        .parse()
+        .ok()
+}
+
+fn cancelled_error() -> ServerError {
+    ServerError::new(
+        ServerError::CODE_CANCELLED,
+        0,
+        Severity::Error,
+        b"HY008".to_vec(),
+        "current operation cancelled by request and transaction rolled back".to_string(),
+    )
+}
+
+fn not_scripted(sql: &str) -> MockReply {
+    error_reply(&ServerError::new(
+        257,
+        0,
+        Severity::Error,
+        b"42000".to_vec(),
+        format!("sql syntax error: statement is not scripted in the mock server: {sql}"),
+    ))
+}
@@ -0,0 +1,237 @@
+use crate::{
+    impl_err,
+    protocol::{
+        emit_part_header, emit_reply_packet_header, padsize, parse_part_header,
+        parse_request_packet_header, PartKind, ReplyType, RequestPacketHeader,
+    },
+    HdbResult,
+};
+use std::io::{Read, Write};
+
+// The size of the initial request that the client sends to start the communication
+const INITIAL_REQUEST_SIZE: usize = 14;
+// The size of the initial response that the client skips over
+const INITIAL_RESPONSE_SIZE: usize = 8;
+// Message header and segment header
+const PACKET_HEADER_SIZE: usize = 56;
+
+// Counterpart of conn::initial_request: consumes the initial request and
+// sends back an initial response; the client ignores its content.
+pub(crate) fn handle_initial_request<S: Read + Write>(stream: &mut S) -> HdbResult<()> {
+    let mut buf = [0_u8; INITIAL_REQUEST_SIZE];
+    stream.read_exact(&mut buf)?;
+    stream.write_all(&[0_u8; INITIAL_RESPONSE_SIZE])?;
+    stream.flush()?;
+    Ok(())
+}
+
+// A request part in its raw form.
+#[derive(Debug)]
+pub(crate) struct RequestPart {
+    pub kind: PartKind,
+    pub count: usize,
+    pub body: Vec<u8>,
+}
+impl RequestPart {
+    pub(crate) fn reader(&self) -> std::io::Cursor<&[u8]> {
+        std::io::Cursor::new(&self.body)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct MockRequest {
+    pub header: RequestPacketHeader,
+    pub parts: Vec<RequestPart>,
+}
+impl MockRequest {
+    // Reads the next request; returns None if the client closed the connection.
+    pub(crate) fn read(rdr: &mut dyn Read) -> HdbResult<Option<Self>> {
+        let mut header_buf = [0_u8; PACKET_HEADER_SIZE];
+        match rdr.read_exact(&mut header_buf) {
+            Ok(()) => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::UnexpectedEof
+                        | std::io::ErrorKind::ConnectionReset
+                        | std::io::ErrorKind::ConnectionAborted
+                ) =>
+            {
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        }
+        let header = parse_request_packet_header(&mut std::io::Cursor::new(&header_buf[..]))?;
+
+        let mut body = vec![0_u8; header.part_buffer_size];
+        rdr.read_exact(&mut body)?;
+        if let Some(uncompressed_size) = header.o_uncompressed_size {
+            body = lz4_flex::block::decompress(&body, uncompressed_size)
+                .map_err(|e| impl_err!("decompression of request failed: {e}"))?;
+        }
+
+        let body_len = body.len() as u64;
+        let mut rdr = std::io::Cursor::new(body);
+        let mut parts = Vec::<RequestPart>::with_capacity(usize::from(header.no_of_parts));
+        for _ in 0..header.no_of_parts {
+            let (kind, _attributes, arg_size, count) = parse_part_header(&mut rdr)?;
+            let mut body = vec![0_u8; arg_size];
+            rdr.read_exact(&mut body)?;
+            // the client pads all parts, but we don't rely on the padding of the last one
+            #[allow(clippy::cast_possible_truncation)]
+            let remaining = (body_len - rdr.position()) as usize;
+            rdr.set_position(rdr.position() + padsize(arg_size).min(remaining) as u64);
+            parts.push(RequestPart { kind, count, body });
+        }
+        Ok(Some(Self { header, parts }))
+    }
+
+    pub(crate) fn part(&self, kind: PartKind) -> Option<&RequestPart> {
+        self.parts.iter().find(|part| part.kind == kind)
+    }
+
+    pub(crate) fn required_part(&self, kind: PartKind) -> HdbResult<&RequestPart> {
+        self.part(kind).ok_or_else(|| {
+            impl_err!(
+                "{:?} request without part {kind:?}",
+                self.header.message_type
+            )
+        })
+    }
+}
+
+// A reply part, with the body already serialized.
+pub(crate) struct ReplyPart {
+    kind: PartKind,
+    attributes: u8,
+    count: usize,
+    body: Vec<u8>,
+}
+impl ReplyPart {
+    pub(crate) fn new<F>(kind: PartKind, count: usize, emit_body: F) -> HdbResult<Self>
+    where
+        F: FnOnce(&mut Vec<u8>) -> HdbResult<()>,
+    {
+        let mut body = Vec::<u8>::new();
+        emit_body(&mut body)?;
+        Ok(Self {
+            kind,
+            attributes: 0,
+            count,
+            body,
+        })
+    }
+
+    pub(crate) fn with_attributes(mut self, attributes: u8) -> Self {
+        self.attributes = attributes;
+        self
+    }
+}
+
+pub(crate) struct MockReply {
+    reply_type: ReplyType,
+    is_error: bool,
+    parts: Vec<ReplyPart>,
+}
+impl MockReply {
+    pub(crate) fn new(reply_type: ReplyType) -> Self {
+        Self {
+            reply_type,
+            is_error: false,
+            parts: Vec::new(),
+        }
+    }
+
+    pub(crate) fn new_error() -> Self {
+        Self {
+            reply_type: ReplyType::Nil,
+            is_error: true,
+            parts: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, part: ReplyPart) {
+        self.parts.push(part);
+    }
+
+    pub(crate) fn reply_type(&self) -> ReplyType {
+        self.reply_type
+    }
+
+    pub(crate) fn push_front(&mut self, part: ReplyPart) {
+        self.parts.insert(0, part);
+    }
+
+    // Inserts the part right before a ResultSet part, where the client expects
+    // the StatementContext of a result set, or else at the front.
+    pub(crate) fn insert_before_result_set(&mut self, part: ReplyPart) {
+        let position = self
+            .parts
+            .iter()
+            .position(|part| part.kind == PartKind::ResultSet)
+            .unwrap_or(0);
+        self.parts.insert(position, part);
+    }
+
+    pub(crate) fn is_error(&self) -> bool {
+        self.is_error
+    }
+
+    // Mirrors the padding expectations of Part::parse_sync() and Part::parse_async():
+    // error parts are not padded (each server error pads itself),
+    // and a trailing ResultSet, ResultSetId, or ReadLobReply part is not padded either.
+    pub(crate) fn emit(
+        &self,
+        session_id: i64,
+        packet_seq_number: u32,
+        w: &mut dyn Write,
+    ) -> HdbResult<()> {
+        let no_of_parts = self.parts.len();
+        let padded_size = |i: usize, part: &ReplyPart| -> usize {
+            let is_last = i + 1 == no_of_parts;
+            match (part.kind, is_last) {
+                (PartKind::ResultSet | PartKind::ResultSetId | PartKind::ReadLobReply, true)
+                | (PartKind::Error, _) => part.body.len(),
+                (_, _) => part.body.len() + padsize(part.body.len()),
+            }
+        };
+        let parts_size: usize = self
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| 16 + padded_size(i, part))
+            .sum();
+
+        let mut buf = Vec::<u8>::with_capacity(PACKET_HEADER_SIZE + parts_size);
+        emit_reply_packet_header(
+            session_id,
+            packet_seq_number,
+            self.reply_type,
+            self.is_error,
+            i16::try_from(no_of_parts).map_err(|_| impl_err!("too many parts"))?,
+            u32::try_from(parts_size).map_err(|_| impl_err!("reply too big"))?,
+            &mut buf,
+        )?;
+        let mut remaining_bufsize = u32::try_from(parts_size).unwrap_or(u32::MAX);
+        for (i, part) in self.parts.iter().enumerate() {
+            emit_part_header(
+                part.kind,
+                part.attributes,
+                part.count,
+                part.body.len(),
+                remaining_bufsize,
+                &mut buf,
+            )?;
+            buf.write_all(&part.body)?;
+            let padded_size = padded_size(i, part);
+            buf.resize(buf.len() + padded_size - part.body.len(), 0);
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                remaining_bufsize = remaining_bufsize.saturating_sub(16 + padded_size as u32);
+            }
+        }
+        w.write_all(&buf)?;
+        w.flush()?;
+        Ok(())
+    }
+}
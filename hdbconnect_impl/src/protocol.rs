@@ -26,5 +26,18 @@ pub(crate) use self::{
 
 pub use self::server_usage::ServerUsage;
 
-const MESSAGE_AND_SEGMENT_HEADER_SIZE: usize = 32 + 24;
+#[cfg(any(feature = "mock_server", feature = "dissector"))]
+pub(crate) use self::{part::padsize, request::parse_packet_header as parse_request_packet_header};
+#[cfg(feature = "dissector")]
+pub(crate) use self::{
+    part::PART_HEADER_SIZE, reply::parse_packet_header as parse_reply_packet_header,
+};
+#[cfg(feature = "mock_server")]
+pub(crate) use self::{
+    part::{emit_header as emit_part_header, parse_header as parse_part_header},
+    reply::emit_packet_header as emit_reply_packet_header,
+    request::RequestPacketHeader,
+};
+
+pub(crate) const MESSAGE_AND_SEGMENT_HEADER_SIZE: usize = 32 + 24;
 const SEGMENT_HEADER_SIZE: u32 = 24;
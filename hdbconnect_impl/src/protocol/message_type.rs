@@ -1,3 +1,5 @@
+use crate::{impl_err, HdbResult};
+
 // Defines the action requested from the database server.
 // Is documented as Message Type.
 // Irrelevant RequestTypes (abap related, "reserved" stuff) are omitted.
@@ -13,6 +15,10 @@ pub(crate) enum MessageType {
     CloseResultSet = 69,  // Closes result set
     DropStatementId = 70, // Drops prepared statement identifier
     FetchNext = 71,       // Fetches next data from result set
+    FetchAbsolute = 72,   // Moves the cursor to the given row number and fetches the data
+    FetchRelative = 73,   // Like above, but moves the cursor relative to the current position
+    FetchFirst = 74,      // Moves the cursor to the first row and fetches the data
+    FetchLast = 75,       // Moves the cursor to the last row and fetches the data
     Disconnect = 77,      // Disconnects session
     DbConnectInfo = 82,   // Request/receive database connect information
     #[cfg(feature = "dist_tx")]
@@ -34,10 +40,6 @@ pub(crate) enum MessageType {
     // FindLob = 18,         // Finds data in a large object
     // Commit = 67,          // Commits current transaction
     // Rollback = 68,        // Rolls back current transaction
-    // FetchAbsolute = 72,   // Moves the cursor to the given row number and fetches the data
-    // FetchRelative = 73,   // Like above, but moves the cursor relative to the current position
-    // FetchFirst = 74,      // Moves the cursor to the first row and fetches the data
-    // FetchLast = 75,       // Moves the cursor to the last row and fetches the data
 }
 impl MessageType {
     // requests that depend on a result set id, or connection id, or prepared statement id
@@ -48,4 +50,92 @@ impl MessageType {
             Self::ExecuteDirect | Self::Prepare | Self::DbConnectInfo
         )
     }
+
+    pub(crate) fn is_fetch(self) -> bool {
+        matches!(
+            self,
+            Self::FetchNext
+                | Self::FetchAbsolute
+                | Self::FetchRelative
+                | Self::FetchFirst
+                | Self::FetchLast
+        )
+    }
+
+    pub(crate) fn is_lob(self) -> bool {
+        matches!(self, Self::ReadLob | Self::WriteLob)
+    }
+
+    // The name that is used in statistics and metrics.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::ExecuteDirect => "ExecuteDirect",
+            Self::Prepare => "Prepare",
+            Self::Execute => "Execute",
+            Self::ReadLob => "ReadLob",
+            Self::WriteLob => "WriteLob",
+            Self::Authenticate => "Authenticate",
+            Self::Connect => "Connect",
+            Self::CloseResultSet => "CloseResultSet",
+            Self::DropStatementId => "DropStatementId",
+            Self::FetchNext => "FetchNext",
+            Self::FetchAbsolute => "FetchAbsolute",
+            Self::FetchRelative => "FetchRelative",
+            Self::FetchFirst => "FetchFirst",
+            Self::FetchLast => "FetchLast",
+            Self::Disconnect => "Disconnect",
+            Self::DbConnectInfo => "DbConnectInfo",
+            #[cfg(feature = "dist_tx")]
+            Self::XAStart => "XAStart",
+            #[cfg(feature = "dist_tx")]
+            Self::XAEnd => "XAEnd",
+            #[cfg(feature = "dist_tx")]
+            Self::XAPrepare => "XAPrepare",
+            #[cfg(feature = "dist_tx")]
+            Self::XACommit => "XACommit",
+            #[cfg(feature = "dist_tx")]
+            Self::XARollback => "XARollback",
+            #[cfg(feature = "dist_tx")]
+            Self::XARecover => "XARecover",
+            #[cfg(feature = "dist_tx")]
+            Self::XAForget => "XAForget",
+        }
+    }
+
+    // used on the server side of the mock server, and for replaying wire traces
+    pub(crate) fn from_i8(val: i8) -> HdbResult<Self> {
+        match val {
+            2 => Ok(Self::ExecuteDirect),
+            3 => Ok(Self::Prepare),
+            13 => Ok(Self::Execute),
+            16 => Ok(Self::ReadLob),
+            17 => Ok(Self::WriteLob),
+            65 => Ok(Self::Authenticate),
+            66 => Ok(Self::Connect),
+            69 => Ok(Self::CloseResultSet),
+            70 => Ok(Self::DropStatementId),
+            71 => Ok(Self::FetchNext),
+            72 => Ok(Self::FetchAbsolute),
+            73 => Ok(Self::FetchRelative),
+            74 => Ok(Self::FetchFirst),
+            75 => Ok(Self::FetchLast),
+            77 => Ok(Self::Disconnect),
+            82 => Ok(Self::DbConnectInfo),
+            #[cfg(feature = "dist_tx")]
+            83 => Ok(Self::XAStart),
+            #[cfg(feature = "dist_tx")]
+            84 => Ok(Self::XAEnd),
+            #[cfg(feature = "dist_tx")]
+            85 => Ok(Self::XAPrepare),
+            #[cfg(feature = "dist_tx")]
+            86 => Ok(Self::XACommit),
+            #[cfg(feature = "dist_tx")]
+            87 => Ok(Self::XARollback),
+            #[cfg(feature = "dist_tx")]
+            88 => Ok(Self::XARecover),
+            #[cfg(feature = "dist_tx")]
+            89 => Ok(Self::XAForget),
+            _ => Err(impl_err!("found unexpected value {val} for MessageType")),
+        }
+    }
 }
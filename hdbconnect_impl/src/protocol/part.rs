@@ -7,9 +7,10 @@ use crate::{
     protocol::{
         parts::{
             AuthFields, ClientContext, ClientInfo, CommandInfo, ConnectOptionsPart, DbConnectInfo,
-            LobFlags, OutputParameters, ParameterDescriptors, ParameterRows, PartitionInformation,
-            Parts, ReadLobReply, ReadLobRequest, ResultSetMetadata, ServerError, SessionContext,
-            StatementContext, Topology, TransactionFlags, WriteLobReply, WriteLobRequest,
+            FetchOptions, LobFlags, OutputParameters, ParameterDescriptors, ParameterRows,
+            PartitionInformation, Parts, ReadLobReply, ReadLobRequest, ResultSetMetadata,
+            ServerError, SessionContext, StatementContext, Topology, TransactionFlags,
+            WriteLobReply, WriteLobRequest,
         },
         util, util_sync, PartAttributes, PartKind,
     },
@@ -18,7 +19,7 @@ use crate::{
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::{cmp::max, io::Write, sync::Arc};
 
-const PART_HEADER_SIZE: usize = 16;
+pub(crate) const PART_HEADER_SIZE: usize = 16;
 
 #[derive(Debug)]
 pub(crate) enum Part<'a> {
@@ -31,7 +32,7 @@ pub(crate) enum Part<'a> {
     ConnectOptions(ConnectOptionsPart),
     DbConnectInfo(DbConnectInfo),
     Error(Vec<ServerError>),
-    // FetchOptions(super::parts::fetch_options::FetchOptions),    // not used by any client
+    FetchOptions(FetchOptions),
     FetchSize(u32),
     LobFlags(LobFlags),
     OutputParameters(OutputParameters),
@@ -68,6 +69,7 @@ impl<'a> Part<'a> {
             Self::ConnectOptions(_) => PartKind::ConnectOptions,
             Self::DbConnectInfo(_) => PartKind::DbConnectInfo,
             Self::Error(_) => PartKind::Error,
+            Self::FetchOptions(_) => PartKind::FetchOptions,
             Self::FetchSize(_) => PartKind::FetchSize,
             Self::LobFlags(_) => PartKind::LobFlags,
             Self::OutputParameters(_) => PartKind::OutputParameters,
@@ -110,7 +112,7 @@ impl<'a> Part<'a> {
             // Part::CommitOptions(ref opts) => opts.count(),
             Part::ConnectOptions(ref opts) => opts.len(),
             Part::DbConnectInfo(ref opts) => opts.len(),
-            // Part::FetchOptions(ref opts) => opts.count(),
+            Part::FetchOptions(ref opts) => opts.len(),
             Part::LobFlags(ref opts) => opts.len(),
             Part::ParameterRows(ref par_rows) => par_rows.count(),
             Part::SessionContext(ref opts) => opts.len(),
@@ -145,7 +147,7 @@ impl<'a> Part<'a> {
             Part::CommandInfo(ref opts) => size += opts.size(),
             // Part::CommitOptions(ref opts) => size += opts.size(),
             Part::ConnectOptions(ref conn_opts) => size += conn_opts.size(),
-            // Part::FetchOptions(ref opts) => size += opts.size(),
+            Part::FetchOptions(ref opts) => size += opts.size(),
             Part::DbConnectInfo(ref conn_info) => size += conn_info.size(),
             Part::FetchSize(_) => size += 4,
             Part::LobFlags(ref opts) => size += opts.size(),
@@ -184,25 +186,15 @@ impl<'a> Part<'a> {
         w: &mut std::io::Cursor<Vec<u8>>,
     ) -> HdbResult<u32> {
         debug!("Serializing part of kind {:?}", self.kind());
-        // PART HEADER 16 bytes
-        w.write_i8(self.kind() as i8)?;
-        w.write_u8(0)?; // U1 Attributes not used in requests
-        match self.count()? {
-            i if i < i16::MAX as usize => {
-                w.write_i16::<LittleEndian>(i as i16)?;
-                w.write_i32::<LittleEndian>(0)?;
-            }
-            // i if i <= i32::MAX as usize => {
-            i if i32::try_from(i).is_ok() => {
-                w.write_i16::<LittleEndian>(-1)?;
-                w.write_i32::<LittleEndian>(i as i32)?;
-            }
-            _ => {
-                return Err(impl_err!("part count bigger than i32::MAX"));
-            }
-        }
-        w.write_i32::<LittleEndian>(self.body_size(false, o_a_descriptors)? as i32)?;
-        w.write_i32::<LittleEndian>(remaining_bufsize as i32)?;
+        // Attributes are not used in requests
+        emit_header(
+            self.kind(),
+            0,
+            self.count()?,
+            self.body_size(false, o_a_descriptors)?,
+            remaining_bufsize,
+            w,
+        )?;
 
         remaining_bufsize -= PART_HEADER_SIZE as u32;
 
@@ -215,8 +207,7 @@ impl<'a> Part<'a> {
             // Part::CommitOptions(ref opts) => opts.emit(w)?,
             Part::ConnectOptions(ref conn_opts) => conn_opts.emit(w)?,
             Part::DbConnectInfo(ref db_conn_info) => db_conn_info.emit(w)?,
-
-            // Part::FetchOptions(ref opts) => opts.emit(w)?,
+            Part::FetchOptions(ref opts) => opts.emit(w)?,
             Part::FetchSize(fs) => {
                 w.write_u32::<LittleEndian>(fs)?;
             }
@@ -526,8 +517,40 @@ impl<'a> Part<'a> {
     }
 }
 
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_possible_wrap)]
+pub(crate) fn emit_header(
+    kind: PartKind,
+    attributes: u8,
+    count: usize,
+    body_size: usize,
+    remaining_bufsize: u32,
+    w: &mut dyn std::io::Write,
+) -> HdbResult<()> {
+    // PART HEADER 16 bytes
+    w.write_i8(kind as i8)?;
+    w.write_u8(attributes)?;
+    match count {
+        i if i < i16::MAX as usize => {
+            w.write_i16::<LittleEndian>(i as i16)?;
+            w.write_i32::<LittleEndian>(0)?;
+        }
+        // i if i <= i32::MAX as usize => {
+        i if i32::try_from(i).is_ok() => {
+            w.write_i16::<LittleEndian>(-1)?;
+            w.write_i32::<LittleEndian>(i as i32)?;
+        }
+        _ => {
+            return Err(impl_err!("part count bigger than i32::MAX"));
+        }
+    }
+    w.write_i32::<LittleEndian>(body_size as i32)?;
+    w.write_i32::<LittleEndian>(remaining_bufsize as i32)?;
+    Ok(())
+}
+
 #[allow(clippy::cast_sign_loss)]
-fn parse_header(
+pub(crate) fn parse_header(
     rdr: &mut dyn std::io::Read,
 ) -> HdbResult<(PartKind, PartAttributes, usize, usize)> {
     // PART HEADER: 16 bytes
@@ -542,7 +565,7 @@ fn parse_header(
     Ok((kind, attributes, arg_size as usize, no_of_args as usize))
 }
 
-fn padsize(size: usize) -> usize {
+pub(crate) fn padsize(size: usize) -> usize {
     match size {
         0 => 0,
         _ => 7 - (size - 1) % 8,
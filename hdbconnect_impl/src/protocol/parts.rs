@@ -9,7 +9,7 @@ mod execution_result;
 mod fetch_options;
 mod field_metadata;
 mod hdb_value;
-mod length_indicator;
+pub(crate) mod length_indicator;
 mod lob_flags;
 mod multiline_option_part;
 mod option_part;
@@ -42,6 +42,7 @@ pub(crate) use self::{
     command_info::CommandInfo,
     connect_options::{ConnOptId, ConnectOptions, ConnectOptionsPart},
     db_connect_info::DbConnectInfo,
+    fetch_options::FetchOptions,
     lob_flags::LobFlags,
     option_value::OptionValue,
     parameter_rows::ParameterRows,
@@ -55,7 +56,15 @@ pub(crate) use self::{
     write_lob_request::WriteLobRequest,
 };
 
+#[cfg(feature = "mock_server")]
+pub(crate) use self::db_connect_info::DbConnectInfoId;
+#[cfg(feature = "dissector")]
+pub(crate) use self::option_part::{OptionId, OptionPart};
+#[cfg(any(feature = "mock_server", feature = "dissector"))]
+pub(crate) use self::parameter_rows::parse as parse_parameter_rows;
 pub(crate) use self::partition_information::PartitionInformation;
+#[cfg(any(feature = "alpha_routing", feature = "mock_server"))]
+pub(crate) use self::partition_information::PartitionMethod;
 pub use self::{
     execution_result::{ExecutionResult, ExecutionResults},
     field_metadata::FieldMetadata,
@@ -121,7 +130,6 @@ impl<'a> Parts<'a> {
             .map(|i| self.0.remove(i))
     }
 
-    #[cfg(feature = "dist_tx")]
     pub fn drop_parts_of_kind(&mut self, kind: PartKind) {
         self.0.retain(|part| (part.kind() as i8) != (kind as i8));
     }
@@ -129,6 +137,9 @@ impl<'a> Parts<'a> {
     pub fn ref_inner(&self) -> &Vec<Part<'a>> {
         &self.0
     }
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Part<'a>> {
+        self.0.iter_mut()
+    }
 }
 
 impl IntoIterator for Parts<'static> {
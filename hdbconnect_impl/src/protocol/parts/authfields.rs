@@ -20,6 +20,11 @@ impl AuthFields {
         Ok(auth_fields)
     }
 
+    #[cfg(feature = "dissector")]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.iter().map(|field| field.0.as_slice())
+    }
+
     pub(crate) fn pop(&mut self) -> Option<Vec<u8>> {
         self.0.pop().map(AuthField::data)
     }
@@ -68,8 +73,14 @@ impl AuthField {
         Ok(())
     }
 
+    // the length indicator needs more than one byte for long fields, like certificates
     fn size(&self) -> usize {
-        1 + self.0.len()
+        let len = self.0.len();
+        match len {
+            l if l <= length_indicator::MAX_1_BYTE_LENGTH as usize => 1 + l,
+            l if l <= 0xFFFF => 3 + l,
+            l => 5 + l,
+        }
     }
 
     fn parse(rdr: &mut dyn std::io::Read) -> HdbResult<Self> {
@@ -77,3 +88,23 @@ impl AuthField {
         Ok(Self(util_sync::parse_bytes(len, rdr)?))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::AuthFields;
+
+    #[test]
+    fn test_size() {
+        for len in [0, 245, 246, 0xFFFF, 0x1_0000] {
+            let mut auth_fields = AuthFields::with_capacity(2);
+            auth_fields.push_string("X509");
+            auth_fields.push(vec![7_u8; len]);
+            let mut buf = Vec::<u8>::new();
+            auth_fields.emit(&mut buf).unwrap();
+            assert_eq!(buf.len(), auth_fields.size());
+
+            let mut parsed = AuthFields::parse(&mut std::io::Cursor::new(buf)).unwrap();
+            assert_eq!(parsed.pop().unwrap().len(), len);
+        }
+    }
+}
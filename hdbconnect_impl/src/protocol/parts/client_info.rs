@@ -1,17 +1,25 @@
 use crate::{
     protocol::parts::hdb_value::{emit_length_and_string, string_length},
-    HdbResult,
+    HdbResult, TraceContext,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::Path;
 
+// The client info is stored by the server in session variables.
+// Only the changed entries are sent to the server; removed entries are sent with an empty value.
 #[derive(Clone, Debug)]
-pub(crate) struct ClientInfo(HashMap<ClientInfoKey, String>);
+pub(crate) struct ClientInfo {
+    values: HashMap<ClientInfoKey, String>,
+    changed: HashSet<ClientInfoKey>,
+}
 
 impl Default for ClientInfo {
     fn default() -> Self {
-        let mut ci = Self(HashMap::<ClientInfoKey, String>::new());
+        let mut ci = Self {
+            values: HashMap::<ClientInfoKey, String>::new(),
+            changed: HashSet::<ClientInfoKey>::new(),
+        };
 
         if let Some(os_str) = env::args_os().next() {
             let p = Path::new(&os_str);
@@ -35,7 +43,7 @@ impl Default for ClientInfo {
 
 impl std::fmt::Display for ClientInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        for (k, v) in &self.0 {
+        for (k, v) in &self.values {
             writeln!(f, "{k:?} = {v}")?;
         }
         Ok(())
@@ -55,6 +63,18 @@ impl ClientInfo {
     pub fn set_application_user(&mut self, application_user: &str) {
         self.set(ClientInfoKey::ApplicationUser, application_user);
     }
+    pub fn set_trace_context(&mut self, o_trace_context: Option<&TraceContext>) {
+        if let Some(trace_context) = o_trace_context {
+            self.set(ClientInfoKey::TraceParent, trace_context.traceparent());
+            match trace_context.tracestate() {
+                Some(tracestate) => self.set(ClientInfoKey::TraceState, tracestate),
+                None => self.remove(ClientInfoKey::TraceState),
+            }
+        } else {
+            self.remove(ClientInfoKey::TraceParent);
+            self.remove(ClientInfoKey::TraceState);
+        }
+    }
     fn set_driver(&mut self, driver: &str) {
         self.set(ClientInfoKey::Driver, driver);
     }
@@ -65,8 +85,33 @@ impl ClientInfo {
         self.set(ClientInfoKey::DriverVersion, driver_version);
     }
 
+    pub fn has_changes(&self) -> bool {
+        !self.changed.is_empty()
+    }
+
+    // Returns the changed entries, for sending them to the server.
+    pub fn take_changes(&mut self) -> Self {
+        let values = self
+            .changed
+            .drain()
+            .map(|key| {
+                let value = self.values.get(&key).cloned().unwrap_or_default();
+                (key, value)
+            })
+            .collect();
+        Self {
+            values,
+            changed: HashSet::new(),
+        }
+    }
+
+    // Lets all entries be sent again, e.g. to a new session.
+    pub fn mark_all_changed(&mut self) {
+        self.changed = self.values.keys().cloned().collect();
+    }
+
     pub fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
-        for (key, value) in &self.0 {
+        for (key, value) in &self.values {
             emit_length_and_string(key, w)?;
             emit_length_and_string(value, w)?;
         }
@@ -75,18 +120,26 @@ impl ClientInfo {
 
     pub fn size(&self) -> usize {
         let mut len = 0;
-        for (key, value) in &self.0 {
+        for (key, value) in &self.values {
             len += string_length(key) + string_length(value);
         }
         len
     }
     pub fn count(&self) -> usize {
-        self.0.len() * 2
+        self.values.len() * 2
     }
 
     fn set(&mut self, key: ClientInfoKey, value: &str) {
-        let value = value.to_string();
-        self.0.insert(key, value);
+        if self.values.get(&key).map(String::as_str) != Some(value) {
+            self.values.insert(key.clone(), value.to_string());
+            self.changed.insert(key);
+        }
+    }
+
+    fn remove(&mut self, key: ClientInfoKey) {
+        if self.values.remove(&key).is_some() {
+            self.changed.insert(key);
+        }
     }
 }
 
@@ -99,6 +152,8 @@ enum ClientInfoKey {
     Driver,
     DriverInfo,
     DriverVersion,
+    TraceParent,
+    TraceState,
 }
 impl AsRef<str> for ClientInfoKey {
     fn as_ref(&self) -> &str {
@@ -110,6 +165,43 @@ impl AsRef<str> for ClientInfoKey {
             Self::Driver => "DRIVER",
             Self::DriverInfo => "DRIVERINFO",
             Self::DriverVersion => "DRIVERVERSION",
+            Self::TraceParent => "TRACEPARENT",
+            Self::TraceState => "TRACESTATE",
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ClientInfo, ClientInfoKey};
+    use crate::TraceContext;
+
+    #[test]
+    fn test_client_info_changes() {
+        let mut client_info = ClientInfo::default();
+        assert_eq!(client_info.take_changes().count(), client_info.count());
+        assert!(!client_info.has_changes());
+
+        client_info.set_application_user("Bob");
+        client_info.set_application_user("Bob");
+        let changes = client_info.take_changes();
+        assert_eq!(changes.count(), 2);
+        assert_eq!(changes.values[&ClientInfoKey::ApplicationUser], "Bob");
+
+        let trace_context =
+            TraceContext::new("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01").unwrap();
+        client_info.set_trace_context(Some(&trace_context));
+        assert_eq!(client_info.take_changes().count(), 2);
+        client_info.set_trace_context(Some(&trace_context));
+        assert!(!client_info.has_changes());
+
+        // removed entries are sent with an empty value
+        client_info.set_trace_context(None);
+        let changes = client_info.take_changes();
+        assert_eq!(changes.values[&ClientInfoKey::TraceParent], "");
+        assert!(!client_info.to_string().contains("TraceParent"));
+
+        client_info.mark_all_changed();
+        assert_eq!(client_info.take_changes().count(), client_info.count());
+    }
+}
@@ -50,6 +50,8 @@ impl ConnectOptions {
     const CLIENT_RECONNECT_WAIT_TIMEOUT_IN_SECONDS: u32 = 600; // server does not allow more
     const DATAFORMAT_VERSION2: u8 = 8;
     const ENABLE_ARRAY_TYPE: bool = true;
+    // enables FetchAbsolute, FetchRelative, FetchFirst, and FetchLast
+    const SCROLLABLE_RESULT_SET: bool = true;
     #[cfg(feature = "alpha_routing")]
     #[allow(dead_code)]
     const ALPHA_ROUTING: bool = false;
@@ -113,6 +115,10 @@ impl ConnectOptions {
             ConnOptId::EnableArrayType,
             OptionValue::BOOLEAN(Self::ENABLE_ARRAY_TYPE),
         );
+        set_opt(
+            ConnOptId::ScrollableResultSet,
+            OptionValue::BOOLEAN(Self::SCROLLABLE_RESULT_SET),
+        );
         set_opt(
             ConnOptId::DataFormatVersion2,
             OptionValue::INT(From::from(Self::DATAFORMAT_VERSION2)),
@@ -145,7 +151,8 @@ impl ConnectOptions {
         if cfg!(feature = "alpha_routing") {
             warn!("Feature alpha_routing is active!");
             set_opt(ConnOptId::DistributionEnabled, OptionValue::BOOLEAN(true));
-            set_opt(ConnOptId::ClientDistributionMode, OptionValue::INT(0));
+            // statement routing
+            set_opt(ConnOptId::ClientDistributionMode, OptionValue::INT(2));
             set_opt(ConnOptId::DistributionProtocolVersion, OptionValue::INT(1));
         } else {
             debug!("Feature alpha_routing is not active.");
@@ -276,6 +283,15 @@ impl ConnectOptions {
         }
     }
 
+    // The connection ID, or None if the connection is not yet established.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn try_get_connection_id(&self) -> Option<u32> {
+        match &self {
+            ConnectOptions::Initial { .. } => None,
+            ConnectOptions::Final { connection_id, .. } => Some(*connection_id),
+        }
+    }
+
     pub(crate) fn get_os_user(&self) -> String {
         match &self {
             ConnectOptions::Initial { .. } => panic_not_final(),
@@ -77,6 +77,30 @@ impl ExecutionResults {
         Ok(Self(vec))
     }
 
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn new(execution_results: Vec<ExecutionResult>) -> Self {
+        Self(execution_results)
+    }
+
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    // Counterpart of parse(), used by the mock server.
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
+        use byteorder::WriteBytesExt;
+        for execution_result in &self.0 {
+            w.write_i32::<LittleEndian>(match *execution_result {
+                ExecutionResult::RowsAffected(count) => i32::try_from(count).unwrap_or(i32::MAX),
+                ExecutionResult::SuccessNoInfo => -2,
+                ExecutionResult::Failure(_) | ExecutionResult::ExtraFailure(_) => -3,
+            })?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn mix_in_server_errors(&mut self, mut err_iter: IntoIter<ServerError>) {
         for execution_result in &mut self.0 {
             if let ExecutionResult::Failure(_) = *execution_result {
@@ -1,33 +1,56 @@
-// commented out, as the docu says: not used by any client implementation
+use crate::protocol::parts::{
+    option_part::{OptionId, OptionPart},
+    option_value::OptionValue,
+};
 
-// use crate::protocol::parts::option_part::OptionId;
-// use crate::protocol::parts::option_part::OptionPart;
+// An Options part that is used by the client when positioning a scrollable cursor;
+// the RESULTSETPOS field specifies the target row of FETCHABSOLUTE or the offset of
+// FETCHRELATIVE.
+pub(crate) type FetchOptions = OptionPart<FetchOptionsId>;
 
-// // An Options part that is used by the client when fetching result set lines;
-// // the RESULTSETPOS field can be used to skip over entries.
-// pub type FetchOptions = OptionPart<FetchOptionsId>;
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub(crate) enum FetchOptionsId {
+    ResultSetPosition, // 1 // INT // Position for Fetch
+    __Unexpected__(u8),
+}
 
-// #[derive(Debug, Eq, PartialEq, Hash)]
-// pub enum FetchOptionsId {
-//     ResultSetPosition, // 1 // INT // Position for Fetch
-//     __Unexpected__(u8),
-// }
+impl OptionId<FetchOptionsId> for FetchOptionsId {
+    fn to_u8(&self) -> u8 {
+        match *self {
+            Self::ResultSetPosition => 1,
+            Self::__Unexpected__(val) => val,
+        }
+    }
 
-// impl OptionId<FetchOptionsId> for FetchOptionsId {
-//     fn to_u8(&self) -> u8 {
-//         match *self {
-//             FetchOptionsId::ResultSetPosition => 1,
-//             FetchOptionsId::__Unexpected__(val) => val,
-//         }
-//     }
+    fn from_u8(val: u8) -> Self {
+        match val {
+            1 => Self::ResultSetPosition,
+            val => {
+                warn!("Unsupported value for FetchOptionsId received: {val}");
+                Self::__Unexpected__(val)
+            }
+        }
+    }
 
-//     fn from_u8(val: u8) -> FetchOptionsId {
-//         match val {
-//             1 => FetchOptionsId::ResultSetPosition,
-//             val => {
-//                 warn!("Unsupported value for FetchOptionsId received: {}", val);
-//                 FetchOptionsId::__Unexpected__(val)
-//             }
-//         }
-//     }
-// }
+    fn part_type(&self) -> &'static str {
+        "FetchOptions"
+    }
+}
+
+impl FetchOptions {
+    pub fn for_position(position: i32) -> Self {
+        let mut fetch_options = Self::default();
+        fetch_options.insert(
+            FetchOptionsId::ResultSetPosition,
+            OptionValue::INT(position),
+        );
+        fetch_options
+    }
+
+    // used on the server side of the mock server
+    #[cfg(feature = "mock_server")]
+    pub fn position(&self) -> crate::HdbResult<i32> {
+        self.get(&FetchOptionsId::ResultSetPosition)?
+            .get_int_as_i32()
+    }
+}
@@ -60,7 +60,35 @@ impl InnerFieldMetadata {
     }
 }
 
+#[cfg(feature = "mock_server")]
+impl InnerFieldMetadata {
+    // Counterpart of the field-wise parsing in ResultSetMetadata::parse(), used by the mock server.
+    pub(crate) fn emit(&self, w: &mut dyn std::io::Write) -> crate::HdbResult<()> {
+        use byteorder::{LittleEndian, WriteBytesExt};
+        w.write_u8(self.column_options)?;
+        w.write_u8(self.type_id as u8)?;
+        w.write_i16::<LittleEndian>(self.scale)?;
+        w.write_i16::<LittleEndian>(self.precision)?;
+        w.write_i16::<LittleEndian>(0)?;
+        w.write_u32::<LittleEndian>(self.tablename_idx)?;
+        w.write_u32::<LittleEndian>(self.schemaname_idx)?;
+        w.write_u32::<LittleEndian>(self.columnname_idx)?;
+        w.write_u32::<LittleEndian>(self.displayname_idx)?;
+        Ok(())
+    }
+}
+
 impl FieldMetadata {
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn inner(&self) -> &InnerFieldMetadata {
+        &self.inner
+    }
+
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn names(&self) -> &VecMap<String> {
+        &self.names
+    }
+
     pub(crate) fn new(inner: InnerFieldMetadata, names: Arc<VecMap<String>>) -> Self {
         Self { inner, names }
     }
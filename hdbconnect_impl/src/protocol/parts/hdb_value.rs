@@ -292,6 +292,203 @@ impl HdbValue<'_> {
     }
 }
 
+// Counterparts of emit() and parse_*(), used by the mock server.
+#[cfg(feature = "mock_server")]
+impl HdbValue<'_> {
+    // Writes a non-LOB value in the format that the database uses in result sets.
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn emit_in_result_set(
+        &self,
+        type_id: TypeId,
+        scale: i16,
+        w: &mut dyn std::io::Write,
+    ) -> HdbResult<()> {
+        use crate::types_impl::{daydate, longdate, seconddate, secondtime};
+        let value_err = || impl_err!("cannot send {self:?} as value of type {type_id:?}");
+        match type_id {
+            TypeId::TINYINT | TypeId::SMALLINT | TypeId::INT | TypeId::BIGINT => match *self {
+                HdbValue::NULL => w.write_u8(0)?,
+                HdbValue::TINYINT(u) if type_id == TypeId::TINYINT => {
+                    w.write_u8(1)?;
+                    w.write_u8(u)?;
+                }
+                HdbValue::SMALLINT(i) if type_id == TypeId::SMALLINT => {
+                    w.write_u8(1)?;
+                    w.write_i16::<LittleEndian>(i)?;
+                }
+                HdbValue::INT(i) if type_id == TypeId::INT => {
+                    w.write_u8(1)?;
+                    w.write_i32::<LittleEndian>(i)?;
+                }
+                HdbValue::BIGINT(i) if type_id == TypeId::BIGINT => {
+                    w.write_u8(1)?;
+                    w.write_i64::<LittleEndian>(i)?;
+                }
+                _ => return Err(value_err()),
+            },
+            TypeId::REAL => match *self {
+                HdbValue::NULL => w.write_u32::<LittleEndian>(u32::MAX)?,
+                HdbValue::REAL(f) => w.write_f32::<LittleEndian>(f)?,
+                _ => return Err(value_err()),
+            },
+            TypeId::DOUBLE => match *self {
+                HdbValue::NULL => w.write_u64::<LittleEndian>(u64::MAX)?,
+                HdbValue::DOUBLE(f) => w.write_f64::<LittleEndian>(f)?,
+                _ => return Err(value_err()),
+            },
+            TypeId::BOOLEAN => match *self {
+                HdbValue::NULL => w.write_u8(1)?,
+                HdbValue::BOOLEAN(b) => emit_bool(b, w)?,
+                _ => return Err(value_err()),
+            },
+            TypeId::DECIMAL => match *self {
+                HdbValue::NULL => {
+                    w.write_all(&[0_u8; 15])?;
+                    w.write_u8(112)?;
+                }
+                HdbValue::DECIMAL(ref bigdec) => decimal::emit(bigdec, type_id, scale, w)?,
+                _ => return Err(value_err()),
+            },
+            TypeId::FIXED8 | TypeId::FIXED12 | TypeId::FIXED16 => match *self {
+                HdbValue::NULL => w.write_u8(0)?,
+                HdbValue::DECIMAL(ref bigdec) => {
+                    w.write_u8(1)?;
+                    decimal::emit(bigdec, type_id, scale, w)?;
+                }
+                _ => return Err(value_err()),
+            },
+            TypeId::CHAR
+            | TypeId::VARCHAR
+            | TypeId::NCHAR
+            | TypeId::NVARCHAR
+            | TypeId::STRING
+            | TypeId::NSTRING
+            | TypeId::SHORTTEXT
+            | TypeId::ALPHANUM => match *self {
+                HdbValue::NULL => w.write_u8(length_indicator::LENGTH_INDICATOR_NULL)?,
+                HdbValue::STR(s) if type_id != TypeId::ALPHANUM => emit_length_and_string(s, w)?,
+                HdbValue::STRING(ref s) if type_id != TypeId::ALPHANUM => {
+                    emit_length_and_string(s, w)?;
+                }
+                HdbValue::STR(s) => emit_alphanum(s, w)?,
+                HdbValue::STRING(ref s) => emit_alphanum(s, w)?,
+                _ => return Err(value_err()),
+            },
+            TypeId::BINARY
+            | TypeId::VARBINARY
+            | TypeId::BSTRING
+            | TypeId::GEOMETRY
+            | TypeId::POINT => match *self {
+                HdbValue::NULL => w.write_u8(length_indicator::LENGTH_INDICATOR_NULL)?,
+                HdbValue::BINARY(ref v) | HdbValue::GEOMETRY(ref v) | HdbValue::POINT(ref v) => {
+                    emit_length_and_bytes(v, w)?;
+                }
+                _ => return Err(value_err()),
+            },
+            TypeId::LONGDATE => match *self {
+                HdbValue::NULL => w.write_i64::<LittleEndian>(longdate::NULL_REPRESENTATION)?,
+                HdbValue::LONGDATE(ref ld) => w.write_i64::<LittleEndian>(*ld.ref_raw())?,
+                _ => return Err(value_err()),
+            },
+            TypeId::SECONDDATE => match *self {
+                HdbValue::NULL => w.write_i64::<LittleEndian>(seconddate::NULL_REPRESENTATION)?,
+                HdbValue::SECONDDATE(ref sd) => w.write_i64::<LittleEndian>(*sd.ref_raw())?,
+                _ => return Err(value_err()),
+            },
+            TypeId::DAYDATE => match *self {
+                HdbValue::NULL => w.write_i32::<LittleEndian>(daydate::NULL_REPRESENTATION)?,
+                HdbValue::DAYDATE(ref dd) => w.write_i32::<LittleEndian>(*dd.ref_raw())?,
+                _ => return Err(value_err()),
+            },
+            TypeId::SECONDTIME => match *self {
+                HdbValue::NULL => w.write_i32::<LittleEndian>(secondtime::NULL_REPRESENTATION)?,
+                HdbValue::SECONDTIME(ref st) => w.write_u32::<LittleEndian>(*st.ref_raw())?,
+                _ => return Err(value_err()),
+            },
+            TypeId::BLOCATOR
+            | TypeId::BLOB
+            | TypeId::BINTEXT
+            | TypeId::CLOB
+            | TypeId::NCLOB
+            | TypeId::TEXT => {
+                return Err(impl_err!(
+                    "LOB values must be emitted with lob::emit_lob_descriptor()"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "mock_server", feature = "dissector"))]
+impl HdbValue<'static> {
+    // Reads a non-null value of a parameter row, after the type code was consumed.
+    pub(crate) fn parse_parameter_value(
+        type_id: TypeId,
+        scale: i16,
+        rdr: &mut dyn std::io::Read,
+    ) -> HdbResult<HdbValue<'static>> {
+        Ok(match type_id {
+            TypeId::TINYINT => HdbValue::TINYINT(rdr.read_u8()?),
+            TypeId::SMALLINT => HdbValue::SMALLINT(rdr.read_i16::<LittleEndian>()?),
+            TypeId::INT => HdbValue::INT(rdr.read_i32::<LittleEndian>()?),
+            TypeId::BIGINT => HdbValue::BIGINT(rdr.read_i64::<LittleEndian>()?),
+            TypeId::REAL => parse_real(false, rdr)?,
+            TypeId::DOUBLE => parse_double(false, rdr)?,
+            TypeId::BOOLEAN => parse_bool(false, rdr)?,
+            TypeId::DECIMAL => decimal::parse(false, type_id, scale, rdr)?,
+            TypeId::FIXED8 | TypeId::FIXED12 | TypeId::FIXED16 => {
+                let len = match type_id {
+                    TypeId::FIXED8 => 8,
+                    TypeId::FIXED12 => 12,
+                    _ => 16,
+                };
+                let bytes = util_sync::parse_bytes(len, rdr)?;
+                HdbValue::DECIMAL(BigDecimal::new(
+                    num_bigint::BigInt::from_signed_bytes_le(&bytes),
+                    i64::from(scale),
+                ))
+            }
+            TypeId::CHAR
+            | TypeId::VARCHAR
+            | TypeId::NCHAR
+            | TypeId::NVARCHAR
+            | TypeId::STRING
+            | TypeId::NSTRING
+            | TypeId::SHORTTEXT
+            | TypeId::ALPHANUM => parse_string(false, TypeId::STRING, rdr)?,
+            TypeId::BINARY
+            | TypeId::VARBINARY
+            | TypeId::BSTRING
+            | TypeId::GEOMETRY
+            | TypeId::POINT => parse_binary(false, type_id, rdr)?,
+            TypeId::LONGDATE => parse_longdate(false, rdr)?,
+            TypeId::SECONDDATE => parse_seconddate(false, rdr)?,
+            TypeId::DAYDATE => parse_daydate(false, rdr)?,
+            TypeId::SECONDTIME => parse_secondtime(false, rdr)?,
+            TypeId::BLOCATOR
+            | TypeId::BLOB
+            | TypeId::BINTEXT
+            | TypeId::CLOB
+            | TypeId::NCLOB
+            | TypeId::TEXT => {
+                return Err(impl_err!("LOB parameters are not parsed as values"));
+            }
+        })
+    }
+}
+
+#[cfg(feature = "mock_server")]
+fn emit_alphanum(s: &str, w: &mut dyn std::io::Write) -> HdbResult<()> {
+    let cesu8 = cesu8::to_cesu8(s);
+    #[allow(clippy::cast_possible_truncation)]
+    let data_length = cesu8.len().min(usize::from(ALPHANUM_LENGTH_MASK)) as u8;
+    w.write_u8(data_length + 1)?;
+    w.write_u8(data_length)?;
+    w.write_all(&cesu8[..usize::from(data_length)])?;
+    Ok(())
+}
+
 impl HdbValue<'static> {
     /// Deserialize into a rust type.
     ///
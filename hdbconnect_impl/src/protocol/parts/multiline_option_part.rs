@@ -2,12 +2,21 @@ use crate::{
     protocol::parts::option_part::{OptionId, OptionPart},
     HdbResult,
 };
+#[cfg(feature = "mock_server")]
+use byteorder::WriteBytesExt;
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::{fmt::Debug, hash::Hash};
 
 #[derive(Debug)]
 pub(crate) struct MultilineOptionPart<T: OptionId<T> + Debug + Eq + PartialEq + Hash>(
-    #[allow(dead_code)] // TODO so far only used in the not yet really implemented Topology
+    #[cfg_attr(
+        not(any(
+            feature = "alpha_routing",
+            feature = "mock_server",
+            feature = "dissector"
+        )),
+        allow(dead_code)
+    )]
     Vec<OptionPart<T>>,
 );
 
@@ -21,4 +30,29 @@ impl<T: OptionId<T> + Debug + Eq + PartialEq + Hash> MultilineOptionPart<T> {
         }
         Ok(Self(option_parts))
     }
+
+    #[cfg(any(feature = "alpha_routing", feature = "dissector"))]
+    pub fn iter(&self) -> std::slice::Iter<'_, OptionPart<T>> {
+        self.0.iter()
+    }
+
+    #[cfg(feature = "mock_server")]
+    pub fn new(option_parts: Vec<OptionPart<T>>) -> Self {
+        Self(option_parts)
+    }
+
+    #[cfg(feature = "mock_server")]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[cfg(feature = "mock_server")]
+    pub fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
+        for option_part in &self.0 {
+            #[allow(clippy::cast_possible_truncation)]
+            w.write_u16::<LittleEndian>(option_part.len() as u16)?;
+            option_part.emit(w)?;
+        }
+        Ok(())
+    }
 }
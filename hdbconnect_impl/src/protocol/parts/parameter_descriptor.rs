@@ -1,43 +1,82 @@
 use crate::{
     impl_err,
     protocol::{util, util_sync},
-    HdbResult, HdbValue, TypeId,
+    usage_err, HdbResult, HdbValue, TypeId,
 };
 use byteorder::{LittleEndian, ReadBytesExt};
 
 /// Describes a set of IN, INOUT, and OUT parameters. Can be empty.
 #[derive(Debug, Default)]
-pub struct ParameterDescriptors(Vec<ParameterDescriptor>);
+pub struct ParameterDescriptors {
+    descriptors: Vec<ParameterDescriptor>,
+    // true if the statement was prepared with named placeholders
+    named: bool,
+}
 impl ParameterDescriptors {
     /// Produces an iterator that returns the IN and INOUT parameters.
     pub fn iter_in(&self) -> impl std::iter::Iterator<Item = &ParameterDescriptor> {
-        self.0.iter().filter(|ms| {
+        self.descriptors.iter().filter(|ms| {
             (ms.direction == ParameterDirection::IN) | (ms.direction == ParameterDirection::INOUT)
         })
     }
     /// Produces an iterator that returns the INOUT and OUT parameters.
     pub fn iter_out(&self) -> impl std::iter::Iterator<Item = &ParameterDescriptor> {
-        self.0.iter().filter(|ms| {
+        self.descriptors.iter().filter(|ms| {
             (ms.direction == ParameterDirection::OUT) | (ms.direction == ParameterDirection::INOUT)
         })
     }
 
+    #[cfg(feature = "dissector")]
+    pub(crate) fn iter(&self) -> impl std::iter::Iterator<Item = &ParameterDescriptor> {
+        self.descriptors.iter()
+    }
+
     /// Returns true if at least one IN or INOUT parameter is contained.
     #[must_use]
     pub fn has_in(&self) -> bool {
         self.iter_in().next().is_some()
     }
 
+    /// Returns true if the statement was prepared with named placeholders
+    /// (see `Connection::prepare_named`).
+    ///
+    /// The descriptors then carry the names of the placeholders, and the input
+    /// for the parameters is bound by name rather than by position.
+    #[must_use]
+    pub fn has_named_placeholders(&self) -> bool {
+        self.named
+    }
+
+    // Replaces the names of the IN and INOUT descriptors with the names of the placeholders
+    // that were used in the statement, in the order of their occurrence;
+    // OUT parameters are not bound, so they keep the names that the server reports.
+    pub(crate) fn set_placeholder_names(&mut self, names: Vec<String>) -> HdbResult<()> {
+        if names.len() != self.descriptors.len() {
+            return Err(usage_err!(
+                "the statement has {} named placeholders, but the server reports {} parameters",
+                names.len(),
+                self.descriptors.len()
+            ));
+        }
+        for (descriptor, name) in self.descriptors.iter_mut().zip(names) {
+            if descriptor.direction != ParameterDirection::OUT {
+                descriptor.set_name(name);
+            }
+        }
+        self.named = true;
+        Ok(())
+    }
+
     /// Returns number of contained descriptors.
     #[must_use]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.descriptors.len()
     }
 
     /// Returns true exactly if the lsit is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.descriptors.is_empty()
     }
 
     pub(crate) fn parse(count: usize, rdr: &mut dyn std::io::Read) -> HdbResult<Self> {
@@ -65,14 +104,61 @@ impl ParameterDescriptors {
                 descriptor.set_name(name);
             }
         }
-        Ok(Self(vec_pd))
+        Ok(Self {
+            descriptors: vec_pd,
+            named: false,
+        })
+    }
+
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn new(descriptors: Vec<ParameterDescriptor>) -> Self {
+        Self {
+            descriptors,
+            named: false,
+        }
+    }
+
+    // Counterpart of parse(), used by the mock server.
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
+        use byteorder::WriteBytesExt;
+        let mut name_offset = 0_u32;
+        for descriptor in &self.descriptors {
+            w.write_u8(descriptor.option_as_u8())?;
+            w.write_u8(descriptor.type_id as u8)?;
+            w.write_u8(match descriptor.direction {
+                ParameterDirection::IN => 1,
+                ParameterDirection::INOUT => 2,
+                ParameterDirection::OUT => 4,
+            })?;
+            w.write_u8(0)?;
+            if let Some(ref name) = descriptor.name {
+                w.write_u32::<LittleEndian>(name_offset)?;
+                #[allow(clippy::cast_possible_truncation)]
+                let cesu8_length = util::cesu8_length(name).min(255) as u32;
+                name_offset += cesu8_length + 1;
+            } else {
+                w.write_u32::<LittleEndian>(u32::MAX)?;
+            }
+            w.write_i16::<LittleEndian>(descriptor.precision)?;
+            w.write_i16::<LittleEndian>(descriptor.scale)?;
+            w.write_u32::<LittleEndian>(0)?;
+        }
+        for name in self.descriptors.iter().filter_map(|d| d.name.as_ref()) {
+            let cesu8 = cesu8::to_cesu8(name);
+            let cesu8 = &cesu8[..cesu8.len().min(255)];
+            #[allow(clippy::cast_possible_truncation)]
+            w.write_u8(cesu8.len() as u8)?;
+            w.write_all(cesu8)?;
+        }
+        Ok(())
     }
 }
 
 impl std::ops::Index<usize> for ParameterDescriptors {
     type Output = ParameterDescriptor;
     fn index(&self, index: usize) -> &Self::Output {
-        self.0.index(index)
+        self.descriptors.index(index)
     }
 }
 
@@ -110,6 +196,41 @@ impl ParameterDescriptor {
         })
     }
 
+    // Used by the mock server.
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn new(
+        name: Option<String>,
+        type_id: TypeId,
+        binding: ParameterBinding,
+        direction: ParameterDirection,
+        precision: i16,
+        scale: i16,
+    ) -> Self {
+        Self {
+            name,
+            type_id,
+            binding,
+            scale,
+            precision,
+            direction,
+            auto_incremented: false,
+            array_type: false,
+        }
+    }
+
+    #[cfg(feature = "mock_server")]
+    fn option_as_u8(&self) -> u8 {
+        (match self.binding {
+            ParameterBinding::Mandatory => 0b_0000_0001_u8,
+            ParameterBinding::Optional => 0b_0000_0010_u8,
+            ParameterBinding::HasDefault => 0b_0000_0100_u8,
+        }) | if self.auto_incremented {
+            0b_0010_0000_u8
+        } else {
+            0
+        } | if self.array_type { 0b_0100_0000_u8 } else { 0 }
+    }
+
     /// Describes whether a parameter can be NULL or not, or if it has a default value.
     #[must_use]
     pub fn binding(&self) -> ParameterBinding {
@@ -1,4 +1,9 @@
-use crate::{impl_err, protocol::parts::ParameterDescriptors, HdbResult, HdbValue};
+#[cfg(any(feature = "mock_server", feature = "dissector"))]
+use crate::TypeId;
+use crate::{
+    impl_err, protocol::parts::ParameterDescriptors, serde_db_impl::named_params::to_named_params,
+    HdbResult, HdbValue,
+};
 use serde_db::ser::to_params;
 
 // Implementation of the PARAMETERS part.
@@ -41,6 +46,10 @@ impl<'a> ParameterRows<'a> {
         self.0.len()
     }
 
+    pub(crate) fn rows(&self) -> impl Iterator<Item = &[HdbValue<'a>]> {
+        self.0.iter().map(|row| row.0.as_slice())
+    }
+
     pub(crate) fn size(&self, descriptors: &ParameterDescriptors) -> HdbResult<usize> {
         let mut size = 0;
         for row in &self.0 {
@@ -56,10 +65,12 @@ impl ParameterRows<'static> {
         input: &T,
         descriptors: &ParameterDescriptors,
     ) -> HdbResult<()> {
-        self.0.push(ParameterRow::new(
-            to_params(input, &mut descriptors.iter_in())?,
-            descriptors,
-        )?);
+        let hdb_values = if descriptors.has_named_placeholders() {
+            to_named_params(input, descriptors)?
+        } else {
+            to_params(input, &mut descriptors.iter_in())?
+        };
+        self.0.push(ParameterRow::new(hdb_values, descriptors)?);
         Ok(())
     }
 }
@@ -121,3 +132,45 @@ impl<'a> ParameterRow<'a> {
         Ok(())
     }
 }
+
+// Counterpart of ParameterRows::emit(), used by the mock server and the dissector;
+// also returns (row, column, type_id) for each LOB value, which is sent separately with
+// WriteLob requests and is represented by NULL in the returned rows.
+#[cfg(any(feature = "mock_server", feature = "dissector"))]
+#[allow(clippy::type_complexity)]
+pub(crate) fn parse(
+    count: usize,
+    descriptors: &ParameterDescriptors,
+    rdr: &mut dyn std::io::Read,
+) -> HdbResult<(Vec<Vec<HdbValue<'static>>>, Vec<(usize, usize, TypeId)>)> {
+    use byteorder::{LittleEndian, ReadBytesExt};
+    let mut rows = Vec::<Vec<HdbValue<'static>>>::with_capacity(count);
+    let mut lob_placeholders = Vec::<(usize, usize, TypeId)>::new();
+    for row_idx in 0..count {
+        let mut row = Vec::<HdbValue<'static>>::new();
+        for (col_idx, descriptor) in descriptors.iter_in().enumerate() {
+            let type_code = rdr.read_u8()?;
+            if type_code >= 128 {
+                row.push(HdbValue::NULL);
+                continue;
+            }
+            let type_id = TypeId::try_new(type_code)?;
+            if let TypeId::BLOB | TypeId::CLOB | TypeId::NCLOB = type_id {
+                // LOB header: options, length, position
+                rdr.read_u8()?;
+                rdr.read_i32::<LittleEndian>()?;
+                rdr.read_i32::<LittleEndian>()?;
+                lob_placeholders.push((row_idx, col_idx, type_id));
+                row.push(HdbValue::NULL);
+            } else {
+                row.push(HdbValue::parse_parameter_value(
+                    type_id,
+                    descriptor.scale(),
+                    rdr,
+                )?);
+            }
+        }
+        rows.push(row);
+    }
+    Ok((rows, lob_placeholders))
+}
@@ -1,16 +1,23 @@
 use crate::{impl_err, protocol::util_sync, HdbResult};
+#[cfg(feature = "mock_server")]
+use byteorder::WriteBytesExt;
 use byteorder::{LittleEndian, ReadBytesExt};
 
-#[derive(Debug)]
-#[allow(dead_code)]
+// Describes how the table of a prepared statement is distributed over the volumes
+// (i.e., the index servers) of a scale-out system.
+//
+// The parameter descriptors, which identify the partitioning key of hash partitioned tables,
+// and the partition ids are skipped, because hash partitions are not routed.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "alpha_routing"), allow(dead_code))]
 pub(crate) struct PartitionInformation {
     partition_method: PartitionMethod,
-    parameter_descriptor: Vec<ParameterDescriptor>,
-    partitions: Vec<Partitions>,
+    // the volume of each partition
+    volumes: Vec<i32>,
 }
 
-#[derive(Debug, Clone, Copy)]
-enum PartitionMethod {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PartitionMethod {
     Invalid,
     RoundRobin,
     Hash,
@@ -25,75 +32,116 @@ impl PartitionMethod {
             _ => Err(impl_err!("PartitionMethod {val} not implemented",)),
         }
     }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum ParameterFunction {
-    Invalid,
-    Year,
-    Month,
-}
-
-impl ParameterFunction {
-    pub fn from_i8(val: i8) -> HdbResult<Self> {
-        match val {
-            0 => Ok(Self::Invalid),
-            1 => Ok(Self::Year),
-            2 => Ok(Self::Month),
-            _ => Err(impl_err!("ParameterFunction {val} not implemented",)),
+    #[cfg(feature = "mock_server")]
+    fn to_i8(self) -> i8 {
+        match self {
+            Self::Invalid => 0,
+            Self::RoundRobin => 1,
+            Self::Hash => 2,
         }
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct ParameterDescriptor {
-    parameter_index: i32,
-    parameter_function: ParameterFunction,
-    attribute_type: i8,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
-pub struct Partitions {
-    val1: i32,
-    val2: i32,
-}
-
 impl PartitionInformation {
     pub fn parse(rdr: &mut dyn std::io::Read) -> HdbResult<Self> {
         let partition_method = PartitionMethod::from_i8(rdr.read_i8()?)?; // I1
         util_sync::skip_bytes(7, rdr)?;
         let num_parameters = rdr.read_i32::<LittleEndian>()?;
         let num_partitions = rdr.read_i32::<LittleEndian>()?;
-        let mut parameter_descriptor = vec![];
+        // parameter index (I4), parameter function (I1), attribute type (I1), 2 filler bytes
         for _ in 0..num_parameters {
-            let desc = ParameterDescriptor {
-                parameter_index: rdr.read_i32::<LittleEndian>()?,
-                parameter_function: ParameterFunction::from_i8(rdr.read_i8()?)?,
-                attribute_type: rdr.read_i8()?,
-            };
-            util_sync::skip_bytes(2, rdr)?;
-            parameter_descriptor.push(desc);
+            util_sync::skip_bytes(8, rdr)?;
         }
 
-        let mut partitions = vec![];
+        let mut volumes = vec![];
 
-        // Missing in documentation, but it is 8 byte per partition
+        // Missing in documentation, but it is 8 byte per partition: partition id, volume id
         // https://help.sap.com/viewer/7e4aba181371442d9e4395e7ff71b777/2.0.03/en-US/a6b5b33a790245efa06c67a781f80d15.html#loioeed44c1df1fc4f139079f36031b42ef1
         for _ in 0..num_partitions {
-            partitions.push({
-                Partitions {
-                    val1: rdr.read_i32::<LittleEndian>()?,
-                    val2: rdr.read_i32::<LittleEndian>()?,
-                }
-            });
+            util_sync::skip_bytes(4, rdr)?;
+            volumes.push(rdr.read_i32::<LittleEndian>()?);
         }
 
         Ok(Self {
             partition_method,
-            parameter_descriptor,
-            partitions,
+            volumes,
         })
     }
+
+    #[cfg(feature = "alpha_routing")]
+    pub fn partition_method(&self) -> PartitionMethod {
+        self.partition_method
+    }
+
+    // The volumes of all partitions, in the order of the partitions.
+    #[cfg(feature = "alpha_routing")]
+    pub fn volumes(&self) -> impl Iterator<Item = i32> + '_ {
+        self.volumes.iter().copied()
+    }
+
+    // Counterpart of parse(), for the mock server.
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn new_for_server(partition_method: PartitionMethod, volumes: &[i32]) -> Self {
+        Self {
+            partition_method,
+            volumes: volumes.to_vec(),
+        }
+    }
+
+    // Writes the partition information, with parameter descriptors for the given
+    // (zero-based) indexes of the input parameters that form the partitioning key.
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn emit(
+        &self,
+        parameter_indexes: &[i32],
+        w: &mut dyn std::io::Write,
+    ) -> HdbResult<()> {
+        w.write_i8(self.partition_method.to_i8())?;
+        w.write_all(&[0; 7])?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        {
+            w.write_i32::<LittleEndian>(parameter_indexes.len() as i32)?;
+            w.write_i32::<LittleEndian>(self.volumes.len() as i32)?;
+        }
+        for parameter_index in parameter_indexes {
+            w.write_i32::<LittleEndian>(*parameter_index)?;
+            // parameter function "invalid", attribute type, filler
+            w.write_all(&[0; 4])?;
+        }
+        for (partition_id, volume_id) in (1..).zip(&self.volumes) {
+            w.write_i32::<LittleEndian>(partition_id)?;
+            w.write_i32::<LittleEndian>(*volume_id)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "alpha_routing")]
+mod test {
+    use super::{PartitionInformation, PartitionMethod};
+
+    // partition method, 7 filler bytes, #parameters, #partitions,
+    // parameter descriptors (index, function, attribute type, 2 filler bytes),
+    // partitions (partition id, volume id)
+    fn hash_partitioning(function: i8) -> Vec<u8> {
+        let mut bytes = vec![2_u8, 0, 0, 0, 0, 0, 0, 0];
+        bytes.extend_from_slice(&1_i32.to_le_bytes());
+        bytes.extend_from_slice(&3_i32.to_le_bytes());
+        bytes.extend_from_slice(&1_i32.to_le_bytes());
+        bytes.extend_from_slice(&[function.to_le_bytes()[0], 0, 0, 0]);
+        for (partition_id, volume_id) in [(1_i32, 2_i32), (2, 3), (3, 4)] {
+            bytes.extend_from_slice(&partition_id.to_le_bytes());
+            bytes.extend_from_slice(&volume_id.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_partition_information() {
+        let info =
+            PartitionInformation::parse(&mut std::io::Cursor::new(hash_partitioning(0))).unwrap();
+        assert_eq!(info.partition_method(), PartitionMethod::Hash);
+        assert_eq!(info.volumes().collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
 }
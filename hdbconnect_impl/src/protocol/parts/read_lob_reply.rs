@@ -17,6 +17,33 @@ impl ReadLobReply {
 }
 
 impl ReadLobReply {
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn new(locator_id: u64, is_last_data: bool, data: Vec<u8>) -> Self {
+        Self {
+            locator_id,
+            is_last_data,
+            data,
+        }
+    }
+
+    // Counterpart of parse(), used by the mock server.
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
+        use byteorder::WriteBytesExt;
+        w.write_u64::<LittleEndian>(self.locator_id)?; // I8
+        w.write_u8(if self.is_last_data {
+            0b110_u8
+        } else {
+            0b010_u8
+        })?; // I1
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_possible_wrap)]
+        w.write_i32::<LittleEndian>(self.data.len() as i32)?; // I4
+        w.write_all(&[0_u8; 3])?; // B3 (filler)
+        w.write_all(&self.data)?; // B[chunk_length]
+        Ok(())
+    }
+
     pub fn parse(rdr: &mut dyn std::io::Read) -> HdbResult<Self> {
         let locator_id = rdr.read_u64::<LittleEndian>()?; // I8
         let options = rdr.read_u8()?; // I1
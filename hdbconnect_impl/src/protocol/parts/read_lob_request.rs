@@ -27,4 +27,30 @@ impl ReadLobRequest {
     pub fn size() -> usize {
         24
     }
+
+    // Counterpart of emit(), used by the mock server and the dissector.
+    #[cfg(any(feature = "mock_server", feature = "dissector"))]
+    pub(crate) fn parse(rdr: &mut dyn std::io::Read) -> HdbResult<Self> {
+        use byteorder::ReadBytesExt;
+        let locator_id = rdr.read_u64::<LittleEndian>()?;
+        let offset = rdr.read_u64::<LittleEndian>()?;
+        let length = rdr.read_u32::<LittleEndian>()?;
+        rdr.read_u32::<LittleEndian>()?; // FILLER
+        Ok(Self::new(locator_id, offset, length))
+    }
+
+    #[cfg(any(feature = "mock_server", feature = "dissector"))]
+    pub(crate) fn locator_id(&self) -> u64 {
+        self.locator_id
+    }
+
+    #[cfg(any(feature = "mock_server", feature = "dissector"))]
+    pub(crate) fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    #[cfg(any(feature = "mock_server", feature = "dissector"))]
+    pub(crate) fn length(&self) -> u32 {
+        self.length
+    }
 }
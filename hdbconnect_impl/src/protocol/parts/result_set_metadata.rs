@@ -101,6 +101,77 @@ impl ResultSetMetadata {
     }
 }
 
+// Used by the mock server to build and send the metadata of a result set.
+#[cfg(feature = "mock_server")]
+impl ResultSetMetadata {
+    // Each field is described by its names (schema, table, column, display name; empty names
+    // are omitted), the column options, the type id, the scale, and the precision.
+    pub(crate) fn new_for_server(fields: Vec<([&str; 4], u8, TypeId, i16, i16)>) -> Self {
+        let mut names = VecMap::<String>::new();
+        let mut offsets = std::collections::HashMap::<String, u32>::new();
+        let mut next_offset = 0_u32;
+        let mut offset_of = |name: &str| -> u32 {
+            if name.is_empty() {
+                return u32::MAX;
+            }
+            if let Some(offset) = offsets.get(name) {
+                return *offset;
+            }
+            let offset = next_offset;
+            #[allow(clippy::cast_possible_truncation)]
+            let cesu8_length = util::cesu8_length(name).min(255) as u32;
+            next_offset += cesu8_length + 1;
+            offsets.insert(name.to_string(), offset);
+            names.insert(offset as usize, name.to_string());
+            offset
+        };
+
+        let inner_fms: Vec<InnerFieldMetadata> = fields
+            .into_iter()
+            .map(
+                |([schema, table, column, display], column_options, type_id, scale, precision)| {
+                    InnerFieldMetadata::new(
+                        offset_of(schema),
+                        offset_of(table),
+                        offset_of(column),
+                        offset_of(display),
+                        column_options,
+                        type_id,
+                        scale,
+                        precision,
+                    )
+                },
+            )
+            .collect();
+
+        let names = Arc::new(names);
+        ResultSetMetadata(
+            inner_fms
+                .into_iter()
+                .map(|inner| FieldMetadata::new(inner, Arc::clone(&names)))
+                .collect(),
+        )
+    }
+
+    // Counterpart of parse().
+    pub(crate) fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
+        use byteorder::WriteBytesExt;
+        for field_metadata in &self.0 {
+            field_metadata.inner().emit(w)?;
+        }
+        if let Some(field_metadata) = self.0.first() {
+            for (_, name) in field_metadata.names() {
+                let cesu8 = cesu8::to_cesu8(name);
+                let cesu8 = &cesu8[..cesu8.len().min(255)];
+                #[allow(clippy::cast_possible_truncation)]
+                w.write_u8(cesu8.len() as u8)?;
+                w.write_all(cesu8)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 fn add_to_names(names: &mut VecMap<String>, offset: u32) {
     if offset != u32::MAX {
         let offset = offset as usize;
@@ -63,6 +63,9 @@ pub struct ServerError {
 const BASE_SIZE: i32 = 4 + 4 + 4 + 1 + 5;
 
 impl ServerError {
+    // The code with which the server reports a cancelled statement execution
+    pub(crate) const CODE_CANCELLED: i32 = 139;
+
     /// Returns the error code.
     #[must_use]
     pub fn code(&self) -> i32 {
@@ -129,6 +132,29 @@ impl ServerError {
 
         Ok(server_errors)
     }
+
+    // Counterpart of parse(), used by the mock server.
+    // Note that each error is padded individually.
+    #[cfg(feature = "mock_server")]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    #[allow(clippy::cast_sign_loss)]
+    pub(crate) fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
+        use byteorder::WriteBytesExt;
+        let text = cesu8::to_cesu8(&self.text);
+        let text_length = text.len() as i32;
+        w.write_i32::<LittleEndian>(self.code)?; // I4
+        w.write_i32::<LittleEndian>(self.position)?; // I4
+        w.write_i32::<LittleEndian>(text_length)?; // I4
+        w.write_i8(self.severity.to_i8())?; // I1
+        let mut sqlstate = self.sqlstate.clone();
+        sqlstate.resize(5, b' ');
+        w.write_all(&sqlstate)?; // B5
+        w.write_all(&text)?; // B[text_length]
+        let pad = 8 - (BASE_SIZE + text_length) % 8;
+        w.write_all(&vec![0_u8; pad as usize])?;
+        Ok(())
+    }
 }
 
 impl Error for ServerError {}
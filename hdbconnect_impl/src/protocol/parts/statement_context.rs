@@ -23,6 +23,25 @@ impl StatementContext {
         );
     }
 
+    #[cfg(feature = "mock_server")]
+    pub fn query_timeout(&self) -> Option<Duration> {
+        match self.get(&StatementContextId::QueryTimeout) {
+            Ok(&OptionValue::BIGINT(value)) => {
+                Some(Duration::from_secs(value.try_into().unwrap_or(0)))
+            }
+            _ => None,
+        }
+    }
+
+    // HANA works with full seconds; a started second counts as full second.
+    pub fn set_query_timeout(&mut self, query_timeout: Duration) {
+        let seconds = query_timeout.as_secs() + u64::from(query_timeout.subsec_nanos() > 0);
+        self.insert(
+            StatementContextId::QueryTimeout,
+            OptionValue::BIGINT(i64::try_from(seconds.max(1)).unwrap_or(i64::MAX)),
+        );
+    }
+
     pub fn server_processing_time(&self) -> Option<Duration> {
         match self.get(&StatementContextId::ServerProcessingTime) {
             Ok(&OptionValue::BIGINT(value)) => {
@@ -32,6 +51,16 @@ impl StatementContext {
         }
     }
 
+    #[cfg(feature = "mock_server")]
+    pub fn set_server_processing_time(&mut self, server_processing_time: Duration) {
+        self.insert(
+            StatementContextId::ServerProcessingTime,
+            OptionValue::BIGINT(
+                i64::try_from(server_processing_time.as_micros()).unwrap_or(i64::MAX),
+            ),
+        );
+    }
+
     pub fn server_cpu_time(&self) -> Option<Duration> {
         match self.get(&StatementContextId::ServerCPUTime) {
             Ok(&OptionValue::BIGINT(value)) => {
@@ -1,7 +1,78 @@
+#[cfg(feature = "mock_server")]
+use crate::protocol::parts::option_part::OptionPart;
+#[cfg(any(feature = "alpha_routing", feature = "mock_server"))]
+use crate::protocol::parts::option_value::OptionValue;
 use crate::protocol::parts::{multiline_option_part::MultilineOptionPart, option_part::OptionId};
 
+// Describes the hosts of a (scale-out) system; each line describes one host.
 pub(crate) type Topology = MultilineOptionPart<TopologyAttrId>;
 
+#[cfg(feature = "alpha_routing")]
+impl Topology {
+    // The volume of the host to which the current session is connected.
+    pub fn current_volume(&self) -> Option<i32> {
+        self.iter()
+            .find(|line| {
+                line.get(&TopologyAttrId::IsCurrentSession)
+                    .and_then(OptionValue::get_bool)
+                    .unwrap_or(false)
+            })
+            .and_then(|line| line.get(&TopologyAttrId::VolumeID).ok())
+            .and_then(|value| value.get_int_as_i32().ok())
+    }
+
+    // Host and port of the index server that hosts the given volume.
+    pub fn host_of_volume(&self, volume_id: i32) -> Option<(String, u16)> {
+        let line = self.iter().find(|line| {
+            line.get(&TopologyAttrId::VolumeID)
+                .and_then(OptionValue::get_int_as_i32)
+                .is_ok_and(|id| id == volume_id)
+        })?;
+        let host = line
+            .get(&TopologyAttrId::HostName)
+            .ok()?
+            .get_string()
+            .ok()?;
+        let port = line
+            .get(&TopologyAttrId::HostPortNumber)
+            .ok()?
+            .get_int_as_i32()
+            .ok()?;
+        Some((host.clone(), u16::try_from(port).ok()?))
+    }
+}
+
+// Counterpart of parse(), for the mock server.
+#[cfg(feature = "mock_server")]
+impl Topology {
+    // The hosts are given with host name, port, volume id, and whether the current session
+    // is connected to them.
+    pub fn new_for_server(hosts: &[(&str, u16, i32, bool)]) -> Self {
+        Self::new(
+            hosts
+                .iter()
+                .map(|(host, port, volume_id, is_current_session)| {
+                    let mut line = OptionPart::default();
+                    line.insert(
+                        TopologyAttrId::HostName,
+                        OptionValue::STRING((*host).to_string()),
+                    );
+                    line.insert(
+                        TopologyAttrId::HostPortNumber,
+                        OptionValue::INT(i32::from(*port)),
+                    );
+                    line.insert(TopologyAttrId::VolumeID, OptionValue::INT(*volume_id));
+                    line.insert(
+                        TopologyAttrId::IsCurrentSession,
+                        OptionValue::BOOLEAN(*is_current_session),
+                    );
+                    line
+                })
+                .collect(),
+        )
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub(crate) enum TopologyAttrId {
     HostName,         //  1 // host name
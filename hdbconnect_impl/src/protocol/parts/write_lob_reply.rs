@@ -13,6 +13,26 @@ impl WriteLobReply {
 }
 
 impl WriteLobReply {
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn new(locator_ids: Vec<u64>) -> Self {
+        Self { locator_ids }
+    }
+
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn len(&self) -> usize {
+        self.locator_ids.len()
+    }
+
+    // Counterpart of parse(), used by the mock server.
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
+        use byteorder::WriteBytesExt;
+        for locator_id in &self.locator_ids {
+            w.write_u64::<LittleEndian>(*locator_id)?; // I8
+        }
+        Ok(())
+    }
+
     pub fn parse(count: usize, rdr: &mut dyn std::io::Read) -> HdbResult<Self> {
         debug!("called with count = {}", count);
         let mut locator_ids = Vec::<u64>::default();
@@ -26,6 +26,19 @@ impl WriteLobRequest<'_> {
         21 + self.buf.len()
     }
 
+    // Counterpart of emit(), used by the mock server and the dissector;
+    // returns the locator id, the offset, the data, and whether this is the last chunk.
+    #[cfg(any(feature = "mock_server", feature = "dissector"))]
+    pub(crate) fn parse(rdr: &mut dyn std::io::Read) -> HdbResult<(u64, i64, Vec<u8>, bool)> {
+        use byteorder::ReadBytesExt;
+        let locator_id = rdr.read_u64::<LittleEndian>()?;
+        let options = rdr.read_u8()?;
+        let offset = rdr.read_i64::<LittleEndian>()?;
+        let length = rdr.read_u32::<LittleEndian>()?;
+        let data = crate::protocol::util_sync::parse_bytes(length as usize, rdr)?;
+        Ok((locator_id, offset, data, (options & 0b100_u8) != 0))
+    }
+
     pub fn emit(&self, w: &mut dyn std::io::Write) -> HdbResult<()> {
         // 1: NULL (not used here), 2: DATA_INCLUDED, 4: LASTDATA
         let options = if self.last_data { 6 } else { 2 };
@@ -1,5 +1,5 @@
 #[cfg(feature = "async")]
-use crate::conn::TcpClient;
+use crate::conn::{TcpClient, Tee};
 use crate::{
     base::{InternalReturnValue, RsState},
     conn::{AmConnCore, ConnectionCore, ConnectionStatistics},
@@ -23,6 +23,11 @@ pub(crate) struct Reply {
     session_id: i64,
     pub replytype: ReplyType,
     pub parts: Parts<'static>,
+    // for the roundtrip statistics and span; the parts they come from can be consumed
+    // during parsing
+    pub o_server_proc_time: Option<std::time::Duration>,
+    pub result_set_count: u64,
+    pub o_result_set_id: Option<u64>,
 }
 impl Reply {
     fn new(session_id: i64, replytype: ReplyType) -> Self {
@@ -30,6 +35,22 @@ impl Reply {
             session_id,
             replytype,
             parts: Parts::default(),
+            o_server_proc_time: None,
+            result_set_count: 0,
+            o_result_set_id: None,
+        }
+    }
+
+    fn note_for_statistics(&mut self, part: &Part) {
+        match part {
+            Part::StatementContext(stmt_ctx) => {
+                self.o_server_proc_time = stmt_ctx.server_processing_time();
+            }
+            Part::ResultSetId(result_set_id) => {
+                self.result_set_count += 1;
+                self.o_result_set_id = Some(*result_set_id);
+            }
+            _ => {}
         }
     }
 
@@ -59,6 +80,14 @@ impl Reply {
             statistics.add_wait_time(Instant::now().duration_since(start));
             parse_packet_header(io_buffer)?
         };
+        statistics.set_last_reply_size(
+            packet_header
+                .o_uncompressed_size
+                .unwrap_or(packet_header.part_buffer_size),
+            packet_header
+                .o_uncompressed_size
+                .map(|_| packet_header.part_buffer_size),
+        );
 
         // read rest of reply into buffer and decompress if necessary
         read_into_buffer_sync(packet_header.part_buffer_size, io_buffer, rdr)?;
@@ -84,6 +113,7 @@ impl Reply {
                 i == packet_header.no_of_parts - 1,
                 o_cursor.as_mut().unwrap_or(io_buffer),
             )?;
+            reply.note_for_statistics(&part);
             reply.push(part);
         }
         Ok(reply)
@@ -106,6 +136,7 @@ impl Reply {
         statistics: &mut ConnectionStatistics,
         io_buffer: &mut Cursor<Vec<u8>>,
         tcp_client: &mut TcpClient,
+        o_copy: Option<&mut Vec<u8>>,
     ) -> HdbResult<Self> {
         match tcp_client {
             TcpClient::AsyncPlain(ref mut cl) => {
@@ -117,7 +148,7 @@ impl Reply {
                     start,
                     statistics,
                     io_buffer,
-                    cl.reader(),
+                    &mut Tee::new(cl.reader(), o_copy),
                 )
                 .await
             }
@@ -130,7 +161,33 @@ impl Reply {
                     start,
                     statistics,
                     io_buffer,
-                    cl.reader(),
+                    &mut Tee::new(cl.reader(), o_copy),
+                )
+                .await
+            }
+            TcpClient::AsyncWebSocket(ref mut cl) => {
+                Reply::parse_async_impl(
+                    o_a_rsmd,
+                    o_a_descriptors,
+                    o_rs,
+                    o_am_conn_core,
+                    start,
+                    statistics,
+                    io_buffer,
+                    &mut Tee::new(cl.reader(), o_copy),
+                )
+                .await
+            }
+            TcpClient::Replay(ref mut cl) => {
+                Reply::parse_async_impl(
+                    o_a_rsmd,
+                    o_a_descriptors,
+                    o_rs,
+                    o_am_conn_core,
+                    start,
+                    statistics,
+                    io_buffer,
+                    cl,
                 )
                 .await
             }
@@ -158,6 +215,14 @@ impl Reply {
             statistics.add_wait_time(Instant::now().duration_since(start));
             parse_packet_header(io_buffer)?
         };
+        statistics.set_last_reply_size(
+            packet_header
+                .o_uncompressed_size
+                .unwrap_or(packet_header.part_buffer_size),
+            packet_header
+                .o_uncompressed_size
+                .map(|_| packet_header.part_buffer_size),
+        );
 
         // read rest of reply into buffer and decompress if necessary
         read_into_buffer_async(packet_header.part_buffer_size, io_buffer, rdr).await?;
@@ -184,6 +249,7 @@ impl Reply {
                 o_cursor.as_mut().unwrap_or(io_buffer),
             )
             .await?;
+            reply.note_for_statistics(&part);
             reply.push(part);
         }
         Ok(reply)
@@ -287,7 +353,14 @@ impl Reply {
             }
             None => {
                 if server_errors.len() == 1 {
-                    Err(HdbError::from(server_errors.remove(0)))
+                    let server_error = server_errors.remove(0);
+                    if server_error.code() == ServerError::CODE_CANCELLED {
+                        Err(HdbError::Cancelled {
+                            source: server_error,
+                        })
+                    } else {
+                        Err(HdbError::from(server_error))
+                    }
                 } else {
                     unreachable!("hopefully...")
                 }
@@ -296,7 +369,7 @@ impl Reply {
     }
 }
 
-fn parse_packet_header(rdr: &mut dyn std::io::Read) -> HdbResult<ReplyPacketHeader> {
+pub(crate) fn parse_packet_header(rdr: &mut dyn std::io::Read) -> HdbResult<ReplyPacketHeader> {
     // TODO validate session_id against ConnectionCore::session_id
     // TODO session_id and packet_count must be 0 for exactly the first roundtrip
     // TODO validate assumptions about seg_size, seg_offset, seg_number being always = (varpart_size, 0, 1)
@@ -364,6 +437,8 @@ fn parse_packet_header(rdr: &mut dyn std::io::Read) -> HdbResult<ReplyPacketHead
                 reply_type, seg_kind, session_id
             );
             Ok(ReplyPacketHeader {
+                is_error: matches!(seg_kind, Kind::Error),
+                packet_seq_number,
                 no_of_parts,
                 o_uncompressed_size: if compressed {
                     Some(uncompressed_size as usize)
@@ -378,6 +453,41 @@ fn parse_packet_header(rdr: &mut dyn std::io::Read) -> HdbResult<ReplyPacketHead
     }
 }
 
+// Counterpart of `parse_packet_header()`, used by the mock server.
+// Replies are always sent uncompressed.
+#[cfg(feature = "mock_server")]
+pub(crate) fn emit_packet_header(
+    session_id: i64,
+    packet_seq_number: u32,
+    reply_type: ReplyType,
+    is_error: bool,
+    no_of_parts: i16,
+    parts_size: u32,
+    w: &mut dyn std::io::Write,
+) -> HdbResult<()> {
+    use byteorder::WriteBytesExt;
+
+    // MESSAGE HEADER: 32 bytes
+    w.write_i64::<LittleEndian>(session_id)?; // I8
+    w.write_u32::<LittleEndian>(packet_seq_number)?; // I4
+    w.write_u32::<LittleEndian>(parts_size + SEGMENT_HEADER_SIZE)?; // UI4
+    w.write_u32::<LittleEndian>(parts_size + SEGMENT_HEADER_SIZE)?; // UI4
+    w.write_i16::<LittleEndian>(1)?; // I2 no_of_segs
+    w.write_all(&[0; 10])?; // no compression, fillers
+
+    // SEGMENT HEADER: 24 bytes
+    #[allow(clippy::cast_possible_wrap)]
+    w.write_i32::<LittleEndian>((parts_size + SEGMENT_HEADER_SIZE) as i32)?; // I4 seg_size
+    w.write_i32::<LittleEndian>(0)?; // I4 seg_offset
+    w.write_i16::<LittleEndian>(no_of_parts)?; // I2
+    w.write_i16::<LittleEndian>(1)?; // I2 seg_number
+    w.write_i8(if is_error { Kind::Error } else { Kind::Reply }.to_i8())?; // I1
+    w.write_u8(0)?; // I1
+    w.write_i16::<LittleEndian>(reply_type.to_i16())?; // I2
+    w.write_all(&[0; 8])?; // B[8]
+    Ok(())
+}
+
 /// Specifies the layout of the remaining segment header structure
 #[derive(Debug)]
 enum Kind {
@@ -394,14 +504,26 @@ impl Kind {
             _ => Err(impl_err!("reply::Kind {val} not implemented",)),
         }
     }
+    #[cfg(feature = "mock_server")]
+    fn to_i8(&self) -> i8 {
+        match *self {
+            Self::Request => 1,
+            Self::Reply => 2,
+            Self::Error => 5,
+        }
+    }
 }
 
-struct ReplyPacketHeader {
-    reply_type: ReplyType,
-    session_id: i64,
-    o_uncompressed_size: Option<usize>,
-    part_buffer_size: usize,
-    no_of_parts: i16,
+pub(crate) struct ReplyPacketHeader {
+    pub reply_type: ReplyType,
+    #[cfg_attr(not(feature = "dissector"), allow(dead_code))]
+    pub is_error: bool,
+    pub session_id: i64,
+    #[cfg_attr(not(feature = "dissector"), allow(dead_code))]
+    pub packet_seq_number: i32,
+    pub o_uncompressed_size: Option<usize>,
+    pub part_buffer_size: usize,
+    pub no_of_parts: i16,
 }
 
 #[cfg(feature = "sync")]
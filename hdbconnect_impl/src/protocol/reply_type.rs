@@ -60,4 +60,35 @@ impl ReplyType {
             _ => Err(impl_err!("found unexpected value {val} for ReplyType")),
         }
     }
+
+    // used on the server side of the mock server
+    #[cfg(feature = "mock_server")]
+    pub(crate) fn to_i16(self) -> i16 {
+        match self {
+            Self::Nil => 0,
+            Self::Ddl => 1,
+            Self::Insert => 2,
+            Self::Update => 3,
+            Self::Delete => 4,
+            Self::Select => 5,
+            Self::SelectForUpdate => 6,
+            Self::Explain => 7,
+            Self::DbProcedureCall => 8,
+            Self::DbProcedureCallWithResult => 9,
+            Self::Fetch => 10,
+            Self::Commit => 11,
+            Self::Rollback => 12,
+            Self::Connect => 14,
+            Self::WriteLob => 15,
+            Self::ReadLob => 16,
+            Self::Disconnect => 18,
+            Self::CloseCursor => 19,
+            Self::FindLob => 20,
+            Self::XaStart => 22,
+            Self::XaJoin => 23,
+            Self::XAControl => 25,
+            Self::XAPrepare => 26,
+            Self::XARecover => 27,
+        }
+    }
 }
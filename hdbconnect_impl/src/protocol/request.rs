@@ -2,12 +2,12 @@ use crate::{
     conn::{CommandOptions, ConnectionConfiguration, ConnectionStatistics},
     protocol::{
         parts::{ParameterDescriptors, Parts, StatementContext},
-        MessageType, Part, MESSAGE_AND_SEGMENT_HEADER_SIZE, SEGMENT_HEADER_SIZE,
+        MessageType, Part, PartKind, MESSAGE_AND_SEGMENT_HEADER_SIZE, SEGMENT_HEADER_SIZE,
     },
-    HdbResult,
+    HdbResult, TraceContext,
 };
 use byteorder::{LittleEndian, WriteBytesExt};
-use std::{io::Cursor, sync::Arc};
+use std::{io::Cursor, sync::Arc, time::Duration};
 
 const ONE_AS_NUMBER_OF_SEGMENTS: i16 = 1;
 const ONE_AS_ORDINAL_OF_THIS_SEGMENT: i16 = 1;
@@ -27,6 +27,8 @@ pub(crate) struct Request<'a> {
     message_type: MessageType,
     command_options: CommandOptions,
     parts: Parts<'a>,
+    o_query_timeout: Option<Duration>,
+    o_trace_context: Option<TraceContext>,
 }
 // Methods for defining a request
 impl<'a> Request<'a> {
@@ -35,6 +37,8 @@ impl<'a> Request<'a> {
             message_type,
             command_options,
             parts: Parts::default(),
+            o_query_timeout: None,
+            o_trace_context: None,
         }
     }
 
@@ -44,16 +48,80 @@ impl<'a> Request<'a> {
     pub fn message_type(&self) -> MessageType {
         self.message_type
     }
+    pub fn command_options(&self) -> CommandOptions {
+        self.command_options
+    }
     pub fn push(&mut self, part: Part<'a>) {
         self.parts.push(part);
     }
 
-    pub fn add_statement_context(&mut self, ssi_value: i64) {
+    // The query timeout is sent with the session parts, as part of the StatementContext.
+    pub fn set_query_timeout(&mut self, o_query_timeout: Option<Duration>) {
+        self.o_query_timeout = o_query_timeout;
+    }
+
+    // The trace context is sent with the session parts, as part of the ClientInfo.
+    pub fn set_trace_context(&mut self, o_trace_context: Option<TraceContext>) {
+        self.o_trace_context = o_trace_context;
+    }
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.o_trace_context.as_ref()
+    }
+
+    // The SQL text of ExecuteDirect and Prepare requests.
+    pub fn command(&self) -> Option<&str> {
+        self.parts.ref_inner().iter().find_map(|part| match part {
+            Part::Command(sql) => Some(*sql),
+            _ => None,
+        })
+    }
+
+    // The statement id of Execute and DropStatementId requests.
+    pub fn statement_id(&self) -> Option<u64> {
+        self.parts.ref_inner().iter().find_map(|part| match part {
+            Part::StatementId(id) => Some(*id),
+            _ => None,
+        })
+    }
+
+    // The result set id of Fetch and CloseResultSet requests.
+    #[cfg(feature = "tracing")]
+    pub fn result_set_id(&self) -> Option<u64> {
+        self.parts.ref_inner().iter().find_map(|part| match part {
+            Part::ResultSetId(id) => Some(*id),
+            _ => None,
+        })
+    }
+
+    pub fn set_statement_id(&mut self, statement_id: u64) {
+        for part in self.parts.iter_mut() {
+            if let Part::StatementId(ref mut id) = part {
+                *id = statement_id;
+            }
+        }
+    }
+
+    // Removes the parts that are added by `ConnectionCore::augment_request()`.
+    pub fn remove_session_parts(&mut self) {
+        self.parts.drop_parts_of_kind(PartKind::StatementContext);
+        self.parts.drop_parts_of_kind(PartKind::ClientInfo);
+    }
+
+    pub fn add_statement_context(&mut self, o_ssi_value: Option<i64>) {
+        if o_ssi_value.is_none() && self.o_query_timeout.is_none() {
+            return;
+        }
         let mut stmt_ctx = StatementContext::default();
-        stmt_ctx.set_statement_sequence_info(ssi_value);
+        if let Some(ssi_value) = o_ssi_value {
+            stmt_ctx.set_statement_sequence_info(ssi_value);
+        }
+        if let Some(query_timeout) = self.o_query_timeout {
+            stmt_ctx.set_query_timeout(query_timeout);
+        }
         trace!(
-            "Sending StatementContext with sequence_info = {:?}",
-            ssi_value
+            "Sending StatementContext with sequence_info = {:?}, query_timeout = {:?}",
+            o_ssi_value,
+            self.o_query_timeout
         );
         self.push(Part::StatementContext(stmt_ctx));
     }
@@ -118,6 +186,11 @@ impl<'a> Request<'a> {
             io_buffer,
         )?;
 
+        statistics.set_last_request_size(
+            uncompressed_parts_size,
+            o_compressed_parts.as_ref().map(Vec::len),
+        );
+
         // serialize request to stream
         let start = std::time::Instant::now();
         if let Some(compressed_parts) = o_compressed_parts {
@@ -262,6 +335,11 @@ impl<'a> Request<'a> {
             io_buffer,
         )?;
 
+        statistics.set_last_request_size(
+            uncompressed_parts_size,
+            o_compressed_parts.as_ref().map(Vec::len),
+        );
+
         // serialize request to stream
         let start = std::time::Instant::now();
         if let Some(compressed_parts) = o_compressed_parts {
@@ -286,6 +364,81 @@ impl<'a> Request<'a> {
     }
 }
 
+// Header information of a request, as seen by the server side
+// (used by the mock server and the dissector).
+#[cfg(any(feature = "mock_server", feature = "dissector"))]
+#[derive(Debug)]
+pub(crate) struct RequestPacketHeader {
+    #[cfg_attr(not(feature = "dissector"), allow(dead_code))]
+    pub session_id: i64,
+    pub packet_seq_number: u32,
+    pub message_type: MessageType,
+    pub auto_commit: bool,
+    pub command_options: CommandOptions,
+    pub no_of_parts: u16,
+    // size of the (possibly compressed) parts that follow the header
+    pub part_buffer_size: usize,
+    pub o_uncompressed_size: Option<usize>,
+}
+
+// Counterpart of `Request::emit_packet_header()`, used by the mock server and the dissector.
+#[cfg(any(feature = "mock_server", feature = "dissector"))]
+pub(crate) fn parse_packet_header(rdr: &mut dyn std::io::Read) -> HdbResult<RequestPacketHeader> {
+    use crate::{impl_err, protocol::util_sync};
+    use byteorder::ReadBytesExt;
+
+    // MESSAGE HEADER: 32 bytes
+    let session_id = rdr.read_i64::<LittleEndian>()?; // I8
+    let packet_seq_number = rdr.read_u32::<LittleEndian>()?; // I4
+    let varpart_size = rdr.read_u32::<LittleEndian>()?; // UI4
+    let _remaining_bufsize = rdr.read_u32::<LittleEndian>()?; // UI4
+    let no_of_segs = rdr.read_i16::<LittleEndian>()?; // I2
+    if no_of_segs != ONE_AS_NUMBER_OF_SEGMENTS {
+        return Err(impl_err!("unexpected number of segments: {no_of_segs}"));
+    }
+    let compressed = match rdr.read_u8()? {
+        0 => false,
+        PACKET_OPTION_COMPRESS => true,
+        v => return Err(impl_err!("unexpected value for compression control: {v}")),
+    };
+    util_sync::skip_bytes(1, rdr)?; // filler1byte
+    let uncompressed_size = rdr.read_u32::<LittleEndian>()?; // UI4
+    util_sync::skip_bytes(4, rdr)?; // filler4byte
+
+    // SEGMENT HEADER: 24 bytes
+    rdr.read_u32::<LittleEndian>()?; // I4 seg_size
+    rdr.read_i32::<LittleEndian>()?; // I4 seg_offset
+    let no_of_parts = rdr.read_u16::<LittleEndian>()?; // I2
+    rdr.read_i16::<LittleEndian>()?; // I2 seg_number
+    let seg_kind = rdr.read_i8()?; // I1
+    if seg_kind != SEGMENT_KIND_REQUEST {
+        return Err(impl_err!("unexpected segment kind: {seg_kind}"));
+    }
+    let message_type = MessageType::from_i8(rdr.read_i8()?)?; // I1
+    let auto_commit = rdr.read_i8()? != 0; // I1
+    let command_options = CommandOptions::from_bits_truncate(rdr.read_u8()?); // I1
+    util_sync::skip_bytes(8, rdr)?; // [B;8]
+
+    let part_buffer_size = varpart_size
+        .checked_sub(SEGMENT_HEADER_SIZE)
+        .ok_or_else(|| impl_err!("invalid varpart size {varpart_size}"))?
+        as usize;
+    Ok(RequestPacketHeader {
+        session_id,
+        packet_seq_number,
+        message_type,
+        auto_commit,
+        command_options,
+        no_of_parts,
+        part_buffer_size,
+        o_uncompressed_size: if compressed {
+            Some(uncompressed_size.saturating_sub(SEGMENT_HEADER_SIZE) as usize)
+        } else {
+            None
+        },
+    })
+}
+
 fn shrunk_by_at_least_five_percent(c: usize, u: usize) -> bool {
     c < u && u - c > u / 20
 }
@@ -1,13 +1,19 @@
 use crate::{impl_err, HdbResult};
+use std::io::Read;
 
-// Read n bytes, return as Vec<u8>
-pub(crate) fn parse_bytes(len: usize, rdr: &mut dyn std::io::Read) -> HdbResult<Vec<u8>> {
-    let mut buf: Vec<u8> = vec![0; len];
-    rdr.read_exact(&mut buf)?;
+// Read n bytes, return as Vec<u8>;
+// the buffer grows with the data, so that a corrupt length cannot cause a huge allocation
+pub(crate) fn parse_bytes(len: usize, rdr: &mut dyn Read) -> HdbResult<Vec<u8>> {
+    const MAX_PREALLOCATION: usize = 1 << 20;
+    let mut buf: Vec<u8> = Vec::with_capacity(len.min(MAX_PREALLOCATION));
+    rdr.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() < len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
     Ok(buf)
 }
 
-pub(crate) fn skip_bytes(n: usize, rdr: &mut dyn std::io::Read) -> HdbResult<()> {
+pub(crate) fn skip_bytes(n: usize, rdr: &mut dyn Read) -> HdbResult<()> {
     const MAXBUFLEN: usize = 16;
     if n > MAXBUFLEN {
         Err(impl_err!("n > MAXBUFLEN (16)"))
@@ -1,4 +1,5 @@
 pub(crate) mod de;
+pub(crate) mod named_params;
 pub(crate) mod ser;
 
 pub mod time;
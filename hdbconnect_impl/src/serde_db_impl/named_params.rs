@@ -0,0 +1,290 @@
+use crate::{usage_err, HdbResult, HdbValue, ParameterDescriptor, ParameterDescriptors};
+use serde::ser::{Impossible, Serialize, SerializeMap, SerializeStruct, Serializer};
+use serde_db::ser::{to_params, SerializationError};
+
+// Serializes the input, a struct or a map, into the values for the IN and INOUT parameters,
+// by matching the field names (or the map keys) with the names of the parameter descriptors.
+//
+// A name that occurs several times in the statement is bound to the same field;
+// fields that do not correspond to a parameter are ignored.
+pub(crate) fn to_named_params<T: Serialize>(
+    input: &T,
+    descriptors: &ParameterDescriptors,
+) -> HdbResult<Vec<HdbValue<'static>>> {
+    let in_descriptors: Vec<&ParameterDescriptor> = descriptors.iter_in().collect();
+    let mut binder = NamedBinder {
+        values: in_descriptors.iter().map(|_| None).collect(),
+        in_descriptors,
+        o_key: None,
+    };
+    input.serialize(&mut binder)?;
+    binder
+        .in_descriptors
+        .iter()
+        .zip(binder.values)
+        .map(|(descriptor, o_value)| {
+            o_value.ok_or_else(|| {
+                usage_err!(
+                    "no value is provided for the named parameter :{}",
+                    descriptor.name().unwrap_or_default()
+                )
+            })
+        })
+        .collect()
+}
+
+struct NamedBinder<'d> {
+    in_descriptors: Vec<&'d ParameterDescriptor>,
+    values: Vec<Option<HdbValue<'static>>>,
+    o_key: Option<String>,
+}
+impl NamedBinder<'_> {
+    // Serializes the value for all parameters with the given name.
+    fn bind<T: ?Sized + Serialize>(&mut self, name: &str, value: &T) -> SerdeResult<()> {
+        for (descriptor, o_value) in self.in_descriptors.iter().zip(self.values.iter_mut()) {
+            if descriptor.name() == Some(name) {
+                let mut values = to_params(&value, &mut std::iter::once(*descriptor))?;
+                if values.len() != 1 {
+                    return Err(SerializationError::StructuralMismatch(
+                        "a named parameter requires a single value",
+                    ));
+                }
+                *o_value = values.pop();
+            }
+        }
+        Ok(())
+    }
+}
+
+type SerdeResult<T> = Result<T, SerializationError>;
+
+fn no_struct_or_map() -> SerializationError {
+    SerializationError::StructuralMismatch("named parameters must be provided as a struct or a map")
+}
+fn no_string_key() -> SerializationError {
+    SerializationError::StructuralMismatch(
+        "the keys of a map with named parameters must be strings",
+    )
+}
+
+// Implements the given methods of `serde::Serializer` by returning an error.
+macro_rules! reject {
+    ($error:ident; $($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> SerdeResult<Self::Ok> {
+                Err($error())
+            }
+        )*
+    };
+}
+
+impl Serializer for &mut NamedBinder<'_> {
+    type Ok = ();
+    type Error = SerializationError;
+    type SerializeSeq = Impossible<(), SerializationError>;
+    type SerializeTuple = Impossible<(), SerializationError>;
+    type SerializeTupleStruct = Impossible<(), SerializationError>;
+    type SerializeTupleVariant = Impossible<(), SerializationError>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), SerializationError>;
+
+    reject!(no_struct_or_map;
+        serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32),
+        serialize_i64(i64), serialize_u8(u8), serialize_u16(u16), serialize_u32(u32),
+        serialize_u64(u64), serialize_f32(f32), serialize_f64(f64), serialize_char(char),
+        serialize_str(&str), serialize_bytes(&[u8]), serialize_none(), serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    );
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> SerdeResult<()> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> SerdeResult<()> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> SerdeResult<()> {
+        Err(no_struct_or_map())
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> SerdeResult<Self::SerializeSeq> {
+        Err(no_struct_or_map())
+    }
+    fn serialize_tuple(self, _len: usize) -> SerdeResult<Self::SerializeTuple> {
+        Err(no_struct_or_map())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeTupleStruct> {
+        Err(no_struct_or_map())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeTupleVariant> {
+        Err(no_struct_or_map())
+    }
+    fn serialize_map(self, _len: Option<usize>) -> SerdeResult<Self::SerializeMap> {
+        Ok(self)
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeStruct> {
+        Ok(self)
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeStructVariant> {
+        Err(no_struct_or_map())
+    }
+}
+
+impl SerializeStruct for &mut NamedBinder<'_> {
+    type Ok = ();
+    type Error = SerializationError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> SerdeResult<()> {
+        self.bind(key, value)
+    }
+    fn end(self) -> SerdeResult<()> {
+        Ok(())
+    }
+}
+
+impl SerializeMap for &mut NamedBinder<'_> {
+    type Ok = ();
+    type Error = SerializationError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> SerdeResult<()> {
+        self.o_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> SerdeResult<()> {
+        let key = self
+            .o_key
+            .take()
+            .ok_or(SerializationError::StructuralMismatch(
+                "map value without key",
+            ))?;
+        self.bind(&key, value)
+    }
+    fn end(self) -> SerdeResult<()> {
+        Ok(())
+    }
+}
+
+// Serializes the keys of maps, which must be strings.
+struct KeySerializer;
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = SerializationError;
+    type SerializeSeq = Impossible<String, SerializationError>;
+    type SerializeTuple = Impossible<String, SerializationError>;
+    type SerializeTupleStruct = Impossible<String, SerializationError>;
+    type SerializeTupleVariant = Impossible<String, SerializationError>;
+    type SerializeMap = Impossible<String, SerializationError>;
+    type SerializeStruct = Impossible<String, SerializationError>;
+    type SerializeStructVariant = Impossible<String, SerializationError>;
+
+    fn serialize_str(self, value: &str) -> SerdeResult<String> {
+        Ok(value.to_string())
+    }
+    fn serialize_char(self, value: char) -> SerdeResult<String> {
+        Ok(value.to_string())
+    }
+
+    reject!(no_string_key;
+        serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32),
+        serialize_i64(i64), serialize_u8(u8), serialize_u16(u16), serialize_u32(u32),
+        serialize_u64(u64), serialize_f32(f32), serialize_f64(f64),
+        serialize_bytes(&[u8]), serialize_none(), serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    );
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> SerdeResult<String> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> SerdeResult<String> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> SerdeResult<String> {
+        Err(no_string_key())
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> SerdeResult<Self::SerializeSeq> {
+        Err(no_string_key())
+    }
+    fn serialize_tuple(self, _len: usize) -> SerdeResult<Self::SerializeTuple> {
+        Err(no_string_key())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeTupleStruct> {
+        Err(no_string_key())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeTupleVariant> {
+        Err(no_string_key())
+    }
+    fn serialize_map(self, _len: Option<usize>) -> SerdeResult<Self::SerializeMap> {
+        Err(no_string_key())
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeStruct> {
+        Err(no_string_key())
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> SerdeResult<Self::SerializeStructVariant> {
+        Err(no_string_key())
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
         ReplyType,
     },
     sync::{HdbReturnValue, ResultSet},
-    usage_err, HdbError, HdbResult,
+    usage_err, ExecutionOutcome, HdbError, HdbResult,
 };
 
 /// Represents all possible non-error responses to a database command.
@@ -383,6 +383,28 @@ impl HdbResponse {
         None
     }
 
+    // A view for the interceptor.
+    pub(crate) fn outcome(&self) -> ExecutionOutcome<'_> {
+        let mut outcome = ExecutionOutcome::default();
+        for return_value in &self.return_values {
+            match return_value {
+                HdbReturnValue::ResultSet(result_set) => {
+                    outcome.add_result_set(result_set.metadata());
+                }
+                HdbReturnValue::AffectedRows(affected_rows) => {
+                    outcome.add_affected_rows(affected_rows);
+                }
+                HdbReturnValue::OutputParameters(output_parameters) => {
+                    outcome.set_output_parameters(output_parameters);
+                }
+                HdbReturnValue::Success => {}
+                #[cfg(feature = "dist_tx")]
+                HdbReturnValue::XaTransactionIds(_) => {}
+            }
+        }
+        outcome
+    }
+
     fn get_err(&self, type_s: &str) -> HdbError {
         let mut errmsg = String::new();
         errmsg.push_str("No ");
@@ -1,6 +1,6 @@
 use crate::{
     base::{new_am_sync, InternalReturnValue, PreparedStatementCore, AM},
-    conn::AmConnCore,
+    conn::{rewrite_sql, session_setup, AmConnCore, Interception},
     impl_err,
     protocol::{
         parts::{
@@ -10,9 +10,9 @@ use crate::{
     },
     sync::HdbResponse,
     types_impl::lob::SyncLobWriter,
-    usage_err, ConnectionConfiguration, HdbResult,
+    usage_err, ConnectionConfiguration, HdbResult, TraceContext,
 };
-use std::{io::Write, sync::Arc};
+use std::{io::Write, sync::Arc, time::Duration};
 
 /// Allows injection-safe SQL execution and repeated calls of the same statement
 /// with different parameters with as few roundtrips as possible.
@@ -75,11 +75,13 @@ use std::{io::Write, sync::Arc};
 #[derive(Clone, Debug)]
 pub struct PreparedStatement {
     am_ps_core: AM<PreparedStatementCore>,
+    sql: String,
     config: ConnectionConfiguration,
     server_usage: ServerUsage,
     a_descriptors: Arc<ParameterDescriptors>,
     o_a_rsmd: Option<Arc<ResultSetMetadata>>,
     batch: ParameterRows<'static>,
+    o_trace_context: Option<TraceContext>,
     _o_table_location: Option<Vec<i32>>,
 }
 
@@ -133,12 +135,17 @@ impl<'a> PreparedStatement {
     /// Several variants of `HdbError` can occur.
     pub fn execute<T: serde::ser::Serialize>(&mut self, input: &T) -> HdbResult<HdbResponse> {
         trace!("PreparedStatement::execute()");
-        if self.a_descriptors.has_in() {
+        let o_rows = if self.a_descriptors.has_in() {
             let mut par_rows = ParameterRows::new();
             par_rows.push(input, &self.a_descriptors)?;
-            return self.execute_parameter_rows(Some(par_rows));
-        }
-        self.execute_parameter_rows(None)
+            Some(par_rows)
+        } else {
+            None
+        };
+        let o_interception = self.begin_interception(o_rows.as_ref())?;
+        let result = self.execute_parameter_rows(o_rows);
+        end_interception(o_interception, &self.server_usage, &result);
+        result
     }
 
     /// Consumes the given `HdbValue`s as a row of parameters for immediate execution.
@@ -190,9 +197,23 @@ impl<'a> PreparedStatement {
     ///
     /// Several variants of `HdbError` can occur.
     pub fn execute_row(&'a mut self, hdb_values: Vec<HdbValue<'a>>) -> HdbResult<HdbResponse> {
+        let row: &[&[HdbValue]] = if self.a_descriptors.has_in() {
+            &[&hdb_values]
+        } else {
+            &[]
+        };
+        let o_interception = Interception::begin(self.config.interceptor(), &self.sql, row)?;
+        let result = self.execute_row_impl(hdb_values);
+        end_interception(o_interception, &self.server_usage, &result);
+        result
+    }
+
+    fn execute_row_impl(&mut self, hdb_values: Vec<HdbValue<'a>>) -> HdbResult<HdbResponse> {
         if self.a_descriptors.has_in() {
             let ps_core_guard = self.am_ps_core.lock_sync()?;
             let mut request = Request::new(MessageType::Execute, self.config.command_options());
+            request.set_query_timeout(self.config.query_timeout());
+            request.set_trace_context(self.o_trace_context.clone());
 
             request.push(Part::StatementId(ps_core_guard.statement_id));
 
@@ -240,9 +261,10 @@ impl<'a> PreparedStatement {
                 .remove_first_of_kind(PartKind::WriteLobReply);
 
             let (mut internal_return_values, replytype) = (
-                main_reply
-                    .parts
-                    .into_internal_return_values_sync(&ps_core_guard.am_conn_core, None)?,
+                main_reply.parts.into_internal_return_values_sync(
+                    &ps_core_guard.am_conn_core,
+                    Some(&mut self.server_usage),
+                )?,
                 main_reply.replytype,
             );
 
@@ -280,10 +302,13 @@ impl<'a> PreparedStatement {
                 }
             }
 
-            // inject statement id
+            // inject statement id, and propagate the cursor type
             for rv in &mut internal_return_values {
                 if let InternalReturnValue::RsState((rs_state, _a_rsmd)) = rv {
                     rs_state.inject_ps_core_sync(Arc::clone(&self.am_ps_core))?;
+                    if self.config.is_scrollable_cursor() {
+                        rs_state.set_scrollable();
+                    }
                 }
             }
             HdbResponse::try_new(internal_return_values, replytype)
@@ -357,7 +382,10 @@ impl<'a> PreparedStatement {
             self.batch.count()
         );
         std::mem::swap(&mut self.batch, &mut batch2);
-        self.execute_parameter_rows(Some(batch2))
+        let o_interception = self.begin_interception(Some(&batch2))?;
+        let result = self.execute_parameter_rows(Some(batch2));
+        end_interception(o_interception, &self.server_usage, &result);
+        result
     }
 
     /// Descriptors of all parameters of the prepared statement (in, out, inout).
@@ -366,11 +394,22 @@ impl<'a> PreparedStatement {
         Arc::clone(&self.a_descriptors)
     }
 
+    // Calls `before_execute` of the interceptor, if any.
+    fn begin_interception(
+        &self,
+        o_rows: Option<&ParameterRows>,
+    ) -> HdbResult<Option<Interception>> {
+        let rows: Vec<&[HdbValue]> = o_rows.map(|rows| rows.rows().collect()).unwrap_or_default();
+        Interception::begin(self.config.interceptor(), &self.sql, &rows)
+    }
+
     fn execute_parameter_rows(&mut self, o_rows: Option<ParameterRows>) -> HdbResult<HdbResponse> {
         trace!("PreparedStatement::execute_parameter_rows()");
 
         let ps_core_guard = self.am_ps_core.lock_sync()?;
         let mut request = Request::new(MessageType::Execute, self.config.command_options());
+        request.set_query_timeout(self.config.query_timeout());
+        request.set_trace_context(self.o_trace_context.clone());
         request.push(Part::StatementId(ps_core_guard.statement_id));
         if let Some(rows) = o_rows {
             request.push(Part::ParameterRows(rows));
@@ -384,18 +423,62 @@ impl<'a> PreparedStatement {
                 Some(&self.a_descriptors),
                 &mut None,
             )?
-            .into_internal_return_values_sync(&ps_core_guard.am_conn_core, None)?;
+            .into_internal_return_values_sync(
+                &ps_core_guard.am_conn_core,
+                Some(&mut self.server_usage),
+            )?;
 
-        // inject statement id
+        // inject statement id, and propagate the cursor type
         for rv in &mut internal_return_values {
             if let InternalReturnValue::RsState((rs_state, _a_rsmd)) = rv {
                 rs_state.inject_ps_core_sync(Arc::clone(&self.am_ps_core))?;
+                if self.config.is_scrollable_cursor() {
+                    rs_state.set_scrollable();
+                }
             }
         }
 
         HdbResponse::try_new(internal_return_values, replytype)
     }
 
+    /// Defines whether the statement's result sets are produced with scrollable cursors.
+    pub fn set_scrollable_cursor(&mut self, scrollable: bool) {
+        self.config.set_scrollable_cursor(scrollable);
+    }
+    /// Returns whether the statement's result sets are produced with scrollable cursors.
+    #[must_use]
+    pub fn is_scrollable_cursor(&self) -> bool {
+        self.config.is_scrollable_cursor()
+    }
+
+    /// Sets the timeout after which the server cancels the execution of this statement;
+    /// the execution then fails with `HdbError::Cancelled`, and the connection remains usable.
+    ///
+    /// By default, the query timeout of the connection applies.
+    /// HANA works with full seconds, so the timeout is rounded up.
+    pub fn set_query_timeout(&mut self, query_timeout: Option<Duration>) {
+        self.config.set_query_timeout(query_timeout);
+    }
+    /// Returns the statement's query timeout.
+    #[must_use]
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.config.query_timeout()
+    }
+
+    /// Sets the W3C trace context that is propagated to the server with the executions
+    /// of this statement.
+    ///
+    /// By default, the trace context of the connection applies.
+    /// See [`TraceContext`] for details.
+    pub fn set_trace_context(&mut self, trace_context: Option<TraceContext>) {
+        self.o_trace_context = trace_context;
+    }
+    /// Returns the statement's trace context.
+    #[must_use]
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.o_trace_context.as_ref()
+    }
+
     /// Provides information about the the server-side resource consumption that
     /// is related to this `PreparedStatement` object.
     #[must_use]
@@ -404,24 +487,29 @@ impl<'a> PreparedStatement {
     }
 
     // Prepare a statement.
-    pub(crate) fn try_new(am_conn_core: AmConnCore, stmt: &str) -> HdbResult<Self> {
+    pub(crate) fn try_new(
+        am_conn_core: AmConnCore,
+        stmt: &str,
+        placeholder_names: Vec<String>,
+    ) -> HdbResult<Self> {
         let config = am_conn_core.lock_sync()?.configuration().clone();
+        let sql = rewrite_sql(config.interceptor(), stmt).into_owned();
+        am_conn_core.set_up_session_sync(session_setup(config.interceptor(), &sql))?;
         let mut request = Request::new(MessageType::Prepare, config.command_options());
-        request.push(Part::Command(stmt));
+        request.push(Part::Command(&sql));
 
         let reply = am_conn_core.send_sync(request)?;
 
         let mut o_table_location: Option<Vec<i32>> = None;
         let mut o_stmt_id: Option<u64> = None;
-        let mut a_descriptors: Arc<ParameterDescriptors> =
-            Arc::new(ParameterDescriptors::default());
+        let mut descriptors = ParameterDescriptors::default();
         let mut o_a_rsmd: Option<Arc<ResultSetMetadata>> = None;
         let mut server_usage = ServerUsage::default();
 
         for part in reply.parts {
             match part {
-                Part::ParameterMetadata(descriptors) => {
-                    a_descriptors = Arc::new(descriptors);
+                Part::ParameterMetadata(pds) => {
+                    descriptors = pds;
                 }
                 Part::StatementId(id) => {
                     o_stmt_id = Some(id);
@@ -455,18 +543,35 @@ impl<'a> PreparedStatement {
             am_conn_core,
             statement_id,
         });
+        if !placeholder_names.is_empty() {
+            descriptors.set_placeholder_names(placeholder_names)?;
+        }
+        let a_descriptors = Arc::new(descriptors);
         debug!(
             "PreparedStatement created with parameter descriptors = {:?}",
             a_descriptors
         );
         Ok(Self {
             am_ps_core,
+            sql,
             config,
             server_usage,
             batch: ParameterRows::new(),
             a_descriptors,
             o_a_rsmd,
+            o_trace_context: None,
             _o_table_location: o_table_location,
         })
     }
 }
+
+// Calls `after_execute` or `on_error` of the interceptor, if any.
+fn end_interception(
+    o_interception: Option<Interception>,
+    server_usage: &ServerUsage,
+    result: &HdbResult<HdbResponse>,
+) {
+    if let Some(interception) = o_interception {
+        interception.end(result.as_ref().map(HdbResponse::outcome), server_usage);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{impl_err, HdbResult, HdbValue};
 use byteorder::{LittleEndian, ReadBytesExt};
 
-const NULL_REPRESENTATION: i32 = 3_652_062;
+pub(crate) const NULL_REPRESENTATION: i32 = 3_652_062;
 
 const ZEITENWENDE: i32 = 1_721_424;
 const JGREG: i32 = 2_299_161;
@@ -22,6 +22,8 @@ const UTF_BUFFER_SIZE: usize = 8 * 1024;
 #[cfg(feature = "sync")]
 pub(crate) use self::sync_lob_writer::SyncLobWriter;
 
+#[cfg(feature = "mock_server")]
+pub(crate) use self::wire::emit_lob_descriptor;
 pub(crate) use self::wire::emit_lob_header;
 #[cfg(feature = "async")]
 pub(crate) use self::wire::{parse_blob_async, parse_clob_async, parse_nclob_async};
@@ -219,6 +219,32 @@ fn parse_lob_2(
     }
 }
 
+// Counterpart of parse_lob_1() and parse_lob_2(), used by the mock server;
+// `o_lob` is None for a NULL value, otherwise it contains
+// (char length, byte length, locator id, first chunk, is last chunk).
+#[cfg(feature = "mock_server")]
+pub(crate) fn emit_lob_descriptor(
+    type_id: TypeId,
+    o_lob: Option<(u64, u64, u64, &[u8], bool)>,
+    w: &mut dyn std::io::Write,
+) -> HdbResult<()> {
+    w.write_u8(type_id as u8)?; // I1
+    match o_lob {
+        None => w.write_u8(0b1_u8)?, // I1
+        Some((char_length, byte_length, locator_id, chunk, is_last_data)) => {
+            w.write_u8(if is_last_data { 0b110_u8 } else { 0b010_u8 })?; // I1
+            w.write_u16::<LittleEndian>(0)?; // U2 (filler)
+            w.write_u64::<LittleEndian>(char_length)?; // I8
+            w.write_u64::<LittleEndian>(byte_length)?; // I8
+            w.write_u64::<LittleEndian>(locator_id)?; // I8
+            #[allow(clippy::cast_possible_truncation)]
+            w.write_u32::<LittleEndian>(chunk.len() as u32)?; // I4
+            w.write_all(chunk)?; // B[chunk_length]
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::cast_possible_truncation)]
 pub(crate) fn emit_lob_header(
     length: u64,
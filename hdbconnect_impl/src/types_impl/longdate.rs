@@ -1,7 +1,7 @@
 use crate::{impl_err, HdbResult, HdbValue};
 use byteorder::{LittleEndian, ReadBytesExt};
 
-const NULL_REPRESENTATION: i64 = 3_155_380_704_000_000_001;
+pub(crate) const NULL_REPRESENTATION: i64 = 3_155_380_704_000_000_001;
 const SECOND_FACTOR: i64 = 10_000_000;
 const MINUTE_FACTOR: i64 = 600_000_000; // 10_000_000 * 60;
 const HOUR_FACTOR: i64 = 36_000_000_000; // 10_000_000 * 60 * 60;
@@ -1,7 +1,7 @@
 use crate::{impl_err, HdbResult, HdbValue};
 use byteorder::{LittleEndian, ReadBytesExt};
 
-const NULL_REPRESENTATION: i64 = 315_538_070_401;
+pub(crate) const NULL_REPRESENTATION: i64 = 315_538_070_401;
 
 const SECOND_FACTOR: i64 = 1;
 const MINUTE_FACTOR: i64 = 60;
@@ -1,7 +1,7 @@
 use crate::{impl_err, HdbResult, HdbValue};
 use byteorder::{LittleEndian, ReadBytesExt};
 
-const NULL_REPRESENTATION: i32 = 86_402;
+pub(crate) const NULL_REPRESENTATION: i32 = 86_402;
 
 const MINUTE_FACTOR: u32 = 60;
 const HOUR_FACTOR: u32 = 3_600;
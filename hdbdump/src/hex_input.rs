@@ -0,0 +1,71 @@
+// Reads the bytes from a hex dump.
+//
+// Supported are plain hex (with or without whitespace, with or without `0x` prefixes),
+// and the common dump formats with leading offsets and trailing ASCII columns,
+// like those of `xxd`, `hexdump -C` and Wireshark's "Copy as Hex Dump".
+pub(crate) fn parse(input: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(input).map_err(|e| format!("hex dump is not text: {e}"))?;
+    let mut bytes = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        // the ASCII column of hexdump -C
+        let line = line.split('|').next().unwrap_or_default();
+        let tokens: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+            .map(|token| token.trim_start_matches("0x"))
+            .collect();
+        let skip = usize::from(is_offset(&tokens));
+        for token in tokens.iter().skip(skip) {
+            if token.len() % 2 != 0 || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+                // the ASCII column of xxd and Wireshark
+                break;
+            }
+            for i in (0..token.len()).step_by(2) {
+                bytes.push(
+                    u8::from_str_radix(&token[i..i + 2], 16)
+                        .map_err(|e| format!("invalid hex dump in line {}: {e}", line_no + 1))?,
+                );
+            }
+        }
+    }
+    if bytes.is_empty() {
+        Err("the hex dump contains no bytes".to_string())
+    } else {
+        Ok(bytes)
+    }
+}
+
+// An offset is either terminated with a colon (xxd), or it is longer than the byte groups
+// that follow (hexdump -C, Wireshark).
+fn is_offset(tokens: &[&str]) -> bool {
+    match tokens {
+        [first, ..] if first.ends_with(':') => true,
+        [first, second, ..] => {
+            first.len() >= 4
+                && first.len() != second.len()
+                && first.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        let expected = vec![0xff, 0xff, 0xff, 0xff, 0x04, 0x00, 0x14, 0x04];
+        for dump in [
+            "ffffffff04001404",
+            "ff ff ff ff\n04 00 14 04\n",
+            "0xff, 0xff, 0xff, 0xff, 0x04, 0x00, 0x14, 0x04",
+            "00000000: ffff ffff 0400 1404                      ........",
+            "00000000  ff ff ff ff 04 00 14 04                           |........|",
+            "0000   ff ff ff ff 04 00 14 04                           ........",
+        ] {
+            assert_eq!(parse(dump.as_bytes()).unwrap(), expected, "{dump}");
+        }
+        assert!(parse(b"no hex here").is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! Decodes captured HANA wire protocol traffic.
+//!
+//! ```text
+//! hdbdump [--json] [--format auto|hdbwire|pcap|hex|raw] [--port PORT] FILE
+//! ```
+//!
+//! `FILE` can be
+//! - a pcap or pcapng file; the TCP connections are reassembled and decoded separately,
+//! - a hex dump of the byte stream of a connection, as written e.g. by `xxd`, `hexdump -C`
+//!   or Wireshark's "Copy as Hex Dump",
+//! - the raw byte stream of a connection,
+//! - or a wire trace, as written by connections for which
+//!   `ConnectionConfiguration::set_wire_trace_dir` was used.
+//!
+//! With `-` as `FILE`, the input is read from stdin.
+//!
+//! TLS-encrypted traffic cannot be decoded.
+#![deny(missing_docs)]
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+
+mod hex_input;
+mod pcap;
+
+use hdbconnect_impl::{
+    dissector::{Dissected, Dissector},
+    HdbResult,
+};
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+const USAGE: &str = "\
+Usage: hdbdump [--json] [--format auto|hdbwire|pcap|hex|raw] [--port PORT] FILE
+
+Decodes captured HANA wire protocol traffic, from a pcap or pcapng file,
+a hex dump, a raw byte stream, or a wire trace. Use - as FILE to read from stdin.
+
+Options:
+    --json           Write JSON instead of human-readable text
+    --format FORMAT  The input format; by default, it is detected from the content
+    --port PORT      Decode only TCP connections from or to PORT (pcap only)
+    --help           Print this text";
+
+const HDBWIRE_MAGIC: &[u8] = b"HDBWIRE\0";
+
+fn main() -> ExitCode {
+    match Args::parse(std::env::args().skip(1)).and_then(|args| run(&args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("hdbdump: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Auto,
+    HdbWire,
+    Pcap,
+    Hex,
+    Raw,
+}
+
+#[derive(Debug)]
+struct Args {
+    json: bool,
+    format: Format,
+    o_port: Option<u16>,
+    file: String,
+}
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut json = false;
+        let mut format = Format::Auto;
+        let mut o_port = None;
+        let mut o_file = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => json = true,
+                "--format" => {
+                    format = match args.next().as_deref() {
+                        Some("auto") => Format::Auto,
+                        Some("hdbwire") => Format::HdbWire,
+                        Some("pcap") => Format::Pcap,
+                        Some("hex") => Format::Hex,
+                        Some("raw") => Format::Raw,
+                        _ => return Err(format!("--format needs a valid value\n\n{USAGE}")),
+                    };
+                }
+                "--port" => {
+                    o_port = Some(
+                        args.next()
+                            .and_then(|port| port.parse().ok())
+                            .ok_or_else(|| format!("--port needs a port number\n\n{USAGE}"))?,
+                    );
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(format!("unknown option {arg}\n\n{USAGE}"));
+                }
+                _ if o_file.is_none() => o_file = Some(arg),
+                _ => return Err(format!("only one FILE can be given\n\n{USAGE}")),
+            }
+        }
+        Ok(Self {
+            json,
+            format,
+            o_port,
+            file: o_file.ok_or_else(|| USAGE.to_string())?,
+        })
+    }
+}
+
+// The decoded traffic of a connection.
+#[derive(Debug, serde::Serialize)]
+struct Connection {
+    connection: String,
+    items: Vec<Dissected>,
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let input = if args.file == "-" {
+        let mut input = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut input)
+            .map_err(|e| format!("cannot read stdin: {e}"))?;
+        input
+    } else {
+        std::fs::read(&args.file).map_err(|e| format!("cannot read {}: {e}", args.file))?
+    };
+
+    let format = match args.format {
+        Format::Auto => detect_format(&input),
+        format => format,
+    };
+    if args.o_port.is_some() && format != Format::Pcap {
+        return Err("--port can only be used with pcap files".to_string());
+    }
+
+    let connections = match format {
+        Format::HdbWire => {
+            if args.file == "-" {
+                return Err("wire traces cannot be read from stdin".to_string());
+            }
+            vec![Connection {
+                connection: args.file.clone(),
+                items: Dissector::new()
+                    .and_then(|mut dissector| {
+                        dissector.dissect_wire_trace(&PathBuf::from(&args.file))
+                    })
+                    .map_err(|e| e.to_string())?,
+            }]
+        }
+        Format::Pcap => pcap::dissect(&input, args.o_port)?,
+        Format::Hex => vec![Connection {
+            connection: args.file.clone(),
+            items: dissect_stream(&hex_input::parse(&input)?).map_err(|e| e.to_string())?,
+        }],
+        Format::Raw => vec![Connection {
+            connection: args.file.clone(),
+            items: dissect_stream(&input).map_err(|e| e.to_string())?,
+        }],
+        Format::Auto => unreachable!("the format was detected"),
+    };
+
+    write_output(&connections, args.json).map_err(|e| format!("cannot write output: {e}"))
+}
+
+fn detect_format(input: &[u8]) -> Format {
+    if input.starts_with(HDBWIRE_MAGIC) {
+        Format::HdbWire
+    } else if pcap::is_pcap(input) {
+        Format::Pcap
+    } else if !input.is_empty()
+        && input
+            .iter()
+            .all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+    {
+        Format::Hex
+    } else {
+        Format::Raw
+    }
+}
+
+fn dissect_stream(stream: &[u8]) -> HdbResult<Vec<Dissected>> {
+    Ok(Dissector::new()?.dissect_stream(stream))
+}
+
+fn write_output(connections: &[Connection], json: bool) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    if json {
+        serde_json::to_writer_pretty(&mut stdout, connections)?;
+        writeln!(stdout)?;
+    } else {
+        for connection in connections {
+            writeln!(stdout, "=== {} ===", connection.connection)?;
+            for item in &connection.items {
+                writeln!(stdout, "{item}")?;
+            }
+        }
+    }
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{detect_format, Args, Format};
+
+    #[test]
+    fn test_args_and_detection() {
+        let args = Args::parse(
+            ["--json", "--port", "30015", "capture.pcap"]
+                .into_iter()
+                .map(ToString::to_string),
+        )
+        .unwrap();
+        assert!(args.json);
+        assert_eq!(args.o_port, Some(30015));
+        assert_eq!(args.format, Format::Auto);
+        assert_eq!(args.file, "capture.pcap");
+        assert!(Args::parse(["--port", "x", "f"].into_iter().map(ToString::to_string)).is_err());
+        assert!(Args::parse(std::iter::empty()).is_err());
+
+        assert_eq!(detect_format(b"HDBWIRE\0\x01\x00"), Format::HdbWire);
+        assert_eq!(detect_format(&[0xd4, 0xc3, 0xb2, 0xa1, 2, 0]), Format::Pcap);
+        assert_eq!(detect_format(b"0000  ff ff ff ff\n"), Format::Hex);
+        assert_eq!(detect_format(&[0xff, 0xff, 0xff, 0xff, 4, 0]), Format::Raw);
+    }
+}
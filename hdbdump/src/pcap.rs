@@ -0,0 +1,666 @@
+// Reads the TCP connections from pcap and pcapng files.
+//
+// The TCP segments of each connection are reassembled per direction, using the sequence
+// numbers; retransmitted data are dropped. The data of both directions are then merged
+// in the order of their capture times into a single byte stream, which is decoded with
+// a separate `Dissector` per connection.
+//
+// IPv4 and IPv6 over Ethernet (with or without VLAN tags), Linux cooked captures (SLL, SLL2),
+// raw IP and BSD loopback are supported. IP fragments and IPv6 extension headers are not
+// supported; the affected packets are skipped.
+use crate::Connection;
+use hdbconnect_impl::dissector::{Dissected, DissectedItem, Dissector};
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+// pcapng block types
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const SIMPLE_PACKET_BLOCK: u32 = 3;
+const ENHANCED_PACKET_BLOCK: u32 = 6;
+const IF_TSRESOL: u16 = 9;
+
+// link types
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LOOP: u32 = 108;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const IP_PROTOCOL_TCP: u8 = 6;
+const TCP_SYN: u8 = 0x02;
+const TCP_ACK: u8 = 0x10;
+
+pub(crate) fn is_pcap(input: &[u8]) -> bool {
+    input.len() >= 4 && {
+        let magic = u32::from_le_bytes(input[0..4].try_into().unwrap(/*OK*/));
+        [PCAP_MAGIC_MICROS, PCAP_MAGIC_NANOS, PCAPNG_SECTION_HEADER].contains(&magic)
+            || [PCAP_MAGIC_MICROS, PCAP_MAGIC_NANOS].contains(&magic.swap_bytes())
+    }
+}
+
+// Decodes the TCP connections in the capture, optionally only those from or to the given port.
+// Without port, connections in which no HANA message is found are omitted.
+pub(crate) fn dissect(input: &[u8], o_port: Option<u16>) -> Result<Vec<Connection>, String> {
+    let mut connections = BTreeMap::<(Endpoint, Endpoint), TcpConnection>::new();
+    for packet in read_packets(input)? {
+        let Some(segment) = parse_link_layer(packet.link_type, packet.data) else {
+            continue;
+        };
+        if o_port.is_some_and(|port| segment.src.1 != port && segment.dst.1 != port) {
+            continue;
+        }
+        let key = if segment.src <= segment.dst {
+            (segment.src, segment.dst)
+        } else {
+            (segment.dst, segment.src)
+        };
+        connections
+            .entry(key)
+            .or_insert_with(|| TcpConnection::new(segment.src, segment.dst))
+            .add(packet.timestamp, &segment);
+    }
+
+    let mut result = Vec::new();
+    for connection in connections.into_values() {
+        let (stream, timestamps, gaps) = connection.reassemble();
+        let mut dissector = Dissector::new().map_err(|e| e.to_string())?;
+        let mut items = dissector.dissect_stream(&stream);
+        if o_port.is_none()
+            && !items.iter().any(|item| {
+                matches!(
+                    item.item,
+                    DissectedItem::Request(_) | DissectedItem::Reply(_)
+                )
+            })
+        {
+            continue;
+        }
+        set_timestamps(&mut items, &timestamps);
+        result.push(Connection {
+            connection: if gaps > 0 {
+                format!(
+                    "{} ({gaps} bytes missing in the capture)",
+                    connection.name()
+                )
+            } else {
+                connection.name()
+            },
+            items,
+        });
+    }
+    Ok(result)
+}
+
+// Each item gets the capture time of the packet in which it starts.
+fn set_timestamps(items: &mut [Dissected], timestamps: &[(usize, SystemTime)]) {
+    for item in items {
+        let idx = timestamps.partition_point(|(offset, _)| *offset <= item.offset);
+        if let Some((_, timestamp)) = idx.checked_sub(1).map(|idx| timestamps[idx]) {
+            item.set_timestamp(timestamp);
+        }
+    }
+}
+
+type Endpoint = (IpAddr, u16);
+
+struct Packet<'a> {
+    link_type: u32,
+    timestamp: SystemTime,
+    data: &'a [u8],
+}
+
+struct TcpSegment<'a> {
+    src: Endpoint,
+    dst: Endpoint,
+    seq: u32,
+    syn: bool,
+    ack: bool,
+    payload: &'a [u8],
+}
+
+// The captured segments of a TCP connection; the first direction is that from the client,
+// if the connection setup was captured, otherwise that of the first segment.
+struct TcpConnection {
+    endpoints: (Endpoint, Endpoint),
+    directions: [Direction; 2],
+}
+impl TcpConnection {
+    fn new(src: Endpoint, dst: Endpoint) -> Self {
+        Self {
+            endpoints: (src, dst),
+            directions: [Direction::default(), Direction::default()],
+        }
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "{} -> {}",
+            endpoint_name(self.endpoints.0),
+            endpoint_name(self.endpoints.1)
+        )
+    }
+
+    fn add(&mut self, timestamp: SystemTime, segment: &TcpSegment) {
+        // the client sends the first SYN
+        if segment.syn
+            && !segment.ack
+            && self
+                .directions
+                .iter()
+                .all(|direction| direction.o_isn.is_none())
+            && segment.src != self.endpoints.0
+        {
+            self.endpoints = (self.endpoints.1, self.endpoints.0);
+            self.directions.swap(0, 1);
+        }
+        let direction = &mut self.directions[usize::from(segment.src != self.endpoints.0)];
+        if segment.syn {
+            direction.o_isn = Some(segment.seq.wrapping_add(1));
+        }
+        if !segment.payload.is_empty() {
+            direction
+                .segments
+                .push((segment.seq, timestamp, segment.payload.to_vec()));
+        }
+    }
+
+    // Returns the merged byte stream, the capture times of its chunks,
+    // and the number of bytes that are missing in the capture.
+    fn reassemble(&self) -> (Vec<u8>, Vec<(usize, SystemTime)>, u64) {
+        let (chunks0, gaps0) = self.directions[0].reassemble();
+        let (chunks1, gaps1) = self.directions[1].reassemble();
+
+        // merge by capture time, but keep the order within each direction
+        let mut chunks0 = chunks0.into_iter().peekable();
+        let mut chunks1 = chunks1.into_iter().peekable();
+        let mut stream = Vec::new();
+        let mut timestamps = Vec::new();
+        while let Some((timestamp, data)) = match (chunks0.peek(), chunks1.peek()) {
+            (Some((t0, _)), Some((t1, _))) if t1 < t0 => chunks1.next(),
+            (Some(_), _) => chunks0.next(),
+            (None, _) => chunks1.next(),
+        } {
+            timestamps.push((stream.len(), timestamp));
+            stream.extend_from_slice(&data);
+        }
+        (stream, timestamps, gaps0 + gaps1)
+    }
+}
+
+#[derive(Default)]
+struct Direction {
+    o_isn: Option<u32>,
+    segments: Vec<(u32, SystemTime, Vec<u8>)>,
+}
+impl Direction {
+    // Orders the segments by sequence number, and removes retransmitted data.
+    fn reassemble(&self) -> (Vec<(SystemTime, Vec<u8>)>, u64) {
+        let Some(base) = self
+            .o_isn
+            .or_else(|| self.segments.first().map(|(seq, _, _)| *seq))
+        else {
+            return (Vec::new(), 0);
+        };
+        let mut segments: Vec<(i64, SystemTime, &[u8])> = self
+            .segments
+            .iter()
+            .map(|(seq, timestamp, data)| {
+                // relative to the initial sequence number, robust against wrap-around
+                #[allow(clippy::cast_possible_wrap)]
+                let rel_seq = i64::from(seq.wrapping_sub(base) as i32);
+                (rel_seq, *timestamp, data.as_slice())
+            })
+            .collect();
+        segments.sort_by_key(|(rel_seq, timestamp, _)| (*rel_seq, *timestamp));
+
+        let mut chunks = Vec::new();
+        let mut gaps = 0;
+        let mut o_next: Option<i64> = None;
+        for (rel_seq, timestamp, data) in segments {
+            let next = o_next.unwrap_or(rel_seq);
+            let end = rel_seq + i64::try_from(data.len()).unwrap_or(i64::MAX);
+            if end <= next {
+                continue;
+            }
+            if rel_seq > next {
+                gaps += u64::try_from(rel_seq - next).unwrap_or_default();
+            }
+            let skip = usize::try_from(next - rel_seq).unwrap_or_default();
+            chunks.push((timestamp, data[skip..].to_vec()));
+            o_next = Some(end);
+        }
+        (chunks, gaps)
+    }
+}
+
+fn endpoint_name((ip, port): Endpoint) -> String {
+    match ip {
+        IpAddr::V4(ip) => format!("{ip}:{port}"),
+        IpAddr::V6(ip) => format!("[{ip}]:{port}"),
+    }
+}
+
+// Byte order aware reading of the file headers.
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+impl<'a> Reader<'a> {
+    fn u16(self, pos: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.bytes.get(pos..pos + 2)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+    fn u32(self, pos: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.bytes.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+    fn slice(self, pos: usize, len: usize) -> Option<&'a [u8]> {
+        self.bytes.get(pos..pos.checked_add(len)?)
+    }
+}
+
+fn read_packets(input: &[u8]) -> Result<Vec<Packet<'_>>, String> {
+    let magic = input
+        .get(0..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap(/*OK*/)))
+        .ok_or("the capture file is too short")?;
+    if magic == PCAPNG_SECTION_HEADER {
+        read_pcapng(input)
+    } else {
+        read_pcap(input)
+    }
+}
+
+fn read_pcap(input: &[u8]) -> Result<Vec<Packet<'_>>, String> {
+    let le_magic = u32::from_le_bytes(input[0..4].try_into().unwrap(/*OK*/));
+    let (big_endian, nanos) = match (le_magic, le_magic.swap_bytes()) {
+        (PCAP_MAGIC_MICROS, _) => (false, false),
+        (PCAP_MAGIC_NANOS, _) => (false, true),
+        (_, PCAP_MAGIC_MICROS) => (true, false),
+        (_, PCAP_MAGIC_NANOS) => (true, true),
+        _ => return Err("not a pcap file".to_string()),
+    };
+    let rdr = Reader {
+        bytes: input,
+        big_endian,
+    };
+    let link_type = rdr.u32(20).ok_or("truncated pcap file header")? & 0x0fff_ffff;
+
+    let mut packets = Vec::new();
+    let mut pos = 24;
+    while pos < input.len() {
+        let (Some(seconds), Some(fraction), Some(length)) =
+            (rdr.u32(pos), rdr.u32(pos + 4), rdr.u32(pos + 8))
+        else {
+            eprintln!("hdbdump: the pcap file is truncated");
+            break;
+        };
+        let Some(data) = rdr.slice(pos + 16, length as usize) else {
+            eprintln!("hdbdump: the pcap file is truncated");
+            break;
+        };
+        let fraction = if nanos {
+            Duration::from_nanos(u64::from(fraction))
+        } else {
+            Duration::from_micros(u64::from(fraction))
+        };
+        packets.push(Packet {
+            link_type,
+            timestamp: UNIX_EPOCH + Duration::from_secs(u64::from(seconds)) + fraction,
+            data,
+        });
+        pos += 16 + length as usize;
+    }
+    Ok(packets)
+}
+
+fn read_pcapng(input: &[u8]) -> Result<Vec<Packet<'_>>, String> {
+    let mut packets = Vec::new();
+    // per interface: link type, and time units per second
+    let mut interfaces = Vec::<(u32, u64)>::new();
+    let mut rdr = Reader {
+        bytes: input,
+        big_endian: false,
+    };
+    let mut pos = 0;
+    while pos + 12 <= input.len() {
+        if rdr.u32(pos) == Some(PCAPNG_SECTION_HEADER) {
+            // each section defines its byte order and its interfaces
+            rdr.big_endian = false;
+            rdr.big_endian = match rdr.u32(pos + 8) {
+                Some(PCAPNG_BYTE_ORDER_MAGIC) => false,
+                Some(magic) if magic.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => true,
+                _ => return Err("invalid pcapng section header".to_string()),
+            };
+            interfaces.clear();
+        }
+        let block_type = rdr.u32(pos).ok_or("truncated pcapng block")?;
+        let block_length = rdr.u32(pos + 4).ok_or("truncated pcapng block")? as usize;
+        if block_length < 12 || pos + block_length > input.len() {
+            eprintln!("hdbdump: the pcapng file is truncated");
+            break;
+        }
+        let body = Reader {
+            bytes: &input[pos + 8..pos + block_length - 4],
+            big_endian: rdr.big_endian,
+        };
+        match block_type {
+            INTERFACE_DESCRIPTION_BLOCK => {
+                let link_type = u32::from(body.u16(0).ok_or("invalid interface block")?);
+                interfaces.push((link_type, units_per_second(body)));
+            }
+            ENHANCED_PACKET_BLOCK => {
+                let (Some(interface), Some(high), Some(low), Some(length)) =
+                    (body.u32(0), body.u32(4), body.u32(8), body.u32(12))
+                else {
+                    return Err("invalid enhanced packet block".to_string());
+                };
+                let &(link_type, units) = interfaces
+                    .get(interface as usize)
+                    .ok_or("packet refers to an unknown interface")?;
+                let time = (u64::from(high) << 32) | u64::from(low);
+                let timestamp = UNIX_EPOCH
+                    .checked_add(Duration::from_secs(time / units))
+                    .and_then(|timestamp| {
+                        timestamp.checked_add(Duration::from_nanos(
+                            u64::try_from(
+                                u128::from(time % units) * 1_000_000_000 / u128::from(units),
+                            )
+                            .unwrap_or_default(),
+                        ))
+                    })
+                    .ok_or("invalid timestamp in enhanced packet block")?;
+                packets.push(Packet {
+                    link_type,
+                    timestamp,
+                    data: body
+                        .slice(20, length as usize)
+                        .ok_or("invalid enhanced packet block")?,
+                });
+            }
+            SIMPLE_PACKET_BLOCK => {
+                let &(link_type, _) = interfaces
+                    .first()
+                    .ok_or("packet refers to an unknown interface")?;
+                let length = body.u32(0).ok_or("invalid simple packet block")? as usize;
+                packets.push(Packet {
+                    link_type,
+                    timestamp: UNIX_EPOCH,
+                    data: body
+                        .slice(4, length.min(body.bytes.len().saturating_sub(4)))
+                        .ok_or("invalid simple packet block")?,
+                });
+            }
+            _ => {}
+        }
+        pos += block_length;
+    }
+    Ok(packets)
+}
+
+// The resolution of the timestamps of an interface, from the option if_tsresol.
+fn units_per_second(body: Reader) -> u64 {
+    let mut pos = 8;
+    while let (Some(code), Some(length)) = (body.u16(pos), body.u16(pos + 2)) {
+        if code == 0 {
+            break;
+        }
+        if code == IF_TSRESOL {
+            if let Some(&[resolution]) = body.slice(pos + 4, 1) {
+                let exponent = u32::from(resolution & 0x7f);
+                return if resolution & 0x80 == 0 {
+                    10_u64.checked_pow(exponent)
+                } else {
+                    2_u64.checked_pow(exponent)
+                }
+                .unwrap_or(1_000_000);
+            }
+        }
+        pos += 4 + usize::from(length).div_ceil(4) * 4;
+    }
+    1_000_000
+}
+
+fn parse_link_layer(link_type: u32, data: &[u8]) -> Option<TcpSegment<'_>> {
+    let (ethertype, ip_packet) = match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut ethertype = u16::from_be_bytes(data.get(12..14)?.try_into().ok()?);
+            let mut header_size = 14;
+            while ethertype == ETHERTYPE_VLAN {
+                ethertype = u16::from_be_bytes(
+                    data.get(header_size + 2..header_size + 4)?
+                        .try_into()
+                        .ok()?,
+                );
+                header_size += 4;
+            }
+            (ethertype, data.get(header_size..)?)
+        }
+        LINKTYPE_LINUX_SLL => (
+            u16::from_be_bytes(data.get(14..16)?.try_into().ok()?),
+            data.get(16..)?,
+        ),
+        LINKTYPE_LINUX_SLL2 => (
+            u16::from_be_bytes(data.get(0..2)?.try_into().ok()?),
+            data.get(20..)?,
+        ),
+        LINKTYPE_NULL | LINKTYPE_LOOP => (ip_version(data.get(4..)?)?, data.get(4..)?),
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => (ip_version(data)?, data),
+        _ => return None,
+    };
+    match ethertype {
+        ETHERTYPE_IPV4 => parse_ipv4(ip_packet),
+        ETHERTYPE_IPV6 => parse_ipv6(ip_packet),
+        _ => None,
+    }
+}
+
+fn ip_version(ip_packet: &[u8]) -> Option<u16> {
+    match ip_packet.first()? >> 4 {
+        4 => Some(ETHERTYPE_IPV4),
+        6 => Some(ETHERTYPE_IPV6),
+        _ => None,
+    }
+}
+
+fn parse_ipv4(packet: &[u8]) -> Option<TcpSegment<'_>> {
+    let header_size = usize::from(packet.first()? & 0x0f) * 4;
+    let total_length = usize::from(u16::from_be_bytes(packet.get(2..4)?.try_into().ok()?));
+    let fragmented = u16::from_be_bytes(packet.get(6..8)?.try_into().ok()?) & 0x3fff != 0;
+    if *packet.get(9)? != IP_PROTOCOL_TCP || fragmented {
+        return None;
+    }
+    let src: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+    let dst: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+    parse_tcp(
+        IpAddr::V4(Ipv4Addr::from(src)),
+        IpAddr::V4(Ipv4Addr::from(dst)),
+        packet.get(header_size..total_length.min(packet.len()))?,
+    )
+}
+
+fn parse_ipv6(packet: &[u8]) -> Option<TcpSegment<'_>> {
+    let payload_length = usize::from(u16::from_be_bytes(packet.get(4..6)?.try_into().ok()?));
+    if *packet.get(6)? != IP_PROTOCOL_TCP {
+        return None;
+    }
+    let src: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+    let dst: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+    parse_tcp(
+        IpAddr::V6(Ipv6Addr::from(src)),
+        IpAddr::V6(Ipv6Addr::from(dst)),
+        packet.get(40..(40 + payload_length).min(packet.len()))?,
+    )
+}
+
+fn parse_tcp(src_ip: IpAddr, dst_ip: IpAddr, segment: &[u8]) -> Option<TcpSegment<'_>> {
+    let src_port = u16::from_be_bytes(segment.get(0..2)?.try_into().ok()?);
+    let dst_port = u16::from_be_bytes(segment.get(2..4)?.try_into().ok()?);
+    let seq = u32::from_be_bytes(segment.get(4..8)?.try_into().ok()?);
+    let header_size = usize::from(segment.get(12)? >> 4) * 4;
+    let flags = *segment.get(13)?;
+    Some(TcpSegment {
+        src: (src_ip, src_port),
+        dst: (dst_ip, dst_port),
+        seq,
+        syn: flags & TCP_SYN != 0,
+        ack: flags & TCP_ACK != 0,
+        payload: segment.get(header_size..)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{dissect, is_pcap};
+    use hdbconnect_impl::dissector::DissectedItem;
+
+    // An Ethernet frame with an IPv4 TCP segment from 10.0.0.1:40000 to 10.0.0.2:30015.
+    fn frame(seq: u32, syn: bool, to_server: bool, payload: &[u8]) -> Vec<u8> {
+        let (src, dst, src_port, dst_port) = if to_server {
+            ([10, 0, 0, 1], [10, 0, 0, 2], 40000_u16, 30015_u16)
+        } else {
+            ([10, 0, 0, 2], [10, 0, 0, 1], 30015, 40000)
+        };
+        let mut frame = vec![0_u8; 12];
+        frame.extend_from_slice(&0x0800_u16.to_be_bytes());
+        let total_length = u16::try_from(20 + 20 + payload.len()).unwrap();
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&total_length.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0, 64, 6, 0, 0]);
+        frame.extend_from_slice(&src);
+        frame.extend_from_slice(&dst);
+        frame.extend_from_slice(&src_port.to_be_bytes());
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        frame.extend_from_slice(&seq.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0, 0, 0x50, if syn { 0x02 } else { 0x18 }]);
+        frame.extend_from_slice(&[0xff, 0xff, 0, 0, 0, 0]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn pcap(frames: &[Vec<u8>]) -> Vec<u8> {
+        let mut pcap = Vec::new();
+        pcap.extend_from_slice(&0xa1b2_c3d4_u32.to_le_bytes());
+        pcap.extend_from_slice(&[2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        pcap.extend_from_slice(&65535_u32.to_le_bytes());
+        pcap.extend_from_slice(&1_u32.to_le_bytes());
+        for (i, frame) in frames.iter().enumerate() {
+            let length = u32::try_from(frame.len()).unwrap();
+            pcap.extend_from_slice(&1_700_000_000_u32.to_le_bytes());
+            pcap.extend_from_slice(&u32::try_from(i).unwrap().to_le_bytes());
+            pcap.extend_from_slice(&length.to_le_bytes());
+            pcap.extend_from_slice(&length.to_le_bytes());
+            pcap.extend_from_slice(frame);
+        }
+        pcap
+    }
+
+    // A pcapng section with one interface of the given timestamp resolution, and one packet.
+    fn pcapng(tsresol: u8, time: u64, frame: &[u8]) -> Vec<u8> {
+        let block = |block_type: u32, body: &[u8]| {
+            let length = u32::try_from(12 + body.len().div_ceil(4) * 4).unwrap();
+            let mut block = block_type.to_le_bytes().to_vec();
+            block.extend_from_slice(&length.to_le_bytes());
+            block.extend_from_slice(body);
+            block.resize(length as usize - 4, 0);
+            block.extend_from_slice(&length.to_le_bytes());
+            block
+        };
+        let mut section_header = 0x1a2b_3c4d_u32.to_le_bytes().to_vec();
+        section_header.extend_from_slice(&[1, 0, 0, 0]);
+        section_header.extend_from_slice(&u64::MAX.to_le_bytes());
+        let mut interface = vec![1, 0, 0, 0, 0xff, 0xff, 0, 0];
+        interface.extend_from_slice(&[9, 0, 1, 0, tsresol, 0, 0, 0, 0, 0, 0, 0]);
+        let mut packet = 0_u32.to_le_bytes().to_vec();
+        packet.extend_from_slice(&u32::try_from(time >> 32).unwrap().to_le_bytes());
+        packet.extend_from_slice(&u32::try_from(time & 0xffff_ffff).unwrap().to_le_bytes());
+        let length = u32::try_from(frame.len()).unwrap();
+        packet.extend_from_slice(&length.to_le_bytes());
+        packet.extend_from_slice(&length.to_le_bytes());
+        packet.extend_from_slice(frame);
+
+        let mut pcapng = block(0x0a0d_0d0a, &section_header);
+        pcapng.extend(block(1, &interface));
+        pcapng.extend(block(6, &packet));
+        pcapng
+    }
+
+    #[test]
+    fn test_reassembly() {
+        let initial_request = [0xff, 0xff, 0xff, 0xff, 4, 0, 0x14, 4, 0, 1, 0, 1, 1, 1];
+        // the initial request in two segments, out of order and with a retransmission
+        let pcap = pcap(&[
+            frame(99, true, true, &[]),
+            frame(106, false, true, &initial_request[6..]),
+            frame(100, false, true, &initial_request[..8]),
+            frame(106, false, true, &initial_request[6..]),
+        ]);
+        assert!(is_pcap(&pcap));
+        let connections = dissect(&pcap, Some(30015)).unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(
+            connections[0].connection,
+            "10.0.0.1:40000 -> 10.0.0.2:30015"
+        );
+        let items = &connections[0].items;
+        assert_eq!(items.len(), 1);
+        assert!(matches!(
+            items[0].item,
+            DissectedItem::InitialRequest { .. }
+        ));
+        assert_eq!(items[0].length, initial_request.len());
+        assert!(items[0]
+            .timestamp
+            .as_ref()
+            .unwrap()
+            .starts_with("2023-11-14T22:13:20"));
+
+        // without port, connections without HANA messages are omitted
+        assert!(dissect(&pcap, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pcapng_timestamps() {
+        let initial_request = [0xff, 0xff, 0xff, 0xff, 4, 0, 0x14, 4, 0, 1, 0, 1, 1, 1];
+        let frame = frame(100, false, true, &initial_request);
+
+        // microseconds, and seconds
+        for (tsresol, time) in [(6, 1_700_000_000_000_000), (0, 1_700_000_000)] {
+            let pcapng = pcapng(tsresol, time, &frame);
+            assert!(is_pcap(&pcapng));
+            let connections = dissect(&pcapng, Some(30015)).unwrap();
+            assert!(connections[0].items[0]
+                .timestamp
+                .as_ref()
+                .unwrap()
+                .starts_with("2023-11-14T22:13:20"));
+        }
+
+        // a timestamp that is not representable is rejected
+        let error = dissect(&pcapng(0, u64::MAX, &frame), Some(30015)).unwrap_err();
+        assert!(error.contains("timestamp"), "{error}");
+    }
+}